@@ -0,0 +1,205 @@
+//! The bearer-token JSON client behind the REST cloud APIs (DigitalOcean,
+//! Hetzner, Vultr, Linode). Each provider wraps it with its base URL and a
+//! parser for its error bodies.
+use crate::error::AppError;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads the message out of a provider's error body; `None` if the body
+/// isn't in that provider's error format.
+pub type ErrorParser = fn(&str) -> Option<String>;
+
+pub struct ApiClient {
+    http: reqwest::Client,
+    /// Names the provider in error messages, e.g. "Hetzner API error 401".
+    name: &'static str,
+    base_url: String,
+    token: String,
+    error_parser: ErrorParser,
+}
+
+impl ApiClient {
+    /// `base_url` is the API root; tests point it at a local mock server.
+    pub fn new(name: &'static str, base_url: &str, token: &str, error_parser: ErrorParser) -> Self {
+        ApiClient {
+            http: reqwest::Client::new(),
+            name,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            error_parser,
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, AppError> {
+        let resp = self.send(self.http.get(self.url(path))).await?;
+        self.parse(self.check(resp).await?).await
+    }
+
+    /// Like `get`, but a 404 is `None` rather than an error.
+    pub async fn get_opt<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, AppError> {
+        let resp = self.send(self.http.get(self.url(path))).await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        self.parse(self.check(resp).await?).await.map(Some)
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, AppError> {
+        let resp = self.send(self.http.post(self.url(path)).json(body)).await?;
+        self.parse(self.check(resp).await?).await
+    }
+
+    /// POST to an endpoint that answers 204 No Content, e.g. adding a
+    /// Droplet to a firewall.
+    pub async fn post_empty<B: Serialize>(&self, path: &str, body: &B) -> Result<(), AppError> {
+        let resp = self.send(self.http.post(self.url(path)).json(body)).await?;
+        self.check(resp).await?;
+        Ok(())
+    }
+
+    pub async fn put<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, AppError> {
+        let resp = self.send(self.http.put(self.url(path)).json(body)).await?;
+        self.parse(self.check(resp).await?).await
+    }
+
+    /// Delete a resource. Already gone counts as deleted, so a retried
+    /// destroy gets past what an earlier one removed.
+    pub async fn delete(&self, path: &str) -> Result<(), AppError> {
+        let resp = self.send(self.http.delete(self.url(path))).await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        self.check(resp).await?;
+        Ok(())
+    }
+
+    /// DELETE with a JSON body, e.g. removing rules from a firewall.
+    pub async fn delete_with_body<B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<(), AppError> {
+        let resp = self
+            .send(self.http.delete(self.url(path)).json(body))
+            .await?;
+        self.check(resp).await?;
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, AppError> {
+        request
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| AppError::General(format!("{} API request failed: {}", self.name, e)))
+    }
+
+    /// Turn a non-2xx response into an error carrying the provider's message.
+    async fn check(&self, resp: reqwest::Response) -> Result<reqwest::Response, AppError> {
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let body = resp.text().await.unwrap_or_default();
+        Err(AppError::General(format!(
+            "{} API error {}: {}",
+            self.name,
+            status,
+            self.error_message(&body)
+        )))
+    }
+
+    async fn parse<T: DeserializeOwned>(&self, resp: reqwest::Response) -> Result<T, AppError> {
+        resp.json::<T>().await.map_err(|e| {
+            AppError::General(format!("{} API response parse error: {}", self.name, e))
+        })
+    }
+
+    /// The provider's message from an error body, or the raw body if the
+    /// parser doesn't recognise it.
+    fn error_message(&self, body: &str) -> String {
+        (self.error_parser)(body).unwrap_or_else(|| body.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    /// Reads `{"message": "..."}`, standing in for a provider's parser.
+    fn message(body: &str) -> Option<String> {
+        let value: serde_json::Value = serde_json::from_str(body).ok()?;
+        value["message"].as_str().map(str::to_string)
+    }
+
+    fn client(server: &MockServer) -> ApiClient {
+        ApiClient::new("Test", &server.url, "secret-token", message)
+    }
+
+    #[tokio::test]
+    async fn sends_the_token_as_bearer_auth() {
+        let server = MockServer::start(|_| (200, "{}".into())).await;
+        let _: serde_json::Value = client(&server).get("/account").await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/account");
+        assert_eq!(
+            requests[0].authorization.as_deref(),
+            Some("Bearer secret-token")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_opt_maps_404_to_none() {
+        let server = MockServer::start(|_| (404, r#"{"message": "not found"}"#.into())).await;
+        let client = client(&server);
+        let found: Option<serde_json::Value> = client.get_opt("/servers/1").await.unwrap();
+        assert!(found.is_none());
+        assert!(client.get::<serde_json::Value>("/servers/1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_of_a_missing_resource_succeeds() {
+        let server = MockServer::start(|_| (404, r#"{"message": "not found"}"#.into())).await;
+        client(&server).delete("/keys/7").await.unwrap();
+        assert_eq!(server.requests()[0].method, "DELETE");
+    }
+
+    #[tokio::test]
+    async fn errors_carry_the_parsed_message() {
+        let server = MockServer::start(|_| (401, r#"{"message": "bad token"}"#.into())).await;
+        let err = client(&server)
+            .post::<_, serde_json::Value>("/keys", &serde_json::json!({}))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Test API error 401"), "{}", err);
+        assert!(err.ends_with(": bad token"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn errors_fall_back_to_the_raw_body() {
+        let server = MockServer::start(|_| (502, "Bad Gateway".into())).await;
+        let err = client(&server)
+            .delete("/keys/7")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("502"), "{}", err);
+        assert!(err.ends_with(": Bad Gateway"), "{}", err);
+    }
+}
@@ -2,35 +2,103 @@ use aws_config::SdkConfig;
 use aws_sdk_ec2::types::Filter;
 
 use crate::error::AppError;
+use crate::server_image::{Architecture, OsRelease, ServerImage};
+
+/// Canonical's official AWS account ID — same in all regions.
+const CANONICAL_OWNER: &str = "099720109477";
+/// The Debian cloud team's AWS account ID.
+const DEBIAN_OWNER: &str = "136693071363";
+
+/// The public SSM parameter holding the latest AMI id for `image`.
+fn ssm_parameter(image: &ServerImage) -> String {
+    let arch = image.arch.code();
+    match image.os {
+        OsRelease::Ubuntu2204 => format!(
+            "/aws/service/canonical/ubuntu/server/22.04/stable/current/{}/hvm/ebs-gp2/ami-id",
+            arch
+        ),
+        OsRelease::Ubuntu2404 => format!(
+            "/aws/service/canonical/ubuntu/server/24.04/stable/current/{}/hvm/ebs-gp3/ami-id",
+            arch
+        ),
+        OsRelease::Debian12 => format!("/aws/service/debian/release/12/latest/{}", arch),
+    }
+}
+
+/// The owner and name glob of `image`'s AMIs, for DescribeImages.
+fn image_filter(image: &ServerImage) -> (&'static str, String) {
+    let arch = image.arch.code();
+    match image.os {
+        OsRelease::Ubuntu2204 => (
+            CANONICAL_OWNER,
+            format!("ubuntu/images/hvm-ssd/ubuntu-jammy-22.04-{}-server-*", arch),
+        ),
+        OsRelease::Ubuntu2404 => (
+            CANONICAL_OWNER,
+            format!(
+                "ubuntu/images/hvm-ssd-gp3/ubuntu-noble-24.04-{}-server-*",
+                arch
+            ),
+        ),
+        OsRelease::Debian12 => (DEBIAN_OWNER, format!("debian-12-{}-*", arch)),
+    }
+}
+
+/// An AMI to launch from.
+pub struct Ami {
+    pub id: String,
+    /// See `root_device_name`.
+    pub root_device: &'static str,
+}
 
-/// Look up the latest Ubuntu 22.04 LTS AMI.
+/// The root volume's device name in `image`'s AMIs. The block device
+/// mapping must name it, or the instance gets a second, unused volume.
+pub fn root_device_name(image: &ServerImage) -> &'static str {
+    match image.os {
+        OsRelease::Ubuntu2204 | OsRelease::Ubuntu2404 => "/dev/sda1",
+        OsRelease::Debian12 => "/dev/xvda",
+    }
+}
+
+/// EC2's name for the architecture.
+fn ec2_architecture(arch: Architecture) -> &'static str {
+    match arch {
+        Architecture::Amd64 => "x86_64",
+        Architecture::Arm64 => "arm64",
+    }
+}
+
+/// Look up the latest AMI of `image`.
 ///
 /// Strategy:
 ///   1. Try SSM Parameter Store (fast, no extra permissions needed on most setups).
-///   2. Fall back to EC2 DescribeImages with Canonical's official owner + name filter.
-pub async fn lookup_ubuntu_ami(config: &SdkConfig) -> Result<String, AppError> {
-    match lookup_via_ssm(config).await {
-        Ok(ami_id) => Ok(ami_id),
+///   2. Fall back to EC2 DescribeImages with the publisher's owner id + name filter.
+pub async fn lookup_ami(config: &SdkConfig, image: &ServerImage) -> Result<Ami, AppError> {
+    let id = match lookup_via_ssm(config, image).await {
+        Ok(ami_id) => ami_id,
         Err(ssm_err) => {
             tracing::warn!(
                 "SSM AMI lookup failed ({}), falling back to EC2 DescribeImages",
                 ssm_err
             );
-            lookup_via_describe_images(config).await
+            lookup_via_describe_images(config, image).await?
         }
-    }
+    };
+    Ok(Ami {
+        id,
+        root_device: root_device_name(image),
+    })
 }
 
-async fn lookup_via_ssm(config: &SdkConfig) -> Result<String, AppError> {
+async fn lookup_via_ssm(config: &SdkConfig, image: &ServerImage) -> Result<String, AppError> {
     let ssm_client = aws_sdk_ssm::Client::new(config);
-    let param_name =
-        "/aws/service/canonical/ubuntu/server/22.04/stable/current/amd64/hvm/ebs-gp2/ami-id";
+    let param_name = ssm_parameter(image);
 
-    tracing::info!("Looking up Ubuntu 22.04 AMI via SSM: {}", param_name);
+    tracing::info!("Looking up {} AMI via SSM: {}", image, param_name);
 
     let resp = ssm_client
         .get_parameter()
-        .name(param_name)
+        .name(&param_name)
         .send()
         .await
         .map_err(|e| {
@@ -45,18 +113,20 @@ async fn lookup_via_ssm(config: &SdkConfig) -> Result<String, AppError> {
         .ok_or_else(|| AppError::Aws("SSM parameter returned no value".into()))?
         .to_string();
 
-    tracing::info!("Resolved Ubuntu 22.04 AMI via SSM: {}", ami_id);
+    tracing::info!("Resolved {} AMI via SSM: {}", image, ami_id);
     Ok(ami_id)
 }
 
-async fn lookup_via_describe_images(config: &SdkConfig) -> Result<String, AppError> {
-    // Canonical's official AWS account ID — same in all regions.
-    let canonical_owner = "099720109477";
-    let name_glob = "ubuntu/images/hvm-ssd/ubuntu-jammy-22.04-amd64-server-*";
+async fn lookup_via_describe_images(
+    config: &SdkConfig,
+    image: &ServerImage,
+) -> Result<String, AppError> {
+    let (owner, name_glob) = image_filter(image);
 
     tracing::info!(
-        "Searching for Ubuntu 22.04 AMI via EC2 DescribeImages (owner: {}, name: {})",
-        canonical_owner,
+        "Searching for {} AMI via EC2 DescribeImages (owner: {}, name: {})",
+        image,
+        owner,
         name_glob
     );
 
@@ -64,23 +134,13 @@ async fn lookup_via_describe_images(config: &SdkConfig) -> Result<String, AppErr
 
     let resp = ec2_client
         .describe_images()
-        .owners(canonical_owner)
-        .filters(
-            Filter::builder()
-                .name("name")
-                .values(name_glob)
-                .build(),
-        )
-        .filters(
-            Filter::builder()
-                .name("state")
-                .values("available")
-                .build(),
-        )
+        .owners(owner)
+        .filters(Filter::builder().name("name").values(&name_glob).build())
+        .filters(Filter::builder().name("state").values("available").build())
         .filters(
             Filter::builder()
                 .name("architecture")
-                .values("x86_64")
+                .values(ec2_architecture(image.arch))
                 .build(),
         )
         .filters(
@@ -103,9 +163,10 @@ async fn lookup_via_describe_images(config: &SdkConfig) -> Result<String, AppErr
     tracing::info!("DescribeImages returned {} candidates", images.len());
 
     if images.is_empty() {
-        return Err(AppError::Aws(
-            "No Ubuntu 22.04 AMIs found in this region via DescribeImages".into(),
-        ));
+        return Err(AppError::Aws(format!(
+            "No {} AMIs found in this region via DescribeImages",
+            image
+        )));
     }
 
     // Sort by creation date descending, pick the newest.
@@ -115,18 +176,58 @@ async fn lookup_via_describe_images(config: &SdkConfig) -> Result<String, AppErr
             .cmp(a.creation_date().unwrap_or(""))
     });
 
-    let image = &images[0];
-    let ami_id = image
+    let newest = &images[0];
+    let ami_id = newest
         .image_id()
         .ok_or_else(|| AppError::Aws("AMI found but has no image ID".into()))?
         .to_string();
 
     tracing::info!(
-        "Resolved Ubuntu 22.04 AMI via DescribeImages: {} (name: {}, created: {})",
+        "Resolved {} AMI via DescribeImages: {} (name: {}, created: {})",
+        image,
         ami_id,
-        image.name().unwrap_or("unknown"),
-        image.creation_date().unwrap_or("unknown")
+        newest.name().unwrap_or("unknown"),
+        newest.creation_date().unwrap_or("unknown")
     );
 
     Ok(ami_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graviton_images_use_the_arm64_parameters() {
+        let image = ServerImage {
+            os: OsRelease::Ubuntu2404,
+            arch: Architecture::Arm64,
+        };
+        assert_eq!(
+            ssm_parameter(&image),
+            "/aws/service/canonical/ubuntu/server/24.04/stable/current/arm64/hvm/ebs-gp3/ami-id"
+        );
+        assert_eq!(
+            image_filter(&image).1,
+            "ubuntu/images/hvm-ssd-gp3/ubuntu-noble-24.04-arm64-server-*"
+        );
+        assert_eq!(ec2_architecture(image.arch), "arm64");
+    }
+
+    #[test]
+    fn debian_comes_from_the_debian_account() {
+        let image = ServerImage {
+            os: OsRelease::Debian12,
+            arch: Architecture::Amd64,
+        };
+        assert_eq!(
+            ssm_parameter(&image),
+            "/aws/service/debian/release/12/latest/amd64"
+        );
+        assert_eq!(
+            image_filter(&image),
+            (DEBIAN_OWNER, "debian-12-amd64-*".to_string())
+        );
+        assert_eq!(root_device_name(&image), "/dev/xvda");
+    }
+}
@@ -1,15 +1,31 @@
 use aws_sdk_ec2::Client;
 
+use crate::aws::ami::Ami;
 use crate::error::AppError;
+use crate::ssh::host_key;
 
 /// Generate an SSH key pair via EC2 API and return (key_pair_name, private_key_pem).
 pub async fn create_key_pair(ec2: &Client) -> Result<(String, String), AppError> {
-    let key_name = format!("createmyvpn-key-{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap());
+    let key_name = format!(
+        "createmyvpn-key-{}",
+        uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
+    );
 
     let resp = ec2
         .create_key_pair()
         .key_name(&key_name)
         .key_type(aws_sdk_ec2::types::KeyType::Rsa)
+        .tag_specifications(
+            aws_sdk_ec2::types::TagSpecification::builder()
+                .resource_type(aws_sdk_ec2::types::ResourceType::KeyPair)
+                .tags(
+                    aws_sdk_ec2::types::Tag::builder()
+                        .key("ManagedBy")
+                        .value("createmyvpn")
+                        .build(),
+                )
+                .build(),
+        )
         .send()
         .await
         .map_err(|e| AppError::Aws(format!("Failed to create key pair: {}", e)))?;
@@ -23,43 +39,51 @@ pub async fn create_key_pair(ec2: &Client) -> Result<(String, String), AppError>
     Ok((key_name, private_key))
 }
 
-/// Launch a t2.micro instance with user_data that enables IP forwarding.
-pub async fn launch_instance(
-    ec2: &Client,
-    ami_id: &str,
-    instance_type: &str,
-    subnet_id: &str,
-    security_group_id: &str,
-    key_name: &str,
-) -> Result<String, AppError> {
-    let user_data = r#"#!/bin/bash
+/// User data for servers set up over SSH: only IPv4 and IPv6 forwarding.
+const FORWARDING_USER_DATA: &str = r#"#!/bin/bash
 set -e
 exec > /var/log/user-data.log 2>&1
 echo "=== CreateMyVpn VPN Server Bootstrap ==="
 apt-get update -y
 echo 'net.ipv4.ip_forward=1' > /etc/sysctl.d/99-vpn.conf
-echo 'net.ipv6.conf.all.disable_ipv6=1' >> /etc/sysctl.d/99-vpn.conf
+echo 'net.ipv6.conf.all.forwarding=1' >> /etc/sysctl.d/99-vpn.conf
+echo 'net.ipv6.conf.all.accept_ra=2' >> /etc/sysctl.d/99-vpn.conf
+echo 'net.ipv6.conf.default.accept_ra=2' >> /etc/sysctl.d/99-vpn.conf
 sysctl -p /etc/sysctl.d/99-vpn.conf
 echo "=== IP Forwarding Enabled ==="
 touch /tmp/user-data-complete
 echo "=== Bootstrap Complete ==="
 "#;
 
+/// Launch an instance running `user_data` on first boot, or a script that
+/// only enables IPv4 and IPv6 forwarding when it is `None`.
+pub async fn launch_instance(
+    ec2: &Client,
+    ami: &Ami,
+    instance_type: &str,
+    subnet_id: &str,
+    security_group_id: &str,
+    key_name: &str,
+    user_data: Option<&str>,
+) -> Result<String, AppError> {
+    let user_data = user_data.unwrap_or(FORWARDING_USER_DATA);
+
     let user_data_b64 = base64_encode(user_data);
 
     let resp = ec2
         .run_instances()
-        .image_id(ami_id)
+        .image_id(&ami.id)
         .instance_type(aws_sdk_ec2::types::InstanceType::from(instance_type))
         .min_count(1)
         .max_count(1)
         .subnet_id(subnet_id)
         .security_group_ids(security_group_id)
         .key_name(key_name)
+        .ipv6_address_count(1)
         .user_data(&user_data_b64)
         .block_device_mappings(
             aws_sdk_ec2::types::BlockDeviceMapping::builder()
-                .device_name("/dev/sda1")
+                .device_name(ami.root_device)
                 .ebs(
                     aws_sdk_ec2::types::EbsBlockDevice::builder()
                         .volume_type(aws_sdk_ec2::types::VolumeType::Gp3)
@@ -153,6 +177,12 @@ pub async fn allocate_and_associate_eip(
                         .value("createmyvpn-eip")
                         .build(),
                 )
+                .tags(
+                    aws_sdk_ec2::types::Tag::builder()
+                        .key("ManagedBy")
+                        .value("createmyvpn")
+                        .build(),
+                )
                 .build(),
         )
         .send()
@@ -189,6 +219,34 @@ pub async fn allocate_and_associate_eip(
     Ok((allocation_id, association_id, elastic_ip))
 }
 
+/// Read the SSH host key fingerprints cloud-init printed to the instance console.
+/// Returns an empty list if the console log isn't available yet (EC2 can take a
+/// few minutes to publish it after boot).
+pub async fn get_host_key_fingerprints(
+    ec2: &Client,
+    instance_id: &str,
+) -> Result<Vec<String>, AppError> {
+    use base64::Engine;
+
+    let resp = ec2
+        .get_console_output()
+        .instance_id(instance_id)
+        .send()
+        .await
+        .map_err(|e| AppError::Aws(format!("Failed to get console output: {}", e)))?;
+
+    let Some(encoded) = resp.output() else {
+        return Ok(Vec::new());
+    };
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| AppError::Aws(format!("Console output is not valid base64: {}", e)))?;
+
+    Ok(host_key::parse_console_fingerprints(
+        &String::from_utf8_lossy(&decoded),
+    ))
+}
+
 fn base64_encode(input: &str) -> String {
     use base64::Engine;
     base64::engine::general_purpose::STANDARD.encode(input.as_bytes())
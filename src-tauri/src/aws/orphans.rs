@@ -0,0 +1,569 @@
+//! Finds AWS resources this app created that no local deployment tracks any
+//! more (lost or quarantined state.json), and plans their teardown.
+//!
+//! Resources are found by their `ManagedBy=createmyvpn` tag. Security groups,
+//! key pairs and Elastic IPs created before they were tagged that way are
+//! also matched by their `createmyvpn-*` names.
+use std::collections::HashSet;
+
+use aws_sdk_ec2::types::{Filter, Tag};
+use aws_sdk_ec2::Client;
+
+use crate::error::AppError;
+use crate::pricing::{
+    aws_instance_hourly, GP3_GB_MONTHLY_USD, HOURS_PER_MONTH, PUBLIC_IPV4_HOURLY_USD,
+    ROOT_VOLUME_GB,
+};
+use crate::state::{DeploymentState, OrphanResource};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScannedResource {
+    pub id: String,
+    pub name: Option<String>,
+    pub vpc_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScannedInstance {
+    pub id: String,
+    pub name: Option<String>,
+    pub instance_type: String,
+    /// "pending", "running", "stopping" or "stopped".
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScannedAddress {
+    pub allocation_id: String,
+    pub public_ip: Option<String>,
+    pub association_id: Option<String>,
+    pub instance_id: Option<String>,
+}
+
+/// One call in an orphan teardown.
+#[derive(Debug, Clone)]
+pub enum TeardownStep {
+    /// Whatever `teardown::teardown_all` deletes from the fields that are set.
+    Resources(DeploymentState),
+    /// A gateway whose VPC is kept: it is detached from `vpc_id` (the VPC it
+    /// is attached to) and deleted.
+    Gateway {
+        igw_id: String,
+        vpc_id: Option<String>,
+    },
+}
+
+/// Everything found in one region, with the relations teardown needs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AwsInventory {
+    pub instances: Vec<ScannedInstance>,
+    pub addresses: Vec<ScannedAddress>,
+    /// Key pairs are addressed by name; `id` holds the name.
+    pub key_pairs: Vec<ScannedResource>,
+    pub security_groups: Vec<ScannedResource>,
+    pub subnets: Vec<ScannedResource>,
+    pub route_tables: Vec<ScannedResource>,
+    /// `vpc_id` is the VPC the gateway is attached to, if any.
+    pub internet_gateways: Vec<ScannedResource>,
+    pub vpcs: Vec<ScannedResource>,
+}
+
+fn filter(name: &str, value: &str) -> Filter {
+    Filter::builder().name(name).values(value).build()
+}
+
+fn managed_by() -> Filter {
+    filter("tag:ManagedBy", "createmyvpn")
+}
+
+fn tag(tags: &[Tag], key: &str) -> Option<String> {
+    tags.iter()
+        .find(|t| t.key() == Some(key))
+        .and_then(|t| t.value())
+        .map(str::to_string)
+}
+
+fn aws_err(what: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::Aws(format!("Failed to list {}: {}", what, e))
+}
+
+/// List every resource in the client's region that carries our tag.
+pub async fn scan_region(ec2: &Client) -> Result<AwsInventory, AppError> {
+    let mut inv = AwsInventory::default();
+
+    let resp = ec2
+        .describe_instances()
+        .filters(managed_by())
+        .filters(
+            Filter::builder()
+                .name("instance-state-name")
+                .values("pending")
+                .values("running")
+                .values("stopping")
+                .values("stopped")
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| aws_err("instances", e))?;
+    for instance in resp.reservations().iter().flat_map(|r| r.instances()) {
+        let Some(id) = instance.instance_id() else {
+            continue;
+        };
+        inv.instances.push(ScannedInstance {
+            id: id.to_string(),
+            name: tag(instance.tags(), "Name"),
+            instance_type: instance
+                .instance_type()
+                .map(|t| t.as_str().to_string())
+                .unwrap_or_default(),
+            state: instance
+                .state()
+                .and_then(|s| s.name())
+                .map(|n| n.as_str().to_string())
+                .unwrap_or_default(),
+        });
+    }
+
+    for f in [managed_by(), filter("tag:Name", "createmyvpn-eip")] {
+        let resp = ec2
+            .describe_addresses()
+            .filters(f)
+            .send()
+            .await
+            .map_err(|e| aws_err("Elastic IPs", e))?;
+        for addr in resp.addresses() {
+            let Some(id) = addr.allocation_id() else {
+                continue;
+            };
+            if inv.addresses.iter().any(|a| a.allocation_id == id) {
+                continue;
+            }
+            inv.addresses.push(ScannedAddress {
+                allocation_id: id.to_string(),
+                public_ip: addr.public_ip().map(str::to_string),
+                association_id: addr.association_id().map(str::to_string),
+                instance_id: addr.instance_id().map(str::to_string),
+            });
+        }
+    }
+
+    for f in [managed_by(), filter("key-name", "createmyvpn-key-*")] {
+        let resp = ec2
+            .describe_key_pairs()
+            .filters(f)
+            .send()
+            .await
+            .map_err(|e| aws_err("key pairs", e))?;
+        for key in resp.key_pairs() {
+            let Some(name) = key.key_name() else { continue };
+            push_unique(&mut inv.key_pairs, name, None, None);
+        }
+    }
+
+    for f in [managed_by(), filter("group-name", "createmyvpn-sg-*")] {
+        let resp = ec2
+            .describe_security_groups()
+            .filters(f)
+            .send()
+            .await
+            .map_err(|e| aws_err("security groups", e))?;
+        for sg in resp.security_groups() {
+            let Some(id) = sg.group_id() else { continue };
+            let name = sg.group_name().map(str::to_string);
+            push_unique(&mut inv.security_groups, id, name, sg.vpc_id());
+        }
+    }
+
+    let resp = ec2
+        .describe_subnets()
+        .filters(managed_by())
+        .send()
+        .await
+        .map_err(|e| aws_err("subnets", e))?;
+    for subnet in resp.subnets() {
+        let Some(id) = subnet.subnet_id() else {
+            continue;
+        };
+        let name = tag(subnet.tags(), "Name");
+        push_unique(&mut inv.subnets, id, name, subnet.vpc_id());
+    }
+
+    let resp = ec2
+        .describe_route_tables()
+        .filters(managed_by())
+        .send()
+        .await
+        .map_err(|e| aws_err("route tables", e))?;
+    for rt in resp.route_tables() {
+        let Some(id) = rt.route_table_id() else {
+            continue;
+        };
+        let name = tag(rt.tags(), "Name");
+        push_unique(&mut inv.route_tables, id, name, rt.vpc_id());
+    }
+
+    let resp = ec2
+        .describe_internet_gateways()
+        .filters(managed_by())
+        .send()
+        .await
+        .map_err(|e| aws_err("internet gateways", e))?;
+    for igw in resp.internet_gateways() {
+        let Some(id) = igw.internet_gateway_id() else {
+            continue;
+        };
+        let name = tag(igw.tags(), "Name");
+        let vpc_id = igw.attachments().first().and_then(|a| a.vpc_id());
+        push_unique(&mut inv.internet_gateways, id, name, vpc_id);
+    }
+
+    let resp = ec2
+        .describe_vpcs()
+        .filters(managed_by())
+        .send()
+        .await
+        .map_err(|e| aws_err("VPCs", e))?;
+    for vpc in resp.vpcs() {
+        let Some(id) = vpc.vpc_id() else { continue };
+        let name = tag(vpc.tags(), "Name");
+        push_unique(&mut inv.vpcs, id, name, Some(id));
+    }
+
+    Ok(inv)
+}
+
+fn push_unique(list: &mut Vec<ScannedResource>, id: &str, name: Option<String>, vpc: Option<&str>) {
+    if list.iter().all(|r| r.id != id) {
+        list.push(ScannedResource {
+            id: id.to_string(),
+            name,
+            vpc_id: vpc.map(str::to_string),
+        });
+    }
+}
+
+/// Every resource id (and key pair name) recorded by the given deployments.
+pub fn tracked_ids(deployments: &[DeploymentState]) -> HashSet<String> {
+    deployments
+        .iter()
+        .flat_map(|d| {
+            [
+                &d.vpc_id,
+                &d.igw_id,
+                &d.subnet_id,
+                &d.route_table_id,
+                &d.security_group_id,
+                &d.key_pair_name,
+                &d.instance_id,
+                &d.allocation_id,
+            ]
+        })
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Estimated monthly cost of an instance type in the given state. Unknown
+/// types count as zero rather than a guess; the root volume keeps billing
+/// while the instance is stopped.
+pub fn instance_monthly_cost(instance_type: &str, state: &str) -> f64 {
+    let compute = match state {
+        "pending" | "running" => aws_instance_hourly(instance_type)
+            .map(|hourly| hourly * HOURS_PER_MONTH)
+            .unwrap_or(0.0),
+        _ => 0.0,
+    };
+    compute + GP3_GB_MONTHLY_USD * ROOT_VOLUME_GB
+}
+
+impl AwsInventory {
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+            && self.addresses.is_empty()
+            && self.key_pairs.is_empty()
+            && self.security_groups.is_empty()
+            && self.subnets.is_empty()
+            && self.route_tables.is_empty()
+            && self.internet_gateways.is_empty()
+            && self.vpcs.is_empty()
+    }
+
+    /// Keep only resources whose id passes `keep`.
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.instances.retain(|r| keep(&r.id));
+        self.addresses.retain(|r| keep(&r.allocation_id));
+        for list in [
+            &mut self.key_pairs,
+            &mut self.security_groups,
+            &mut self.subnets,
+            &mut self.route_tables,
+            &mut self.internet_gateways,
+            &mut self.vpcs,
+        ] {
+            list.retain(|r| keep(&r.id));
+        }
+    }
+
+    /// Flatten into report rows with a cost estimate each.
+    pub fn resources(&self, region: &str) -> Vec<OrphanResource> {
+        let row = |kind: &str, id: &str, name: &Option<String>, detail: Option<String>, cost| {
+            OrphanResource {
+                kind: kind.to_string(),
+                id: id.to_string(),
+                region: region.to_string(),
+                name: name.clone(),
+                detail,
+                monthly_cost_usd: cost,
+            }
+        };
+
+        let mut rows = Vec::new();
+        for i in &self.instances {
+            let cost = instance_monthly_cost(&i.instance_type, &i.state);
+            let detail = format!("{}, {}", i.instance_type, i.state);
+            rows.push(row("instance", &i.id, &i.name, Some(detail), cost));
+        }
+        for a in &self.addresses {
+            let cost = PUBLIC_IPV4_HOURLY_USD * HOURS_PER_MONTH;
+            rows.push(row(
+                "elastic_ip",
+                &a.allocation_id,
+                &None,
+                a.public_ip.clone(),
+                cost,
+            ));
+        }
+        let free = [
+            ("key_pair", &self.key_pairs),
+            ("security_group", &self.security_groups),
+            ("subnet", &self.subnets),
+            ("route_table", &self.route_tables),
+            ("internet_gateway", &self.internet_gateways),
+            ("vpc", &self.vpcs),
+        ];
+        for (kind, list) in free {
+            for r in list {
+                let detail = match kind {
+                    "vpc" | "key_pair" => None,
+                    _ => r.vpc_id.clone(),
+                };
+                rows.push(row(kind, &r.id, &r.name, detail, 0.0));
+            }
+        }
+        rows
+    }
+
+    /// Split the inventory into teardown steps, in dependency order:
+    /// instances (with their Elastic IP), loose Elastic IPs, key pairs,
+    /// security groups, subnets, route tables, gateways whose VPC is kept,
+    /// then each VPC together with its internet gateway. `teardown_all`
+    /// deletes whatever fields are set, so every step only carries what it
+    /// should remove.
+    pub fn teardown_plan(&self) -> Vec<TeardownStep> {
+        let mut plan = Vec::new();
+        let mut released = HashSet::new();
+
+        for instance in &self.instances {
+            let mut step = DeploymentState {
+                instance_id: Some(instance.id.clone()),
+                ..Default::default()
+            };
+            let eip = self
+                .addresses
+                .iter()
+                .find(|a| a.instance_id.as_deref() == Some(instance.id.as_str()));
+            if let Some(eip) = eip {
+                step.allocation_id = Some(eip.allocation_id.clone());
+                step.association_id = eip.association_id.clone();
+                released.insert(eip.allocation_id.clone());
+            }
+            plan.push(TeardownStep::Resources(step));
+        }
+        for eip in self
+            .addresses
+            .iter()
+            .filter(|a| !released.contains(&a.allocation_id))
+        {
+            plan.push(TeardownStep::Resources(DeploymentState {
+                allocation_id: Some(eip.allocation_id.clone()),
+                association_id: eip.association_id.clone(),
+                ..Default::default()
+            }));
+        }
+        for key in &self.key_pairs {
+            plan.push(TeardownStep::Resources(DeploymentState {
+                key_pair_name: Some(key.id.clone()),
+                ..Default::default()
+            }));
+        }
+        for sg in &self.security_groups {
+            plan.push(TeardownStep::Resources(DeploymentState {
+                security_group_id: Some(sg.id.clone()),
+                ..Default::default()
+            }));
+        }
+        for subnet in &self.subnets {
+            plan.push(TeardownStep::Resources(DeploymentState {
+                subnet_id: Some(subnet.id.clone()),
+                ..Default::default()
+            }));
+        }
+        for rt in &self.route_tables {
+            plan.push(TeardownStep::Resources(DeploymentState {
+                route_table_id: Some(rt.id.clone()),
+                ..Default::default()
+            }));
+        }
+
+        // A gateway is detached as part of its VPC's step. Gateways whose VPC
+        // isn't being removed are only detached from it and deleted.
+        let vpc_ids: HashSet<&str> = self.vpcs.iter().map(|v| v.id.as_str()).collect();
+        for igw in &self.internet_gateways {
+            let attached_to_removed_vpc = igw
+                .vpc_id
+                .as_deref()
+                .is_some_and(|vpc| vpc_ids.contains(vpc));
+            if !attached_to_removed_vpc {
+                plan.push(TeardownStep::Gateway {
+                    igw_id: igw.id.clone(),
+                    vpc_id: igw.vpc_id.clone(),
+                });
+            }
+        }
+        for vpc in &self.vpcs {
+            let igw = self
+                .internet_gateways
+                .iter()
+                .find(|g| g.vpc_id.as_deref() == Some(vpc.id.as_str()));
+            plan.push(TeardownStep::Resources(DeploymentState {
+                vpc_id: Some(vpc.id.clone()),
+                igw_id: igw.map(|g| g.id.clone()),
+                ..Default::default()
+            }));
+        }
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn res(id: &str, vpc: Option<&str>) -> ScannedResource {
+        ScannedResource {
+            id: id.into(),
+            name: None,
+            vpc_id: vpc.map(String::from),
+        }
+    }
+
+    fn resources(step: &TeardownStep) -> &DeploymentState {
+        match step {
+            TeardownStep::Resources(state) => state,
+            other => panic!("expected a resources step, got {:?}", other),
+        }
+    }
+
+    fn one_deployment() -> AwsInventory {
+        AwsInventory {
+            instances: vec![ScannedInstance {
+                id: "i-1".into(),
+                name: Some("createmyvpn-vpn-server".into()),
+                instance_type: "t2.micro".into(),
+                state: "running".into(),
+            }],
+            addresses: vec![ScannedAddress {
+                allocation_id: "eipalloc-1".into(),
+                public_ip: Some("54.1.2.3".into()),
+                association_id: Some("eipassoc-1".into()),
+                instance_id: Some("i-1".into()),
+            }],
+            key_pairs: vec![res("createmyvpn-key-ab12", None)],
+            security_groups: vec![res("sg-1", Some("vpc-1"))],
+            subnets: vec![res("subnet-1", Some("vpc-1"))],
+            route_tables: vec![res("rtb-1", Some("vpc-1"))],
+            internet_gateways: vec![res("igw-1", Some("vpc-1"))],
+            vpcs: vec![res("vpc-1", Some("vpc-1"))],
+        }
+    }
+
+    #[test]
+    fn plan_follows_dependency_order() {
+        let steps = one_deployment().teardown_plan();
+        let plan: Vec<_> = steps.iter().map(resources).collect();
+        assert_eq!(plan.len(), 6);
+        assert_eq!(plan[0].instance_id.as_deref(), Some("i-1"));
+        assert_eq!(plan[0].allocation_id.as_deref(), Some("eipalloc-1"));
+        assert_eq!(plan[0].association_id.as_deref(), Some("eipassoc-1"));
+        assert_eq!(
+            plan[1].key_pair_name.as_deref(),
+            Some("createmyvpn-key-ab12")
+        );
+        assert_eq!(plan[2].security_group_id.as_deref(), Some("sg-1"));
+        assert_eq!(plan[3].subnet_id.as_deref(), Some("subnet-1"));
+        assert_eq!(plan[4].route_table_id.as_deref(), Some("rtb-1"));
+        assert_eq!(plan[5].vpc_id.as_deref(), Some("vpc-1"));
+        assert_eq!(plan[5].igw_id.as_deref(), Some("igw-1"));
+        // Only the last step may delete a VPC.
+        assert!(plan[..5].iter().all(|s| s.vpc_id.is_none()));
+    }
+
+    #[test]
+    fn loose_eip_and_gateway_get_their_own_steps() {
+        let mut inv = one_deployment();
+        inv.retain(|id| id == "eipalloc-1" || id == "igw-1");
+        let plan = inv.teardown_plan();
+        assert_eq!(plan.len(), 2);
+        let eip = resources(&plan[0]);
+        assert_eq!(eip.allocation_id.as_deref(), Some("eipalloc-1"));
+        assert!(eip.instance_id.is_none());
+        // The gateway is detached from the VPC it is in, which is kept.
+        match &plan[1] {
+            TeardownStep::Gateway { igw_id, vpc_id } => {
+                assert_eq!(igw_id, "igw-1");
+                assert_eq!(vpc_id.as_deref(), Some("vpc-1"));
+            }
+            other => panic!("expected a gateway step, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tracked_resources_are_left_alone() {
+        let tracked = tracked_ids(&[DeploymentState {
+            instance_id: Some("i-1".into()),
+            vpc_id: Some("vpc-1".into()),
+            key_pair_name: Some("createmyvpn-key-ab12".into()),
+            ..Default::default()
+        }]);
+        let mut inv = one_deployment();
+        inv.retain(|id| !tracked.contains(id));
+        assert!(inv.instances.is_empty());
+        assert!(inv.vpcs.is_empty());
+        assert!(inv.key_pairs.is_empty());
+        assert_eq!(inv.subnets.len(), 1);
+        assert!(!inv.is_empty());
+    }
+
+    #[test]
+    fn costs_running_instances_and_public_ips() {
+        let rows = one_deployment().resources("us-east-1");
+        assert_eq!(rows.len(), 8);
+        let cost = |kind: &str| {
+            rows.iter()
+                .find(|r| r.kind == kind)
+                .unwrap()
+                .monthly_cost_usd
+        };
+        assert!((cost("instance") - (0.0116 * 730.0 + 1.6)).abs() < 1e-9);
+        assert!((cost("elastic_ip") - 3.65).abs() < 1e-9);
+        assert_eq!(cost("vpc"), 0.0);
+        assert!(rows.iter().all(|r| r.region == "us-east-1"));
+    }
+
+    #[test]
+    fn stopped_and_unknown_instances_cost_only_storage() {
+        assert!((instance_monthly_cost("t2.micro", "stopped") - 1.6).abs() < 1e-9);
+        assert!((instance_monthly_cost("m7i.48xlarge", "running") - 1.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,407 @@
+//! `CloudProvider` for AWS. Every deployment gets its own VPC, security group
+//! and key pair, and an Elastic IP on a single EC2 instance.
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{Filter, LocationType};
+
+use crate::aws::{ami, client, ec2, security_group, teardown, vpc};
+use crate::commands::settings::aws_regions;
+use crate::error::AppError;
+use crate::progress::ProgressSink;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+use crate::server_image::{Architecture, OsRelease, ServerImage};
+use crate::state::{AwsCredentials, DeploymentState};
+
+pub struct AwsProvider {
+    creds: AwsCredentials,
+    region: String,
+}
+
+impl AwsProvider {
+    pub fn new(creds: AwsCredentials, region: &str) -> Self {
+        AwsProvider {
+            creds,
+            region: region.to_string(),
+        }
+    }
+
+    async fn config(&self) -> Result<SdkConfig, AppError> {
+        client::build_config(&self.creds, &self.region).await
+    }
+
+    async fn ec2(&self) -> Result<aws_sdk_ec2::Client, AppError> {
+        Ok(aws_sdk_ec2::Client::new(&self.config().await?))
+    }
+}
+
+/// The instance types the app deploys on, smallest first.
+pub const INSTANCE_TYPES: [(&str, &str); 5] = [
+    ("t2.micro", "1 vCPU, 1GB (Free Tier)"),
+    ("t3.micro", "2 vCPU, 1GB"),
+    ("t3.small", "2 vCPU, 2GB"),
+    ("t4g.micro", "2 vCPU, 1GB (Graviton, arm64)"),
+    ("t4g.small", "2 vCPU, 2GB (Graviton, arm64)"),
+];
+
+/// The CPUs of `instance_type`. Graviton families carry a "g" after the
+/// generation, e.g. t4g, m7gd or c6gn; a1 is the first generation.
+pub fn instance_architecture(instance_type: &str) -> Architecture {
+    let family = instance_type.split('.').next().unwrap_or_default();
+    let attributes = family.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    let graviton = family == "a1"
+        || attributes
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .contains('g');
+    if graviton {
+        Architecture::Arm64
+    } else {
+        Architecture::Amd64
+    }
+}
+
+fn recorded(field: &Option<String>, what: &str) -> Result<String, AppError> {
+    field
+        .clone()
+        .ok_or_else(|| AppError::State(format!("No {} recorded for this deployment", what)))
+}
+
+#[async_trait]
+impl CloudProvider for AwsProvider {
+    fn mode(&self) -> &'static str {
+        "aws"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "AWS"
+    }
+
+    /// The default user of the official images.
+    fn ssh_user(&self, image: &ServerImage) -> &'static str {
+        match image.os {
+            OsRelease::Debian12 => "admin",
+            OsRelease::Ubuntu2204 | OsRelease::Ubuntu2404 => "ubuntu",
+        }
+    }
+
+    /// Canonical and Debian publish every release for both architectures.
+    fn check_image(&self, image: &ServerImage, size: &str) -> Result<(), AppError> {
+        image.check_size(size, instance_architecture(size))
+    }
+
+    async fn validate_credentials(&self) -> Result<String, AppError> {
+        client::validate_credentials(&self.creds, &self.region).await
+    }
+
+    /// The regions enabled for the account; opt-in regions that were never
+    /// enabled are left out by `DescribeRegions`.
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError> {
+        let resp = self
+            .ec2()
+            .await?
+            .describe_regions()
+            .send()
+            .await
+            .map_err(|e| AppError::Aws(format!("Failed to list regions: {}", e)))?;
+        let names = aws_regions();
+        let mut regions: Vec<Region> = resp
+            .regions()
+            .iter()
+            .filter_map(|r| r.region_name())
+            .map(|code| Region {
+                code: code.to_string(),
+                name: names
+                    .iter()
+                    .find(|r| r.code == code)
+                    .map_or_else(|| code.to_string(), |r| r.name.clone()),
+            })
+            .collect();
+        regions.sort_by(|a, b| a.code.cmp(&b.code));
+        Ok(regions)
+    }
+
+    /// The supported instance types `region` offers.
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError> {
+        let config = client::build_config(&self.creds, region).await?;
+        let resp = aws_sdk_ec2::Client::new(&config)
+            .describe_instance_type_offerings()
+            .location_type(LocationType::Region)
+            .filters(
+                Filter::builder()
+                    .name("instance-type")
+                    .set_values(Some(
+                        INSTANCE_TYPES
+                            .iter()
+                            .map(|(code, _)| code.to_string())
+                            .collect(),
+                    ))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| AppError::Aws(format!("Failed to list instance types: {}", e)))?;
+        let offered: Vec<&str> = resp
+            .instance_type_offerings()
+            .iter()
+            .filter_map(|o| o.instance_type())
+            .map(|t| t.as_str())
+            .collect();
+        Ok(INSTANCE_TYPES
+            .iter()
+            .filter(|(code, _)| offered.contains(code))
+            .map(|(code, description)| ServerSize {
+                code: code.to_string(),
+                description: description.to_string(),
+                monthly_price: None,
+            })
+            .collect())
+    }
+
+    /// Creates the VPC, internet gateway, subnet and route table the
+    /// instance needs, then the security group.
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError> {
+        let ec2_client = self.ec2().await?;
+
+        let vpc_id = match state.vpc_id.clone() {
+            Some(id) => {
+                tracing::info!("[AWS] Reusing VPC: {}", id);
+                id
+            }
+            None => {
+                let vpc_id = vpc::create_vpc(&ec2_client).await?;
+                tracing::info!("[AWS] VPC created: {}", vpc_id);
+                state.vpc_id = Some(vpc_id.clone());
+                vpc_id
+            }
+        };
+
+        let igw_id = match state.igw_id.clone() {
+            Some(id) => {
+                tracing::info!("[AWS] Reusing IGW: {}", id);
+                id
+            }
+            None => {
+                let igw_id = vpc::create_internet_gateway(&ec2_client, &vpc_id).await?;
+                tracing::info!("[AWS] IGW created: {}", igw_id);
+                state.igw_id = Some(igw_id.clone());
+                igw_id
+            }
+        };
+
+        let subnet_id = match state.subnet_id.clone() {
+            Some(id) => {
+                tracing::info!("[AWS] Reusing subnet: {}", id);
+                id
+            }
+            None => {
+                let vpc_ipv6_cidr = vpc::wait_for_vpc_ipv6_cidr(&ec2_client, &vpc_id).await?;
+                let subnet_ipv6_cidr = vpc::first_ipv6_subnet(&vpc_ipv6_cidr)?;
+                tracing::info!(
+                    "[AWS] Creating subnet in {}a (IPv6 {})",
+                    self.region,
+                    subnet_ipv6_cidr
+                );
+                let subnet_id =
+                    vpc::create_subnet(&ec2_client, &vpc_id, &self.region, &subnet_ipv6_cidr)
+                        .await?;
+                tracing::info!("[AWS] Subnet created: {}", subnet_id);
+                state.subnet_id = Some(subnet_id.clone());
+                subnet_id
+            }
+        };
+
+        if let Some(ref rt_id) = state.route_table_id {
+            tracing::info!("[AWS] Reusing route table: {}", rt_id);
+        } else {
+            let rt_id = vpc::create_route_table(&ec2_client, &vpc_id, &igw_id, &subnet_id).await?;
+            tracing::info!("[AWS] Route table created: {}", rt_id);
+            state.route_table_id = Some(rt_id);
+        }
+
+        if let Some(ref sg_id) = state.security_group_id {
+            tracing::info!("[AWS] Reusing security group: {}", sg_id);
+        } else {
+            let sg_id = security_group::create_security_group(
+                &ec2_client,
+                &vpc_id,
+                wireguard_port,
+                provider::firewall_ssh_source(state)?,
+            )
+            .await?;
+            tracing::info!("[AWS] Security group created: {}", sg_id);
+            state.security_group_id = Some(sg_id);
+        }
+        Ok(())
+    }
+
+    /// The rule old deployments opened SSH with covered IPv4 only.
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        _wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError> {
+        let sg_id = recorded(&state.security_group_id, "security group")?;
+        let current = provider::current_ssh_sources(state).and_then(|s| s.into_iter().next());
+        security_group::set_ssh_access(&self.ec2().await?, &sg_id, current.as_deref(), source).await
+    }
+
+    /// AWS generates the key pair and hands back the private key once.
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError> {
+        if let (Some(name), Some(_)) = (&state.key_pair_name, &state.ssh_private_key) {
+            tracing::info!("[AWS] Reusing key pair: {}", name);
+            return Ok(());
+        }
+        let (key_name, private_key) = ec2::create_key_pair(&self.ec2().await?).await?;
+        tracing::info!("[AWS] Key pair created: {}", key_name);
+        state.key_pair_name = Some(key_name);
+        state.ssh_private_key = Some(private_key);
+        Ok(())
+    }
+
+    fn runs_user_data(&self) -> bool {
+        true
+    }
+
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        user_data: Option<&str>,
+    ) -> Result<(), AppError> {
+        if let Some(ref id) = state.instance_id {
+            tracing::info!("[AWS] Reusing instance: {}", id);
+            return Ok(());
+        }
+        let subnet_id = recorded(&state.subnet_id, "subnet")?;
+        let sg_id = recorded(&state.security_group_id, "security group")?;
+        let key_name = recorded(&state.key_pair_name, "key pair")?;
+
+        let image = state.server_image();
+        let config = self.config().await?;
+        tracing::info!("[AWS] Looking up latest {} AMI in {}", image, self.region);
+        let ami = ami::lookup_ami(&config, &image).await?;
+        tracing::info!(
+            "[AWS] Launching EC2 instance (ami={}, type={}, subnet={}, sg={})",
+            ami.id,
+            size,
+            subnet_id,
+            sg_id
+        );
+        let instance_id = ec2::launch_instance(
+            &aws_sdk_ec2::Client::new(&config),
+            &ami,
+            size,
+            &subnet_id,
+            &sg_id,
+            &key_name,
+            user_data,
+        )
+        .await?;
+        tracing::info!("[AWS] Instance launched: {}", instance_id);
+        state.instance_id = Some(instance_id);
+        Ok(())
+    }
+
+    /// Waits for the instance to run, then gives it an Elastic IP so the
+    /// address survives a stop and start.
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError> {
+        let instance_id = recorded(&state.instance_id, "instance")?;
+        let ec2_client = self.ec2().await?;
+
+        tracing::info!(
+            "[AWS] Waiting for instance {} to reach running state...",
+            instance_id
+        );
+        ec2::wait_for_instance_running(&ec2_client, &instance_id).await?;
+        tracing::info!("[AWS] Instance {} is running", instance_id);
+
+        if let (Some(alloc_id), Some(ip)) = (&state.allocation_id, &state.elastic_ip) {
+            tracing::info!("[AWS] Reusing EIP: {} (alloc={})", ip, alloc_id);
+            return Ok(ip.clone());
+        }
+        let (alloc_id, assoc_id, elastic_ip) =
+            ec2::allocate_and_associate_eip(&ec2_client, &instance_id).await?;
+        tracing::info!(
+            "[AWS] EIP allocated: {} (alloc={}, assoc={})",
+            elastic_ip,
+            alloc_id,
+            assoc_id
+        );
+        state.allocation_id = Some(alloc_id);
+        state.association_id = Some(assoc_id);
+        state.elastic_ip = Some(elastic_ip.clone());
+        Ok(elastic_ip)
+    }
+
+    /// Read from the console log, once cloud-init has published them.
+    async fn host_key_fingerprints(&self, state: &DeploymentState) -> Vec<String> {
+        let Some(ref instance_id) = state.instance_id else {
+            return Vec::new();
+        };
+        let result = match self.ec2().await {
+            Ok(ec2_client) => ec2::get_host_key_fingerprints(&ec2_client, instance_id).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(fps) if !fps.is_empty() => {
+                tracing::info!("[AWS] Host key fingerprints from console output: {:?}", fps);
+                fps
+            }
+            Ok(_) => {
+                tracing::info!(
+                    "[AWS] Console output not available yet — trusting host key on first use"
+                );
+                Vec::new()
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "[AWS] Could not read console output ({}) — trusting host key on first use",
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError> {
+        progress.emit(1, 3, "Connecting to AWS...", "running");
+        tracing::info!("[Destroy 1/3] Connecting to AWS in {}...", self.region);
+        let ec2_client = self.ec2().await?;
+        tracing::info!("[Destroy 1/3] AWS connection established");
+
+        progress.emit(2, 3, "Destroying infrastructure...", "running");
+        tracing::info!("[Destroy 2/3] Tearing down all AWS resources...");
+        teardown::teardown_all(&ec2_client, state).await?;
+        tracing::info!("[Destroy 2/3] All AWS resources destroyed");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graviton_instance_types_are_arm64() {
+        for arm in [
+            "t4g.micro",
+            "m7gd.large",
+            "c6gn.medium",
+            "a1.medium",
+            "im4gn.large",
+        ] {
+            assert_eq!(instance_architecture(arm), Architecture::Arm64, "{}", arm);
+        }
+        for x86 in ["t2.micro", "t3a.small", "g4dn.xlarge", "m5zn.large"] {
+            assert_eq!(instance_architecture(x86), Architecture::Amd64, "{}", x86);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! Picks an interrupted AWS deploy up where it stopped. Every resource id is
+//! saved as soon as it is created, so resuming checks which of them still
+//! exist and rolls the state back to the first one that doesn't; `deploy`
+//! then reuses everything before that point and creates the rest.
+use std::collections::HashSet;
+
+use aws_sdk_ec2::types::Filter;
+use aws_sdk_ec2::Client;
+
+use crate::error::AppError;
+use crate::state::DeploymentState;
+
+/// The deploy step (see `commands::deploy::run_steps`) that records each
+/// resource, in creation order. The VPC, networking and security group all
+/// come from the firewall step.
+const VPC_STEP: u32 = 2;
+const NETWORK_STEP: u32 = 2;
+const SECURITY_GROUP_STEP: u32 = 2;
+const KEY_PAIR_STEP: u32 = 3;
+const INSTANCE_STEP: u32 = 4;
+const ELASTIC_IP_STEP: u32 = 5;
+/// WireGuard is configured over SSH; its keys only mean something for the
+/// instance they were installed on. Keys baked into the instance's user
+/// data instead exist from the instance step on.
+const CONFIGURE_STEP: u32 = 6;
+
+fn filter(name: &str, value: &str) -> Filter {
+    Filter::builder().name(name).values(value).build()
+}
+
+fn describe_error(what: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::Aws(format!("Failed to look up {}: {}", what, e))
+}
+
+/// Which of the recorded resource ids still exist. Lookups use filters, so a
+/// deleted resource comes back as an empty list rather than a NotFound error.
+/// Instances only count while pending or running.
+pub async fn existing_ids(
+    ec2: &Client,
+    state: &DeploymentState,
+) -> Result<HashSet<String>, AppError> {
+    let mut found = HashSet::new();
+
+    if let Some(id) = &state.vpc_id {
+        let resp = ec2
+            .describe_vpcs()
+            .filters(filter("vpc-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("VPC", e))?;
+        found.extend(
+            resp.vpcs()
+                .iter()
+                .filter_map(|v| v.vpc_id())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.igw_id {
+        let resp = ec2
+            .describe_internet_gateways()
+            .filters(filter("internet-gateway-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("internet gateway", e))?;
+        found.extend(
+            resp.internet_gateways()
+                .iter()
+                .filter_map(|g| g.internet_gateway_id())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.subnet_id {
+        let resp = ec2
+            .describe_subnets()
+            .filters(filter("subnet-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("subnet", e))?;
+        found.extend(
+            resp.subnets()
+                .iter()
+                .filter_map(|s| s.subnet_id())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.route_table_id {
+        let resp = ec2
+            .describe_route_tables()
+            .filters(filter("route-table-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("route table", e))?;
+        found.extend(
+            resp.route_tables()
+                .iter()
+                .filter_map(|r| r.route_table_id())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.security_group_id {
+        let resp = ec2
+            .describe_security_groups()
+            .filters(filter("group-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("security group", e))?;
+        found.extend(
+            resp.security_groups()
+                .iter()
+                .filter_map(|g| g.group_id())
+                .map(String::from),
+        );
+    }
+    if let Some(name) = &state.key_pair_name {
+        let resp = ec2
+            .describe_key_pairs()
+            .filters(filter("key-name", name))
+            .send()
+            .await
+            .map_err(|e| describe_error("key pair", e))?;
+        found.extend(
+            resp.key_pairs()
+                .iter()
+                .filter_map(|k| k.key_name())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.instance_id {
+        let resp = ec2
+            .describe_instances()
+            .filters(filter("instance-id", id))
+            .filters(
+                Filter::builder()
+                    .name("instance-state-name")
+                    .values("pending")
+                    .values("running")
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| describe_error("instance", e))?;
+        found.extend(
+            resp.reservations()
+                .iter()
+                .flat_map(|r| r.instances())
+                .filter_map(|i| i.instance_id())
+                .map(String::from),
+        );
+    }
+    if let Some(id) = &state.allocation_id {
+        let resp = ec2
+            .describe_addresses()
+            .filters(filter("allocation-id", id))
+            .send()
+            .await
+            .map_err(|e| describe_error("Elastic IP", e))?;
+        // An address that lost its association has to be associated again.
+        found.extend(
+            resp.addresses()
+                .iter()
+                .filter(|a| a.association_id().is_some())
+                .filter_map(|a| a.allocation_id())
+                .map(String::from),
+        );
+    }
+
+    Ok(found)
+}
+
+/// Roll `state` back to the first recorded resource that no longer exists:
+/// that resource and everything created after it are forgotten so the deploy
+/// creates them again, and `completed_step` drops to the step before. Returns
+/// the forgotten ids that do still exist — they are left behind in the
+/// account (the leftover resource scan finds them).
+pub fn prune_missing(state: &mut DeploymentState, existing: &HashSet<String>) -> Vec<String> {
+    let mut recorded: Vec<(u32, Option<String>)> = vec![
+        (VPC_STEP, state.vpc_id.clone()),
+        (NETWORK_STEP, state.igw_id.clone()),
+        (NETWORK_STEP, state.subnet_id.clone()),
+        (NETWORK_STEP, state.route_table_id.clone()),
+        (SECURITY_GROUP_STEP, state.security_group_id.clone()),
+        // Without the private key the key pair is of no use.
+        (
+            KEY_PAIR_STEP,
+            state
+                .key_pair_name
+                .clone()
+                .filter(|_| state.ssh_private_key.is_some()),
+        ),
+        (INSTANCE_STEP, state.instance_id.clone()),
+        (ELASTIC_IP_STEP, state.allocation_id.clone()),
+    ];
+    // A step that finished without recording its id can't be trusted either.
+    for (step, id) in &mut recorded {
+        if *step <= state.completed_step && id.is_none() {
+            *id = Some(String::new());
+        }
+    }
+
+    let Some(first_missing) = recorded
+        .iter()
+        .position(|(_, id)| id.as_ref().is_some_and(|id| !existing.contains(id)))
+    else {
+        return Vec::new();
+    };
+    let step = recorded[first_missing].0;
+    state.completed_step = state.completed_step.min(step - 1);
+
+    let mut abandoned = Vec::new();
+    let mut forget = |field: &mut Option<String>| {
+        if let Some(id) = field.take() {
+            if existing.contains(&id) {
+                abandoned.push(id);
+            }
+        }
+    };
+    let fields: [&mut Option<String>; 8] = [
+        &mut state.vpc_id,
+        &mut state.igw_id,
+        &mut state.subnet_id,
+        &mut state.route_table_id,
+        &mut state.security_group_id,
+        &mut state.key_pair_name,
+        &mut state.instance_id,
+        &mut state.allocation_id,
+    ];
+    for field in fields.into_iter().skip(first_missing) {
+        forget(field);
+    }
+
+    if state.key_pair_name.is_none() {
+        state.ssh_private_key = None;
+    }
+    if state.allocation_id.is_none() {
+        state.association_id = None;
+        state.elastic_ip = None;
+    }
+    let keys_step = if state.user_data_bootstrap {
+        INSTANCE_STEP
+    } else {
+        CONFIGURE_STEP
+    };
+    if state.completed_step < CONFIGURE_STEP {
+        state.ssh_host_key_fingerprint = None;
+        state.client_config = None;
+    }
+    if state.completed_step < keys_step {
+        state.server_public_key = None;
+        state.client_private_key = None;
+        state.client_public_key = None;
+    }
+    abandoned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interrupted_after_instance() -> DeploymentState {
+        DeploymentState {
+            completed_step: 4,
+            vpc_id: Some("vpc-1".into()),
+            igw_id: Some("igw-1".into()),
+            subnet_id: Some("subnet-1".into()),
+            route_table_id: Some("rtb-1".into()),
+            security_group_id: Some("sg-1".into()),
+            key_pair_name: Some("createmyvpn-key-ab12".into()),
+            ssh_private_key: Some("PEM".into()),
+            instance_id: Some("i-1".into()),
+            ..Default::default()
+        }
+    }
+
+    fn all_of(state: &DeploymentState) -> HashSet<String> {
+        [
+            &state.vpc_id,
+            &state.igw_id,
+            &state.subnet_id,
+            &state.route_table_id,
+            &state.security_group_id,
+            &state.key_pair_name,
+            &state.instance_id,
+            &state.allocation_id,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect()
+    }
+
+    #[test]
+    fn keeps_everything_that_still_exists() {
+        let mut state = interrupted_after_instance();
+        let existing = all_of(&state);
+        assert!(prune_missing(&mut state, &existing).is_empty());
+        assert_eq!(state.completed_step, 4);
+        assert_eq!(state.instance_id.as_deref(), Some("i-1"));
+    }
+
+    #[test]
+    fn terminated_instance_is_launched_again() {
+        let mut state = interrupted_after_instance();
+        let mut existing = all_of(&state);
+        existing.remove("i-1");
+        assert!(prune_missing(&mut state, &existing).is_empty());
+        assert_eq!(state.completed_step, 3);
+        assert!(state.instance_id.is_none());
+        assert_eq!(state.security_group_id.as_deref(), Some("sg-1"));
+        assert_eq!(state.ssh_private_key.as_deref(), Some("PEM"));
+    }
+
+    #[test]
+    fn user_data_keys_stay_with_their_instance() {
+        let mut state = DeploymentState {
+            completed_step: 5,
+            user_data_bootstrap: true,
+            allocation_id: Some("eipalloc-1".into()),
+            server_public_key: Some("SRV_PUB".into()),
+            client_private_key: Some("CLI_PRIV".into()),
+            ..interrupted_after_instance()
+        };
+        let mut existing = all_of(&state);
+        existing.remove("eipalloc-1");
+        prune_missing(&mut state, &existing);
+        assert_eq!(state.completed_step, 4);
+        assert_eq!(state.server_public_key.as_deref(), Some("SRV_PUB"));
+
+        existing.remove("i-1");
+        prune_missing(&mut state, &existing);
+        assert_eq!(state.completed_step, 3);
+        assert!(state.server_public_key.is_none());
+        assert!(state.client_private_key.is_none());
+    }
+
+    #[test]
+    fn missing_subnet_forgets_later_resources() {
+        let mut state = interrupted_after_instance();
+        let mut existing = all_of(&state);
+        existing.remove("subnet-1");
+        let abandoned = prune_missing(&mut state, &existing);
+        assert_eq!(state.completed_step, 1);
+        assert_eq!(state.igw_id.as_deref(), Some("igw-1"));
+        assert!(state.subnet_id.is_none());
+        assert!(state.instance_id.is_none());
+        assert!(state.ssh_private_key.is_none());
+        assert_eq!(
+            abandoned,
+            vec!["rtb-1", "sg-1", "createmyvpn-key-ab12", "i-1"]
+        );
+    }
+
+    #[test]
+    fn partial_step_keeps_what_it_recorded() {
+        // Interrupted halfway through step 2: IGW created, subnet not yet.
+        let mut state = DeploymentState {
+            completed_step: 1,
+            vpc_id: Some("vpc-1".into()),
+            igw_id: Some("igw-1".into()),
+            ..Default::default()
+        };
+        let existing = all_of(&state);
+        assert!(prune_missing(&mut state, &existing).is_empty());
+        assert_eq!(state.completed_step, 1);
+        assert_eq!(state.igw_id.as_deref(), Some("igw-1"));
+    }
+
+    #[test]
+    fn key_pair_without_private_key_is_recreated() {
+        let mut state = interrupted_after_instance();
+        state.ssh_private_key = None;
+        let existing = all_of(&state);
+        let abandoned = prune_missing(&mut state, &existing);
+        assert_eq!(state.completed_step, 2);
+        assert!(state.key_pair_name.is_none());
+        assert_eq!(abandoned, vec!["createmyvpn-key-ab12", "i-1"]);
+    }
+
+    #[test]
+    fn lost_elastic_ip_drops_wireguard_setup() {
+        let mut state = interrupted_after_instance();
+        state.completed_step = 6;
+        state.allocation_id = Some("eipalloc-1".into());
+        state.elastic_ip = Some("54.1.2.3".into());
+        state.server_public_key = Some("server".into());
+        let mut existing = all_of(&state);
+        existing.remove("eipalloc-1");
+        prune_missing(&mut state, &existing);
+        assert_eq!(state.completed_step, 4);
+        assert!(state.elastic_ip.is_none());
+        assert!(state.server_public_key.is_none());
+    }
+}
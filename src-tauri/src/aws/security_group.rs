@@ -1,13 +1,15 @@
-use aws_sdk_ec2::types::{IpPermission, IpRange};
+use aws_sdk_ec2::types::{IpPermission, IpRange, Ipv6Range};
 use aws_sdk_ec2::Client;
 
 use crate::error::AppError;
 
-/// Create security group with SSH (TCP 22) + WireGuard (UDP 51820) inbound rules.
+/// Create security group with SSH (TCP 22) from `ssh_source` + WireGuard (UDP 51820)
+/// inbound rules. There is no SSH rule when `ssh_source` is `None`.
 pub async fn create_security_group(
     ec2: &Client,
     vpc_id: &str,
     wireguard_port: u16,
+    ssh_source: Option<&str>,
 ) -> Result<String, AppError> {
     let resp = ec2
         .create_security_group()
@@ -23,26 +25,22 @@ pub async fn create_security_group(
         .ok_or_else(|| AppError::Aws("Security group created but no ID returned".into()))?
         .to_string();
 
-    // SSH rule (TCP 22) - from anywhere for initial setup
-    let ssh_rule = IpPermission::builder()
-        .ip_protocol("tcp")
-        .from_port(22)
-        .to_port(22)
-        .ip_ranges(IpRange::builder().cidr_ip("0.0.0.0/0").description("SSH access").build())
-        .build();
-
     // WireGuard rule (UDP 51820)
     let wg_rule = IpPermission::builder()
         .ip_protocol("udp")
         .from_port(wireguard_port as i32)
         .to_port(wireguard_port as i32)
         .ip_ranges(IpRange::builder().cidr_ip("0.0.0.0/0").description("WireGuard VPN").build())
+        .ipv6_ranges(Ipv6Range::builder().cidr_ipv6("::/0").description("WireGuard VPN").build())
         .build();
 
+    // SSH rule (TCP 22) - from the deploying computer only
+    let mut rules = vec![wg_rule];
+    rules.extend(ssh_source.map(ssh_rule));
+
     ec2.authorize_security_group_ingress()
         .group_id(&sg_id)
-        .ip_permissions(ssh_rule)
-        .ip_permissions(wg_rule)
+        .set_ip_permissions(Some(rules))
         .send()
         .await
         .map_err(|e| AppError::Aws(format!("Failed to add ingress rules: {}", e)))?;
@@ -56,6 +54,12 @@ pub async fn create_security_group(
                 .value("createmyvpn-sg")
                 .build(),
         )
+        .tags(
+            aws_sdk_ec2::types::Tag::builder()
+                .key("ManagedBy")
+                .value("createmyvpn")
+                .build(),
+        )
         .send()
         .await
         .map_err(|e| AppError::Aws(format!("Failed to tag security group: {}", e)))?;
@@ -63,3 +67,40 @@ pub async fn create_security_group(
     tracing::info!("Created security group: {}", sg_id);
     Ok(sg_id)
 }
+
+fn ssh_rule(source: &str) -> IpPermission {
+    IpPermission::builder()
+        .ip_protocol("tcp")
+        .from_port(22)
+        .to_port(22)
+        .ip_ranges(IpRange::builder().cidr_ip(source).description("SSH access").build())
+        .build()
+}
+
+/// Revoke the SSH rule letting in `current` (`None` if there is none), then let SSH
+/// in from `ssh_source` unless it is `None`.
+pub async fn set_ssh_access(
+    ec2: &Client,
+    sg_id: &str,
+    current: Option<&str>,
+    ssh_source: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(current) = current {
+        ec2.revoke_security_group_ingress()
+            .group_id(sg_id)
+            .ip_permissions(ssh_rule(current))
+            .send()
+            .await
+            .map_err(|e| AppError::Aws(format!("Failed to revoke SSH access: {}", e)))?;
+    }
+    if let Some(source) = ssh_source {
+        ec2.authorize_security_group_ingress()
+            .group_id(sg_id)
+            .ip_permissions(ssh_rule(source))
+            .send()
+            .await
+            .map_err(|e| AppError::Aws(format!("Failed to allow SSH access: {}", e)))?;
+    }
+    tracing::info!("SSH access on {} set to {}", sg_id, ssh_source.unwrap_or("none"));
+    Ok(())
+}
@@ -1,68 +1,82 @@
+use aws_sdk_ec2::error::ProvideErrorMetadata;
 use aws_sdk_ec2::Client;
 
 use crate::error::AppError;
 use crate::state::DeploymentState;
 
+/// Whether AWS answered that the resource is already gone.
+fn already_gone(e: &impl ProvideErrorMetadata) -> bool {
+    e.code()
+        .is_some_and(|code| code.ends_with(".NotFound") || code == "Gateway.NotAttached")
+}
+
+/// Note a failed call in `failures`. A resource that is already gone counts
+/// as removed, so a retried teardown gets past what an earlier one deleted.
+fn check<T, E>(failures: &mut Vec<String>, what: String, result: Result<T, E>) -> bool
+where
+    E: ProvideErrorMetadata + std::fmt::Display,
+{
+    match result {
+        Ok(_) => true,
+        Err(e) if already_gone(&e) => true,
+        Err(e) => {
+            tracing::warn!("Failed to delete {}: {}", what, e);
+            failures.push(format!("{}: {}", what, e));
+            false
+        }
+    }
+}
+
 /// Ordered teardown of all AWS resources. Reads state to know what exists.
 /// Order: EIP association → EIP → Instance → Key pair → SG → Subnet → RT → IGW → VPC
+/// Every resource is attempted; failures are reported together at the end.
 pub async fn teardown_all(ec2: &Client, state: &DeploymentState) -> Result<(), AppError> {
+    let mut failures = Vec::new();
+
     // 1. Disassociate EIP
     if let Some(ref assoc_id) = state.association_id {
         tracing::info!("Disassociating EIP: {}", assoc_id);
-        let _ = ec2
+        let result = ec2
             .disassociate_address()
             .association_id(assoc_id)
             .send()
             .await;
+        check(
+            &mut failures,
+            format!("EIP association {}", assoc_id),
+            result,
+        );
     }
 
     // 2. Release EIP
     if let Some(ref alloc_id) = state.allocation_id {
         tracing::info!("Releasing EIP: {}", alloc_id);
-        let _ = ec2
-            .release_address()
-            .allocation_id(alloc_id)
-            .send()
-            .await;
+        let result = ec2.release_address().allocation_id(alloc_id).send().await;
+        check(&mut failures, format!("Elastic IP {}", alloc_id), result);
     }
 
     // 3. Terminate instance and wait
     if let Some(ref instance_id) = state.instance_id {
         tracing::info!("Terminating instance: {}", instance_id);
-        let _ = ec2
+        let result = ec2
             .terminate_instances()
             .instance_ids(instance_id)
             .send()
             .await;
-
-        // Wait for termination
-        for _ in 0..60 {
-            let resp = ec2
-                .describe_instances()
-                .instance_ids(instance_id)
-                .send()
-                .await;
-
-            if let Ok(resp) = resp {
-                if let Some(reservation) = resp.reservations().first() {
-                    if let Some(instance) = reservation.instances().first() {
-                        if let Some(s) = instance.state() {
-                            let name = s.name().map(|n| n.as_str()).unwrap_or("");
-                            if name == "terminated" {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        let what = format!("instance {}", instance_id);
+        if check(&mut failures, what.clone(), result)
+            && !wait_for_terminated(ec2, instance_id).await
+        {
+            tracing::warn!("Instance {} did not terminate in time", instance_id);
+            failures.push(format!("{}: still not terminated after 5 minutes", what));
         }
     }
 
     // 4. Delete key pair
     if let Some(ref key_name) = state.key_pair_name {
         tracing::info!("Deleting key pair: {}", key_name);
-        let _ = ec2.delete_key_pair().key_name(key_name).send().await;
+        let result = ec2.delete_key_pair().key_name(key_name).send().await;
+        check(&mut failures, format!("key pair {}", key_name), result);
     }
 
     // 5. Delete security group (retry with backoff - may need instance to fully terminate)
@@ -70,13 +84,12 @@ pub async fn teardown_all(ec2: &Client, state: &DeploymentState) -> Result<(), A
         tracing::info!("Deleting security group: {}", sg_id);
         for attempt in 0..10 {
             match ec2.delete_security_group().group_id(sg_id).send().await {
-                Ok(_) => break,
-                Err(e) => {
-                    if attempt == 9 {
-                        tracing::warn!("Failed to delete security group after retries: {}", e);
-                    } else {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    }
+                Err(e) if attempt < 9 && !already_gone(&e) => {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                }
+                result => {
+                    check(&mut failures, format!("security group {}", sg_id), result);
+                    break;
                 }
             }
         }
@@ -85,40 +98,114 @@ pub async fn teardown_all(ec2: &Client, state: &DeploymentState) -> Result<(), A
     // 6. Delete subnet
     if let Some(ref subnet_id) = state.subnet_id {
         tracing::info!("Deleting subnet: {}", subnet_id);
-        let _ = ec2.delete_subnet().subnet_id(subnet_id).send().await;
+        let result = ec2.delete_subnet().subnet_id(subnet_id).send().await;
+        check(&mut failures, format!("subnet {}", subnet_id), result);
     }
 
     // 7. Delete route table
     if let Some(ref rt_id) = state.route_table_id {
         tracing::info!("Deleting route table: {}", rt_id);
-        let _ = ec2.delete_route_table().route_table_id(rt_id).send().await;
+        let result = ec2.delete_route_table().route_table_id(rt_id).send().await;
+        check(&mut failures, format!("route table {}", rt_id), result);
     }
 
     // 8. Detach and delete IGW
     if let Some(ref igw_id) = state.igw_id {
-        if let Some(ref vpc_id) = state.vpc_id {
-            tracing::info!("Detaching IGW: {} from VPC: {}", igw_id, vpc_id);
-            let _ = ec2
-                .detach_internet_gateway()
-                .internet_gateway_id(igw_id)
-                .vpc_id(vpc_id)
-                .send()
-                .await;
-        }
-        tracing::info!("Deleting IGW: {}", igw_id);
-        let _ = ec2
-            .delete_internet_gateway()
-            .internet_gateway_id(igw_id)
-            .send()
-            .await;
+        remove_gateway(ec2, &mut failures, igw_id, state.vpc_id.as_deref()).await;
     }
 
     // 9. Delete VPC
     if let Some(ref vpc_id) = state.vpc_id {
         tracing::info!("Deleting VPC: {}", vpc_id);
-        let _ = ec2.delete_vpc().vpc_id(vpc_id).send().await;
+        let result = ec2.delete_vpc().vpc_id(vpc_id).send().await;
+        check(&mut failures, format!("VPC {}", vpc_id), result);
+    }
+
+    if failures.is_empty() {
+        tracing::info!("Teardown complete");
+    }
+    into_result(failures)
+}
+
+/// Detach and delete an internet gateway whose VPC is kept. `vpc_id` is the
+/// VPC it is attached to, if any.
+pub async fn teardown_gateway(
+    ec2: &Client,
+    igw_id: &str,
+    vpc_id: Option<&str>,
+) -> Result<(), AppError> {
+    let mut failures = Vec::new();
+    remove_gateway(ec2, &mut failures, igw_id, vpc_id).await;
+    into_result(failures)
+}
+
+/// Detach the gateway from `vpc_id`, if given, then delete it.
+async fn remove_gateway(
+    ec2: &Client,
+    failures: &mut Vec<String>,
+    igw_id: &str,
+    vpc_id: Option<&str>,
+) {
+    let what = format!("internet gateway {}", igw_id);
+    if let Some(vpc_id) = vpc_id {
+        tracing::info!("Detaching IGW: {} from VPC: {}", igw_id, vpc_id);
+        let result = ec2
+            .detach_internet_gateway()
+            .internet_gateway_id(igw_id)
+            .vpc_id(vpc_id)
+            .send()
+            .await;
+        if !check(failures, what.clone(), result) {
+            return;
+        }
+    }
+    tracing::info!("Deleting IGW: {}", igw_id);
+    let result = ec2
+        .delete_internet_gateway()
+        .internet_gateway_id(igw_id)
+        .send()
+        .await;
+    check(failures, what, result);
+}
+
+fn into_result(failures: Vec<String>) -> Result<(), AppError> {
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Aws(format!(
+            "Could not delete {}",
+            failures.join("; ")
+        )))
     }
+}
+
+/// Poll until the instance is terminated or no longer listed, for up to five
+/// minutes. Its security group can't be deleted before then.
+async fn wait_for_terminated(ec2: &Client, instance_id: &str) -> bool {
+    for _ in 0..60 {
+        let resp = ec2
+            .describe_instances()
+            .instance_ids(instance_id)
+            .send()
+            .await;
 
-    tracing::info!("Teardown complete");
-    Ok(())
+        match resp {
+            Ok(resp) => {
+                let state = resp
+                    .reservations()
+                    .first()
+                    .and_then(|r| r.instances().first())
+                    .and_then(|i| i.state())
+                    .and_then(|s| s.name())
+                    .map(|n| n.as_str());
+                if matches!(state, None | Some("terminated")) {
+                    return true;
+                }
+            }
+            Err(e) if already_gone(&e) => return true,
+            Err(_) => {}
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+    }
+    false
 }
@@ -10,10 +10,12 @@ pub struct VpcResources {
     pub route_table_id: String,
 }
 
+/// Create the VPC with an Amazon-provided /56 IPv6 block alongside 10.0.0.0/16.
 pub async fn create_vpc(ec2: &Client) -> Result<String, AppError> {
     let resp = ec2
         .create_vpc()
         .cidr_block("10.0.0.0/16")
+        .amazon_provided_ipv6_cidr_block(true)
         .send()
         .await
         .map_err(|e| AppError::Aws(format!("Failed to create VPC: {}", e)))?;
@@ -73,13 +75,75 @@ pub async fn create_internet_gateway(ec2: &Client, vpc_id: &str) -> Result<Strin
     Ok(igw_id)
 }
 
-pub async fn create_subnet(ec2: &Client, vpc_id: &str, region: &str) -> Result<String, AppError> {
+/// Wait for the VPC's Amazon-provided IPv6 block to finish associating and
+/// return it, e.g. "2600:1f18:abc:de00::/56".
+pub async fn wait_for_vpc_ipv6_cidr(ec2: &Client, vpc_id: &str) -> Result<String, AppError> {
+    for _ in 0..30 {
+        let resp = ec2
+            .describe_vpcs()
+            .vpc_ids(vpc_id)
+            .send()
+            .await
+            .map_err(|e| AppError::Aws(format!("Failed to describe VPC: {}", e)))?;
+
+        let cidr = resp
+            .vpcs()
+            .first()
+            .map(|v| v.ipv6_cidr_block_association_set())
+            .unwrap_or_default()
+            .iter()
+            .find(|a| {
+                a.ipv6_cidr_block_state().and_then(|s| s.state())
+                    == Some(&aws_sdk_ec2::types::VpcCidrBlockStateCode::Associated)
+            })
+            .and_then(|a| a.ipv6_cidr_block());
+        if let Some(cidr) = cidr {
+            tracing::info!("VPC IPv6 block: {}", cidr);
+            return Ok(cidr.to_string());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+
+    Err(AppError::Aws(format!(
+        "VPC {} has no IPv6 block after 60 seconds",
+        vpc_id
+    )))
+}
+
+/// The first /64 of a VPC's IPv6 block. The block's network address is also
+/// the first /64's, so only the prefix length changes.
+pub fn first_ipv6_subnet(vpc_cidr: &str) -> Result<String, AppError> {
+    let (addr, len) = vpc_cidr
+        .split_once('/')
+        .ok_or_else(|| AppError::Aws(format!("Invalid IPv6 CIDR: {}", vpc_cidr)))?;
+    let addr: std::net::Ipv6Addr = addr
+        .parse()
+        .map_err(|_| AppError::Aws(format!("Invalid IPv6 CIDR: {}", vpc_cidr)))?;
+    let len: u8 = len
+        .parse()
+        .map_err(|_| AppError::Aws(format!("Invalid IPv6 CIDR: {}", vpc_cidr)))?;
+    if len > 64 {
+        return Err(AppError::Aws(format!(
+            "IPv6 block {} is smaller than a /64",
+            vpc_cidr
+        )));
+    }
+    Ok(format!("{}/64", addr))
+}
+
+pub async fn create_subnet(
+    ec2: &Client,
+    vpc_id: &str,
+    region: &str,
+    ipv6_cidr: &str,
+) -> Result<String, AppError> {
     let az = format!("{}a", region);
 
     let resp = ec2
         .create_subnet()
         .vpc_id(vpc_id)
         .cidr_block("10.0.1.0/24")
+        .ipv6_cidr_block(ipv6_cidr)
         .availability_zone(&az)
         .send()
         .await
@@ -99,6 +163,18 @@ pub async fn create_subnet(ec2: &Client, vpc_id: &str, region: &str) -> Result<S
         .await
         .map_err(|e| AppError::Aws(format!("Failed to enable public IP on subnet: {}", e)))?;
 
+    // And a public IPv6 address, for IPv6 egress from the tunnel
+    ec2.modify_subnet_attribute()
+        .subnet_id(&subnet_id)
+        .assign_ipv6_address_on_creation(
+            aws_sdk_ec2::types::AttributeBooleanValue::builder()
+                .value(true)
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| AppError::Aws(format!("Failed to enable IPv6 on subnet: {}", e)))?;
+
     tag_resource(ec2, &subnet_id, "createmyvpn-subnet").await?;
 
     tracing::info!("Created subnet: {}", subnet_id);
@@ -133,6 +209,14 @@ pub async fn create_route_table(
         .await
         .map_err(|e| AppError::Aws(format!("Failed to create route: {}", e)))?;
 
+    ec2.create_route()
+        .route_table_id(&rt_id)
+        .destination_ipv6_cidr_block("::/0")
+        .gateway_id(igw_id)
+        .send()
+        .await
+        .map_err(|e| AppError::Aws(format!("Failed to create IPv6 route: {}", e)))?;
+
     // Associate with subnet
     ec2.associate_route_table()
         .route_table_id(&rt_id)
@@ -155,7 +239,8 @@ pub async fn create_all(ec2: &Client, config: &SdkConfig) -> Result<VpcResources
 
     let vpc_id = create_vpc(ec2).await?;
     let igw_id = create_internet_gateway(ec2, &vpc_id).await?;
-    let subnet_id = create_subnet(ec2, &vpc_id, &region).await?;
+    let ipv6_cidr = first_ipv6_subnet(&wait_for_vpc_ipv6_cidr(ec2, &vpc_id).await?)?;
+    let subnet_id = create_subnet(ec2, &vpc_id, &region, &ipv6_cidr).await?;
     let route_table_id = create_route_table(ec2, &vpc_id, &igw_id, &subnet_id).await?;
 
     Ok(VpcResources {
@@ -187,3 +272,23 @@ async fn tag_resource(ec2: &Client, resource_id: &str, name: &str) -> Result<(),
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_ipv6_subnet_narrows_vpc_block() {
+        assert_eq!(
+            first_ipv6_subnet("2600:1f18:abc:de00::/56").unwrap(),
+            "2600:1f18:abc:de00::/64"
+        );
+    }
+
+    #[test]
+    fn first_ipv6_subnet_rejects_bad_input() {
+        assert!(first_ipv6_subnet("2600:1f18::").is_err());
+        assert!(first_ipv6_subnet("not-an-ip/56").is_err());
+        assert!(first_ipv6_subnet("2600:1f18::/96").is_err());
+    }
+}
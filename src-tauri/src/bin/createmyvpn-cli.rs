@@ -0,0 +1,1317 @@
+//! Headless front end for CreateMyVpn, for servers and scripts.
+//!
+//! Runs the same deploy/destroy/connect code as the desktop app and shares its
+//! state in `~/.createmyvpn`, so a deployment made in one shows up in the other.
+//!
+//! Exit codes: 0 success, 1 operation failed, 2 bad usage, 3 nothing deployed.
+use std::process::ExitCode;
+
+use createmyvpn_lib::aws::provider::AwsProvider;
+use createmyvpn_lib::catalog;
+use createmyvpn_lib::commands::{byo, connect, deploy, destroy, logs, orphans, ssh_access};
+use createmyvpn_lib::do_cloud::provider::DoProvider;
+use createmyvpn_lib::error::AppError;
+use createmyvpn_lib::hetzner::provider::HetznerProvider;
+use createmyvpn_lib::linode::provider::LinodeProvider;
+use createmyvpn_lib::persistence::{store, vault};
+use createmyvpn_lib::pricing;
+use createmyvpn_lib::progress::ProgressSink;
+use createmyvpn_lib::provider::CloudProvider;
+use createmyvpn_lib::server_image::{Architecture, OsRelease, ServerImage};
+use createmyvpn_lib::state::{
+    AppSettings, AwsCredentials, DeploymentState, DoCredentials, HetznerCredentials,
+    LinodeCredentials, OrphanScan, VultrCredentials,
+};
+use createmyvpn_lib::vultr::provider::VultrProvider;
+use createmyvpn_lib::wireguard::tunnel;
+use createmyvpn_lib::wireguard::userspace::TunnelEvent;
+
+const USAGE: &str = "\
+Usage: createmyvpn-cli <command> [options]
+
+Commands:
+  deploy aws [--region R] [--instance-type T] [--auto-destroy-hours N]
+             [--id ID]
+  deploy do --region R [--size S] [--auto-destroy-hours N] [--id ID]
+  deploy hetzner --location L [--server-type T] [--auto-destroy-hours N]
+                 [--id ID]
+  deploy vultr --region R [--plan P] [--auto-destroy-hours N] [--id ID]
+  deploy linode --region R [--type T] [--auto-destroy-hours N] [--id ID]
+  deploy byo --host IP --key-file PATH [--user U] [--port P]
+             [--host-key-fingerprint SHA256:...] [--id ID]
+  resume [--id ID] [--auto-destroy-hours N]
+                     Continue a failed AWS deploy after its last
+                     completed step, reusing what it already created
+  destroy [--id ID]
+  status             List all deployments
+  connect [--id ID]  Bring the tunnel up and keep it up until Ctrl+C
+  disconnect         Stop a tunnel started with `connect`
+  config export [--id ID] [--output FILE]
+  ssh open [--id ID] Let SSH to the server in from this computer's
+                     current address, e.g. after it was locked down
+  ssh close [--id ID]
+                     Remove the SSH rule from the server's firewall
+  orphans aws [--clean REGION]
+                     List leftover tagged AWS resources no deployment
+                     tracks; --clean removes those in REGION
+  orphans do [--clean all|ID,ID,...]
+                     The same for DigitalOcean Droplets, firewalls and
+                     SSH keys; --clean removes all or the listed ids
+  logs
+
+Cloud deploys also take --os ubuntu-22.04|ubuntu-24.04|debian-12 and
+--arch amd64|arm64 (arm64 on AWS t4g.* and Hetzner cax* only); both
+default to the ones chosen in the desktop app's Settings.
+
+--id picks the deployment. It may be left out when only one exists;
+deploy generates a new id when none is given.
+
+Credentials are read from AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY,
+DIGITALOCEAN_TOKEN, HCLOUD_TOKEN, VULTR_API_KEY and LINODE_TOKEN,
+falling back to the ones saved by the desktop app.
+Without an OS keyring, secrets are encrypted with the passphrase in
+CREATEMYVPN_PASSPHRASE.";
+
+/// Unlocks the passphrase vault when the OS keyring isn't available.
+const PASSPHRASE_ENV: &str = "CREATEMYVPN_PASSPHRASE";
+
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_NOT_DEPLOYED: u8 = 3;
+
+/// Prints each progress step on its own line, and the server's command
+/// output indented below it.
+struct TerminalProgress;
+
+impl ProgressSink for TerminalProgress {
+    fn emit(&self, step: u32, total: u32, message: &str, status: &str) {
+        match status {
+            "error" => eprintln!("[{}/{}] error: {}", step, total, message),
+            _ => println!("[{}/{}] {}", step, total, message),
+        }
+    }
+
+    fn log(&self, line: &str) {
+        println!("      {}", line);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    DeployAws {
+        id: Option<String>,
+        region: Option<String>,
+        instance_type: Option<String>,
+        image: ImageOptions,
+        auto_destroy_hours: Option<u32>,
+    },
+    DeployDo {
+        id: Option<String>,
+        region: String,
+        size: String,
+        image: ImageOptions,
+        auto_destroy_hours: Option<u32>,
+    },
+    DeployHetzner {
+        id: Option<String>,
+        location: String,
+        server_type: String,
+        image: ImageOptions,
+        auto_destroy_hours: Option<u32>,
+    },
+    DeployVultr {
+        id: Option<String>,
+        region: String,
+        plan: String,
+        image: ImageOptions,
+        auto_destroy_hours: Option<u32>,
+    },
+    DeployLinode {
+        id: Option<String>,
+        region: String,
+        linode_type: String,
+        image: ImageOptions,
+        auto_destroy_hours: Option<u32>,
+    },
+    DeployByo {
+        id: Option<String>,
+        host: String,
+        key_file: String,
+        user: String,
+        port: u16,
+        host_key_fingerprint: Option<String>,
+        auto_destroy_hours: Option<u32>,
+    },
+    Resume {
+        id: Option<String>,
+        auto_destroy_hours: Option<u32>,
+    },
+    Destroy {
+        id: Option<String>,
+    },
+    Status,
+    Connect {
+        id: Option<String>,
+    },
+    Disconnect,
+    ConfigExport {
+        id: Option<String>,
+        output: Option<String>,
+    },
+    SshOpen {
+        id: Option<String>,
+    },
+    SshClose {
+        id: Option<String>,
+    },
+    OrphansAws {
+        clean_region: Option<String>,
+    },
+    OrphansDo {
+        clean: Option<String>,
+    },
+    Logs,
+}
+
+/// `--os` and `--arch` of a cloud deploy; the ones left out come from the
+/// settings.
+#[derive(Debug, Default, PartialEq)]
+struct ImageOptions {
+    os: Option<OsRelease>,
+    arch: Option<Architecture>,
+}
+
+impl ImageOptions {
+    fn resolve(&self, settings: &AppSettings) -> ServerImage {
+        ServerImage {
+            os: self.os.unwrap_or(settings.os_release),
+            arch: self.arch.unwrap_or(settings.architecture),
+        }
+    }
+}
+
+/// Parsed `--name value` pairs. Every option takes exactly one value.
+struct Options(Vec<(String, String)>);
+
+impl Options {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self, String> {
+        let mut pairs = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg
+                .strip_prefix("--")
+                .filter(|n| allowed.contains(n))
+                .ok_or_else(|| format!("unexpected argument '{}'", arg))?;
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("--{} needs a value", name))?;
+            pairs.push((name.to_string(), value.clone()));
+        }
+        Ok(Options(pairs))
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+    }
+
+    fn require(&self, name: &str) -> Result<String, String> {
+        self.get(name)
+            .ok_or_else(|| format!("--{} is required", name))
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.get(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("--{} must be a number, got '{}'", name, v))
+            })
+            .transpose()
+    }
+
+    fn image(&self) -> Result<ImageOptions, String> {
+        let os = self.get("os").map(|v| OsRelease::parse(&v)).transpose();
+        let arch = self
+            .get("arch")
+            .map(|v| Architecture::parse(&v))
+            .transpose();
+        Ok(ImageOptions {
+            os: os.map_err(|e| e.to_string())?,
+            arch: arch.map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let words: Vec<&str> = args.iter().map(String::as_str).collect();
+    match words.as_slice() {
+        ["deploy", "aws", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &[
+                    "id",
+                    "region",
+                    "instance-type",
+                    "os",
+                    "arch",
+                    "auto-destroy-hours",
+                ],
+            )?;
+            Ok(Command::DeployAws {
+                id: opts.get("id"),
+                region: opts.get("region"),
+                instance_type: opts.get("instance-type"),
+                image: opts.image()?,
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", "do", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &["id", "region", "size", "os", "arch", "auto-destroy-hours"],
+            )?;
+            Ok(Command::DeployDo {
+                id: opts.get("id"),
+                region: opts.require("region")?,
+                size: opts.get("size").unwrap_or_else(|| "s-1vcpu-1gb".into()),
+                image: opts.image()?,
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", "hetzner", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &[
+                    "id",
+                    "location",
+                    "server-type",
+                    "os",
+                    "arch",
+                    "auto-destroy-hours",
+                ],
+            )?;
+            Ok(Command::DeployHetzner {
+                id: opts.get("id"),
+                location: opts.require("location")?,
+                server_type: opts.get("server-type").unwrap_or_else(|| "cx22".into()),
+                image: opts.image()?,
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", "vultr", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &["id", "region", "plan", "os", "arch", "auto-destroy-hours"],
+            )?;
+            Ok(Command::DeployVultr {
+                id: opts.get("id"),
+                region: opts.require("region")?,
+                plan: opts.get("plan").unwrap_or_else(|| "vc2-1c-1gb".into()),
+                image: opts.image()?,
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", "linode", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &["id", "region", "type", "os", "arch", "auto-destroy-hours"],
+            )?;
+            Ok(Command::DeployLinode {
+                id: opts.get("id"),
+                region: opts.require("region")?,
+                linode_type: opts.get("type").unwrap_or_else(|| "g6-nanode-1".into()),
+                image: opts.image()?,
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", "byo", ..] => {
+            let opts = Options::parse(
+                &args[2..],
+                &[
+                    "id",
+                    "host",
+                    "key-file",
+                    "user",
+                    "port",
+                    "host-key-fingerprint",
+                    "auto-destroy-hours",
+                ],
+            )?;
+            Ok(Command::DeployByo {
+                id: opts.get("id"),
+                host: opts.require("host")?,
+                key_file: opts.require("key-file")?,
+                user: opts.get("user").unwrap_or_else(|| "ubuntu".into()),
+                port: opts.number("port")?.unwrap_or(22),
+                host_key_fingerprint: opts.get("host-key-fingerprint"),
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["deploy", ..] => {
+            Err("deploy needs a provider: aws, do, hetzner, vultr, linode or byo".into())
+        }
+        ["resume", ..] => {
+            let opts = Options::parse(&args[1..], &["id", "auto-destroy-hours"])?;
+            Ok(Command::Resume {
+                id: opts.get("id"),
+                auto_destroy_hours: opts.number("auto-destroy-hours")?,
+            })
+        }
+        ["destroy", ..] => {
+            let opts = Options::parse(&args[1..], &["id"])?;
+            Ok(Command::Destroy { id: opts.get("id") })
+        }
+        ["status"] => Ok(Command::Status),
+        ["connect", ..] => {
+            let opts = Options::parse(&args[1..], &["id"])?;
+            Ok(Command::Connect { id: opts.get("id") })
+        }
+        ["disconnect"] => Ok(Command::Disconnect),
+        ["config", "export", ..] => {
+            let opts = Options::parse(&args[2..], &["id", "output"])?;
+            Ok(Command::ConfigExport {
+                id: opts.get("id"),
+                output: opts.get("output"),
+            })
+        }
+        ["ssh", "open", ..] => {
+            let opts = Options::parse(&args[2..], &["id"])?;
+            Ok(Command::SshOpen { id: opts.get("id") })
+        }
+        ["ssh", "close", ..] => {
+            let opts = Options::parse(&args[2..], &["id"])?;
+            Ok(Command::SshClose { id: opts.get("id") })
+        }
+        ["ssh", ..] => Err("ssh needs open or close".into()),
+        ["orphans", "aws", ..] => {
+            let opts = Options::parse(&args[2..], &["clean"])?;
+            Ok(Command::OrphansAws {
+                clean_region: opts.get("clean"),
+            })
+        }
+        ["orphans", "do", ..] => {
+            let opts = Options::parse(&args[2..], &["clean"])?;
+            Ok(Command::OrphansDo {
+                clean: opts.get("clean"),
+            })
+        }
+        ["orphans", ..] => Err("orphans needs a provider: aws or do".into()),
+        ["logs"] => Ok(Command::Logs),
+        [] => Err("missing command".into()),
+        [other, ..] => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(
+        args.first().map(String::as_str),
+        Some("-h" | "--help" | "help")
+    ) {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let command = match parse_args(&args) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    init_logging();
+
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if let Err(e) = vault::unlock(&passphrase) {
+            eprintln!("error: {}", e);
+            return ExitCode::from(EXIT_FAILED);
+        }
+    }
+
+    let needs_deployment = matches!(
+        command,
+        Command::Destroy { .. }
+            | Command::Connect { .. }
+            | Command::ConfigExport { .. }
+            | Command::SshOpen { .. }
+            | Command::SshClose { .. }
+    );
+    if needs_deployment && !is_deployed() {
+        eprintln!("error: nothing is deployed");
+        return ExitCode::from(EXIT_NOT_DEPLOYED);
+    }
+
+    match run(command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_FAILED)
+        }
+    }
+}
+
+/// Everything at debug goes to the shared log file (so `logs` and the desktop
+/// log viewer see CLI runs); only warnings reach the terminal unless RUST_LOG
+/// says otherwise.
+fn init_logging() {
+    use tracing_subscriber::{
+        fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
+    };
+
+    let registry =
+        tracing_subscriber::registry().with(fmt::layer().with_writer(std::io::stderr).with_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| "createmyvpn_lib=warn".into()),
+        ));
+
+    let log_file = store::logs_dir().ok().and_then(|dir| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("createmyvpn.log"))
+            .ok()
+    });
+    match log_file {
+        Some(file) => registry
+            .with(
+                fmt::layer()
+                    .with_writer(std::sync::Mutex::new(file))
+                    .with_ansi(false)
+                    .with_filter(EnvFilter::new("createmyvpn_lib=debug")),
+            )
+            .init(),
+        None => registry.init(),
+    }
+}
+
+fn is_deployed() -> bool {
+    // An unreadable state (e.g. locked secrets) is reported by the command itself.
+    store::list_deployments()
+        .map(|d| !d.is_empty())
+        .unwrap_or(true)
+}
+
+/// The deployment a command acts on: the one named by `--id`, or the only
+/// one there is.
+fn resolve_deployment(id: Option<String>) -> Result<String, AppError> {
+    if let Some(id) = id {
+        return store::load_deployment(&id).map(|s| s.id);
+    }
+    let mut deployments = store::list_deployments()?;
+    match deployments.len() {
+        1 => Ok(deployments.remove(0).id),
+        0 => Err(AppError::State("Nothing is deployed".into())),
+        _ => Err(AppError::General(
+            "Several deployments exist — pick one with --id (see `createmyvpn-cli status`)".into(),
+        )),
+    }
+}
+
+async fn run(command: Command) -> Result<(), AppError> {
+    match command {
+        Command::DeployAws {
+            id,
+            region,
+            instance_type,
+            image,
+            auto_destroy_hours,
+        } => {
+            let creds = aws_credentials()?;
+            let settings = store::load_settings()?;
+            let region = region.unwrap_or(settings.region);
+            let instance_type = instance_type.unwrap_or(settings.instance_type);
+            let provider = AwsProvider::new(creds, &region);
+            deploy_with(
+                &provider,
+                id,
+                region,
+                instance_type,
+                &image,
+                auto_destroy_hours,
+            )
+            .await?;
+        }
+        Command::DeployDo {
+            id,
+            region,
+            size,
+            image,
+            auto_destroy_hours,
+        } => {
+            let provider = DoProvider::new(&do_credentials()?);
+            deploy_with(&provider, id, region, size, &image, auto_destroy_hours).await?;
+        }
+        Command::DeployHetzner {
+            id,
+            location,
+            server_type,
+            image,
+            auto_destroy_hours,
+        } => {
+            let provider = HetznerProvider::new(&hetzner_credentials()?);
+            deploy_with(
+                &provider,
+                id,
+                location,
+                server_type,
+                &image,
+                auto_destroy_hours,
+            )
+            .await?;
+        }
+        Command::DeployVultr {
+            id,
+            region,
+            plan,
+            image,
+            auto_destroy_hours,
+        } => {
+            let provider = VultrProvider::new(&vultr_credentials()?);
+            deploy_with(&provider, id, region, plan, &image, auto_destroy_hours).await?;
+        }
+        Command::DeployLinode {
+            id,
+            region,
+            linode_type,
+            image,
+            auto_destroy_hours,
+        } => {
+            let provider = LinodeProvider::new(&linode_credentials()?);
+            deploy_with(
+                &provider,
+                id,
+                region,
+                linode_type,
+                &image,
+                auto_destroy_hours,
+            )
+            .await?;
+        }
+        Command::DeployByo {
+            id,
+            host,
+            key_file,
+            user,
+            port,
+            host_key_fingerprint,
+            auto_destroy_hours,
+        } => {
+            let key = std::fs::read_to_string(&key_file)
+                .map_err(|e| AppError::General(format!("Cannot read {}: {}", key_file, e)))?;
+            let id = store::claim_deployment_id(id)?;
+            let state = byo::deploy_byo_internal(
+                &TerminalProgress,
+                &id,
+                host,
+                key,
+                user,
+                port,
+                host_key_fingerprint,
+                auto_destroy_hours,
+            )
+            .await?;
+            print_deployed(&state);
+        }
+        Command::Resume {
+            id,
+            auto_destroy_hours,
+        } => {
+            let creds = aws_credentials()?;
+            let id = resolve_deployment(id)?;
+            let state =
+                deploy::resume_aws(&TerminalProgress, &id, &creds, auto_destroy_hours).await?;
+            print_deployed(&state);
+        }
+        Command::Destroy { id } => {
+            let id = resolve_deployment(id)?;
+            let provider = env_provider(&store::load_deployment(&id)?);
+            destroy::destroy_with_provider(&TerminalProgress, &id, provider.as_deref()).await?;
+            println!("Deployment {} destroyed.", id);
+        }
+        Command::Status => print_status()?,
+        Command::Connect { id } => run_tunnel(resolve_deployment(id)?).await?,
+        Command::Disconnect => stop_tunnel().await?,
+        Command::ConfigExport { id, output } => export_config(&resolve_deployment(id)?, output)?,
+        Command::SshOpen { id } => {
+            let mut state = store::load_deployment(&resolve_deployment(id)?)?;
+            let provider = env_provider(&state);
+            ssh_access::unlock(&mut state, provider.as_deref()).await?;
+            println!(
+                "SSH to {} is open to {}.",
+                state.elastic_ip.as_deref().unwrap_or("the server"),
+                state.ssh_source.as_deref().unwrap_or("this computer")
+            );
+        }
+        Command::SshClose { id } => {
+            let mut state = store::load_deployment(&resolve_deployment(id)?)?;
+            let provider = env_provider(&state);
+            ssh_access::lock(&mut state, provider.as_deref()).await?;
+            println!(
+                "SSH to {} is locked down.",
+                state.elastic_ip.as_deref().unwrap_or("the server")
+            );
+        }
+        Command::OrphansAws { clean_region } => {
+            let creds = aws_credentials()?;
+            let scan = orphans::scan_aws(&creds).await?;
+            print_orphans(&scan);
+            if let Some(region) = clean_region {
+                let ids: Vec<String> = scan
+                    .resources
+                    .iter()
+                    .filter(|r| r.region == region)
+                    .map(|r| r.id.clone())
+                    .collect();
+                orphans::cleanup_aws(&TerminalProgress, &creds, &region, &ids).await?;
+            }
+        }
+        Command::OrphansDo { clean } => {
+            let creds = do_credentials()?;
+            let scan = orphans::scan_do(&creds).await?;
+            print_orphans(&scan);
+            if let Some(clean) = clean {
+                let ids: Vec<String> = if clean == "all" {
+                    scan.resources.iter().map(|r| r.id.clone()).collect()
+                } else {
+                    clean.split(',').map(|id| id.trim().to_string()).collect()
+                };
+                orphans::cleanup_do(&TerminalProgress, &creds, &ids).await?;
+            }
+        }
+        Command::Logs => print!("{}", logs::get_logs().await?),
+    }
+    Ok(())
+}
+
+/// Deploy on `provider` under a new or given id, rolling back whatever a
+/// failed deploy created.
+async fn deploy_with(
+    provider: &dyn CloudProvider,
+    id: Option<String>,
+    region: String,
+    size: String,
+    image: &ImageOptions,
+    auto_destroy_hours: Option<u32>,
+) -> Result<(), AppError> {
+    let image = image.resolve(&store::load_settings()?);
+    catalog::check_offered(provider, &region, &size).await?;
+    let id = store::claim_deployment_id(id)?;
+    let result = deploy::deploy_on(
+        provider,
+        &TerminalProgress,
+        &id,
+        region,
+        size,
+        image,
+        auto_destroy_hours,
+    )
+    .await;
+    if result.is_err() {
+        destroy::rollback_failed_deploy(&TerminalProgress, &id, provider).await;
+    }
+    print_deployed(&result?);
+    Ok(())
+}
+
+fn print_deployed(state: &DeploymentState) {
+    println!(
+        "VPN deployed at {} (id {}).",
+        state.elastic_ip.as_deref().unwrap_or("unknown address"),
+        state.id
+    );
+    println!(
+        "Run `createmyvpn-cli connect --id {}` to bring the tunnel up.",
+        state.id
+    );
+    if let Some(hourly) = state.hourly_cost {
+        let currency = state.cost_currency.as_deref().unwrap_or("USD");
+        println!(
+            "Estimated cost: {}/hour, {}/month.",
+            pricing::format_amount(currency, hourly),
+            pricing::format_amount(currency, hourly * pricing::HOURS_PER_MONTH)
+        );
+    }
+    if state.ssh_locked {
+        println!(
+            "SSH is locked down; `createmyvpn-cli ssh open --id {}` opens it again.",
+            state.id
+        );
+    }
+    if state.auto_destroy_at.is_some() {
+        // Only the desktop app runs the auto-destroy timer.
+        println!(
+            "Note: auto-destroy is scheduled, but fires only while the desktop app is running."
+        );
+    }
+}
+
+fn env_aws_credentials() -> Option<AwsCredentials> {
+    let access_key_id = std::env::var("AWS_ACCESS_KEY_ID").ok()?;
+    let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok()?;
+    Some(AwsCredentials {
+        access_key_id,
+        secret_access_key,
+    })
+}
+
+fn env_do_credentials() -> Option<DoCredentials> {
+    std::env::var("DIGITALOCEAN_TOKEN")
+        .ok()
+        .map(|api_token| DoCredentials { api_token })
+}
+
+fn env_hetzner_credentials() -> Option<HetznerCredentials> {
+    std::env::var("HCLOUD_TOKEN")
+        .ok()
+        .map(|api_token| HetznerCredentials { api_token })
+}
+
+fn env_vultr_credentials() -> Option<VultrCredentials> {
+    std::env::var("VULTR_API_KEY")
+        .ok()
+        .map(|api_key| VultrCredentials { api_key })
+}
+
+fn env_linode_credentials() -> Option<LinodeCredentials> {
+    std::env::var("LINODE_TOKEN")
+        .ok()
+        .map(|api_token| LinodeCredentials { api_token })
+}
+
+/// The provider `state` was deployed on, if its credentials are in the
+/// environment. `None` lets destroy fall back to the saved credentials.
+fn env_provider(state: &DeploymentState) -> Option<Box<dyn CloudProvider>> {
+    let provider: Box<dyn CloudProvider> = match state.deployment_mode.as_deref() {
+        Some("byo") => return None,
+        Some("do") => Box::new(DoProvider::new(&env_do_credentials()?)),
+        Some("hetzner") => Box::new(HetznerProvider::new(&env_hetzner_credentials()?)),
+        Some("vultr") => Box::new(VultrProvider::new(&env_vultr_credentials()?)),
+        Some("linode") => Box::new(LinodeProvider::new(&env_linode_credentials()?)),
+        _ => Box::new(AwsProvider::new(
+            env_aws_credentials()?,
+            state.region.as_deref()?,
+        )),
+    };
+    Some(provider)
+}
+
+fn aws_credentials() -> Result<AwsCredentials, AppError> {
+    match env_aws_credentials() {
+        Some(creds) => Ok(creds),
+        None => store::load_credentials()?.ok_or_else(|| {
+            AppError::Credential(
+                "No AWS credentials — set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY".into(),
+            )
+        }),
+    }
+}
+
+fn do_credentials() -> Result<DoCredentials, AppError> {
+    match env_do_credentials() {
+        Some(creds) => Ok(creds),
+        None => store::load_do_credentials()?.ok_or_else(|| {
+            AppError::Credential("No DigitalOcean credentials — set DIGITALOCEAN_TOKEN".into())
+        }),
+    }
+}
+
+fn hetzner_credentials() -> Result<HetznerCredentials, AppError> {
+    match env_hetzner_credentials() {
+        Some(creds) => Ok(creds),
+        None => store::load_hetzner_credentials()?.ok_or_else(|| {
+            AppError::Credential("No Hetzner credentials — set HCLOUD_TOKEN".into())
+        }),
+    }
+}
+
+fn vultr_credentials() -> Result<VultrCredentials, AppError> {
+    match env_vultr_credentials() {
+        Some(creds) => Ok(creds),
+        None => store::load_vultr_credentials()?
+            .ok_or_else(|| AppError::Credential("No Vultr credentials — set VULTR_API_KEY".into())),
+    }
+}
+
+fn linode_credentials() -> Result<LinodeCredentials, AppError> {
+    match env_linode_credentials() {
+        Some(creds) => Ok(creds),
+        None => store::load_linode_credentials()?
+            .ok_or_else(|| AppError::Credential("No Linode credentials — set LINODE_TOKEN".into())),
+    }
+}
+
+fn print_status() -> Result<(), AppError> {
+    let deployments = store::list_deployments()?;
+    if deployments.is_empty() {
+        println!("Deployment: not_deployed");
+        return Ok(());
+    }
+    for state in &deployments {
+        let status = serde_json::to_value(&state.status).unwrap_or_default();
+        println!("[{}] {}", state.id, state.name);
+        println!("Deployment: {}", status.as_str().unwrap_or("unknown"));
+        println!(
+            "Provider:   {}",
+            state.deployment_mode.as_deref().unwrap_or("aws")
+        );
+        if let Some(region) = &state.region {
+            println!("Region:     {}", region);
+        }
+        if let Some(ip) = &state.elastic_ip {
+            println!("Server:     {}", ip);
+        }
+        if state.os_release.is_some() {
+            println!("OS:         {}", state.server_image());
+        }
+        if let Some(at) = state.deployed_at {
+            println!("Deployed:   {}", at.format("%Y-%m-%d %H:%M UTC"));
+        }
+        if let Some(at) = state.auto_destroy_at {
+            println!("Destroys:   {}", at.format("%Y-%m-%d %H:%M UTC"));
+        }
+        if let Some(cost) = pricing::accrued(state, chrono::Utc::now()) {
+            println!(
+                "Cost:       {} so far ({}/hour)",
+                pricing::format_amount(&cost.currency, cost.amount),
+                pricing::format_amount(&cost.currency, cost.hourly)
+            );
+        }
+        if state.ssh_locked && state.ssh_source.is_none() {
+            println!("SSH:        never opened (deployed without SSH)");
+        } else if state.ssh_locked {
+            println!("SSH:        locked down");
+        } else if let Some(source) = &state.ssh_source {
+            println!("SSH:        open to {}", source);
+        }
+        if let Some(err) = &state.error_message {
+            println!("Error:      {}", err);
+        }
+        println!();
+    }
+    match running_tunnel_pid() {
+        Some(pid) => println!("Tunnel:     up (createmyvpn-cli connect, pid {})", pid),
+        None => println!("Tunnel:     not started from the CLI"),
+    }
+    Ok(())
+}
+
+fn print_orphans(scan: &OrphanScan) {
+    for r in &scan.resources {
+        println!(
+            "{:<15} {:<17} {:<24} {:<30} ${:.2}/mo",
+            r.region,
+            r.kind,
+            r.id,
+            r.detail.as_deref().or(r.name.as_deref()).unwrap_or(""),
+            r.monthly_cost_usd
+        );
+    }
+    for e in &scan.errors {
+        eprintln!("warning: could not scan {}", e);
+    }
+    println!(
+        "{} leftover resources, about ${:.2}/month.",
+        scan.resources.len(),
+        scan.monthly_cost_usd
+    );
+}
+
+/// The pid of a running `createmyvpn-cli connect`. A pid file left behind by
+/// a crash or `kill -9` is removed instead of trusted, since its pid may have
+/// been reused by another process since.
+fn running_tunnel_pid() -> Option<u32> {
+    let path = store::cli_pid_path().ok()?;
+    let contents = std::fs::read_to_string(&path).ok()?;
+    match contents.trim().parse() {
+        Ok(pid) if is_cli_process(pid) => Some(pid),
+        _ => {
+            tracing::info!("Removing stale tunnel pid file {}", path.display());
+            let _ = std::fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+/// Whether `pid` is a live process running this binary.
+#[cfg(unix)]
+fn is_cli_process(pid: u32) -> bool {
+    std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .is_ok_and(|out| {
+            out.status.success() && is_cli_command(&String::from_utf8_lossy(&out.stdout))
+        })
+}
+
+#[cfg(not(unix))]
+fn is_cli_process(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .is_ok_and(|out| {
+            let stdout = String::from_utf8_lossy(&out.stdout);
+            out.status.success()
+                && stdout
+                    .split(',')
+                    .next()
+                    .is_some_and(|name| is_cli_command(name.trim_matches('"')))
+        })
+}
+
+/// Whether a process's command name is this binary's. `ps` gives the full
+/// path on macOS and the name cut to 15 characters on Linux, which is
+/// exactly "createmyvpn-cli".
+fn is_cli_command(comm: &str) -> bool {
+    let name = comm.trim().rsplit(['/', '\\']).next().unwrap_or_default();
+    name == "createmyvpn-cli" || name == "createmyvpn-cli.exe"
+}
+
+/// Foreground tunnel: the userspace tunnel lives as long as this process, so
+/// `connect` blocks until Ctrl+C, SIGTERM or `createmyvpn-cli disconnect`.
+async fn run_tunnel(deployment_id: String) -> Result<(), AppError> {
+    if let Some(pid) = running_tunnel_pid() {
+        return Err(AppError::WireGuard(format!(
+            "A tunnel is already running (pid {}) — run `createmyvpn-cli disconnect` first",
+            pid
+        )));
+    }
+
+    let on_event = Box::new(|event: TunnelEvent| match event {
+        TunnelEvent::Stats(_) => {}
+        TunnelEvent::Reconnecting(reason) => println!("Reconnecting: {}", reason),
+        TunnelEvent::Reconnected => println!("Reconnected."),
+    });
+    connect::connect_vpn_internal(&deployment_id, Some(on_event))?;
+
+    let pid_path = store::cli_pid_path()?;
+    std::fs::write(&pid_path, std::process::id().to_string())?;
+    println!(
+        "Tunnel up to {}. Press Ctrl+C to disconnect.",
+        deployment_id
+    );
+
+    wait_for_shutdown().await;
+
+    println!("Disconnecting...");
+    let result = tunnel::deactivate_tunnel();
+    let _ = std::fs::remove_file(&pid_path);
+    result
+}
+
+#[cfg(unix)]
+async fn wait_for_shutdown() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut term) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(unix)]
+async fn stop_tunnel() -> Result<(), AppError> {
+    let pid = running_tunnel_pid()
+        .ok_or_else(|| AppError::WireGuard("No tunnel started from the CLI is running".into()))?;
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status()?;
+    if !status.success() {
+        // The process is gone without cleaning up; drop the stale pid file.
+        let _ = std::fs::remove_file(store::cli_pid_path()?);
+        return Err(AppError::WireGuard(format!(
+            "Tunnel process {} is not running",
+            pid
+        )));
+    }
+
+    // The connect process removes the pid file once routes and DNS are restored.
+    let pid_path = store::cli_pid_path()?;
+    for _ in 0..50 {
+        if !pid_path.exists() {
+            println!("Disconnected.");
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+    Err(AppError::WireGuard(format!(
+        "Tunnel process {} did not stop within 10 seconds",
+        pid
+    )))
+}
+
+#[cfg(not(unix))]
+async fn stop_tunnel() -> Result<(), AppError> {
+    Err(AppError::WireGuard(
+        "Press Ctrl+C in the window running `createmyvpn-cli connect` to disconnect".into(),
+    ))
+}
+
+fn export_config(deployment_id: &str, output: Option<String>) -> Result<(), AppError> {
+    let config = store::load_deployment(deployment_id)?
+        .client_config
+        .ok_or_else(|| AppError::State("No client config available".into()))?;
+    let Some(path) = output else {
+        print!("{}", config);
+        return Ok(());
+    };
+
+    std::fs::write(&path, &config)?;
+    // The config holds the client's private key.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    println!("Client config written to {}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_deploy_aws_with_defaults() {
+        assert_eq!(
+            parse_args(&args("deploy aws")).unwrap(),
+            Command::DeployAws {
+                id: None,
+                region: None,
+                instance_type: None,
+                image: ImageOptions::default(),
+                auto_destroy_hours: None
+            }
+        );
+    }
+
+    #[test]
+    fn parses_deploy_do_options() {
+        assert_eq!(
+            parse_args(&args("deploy do --region nyc3 --auto-destroy-hours 4")).unwrap(),
+            Command::DeployDo {
+                id: None,
+                region: "nyc3".into(),
+                size: "s-1vcpu-1gb".into(),
+                image: ImageOptions::default(),
+                auto_destroy_hours: Some(4),
+            }
+        );
+    }
+
+    #[test]
+    fn deploy_do_requires_region() {
+        let err = parse_args(&args("deploy do")).unwrap_err();
+        assert!(err.contains("--region"));
+    }
+
+    #[test]
+    fn parses_deploy_hetzner_options() {
+        assert_eq!(
+            parse_args(&args("deploy hetzner --location fsn1 --server-type cx32")).unwrap(),
+            Command::DeployHetzner {
+                id: None,
+                location: "fsn1".into(),
+                server_type: "cx32".into(),
+                image: ImageOptions::default(),
+                auto_destroy_hours: None,
+            }
+        );
+        let err = parse_args(&args("deploy hetzner")).unwrap_err();
+        assert!(err.contains("--location"));
+    }
+
+    #[test]
+    fn parses_deploy_vultr_and_linode() {
+        assert_eq!(
+            parse_args(&args("deploy vultr --region ams")).unwrap(),
+            Command::DeployVultr {
+                id: None,
+                region: "ams".into(),
+                plan: "vc2-1c-1gb".into(),
+                image: ImageOptions::default(),
+                auto_destroy_hours: None,
+            }
+        );
+        assert_eq!(
+            parse_args(&args("deploy linode --region us-east --type g6-standard-1")).unwrap(),
+            Command::DeployLinode {
+                id: None,
+                region: "us-east".into(),
+                linode_type: "g6-standard-1".into(),
+                image: ImageOptions::default(),
+                auto_destroy_hours: None,
+            }
+        );
+        let err = parse_args(&args("deploy linode")).unwrap_err();
+        assert!(err.contains("--region"));
+    }
+
+    #[test]
+    fn parses_server_image_options() {
+        let cmd = parse_args(&args(
+            "deploy aws --instance-type t4g.micro --os ubuntu-24.04 --arch arm64",
+        ))
+        .unwrap();
+        let Command::DeployAws {
+            instance_type,
+            image,
+            ..
+        } = cmd
+        else {
+            panic!("expected deploy aws, got {:?}", cmd);
+        };
+        assert_eq!(instance_type.as_deref(), Some("t4g.micro"));
+        let settings = AppSettings::new();
+        assert_eq!(
+            image.resolve(&settings),
+            ServerImage {
+                os: OsRelease::Ubuntu2404,
+                arch: Architecture::Arm64,
+            }
+        );
+        assert_eq!(
+            ImageOptions::default().resolve(&settings),
+            settings.server_image()
+        );
+        let err = parse_args(&args("deploy do --region nyc3 --os windows")).unwrap_err();
+        assert!(err.contains("Unknown OS"), "{}", err);
+    }
+
+    #[test]
+    fn parses_deploy_byo() {
+        let cmd = parse_args(&args(
+            "deploy byo --host 1.2.3.4 --key-file id --port 2222 --id home",
+        ))
+        .unwrap();
+        assert_eq!(
+            cmd,
+            Command::DeployByo {
+                id: Some("home".into()),
+                host: "1.2.3.4".into(),
+                key_file: "id".into(),
+                user: "ubuntu".into(),
+                port: 2222,
+                host_key_fingerprint: None,
+                auto_destroy_hours: None,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_numbers_and_unknown_options() {
+        assert!(parse_args(&args("deploy byo --host h --key-file k --port abc")).is_err());
+        assert!(parse_args(&args("deploy aws --zone x")).is_err());
+        assert!(parse_args(&args("deploy aws --region")).is_err());
+    }
+
+    #[test]
+    fn parses_simple_commands() {
+        assert_eq!(parse_args(&args("status")).unwrap(), Command::Status);
+        assert_eq!(
+            parse_args(&args("disconnect")).unwrap(),
+            Command::Disconnect
+        );
+        assert_eq!(
+            parse_args(&args("config export --output wg.conf")).unwrap(),
+            Command::ConfigExport {
+                id: None,
+                output: Some("wg.conf".into())
+            }
+        );
+        assert!(parse_args(&args("destroy now")).is_err());
+    }
+
+    #[test]
+    fn recognizes_cli_command_names() {
+        assert!(is_cli_command("createmyvpn-cli\n"));
+        assert!(is_cli_command("/usr/local/bin/createmyvpn-cli"));
+        assert!(is_cli_command("createmyvpn-cli.exe"));
+        assert!(!is_cli_command("sshd"));
+        assert!(!is_cli_command("/usr/bin/createmyvpn"));
+        assert!(!is_cli_command(""));
+    }
+
+    #[test]
+    fn parses_deployment_ids() {
+        assert_eq!(
+            parse_args(&args("destroy --id a1b2c3d4")).unwrap(),
+            Command::Destroy {
+                id: Some("a1b2c3d4".into())
+            }
+        );
+        assert_eq!(
+            parse_args(&args("connect")).unwrap(),
+            Command::Connect { id: None }
+        );
+        assert_eq!(
+            parse_args(&args("config export --id work")).unwrap(),
+            Command::ConfigExport {
+                id: Some("work".into()),
+                output: None
+            }
+        );
+        assert_eq!(
+            parse_args(&args("resume --id eu --auto-destroy-hours 2")).unwrap(),
+            Command::Resume {
+                id: Some("eu".into()),
+                auto_destroy_hours: Some(2)
+            }
+        );
+        assert!(parse_args(&args("connect --id")).is_err());
+    }
+
+    #[test]
+    fn parses_ssh_access() {
+        assert_eq!(
+            parse_args(&args("ssh close --id eu")).unwrap(),
+            Command::SshClose {
+                id: Some("eu".into())
+            }
+        );
+        assert_eq!(
+            parse_args(&args("ssh open")).unwrap(),
+            Command::SshOpen { id: None }
+        );
+        assert!(parse_args(&args("ssh")).is_err());
+    }
+
+    #[test]
+    fn parses_orphans() {
+        assert_eq!(
+            parse_args(&args("orphans aws")).unwrap(),
+            Command::OrphansAws { clean_region: None }
+        );
+        assert_eq!(
+            parse_args(&args("orphans aws --clean eu-west-1")).unwrap(),
+            Command::OrphansAws {
+                clean_region: Some("eu-west-1".into())
+            }
+        );
+        assert_eq!(
+            parse_args(&args("orphans do --clean all")).unwrap(),
+            Command::OrphansDo {
+                clean: Some("all".into())
+            }
+        );
+        assert!(parse_args(&args("orphans")).is_err());
+        assert!(parse_args(&args("")).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Region and server size lists for the deploy forms, fetched from each
+//! provider's API and cached on disk for a day. When the API can't be reached
+//! (offline, or no credentials saved yet) the last cached list is used, and
+//! failing that the small list bundled with the app.
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::aws::provider::INSTANCE_TYPES;
+use crate::commands::settings::aws_regions;
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::provider::{CloudProvider, Region, ServerSize};
+
+/// How long a fetched list is used before asking the API again.
+const CACHE_TTL_HOURS: i64 = 24;
+
+/// Where the items of a `Catalog` came from.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// Fetched from the provider just now.
+    Live,
+    /// From the on-disk cache, possibly past its TTL if the API failed.
+    Cache,
+    /// The list bundled with the app; it may offer regions or sizes the
+    /// account can't use.
+    Bundled,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Catalog<T> {
+    pub items: Vec<T>,
+    pub source: Source,
+    /// When the items were fetched; `None` for the bundled list.
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: DateTime<Utc>,
+    items: serde_json::Value,
+}
+
+/// Cached lists keyed by e.g. "do/regions" or "do/sizes/ams3".
+type Cache = BTreeMap<String, CacheEntry>;
+
+fn read_cache() -> Cache {
+    store::catalog_cache_path()
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache(cache: &Cache) {
+    let result = store::catalog_cache_path()
+        .and_then(|path| store::write_atomic(&path, &serde_json::to_string_pretty(cache)?));
+    if let Err(e) = result {
+        tracing::warn!("Could not save the region/size cache: {}", e);
+    }
+}
+
+/// The cached list under `key`, if it is younger than the TTL.
+fn fresh<T: DeserializeOwned>(cache: &Cache, key: &str, now: DateTime<Utc>) -> Option<Catalog<T>> {
+    let entry = cache.get(key)?;
+    if now - entry.fetched_at > Duration::hours(CACHE_TTL_HOURS) {
+        return None;
+    }
+    cached(entry)
+}
+
+fn cached<T: DeserializeOwned>(entry: &CacheEntry) -> Option<Catalog<T>> {
+    Some(Catalog {
+        items: serde_json::from_value(entry.items.clone()).ok()?,
+        source: Source::Cache,
+        fetched_at: Some(entry.fetched_at),
+    })
+}
+
+/// Pick the list to show once the API has been asked (`fetched` is `None`
+/// when it couldn't be). A fetched list replaces the cached one; an empty
+/// one is treated as a failure, since every provider offers something.
+fn settle<T: Serialize + DeserializeOwned>(
+    cache: &mut Cache,
+    key: &str,
+    fetched: Option<Result<Vec<T>, AppError>>,
+    bundled: Vec<T>,
+    now: DateTime<Utc>,
+) -> Catalog<T> {
+    match fetched {
+        Some(Ok(items)) if !items.is_empty() => {
+            if let Ok(value) = serde_json::to_value(&items) {
+                cache.insert(
+                    key.to_string(),
+                    CacheEntry {
+                        fetched_at: now,
+                        items: value,
+                    },
+                );
+            }
+            return Catalog {
+                items,
+                source: Source::Live,
+                fetched_at: Some(now),
+            };
+        }
+        Some(Ok(_)) => tracing::warn!("Catalog {}: the provider returned an empty list", key),
+        Some(Err(e)) => tracing::warn!("Catalog {}: could not fetch: {}", key, e),
+        None => {}
+    }
+    cache.get(key).and_then(cached).unwrap_or(Catalog {
+        items: bundled,
+        source: Source::Bundled,
+        fetched_at: None,
+    })
+}
+
+/// Regions for `mode` ("aws", "do", ...). `provider` is `None` when no
+/// credentials are saved, so only cached or bundled lists can be offered.
+pub async fn regions(mode: &str, provider: Option<&dyn CloudProvider>) -> Catalog<Region> {
+    let key = format!("{}/regions", mode);
+    let now = Utc::now();
+    let mut cache = read_cache();
+    if let Some(catalog) = fresh(&cache, &key, now) {
+        return catalog;
+    }
+    let fetched = match provider {
+        Some(provider) => Some(provider.list_regions().await),
+        None => None,
+    };
+    let catalog = settle(&mut cache, &key, fetched, bundled_regions(mode), now);
+    if catalog.source == Source::Live {
+        write_cache(&cache);
+    }
+    catalog
+}
+
+/// Server sizes `mode` offers in `region`, with their monthly price where
+/// the provider publishes one.
+pub async fn sizes(
+    mode: &str,
+    provider: Option<&dyn CloudProvider>,
+    region: &str,
+) -> Catalog<ServerSize> {
+    let key = format!("{}/sizes/{}", mode, region);
+    let now = Utc::now();
+    let mut cache = read_cache();
+    if let Some(catalog) = fresh(&cache, &key, now) {
+        return catalog;
+    }
+    let fetched = match provider {
+        Some(provider) => Some(provider.list_sizes(region).await),
+        None => None,
+    };
+    let catalog = settle(&mut cache, &key, fetched, bundled_sizes(mode), now);
+    if catalog.source == Source::Live {
+        write_cache(&cache);
+    }
+    catalog
+}
+
+/// Refuse a region or size `provider` doesn't offer, before anything is
+/// created. The bundled lists are incomplete, so nothing is refused when
+/// only those are available.
+pub async fn check_offered(
+    provider: &dyn CloudProvider,
+    region: &str,
+    size: &str,
+) -> Result<(), AppError> {
+    let regions = regions(provider.mode(), Some(provider)).await;
+    if regions.source != Source::Bundled && !regions.items.iter().any(|r| r.code == region) {
+        return Err(AppError::State(format!(
+            "{} does not offer region '{}' for this account",
+            provider.display_name(),
+            region
+        )));
+    }
+    let sizes = sizes(provider.mode(), Some(provider), region).await;
+    if sizes.source != Source::Bundled && !sizes.items.iter().any(|s| s.code == size) {
+        return Err(AppError::State(format!(
+            "{} does not offer size '{}' in {}",
+            provider.display_name(),
+            size,
+            region
+        )));
+    }
+    Ok(())
+}
+
+fn region(code: &str, name: &str) -> Region {
+    Region {
+        code: code.to_string(),
+        name: name.to_string(),
+    }
+}
+
+fn size(code: &str, description: &str, monthly_price: f64) -> ServerSize {
+    ServerSize {
+        code: code.to_string(),
+        description: description.to_string(),
+        monthly_price: Some(monthly_price),
+    }
+}
+
+/// Regions bundled with the app, for when the API can't be asked.
+pub fn bundled_regions(mode: &str) -> Vec<Region> {
+    match mode {
+        "aws" => aws_regions()
+            .into_iter()
+            .map(|r| region(&r.code, &r.name))
+            .collect(),
+        "do" => vec![
+            region("nyc1", "New York 1"),
+            region("nyc3", "New York 3"),
+            region("sfo3", "San Francisco 3"),
+            region("ams3", "Amsterdam 3"),
+            region("lon1", "London 1"),
+            region("fra1", "Frankfurt 1"),
+            region("sgp1", "Singapore 1"),
+            region("blr1", "Bangalore 1"),
+            region("tor1", "Toronto 1"),
+            region("syd1", "Sydney 1"),
+        ],
+        "hetzner" => vec![
+            region("fsn1", "Falkenstein, DE"),
+            region("nbg1", "Nuremberg, DE"),
+            region("hel1", "Helsinki, FI"),
+        ],
+        "vultr" => vec![
+            region("ewr", "New Jersey"),
+            region("ord", "Chicago"),
+            region("lax", "Los Angeles"),
+            region("ams", "Amsterdam"),
+            region("lhr", "London"),
+            region("fra", "Frankfurt"),
+            region("nrt", "Tokyo"),
+            region("sgp", "Singapore"),
+            region("syd", "Sydney"),
+        ],
+        "linode" => vec![
+            region("us-east", "Newark, NJ"),
+            region("us-central", "Dallas, TX"),
+            region("us-west", "Fremont, CA"),
+            region("eu-west", "London, UK"),
+            region("eu-central", "Frankfurt, DE"),
+            region("ap-south", "Singapore, SG"),
+            region("ap-northeast", "Tokyo, JP"),
+            region("ap-southeast", "Sydney, AU"),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Sizes bundled with the app, with list prices at the time of writing.
+pub fn bundled_sizes(mode: &str) -> Vec<ServerSize> {
+    match mode {
+        "aws" => INSTANCE_TYPES
+            .iter()
+            .map(|(code, description)| ServerSize {
+                code: code.to_string(),
+                description: description.to_string(),
+                monthly_price: None,
+            })
+            .collect(),
+        "do" => vec![
+            size("s-1vcpu-512mb-10gb", "1 vCPU, 512MB RAM", 4.0),
+            size("s-1vcpu-1gb", "1 vCPU, 1024MB RAM", 6.0),
+            size("s-2vcpu-2gb", "2 vCPU, 2048MB RAM", 18.0),
+        ],
+        "hetzner" => vec![
+            size("cx22", "2 vCPU, 4GB RAM", 4.51),
+            size("cx32", "4 vCPU, 8GB RAM", 7.8),
+        ],
+        "vultr" => vec![
+            size("vc2-1c-1gb", "1 vCPU, 1024MB RAM", 5.0),
+            size("vc2-1c-2gb", "1 vCPU, 2048MB RAM", 10.0),
+        ],
+        "linode" => vec![
+            size("g6-nanode-1", "1 vCPU, 1024MB RAM", 5.0),
+            size("g6-standard-1", "1 vCPU, 2048MB RAM", 12.0),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(fetched_at: DateTime<Utc>, codes: &[&str]) -> CacheEntry {
+        let items: Vec<Region> = codes.iter().map(|c| region(c, c)).collect();
+        CacheEntry {
+            fetched_at,
+            items: serde_json::to_value(items).unwrap(),
+        }
+    }
+
+    #[test]
+    fn fresh_entry_is_used_until_the_ttl() {
+        let now = Utc::now();
+        let mut cache = Cache::new();
+        cache.insert(
+            "do/regions".into(),
+            entry(now - Duration::hours(2), &["ams3"]),
+        );
+        let catalog: Catalog<Region> = fresh(&cache, "do/regions", now).unwrap();
+        assert_eq!(catalog.source, Source::Cache);
+        assert_eq!(catalog.items, vec![region("ams3", "ams3")]);
+
+        let later = now + Duration::hours(CACHE_TTL_HOURS);
+        assert!(fresh::<Region>(&cache, "do/regions", later).is_none());
+    }
+
+    #[test]
+    fn fetched_list_replaces_the_cache() {
+        let now = Utc::now();
+        let mut cache = Cache::new();
+        cache.insert(
+            "do/regions".into(),
+            entry(now - Duration::days(3), &["nyc1"]),
+        );
+        let catalog = settle(
+            &mut cache,
+            "do/regions",
+            Some(Ok(vec![region("fra1", "Frankfurt 1")])),
+            bundled_regions("do"),
+            now,
+        );
+        assert_eq!(catalog.source, Source::Live);
+        let cached: Catalog<Region> = fresh(&cache, "do/regions", now).unwrap();
+        assert_eq!(cached.items, vec![region("fra1", "Frankfurt 1")]);
+    }
+
+    #[test]
+    fn failed_fetch_falls_back_to_stale_cache() {
+        let now = Utc::now();
+        let mut cache = Cache::new();
+        cache.insert(
+            "do/regions".into(),
+            entry(now - Duration::days(3), &["nyc1"]),
+        );
+        let catalog = settle(
+            &mut cache,
+            "do/regions",
+            Some(Err(AppError::General("offline".into()))),
+            bundled_regions("do"),
+            now,
+        );
+        assert_eq!(catalog.source, Source::Cache);
+        assert_eq!(catalog.items, vec![region("nyc1", "nyc1")]);
+    }
+
+    #[test]
+    fn bundled_list_without_cache_or_credentials() {
+        let mut cache = Cache::new();
+        let catalog = settle(
+            &mut cache,
+            "vultr/regions",
+            None,
+            bundled_regions("vultr"),
+            Utc::now(),
+        );
+        assert_eq!(catalog.source, Source::Bundled);
+        assert!(catalog.items.iter().any(|r| r.code == "ams"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn empty_fetch_is_not_cached() {
+        let mut cache = Cache::new();
+        let catalog = settle(
+            &mut cache,
+            "hetzner/sizes/fsn1",
+            Some(Ok(Vec::new())),
+            bundled_sizes("hetzner"),
+            Utc::now(),
+        );
+        assert_eq!(catalog.source, Source::Bundled);
+        assert!(cache.is_empty());
+    }
+}
@@ -1,69 +1,96 @@
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
 use crate::commands::timer;
 use crate::error::AppError;
-use crate::persistence::store;
+use crate::persistence::{store, vault};
+use crate::progress::{ProgressSink, TauriProgress, DEPLOY_EVENT};
 use crate::ssh;
-use crate::state::{DeploymentState, DeploymentStatus, ProgressEvent};
-use crate::wireguard::{client_config, keys, server_config};
+use crate::state::{deployment_name, DeploymentState, DeploymentStatus};
+use crate::wireguard::{client_config, keys, subnet};
 
-fn emit_progress(app: &AppHandle, step: u32, total: u32, message: &str, status: &str) {
-    let event = ProgressEvent {
-        step,
-        total_steps: total,
-        message: message.to_string(),
-        status: status.to_string(),
-    };
-    let _ = app.emit("deploy-progress", event);
+#[tauri::command]
+pub async fn deploy_byo_vps(
+    app: AppHandle,
+    deployment_id: Option<String>,
+    server_ip: String,
+    ssh_private_key: String,
+    ssh_user: String,
+    ssh_port: u16,
+    ssh_host_key_fingerprint: Option<String>,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let id = store::claim_deployment_id(deployment_id)?;
+    let progress = TauriProgress::new(app.clone(), DEPLOY_EVENT);
+    let state = deploy_byo_internal(
+        &progress,
+        &id,
+        server_ip,
+        ssh_private_key,
+        ssh_user,
+        ssh_port,
+        ssh_host_key_fingerprint,
+        auto_destroy_hours,
+    )
+    .await?;
+
+    if let Some(at) = state.auto_destroy_at {
+        timer::spawn_auto_destroy_timer(app, id, at);
+    }
+    Ok(state)
 }
 
 /// Deploy WireGuard on any Ubuntu 22.04/24.04 server via SSH.
 /// Reuses the same SSH configure module as the AWS deploy path.
-#[tauri::command]
-pub async fn deploy_byo_vps(
-    app: AppHandle,
+/// If `ssh_host_key_fingerprint` is given, the server must present that host key;
+/// otherwise the key seen on first connect is pinned for later connections.
+pub async fn deploy_byo_internal(
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
     server_ip: String,
     ssh_private_key: String,
     ssh_user: String,
     ssh_port: u16,
+    ssh_host_key_fingerprint: Option<String>,
     auto_destroy_hours: Option<u32>,
 ) -> Result<DeploymentState, AppError> {
+    // Secrets are saved as soon as they exist; a locked vault must fail first.
+    vault::ensure_unlocked()?;
+    let known_fingerprints: Vec<String> = ssh_host_key_fingerprint
+        .as_deref()
+        .map(str::trim)
+        .filter(|fp| !fp.is_empty())
+        .map(ssh::host_key::parse_user_fingerprint)
+        .transpose()?
+        .into_iter()
+        .collect();
     let total_steps = 4u32;
+    let settings = store::load_settings()?;
+    let tunnel = subnet::validate(&settings.tunnel_cidr)?;
     tracing::info!("=== Starting BYO VPS deployment to {} ===", server_ip);
 
     let mut state = DeploymentState {
+        id: deployment_id.to_string(),
+        name: deployment_name(Some("byo"), None, Some(&server_ip)),
         status: DeploymentStatus::Deploying,
         deployment_mode: Some("byo".to_string()),
         elastic_ip: Some(server_ip.clone()),
         ssh_private_key: Some(ssh_private_key.clone()),
         ssh_user: Some(ssh_user.clone()),
+        ssh_port: Some(ssh_port),
+        tunnel_cidr: Some(tunnel.to_string()),
         ..Default::default()
     };
-    store::save_state(&state)?;
-
-    let settings = store::load_settings()?;
+    store::save_deployment(&state)?;
 
     // Step 1: Generate WireGuard keys
-    emit_progress(&app, 1, total_steps, "Generating WireGuard keys...", "running");
+    progress.emit(1, total_steps, "Generating WireGuard keys...", "running");
     tracing::info!("[BYO 1/{}] Generating WireGuard key pairs", total_steps);
 
     let server_keys = keys::generate_keypair();
     let client_keys = keys::generate_keypair();
 
-    let wg_server_conf = server_config::render_server_config(
-        &server_keys.private_key,
-        &client_keys.public_key,
-        settings.wireguard_port,
-    );
-    let client_conf = client_config::render_client_config(
-        &client_keys.private_key,
-        &server_keys.public_key,
-        &server_ip,
-        settings.wireguard_port,
-    );
-
     // Step 2: SSH connect
-    emit_progress(&app, 2, total_steps, "Connecting via SSH...", "running");
+    progress.emit(2, total_steps, "Connecting via SSH...", "running");
     tracing::info!(
         "[BYO 2/{}] Connecting to {}:{} as {}",
         total_steps,
@@ -71,32 +98,55 @@ pub async fn deploy_byo_vps(
         ssh_port,
         ssh_user
     );
-    let ssh_session =
-        ssh::client::SshSession::connect(&server_ip, ssh_port, &ssh_user, &ssh_private_key, 60)
-            .await?;
+    let ssh_session = ssh::client::SshSession::connect(
+        &server_ip,
+        ssh_port,
+        &ssh_user,
+        &ssh_private_key,
+        60,
+        &known_fingerprints,
+    )
+    .await?;
+    state.ssh_host_key_fingerprint = Some(ssh_session.host_key_fingerprint().to_string());
+    store::save_deployment(&state)?;
     tracing::info!("[BYO 2/{}] SSH connected", total_steps);
 
     // Step 3: Install WireGuard
-    emit_progress(
-        &app,
+    progress.emit(
         3,
         total_steps,
         "Installing WireGuard (this may take a minute)...",
         "running",
     );
     tracing::info!("[BYO 3/{}] Configuring WireGuard via SSH", total_steps);
-    ssh::configure::configure_wireguard(&ssh_session, &wg_server_conf, &server_keys.public_key)
-        .await?;
+    let ipv6 = ssh::configure::configure_wireguard(
+        &ssh_session,
+        progress,
+        &server_keys,
+        &client_keys.public_key,
+        settings.wireguard_port,
+        &tunnel,
+    )
+    .await?;
     tracing::info!("[BYO 3/{}] WireGuard configured on server", total_steps);
 
     // Step 4: Save state and client config
-    emit_progress(&app, 4, total_steps, "Saving client configuration...", "running");
+    progress.emit(4, total_steps, "Saving client configuration...", "running");
     tracing::info!("[BYO 4/{}] Saving state and client config", total_steps);
 
+    let client_conf = client_config::render_client_config(
+        &client_keys.private_key,
+        &server_keys.public_key,
+        &server_ip,
+        settings.wireguard_port,
+        &tunnel,
+        ipv6,
+    );
+    state.tunnel_ipv6 = Some(ipv6);
     state.server_public_key = Some(server_keys.public_key);
     state.client_private_key = Some(client_keys.private_key);
     state.client_public_key = Some(client_keys.public_key);
-    state.client_config = Some(client_conf.clone());
+    state.client_config = Some(client_conf);
     state.status = DeploymentStatus::Deployed;
     state.deployed_at = Some(chrono::Utc::now());
 
@@ -106,14 +156,9 @@ pub async fn deploy_byo_vps(
         tracing::info!("[BYO] Auto-destroy scheduled for {}", destroy_at);
     }
 
-    store::save_state(&state)?;
-    store::save_client_config(&client_conf)?;
-
-    if let Some(at) = state.auto_destroy_at {
-        timer::spawn_auto_destroy_timer(app.clone(), at);
-    }
+    store::save_deployment(&state)?;
 
-    emit_progress(&app, total_steps, total_steps, "Your server is ready!", "done");
+    progress.emit(total_steps, total_steps, "Your server is ready!", "done");
     tracing::info!("=== BYO VPS deployment complete! Server: {} ===", server_ip);
 
     Ok(state)
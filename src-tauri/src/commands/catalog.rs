@@ -0,0 +1,36 @@
+use crate::catalog::{self, Catalog};
+use crate::persistence::store;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+
+/// AWS clients need a region even to list regions.
+fn default_aws_region() -> String {
+    store::load_settings()
+        .map(|s| s.region)
+        .unwrap_or_else(|_| "us-east-1".into())
+}
+
+/// The provider for `mode` if its credentials are saved; without them only
+/// cached or bundled lists are returned.
+pub(crate) fn saved_provider(mode: &str, region: &str) -> Option<Box<dyn CloudProvider>> {
+    match provider::for_mode(Some(mode), Some(region)) {
+        Ok(provider) => provider,
+        Err(e) => {
+            tracing::info!("Catalog: not asking {} for its lists: {}", mode, e);
+            None
+        }
+    }
+}
+
+/// Regions `provider` ("aws", "do", "hetzner", "vultr", "linode") offers.
+#[tauri::command]
+pub async fn list_regions(provider: String) -> Catalog<Region> {
+    let cloud = saved_provider(&provider, &default_aws_region());
+    catalog::regions(&provider, cloud.as_deref()).await
+}
+
+/// Server sizes `provider` offers in `region`.
+#[tauri::command]
+pub async fn list_sizes(provider: String, region: String) -> Catalog<ServerSize> {
+    let cloud = saved_provider(&provider, &region);
+    catalog::sizes(&provider, cloud.as_deref(), &region).await
+}
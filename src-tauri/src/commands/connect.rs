@@ -1,18 +1,51 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter};
+
 use crate::error::AppError;
 use crate::persistence::store;
-use crate::state::VpnConnectionStatus;
+use crate::state::{TunnelStats, VpnConnectionStatus};
 use crate::wireguard::tunnel;
+use crate::wireguard::userspace::{EventCallback, TunnelEvent};
+
+/// The deployment the tunnel was last brought up for. Only meaningful while
+/// the tunnel is active.
+static CONNECTED_DEPLOYMENT: Mutex<Option<String>> = Mutex::new(None);
 
 #[tauri::command]
-pub async fn connect_vpn() -> Result<(), AppError> {
-    tracing::info!("=== VPN Connect requested ===");
-    let state = store::load_state()?;
+pub async fn connect_vpn(app: AppHandle, deployment_id: String) -> Result<(), AppError> {
+    // Forward tunnel thread notifications to the frontend until it stops.
+    let on_event = Box::new(move |event: TunnelEvent| {
+        let _ = match event {
+            TunnelEvent::Stats(stats) => app.emit("tunnel-stats", stats),
+            TunnelEvent::Reconnecting(reason) => app.emit("vpn-reconnecting", reason),
+            TunnelEvent::Reconnected => app.emit("vpn-reconnected", ()),
+        };
+    });
+    connect_vpn_internal(&deployment_id, Some(on_event))
+}
+
+/// Bring the tunnel up to one deployment with its client config and the
+/// saved settings. A tunnel to another server is taken down first. The tunnel
+/// lives as long as this process, until `tunnel::deactivate_tunnel`.
+pub fn connect_vpn_internal(
+    deployment_id: &str,
+    on_event: Option<EventCallback>,
+) -> Result<(), AppError> {
+    tracing::info!("=== VPN Connect requested ({}) ===", deployment_id);
+    let state = store::load_deployment(deployment_id)?;
     let config = state
         .client_config
         .ok_or_else(|| AppError::State("No client config available".into()))?;
+    let settings = store::load_settings()?;
+    if tunnel::is_tunnel_active() {
+        tracing::info!("Switching servers — disconnecting the current tunnel first");
+        tunnel::deactivate_tunnel()?;
+    }
     tracing::info!("Client config loaded, activating tunnel...");
-    match tunnel::activate_tunnel(&config) {
+    match tunnel::activate_tunnel(&config, &settings, on_event) {
         Ok(()) => {
+            *CONNECTED_DEPLOYMENT.lock().unwrap() = Some(deployment_id.to_string());
             tracing::info!("=== VPN Connected successfully ===");
             Ok(())
         }
@@ -27,21 +60,38 @@ pub async fn connect_vpn() -> Result<(), AppError> {
 pub async fn disconnect_vpn() -> Result<(), AppError> {
     tracing::info!("=== VPN Disconnect requested ===");
     tunnel::deactivate_tunnel()?;
+    *CONNECTED_DEPLOYMENT.lock().unwrap() = None;
     tracing::info!("=== VPN Disconnected ===");
     Ok(())
 }
 
+/// `Connecting` while the tunnel is up but the server hasn't completed a
+/// handshake yet, so a wrong key or blocked port doesn't look connected.
 #[tauri::command]
 pub async fn get_vpn_status() -> Result<VpnConnectionStatus, AppError> {
-    if tunnel::is_tunnel_active() {
-        Ok(VpnConnectionStatus::Connected)
-    } else {
-        Ok(VpnConnectionStatus::Disconnected)
+    Ok(match tunnel::tunnel_stats() {
+        None => VpnConnectionStatus::Disconnected,
+        Some(stats) if stats.last_handshake_at.is_none() => VpnConnectionStatus::Connecting,
+        Some(_) => VpnConnectionStatus::Connected,
+    })
+}
+
+#[tauri::command]
+pub async fn get_tunnel_stats() -> Result<Option<TunnelStats>, AppError> {
+    Ok(tunnel::tunnel_stats())
+}
+
+/// Id of the deployment the tunnel is up for, if any.
+#[tauri::command]
+pub async fn get_connected_deployment() -> Result<Option<String>, AppError> {
+    if !tunnel::is_tunnel_active() {
+        return Ok(None);
     }
+    Ok(CONNECTED_DEPLOYMENT.lock().unwrap().clone())
 }
 
 #[tauri::command]
-pub async fn get_client_config() -> Result<Option<String>, AppError> {
-    let state = store::load_state()?;
+pub async fn get_client_config(deployment_id: String) -> Result<Option<String>, AppError> {
+    let state = store::load_deployment(&deployment_id)?;
     Ok(state.client_config)
 }
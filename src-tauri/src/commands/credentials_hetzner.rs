@@ -0,0 +1,27 @@
+use crate::error::AppError;
+use crate::hetzner::client::HetznerClient;
+use crate::persistence::store;
+use crate::state::HetznerCredentials;
+
+/// Hetzner tokens are scoped to one project and carry no account details,
+/// so a valid token has nothing to report back.
+#[tauri::command]
+pub async fn validate_hetzner_credentials(api_token: String) -> Result<(), AppError> {
+    HetznerClient::new(&api_token).validate().await
+}
+
+#[tauri::command]
+pub async fn save_hetzner_credentials(api_token: String) -> Result<(), AppError> {
+    let creds = HetznerCredentials { api_token };
+    store::save_hetzner_credentials(&creds)
+}
+
+#[tauri::command]
+pub async fn load_hetzner_credentials() -> Result<Option<HetznerCredentials>, AppError> {
+    store::load_hetzner_credentials()
+}
+
+#[tauri::command]
+pub async fn delete_hetzner_credentials() -> Result<(), AppError> {
+    store::delete_hetzner_credentials()
+}
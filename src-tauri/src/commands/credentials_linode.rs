@@ -0,0 +1,28 @@
+use crate::error::AppError;
+use crate::linode::provider::LinodeProvider;
+use crate::persistence::store;
+use crate::provider::CloudProvider;
+use crate::state::LinodeCredentials;
+
+/// Returns the username of the profile the token belongs to.
+#[tauri::command]
+pub async fn validate_linode_credentials(api_token: String) -> Result<String, AppError> {
+    let creds = LinodeCredentials { api_token };
+    LinodeProvider::new(&creds).validate_credentials().await
+}
+
+#[tauri::command]
+pub async fn save_linode_credentials(api_token: String) -> Result<(), AppError> {
+    let creds = LinodeCredentials { api_token };
+    store::save_linode_credentials(&creds)
+}
+
+#[tauri::command]
+pub async fn load_linode_credentials() -> Result<Option<LinodeCredentials>, AppError> {
+    store::load_linode_credentials()
+}
+
+#[tauri::command]
+pub async fn delete_linode_credentials() -> Result<(), AppError> {
+    store::delete_linode_credentials()
+}
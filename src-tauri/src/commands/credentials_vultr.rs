@@ -0,0 +1,28 @@
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::provider::CloudProvider;
+use crate::state::VultrCredentials;
+use crate::vultr::provider::VultrProvider;
+
+/// Returns the email of the account the API key belongs to.
+#[tauri::command]
+pub async fn validate_vultr_credentials(api_key: String) -> Result<String, AppError> {
+    let creds = VultrCredentials { api_key };
+    VultrProvider::new(&creds).validate_credentials().await
+}
+
+#[tauri::command]
+pub async fn save_vultr_credentials(api_key: String) -> Result<(), AppError> {
+    let creds = VultrCredentials { api_key };
+    store::save_vultr_credentials(&creds)
+}
+
+#[tauri::command]
+pub async fn load_vultr_credentials() -> Result<Option<VultrCredentials>, AppError> {
+    store::load_vultr_credentials()
+}
+
+#[tauri::command]
+pub async fn delete_vultr_credentials() -> Result<(), AppError> {
+    store::delete_vultr_credentials()
+}
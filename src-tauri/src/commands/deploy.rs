@@ -1,241 +1,732 @@
-use tauri::{AppHandle, Emitter};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
 
-use crate::aws::{ami, client, ec2, security_group, vpc};
-use crate::commands::timer;
+use tauri::AppHandle;
+
+use crate::aws::provider::{instance_architecture, AwsProvider};
+use crate::aws::{client, resume};
+use crate::catalog;
+use crate::commands::{destroy, ssh_access, timer};
+use crate::do_cloud::provider::DoProvider;
 use crate::error::AppError;
-use crate::persistence::store;
+use crate::hetzner::provider::HetznerProvider;
+use crate::linode::provider::LinodeProvider;
+use crate::persistence::{store, vault};
+use crate::pricing;
+use crate::progress::{ProgressSink, TauriProgress, DEPLOY_EVENT, DESTROY_EVENT};
+use crate::provider::CloudProvider;
+use crate::public_ip;
+use crate::server_image::ServerImage;
 use crate::ssh;
-use crate::state::{DeploymentState, DeploymentStatus, ProgressEvent};
-use crate::wireguard::{client_config, keys, server_config};
-
-fn emit_progress(app: &AppHandle, step: u32, total: u32, message: &str, status: &str) {
-    let event = ProgressEvent {
-        step,
-        total_steps: total,
-        message: message.to_string(),
-        status: status.to_string(),
-    };
-    let _ = app.emit("deploy-progress", event);
-}
+use crate::state::{deployment_name, AwsCredentials, DeploymentState, DeploymentStatus};
+use crate::vultr::provider::VultrProvider;
+use crate::wireguard::subnet::{self, TunnelSubnet};
+use crate::wireguard::{bootstrap, client_config, keys, probe};
+
+const DEPLOY_STEPS: u32 = 7;
+/// WireGuard is set up over SSH, or checked on a server that set itself up
+/// from its user data, in this step; a resume past it skips it.
+const CONFIGURE_STEP: u32 = 6;
+/// How long a server deployed without SSH has to boot, install WireGuard
+/// and answer a handshake.
+const HANDSHAKE_TIMEOUT_SECS: u64 = 600;
 
 #[tauri::command]
 pub async fn deploy_vpn(
     app: AppHandle,
+    deployment_id: Option<String>,
     region: String,
     auto_destroy_hours: Option<u32>,
 ) -> Result<DeploymentState, AppError> {
-    let total_steps = 10;
-    tracing::info!("=== Starting VPN deployment to region: {} ===", region);
+    let creds = store::load_credentials()?
+        .ok_or_else(|| AppError::Credential("No credentials saved".into()))?;
+    let size = store::load_settings()?.instance_type;
+    let provider = AwsProvider::new(creds, &region);
+    deploy_command(app, &provider, deployment_id, region, size, auto_destroy_hours).await
+}
 
-    let mut state = DeploymentState {
-        status: DeploymentStatus::Deploying,
-        region: Some(region.clone()),
-        ..Default::default()
+#[tauri::command]
+pub async fn deploy_do(
+    app: AppHandle,
+    deployment_id: Option<String>,
+    region: String,
+    size: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let creds = store::load_do_credentials()?
+        .ok_or_else(|| AppError::Credential("No DigitalOcean credentials saved".into()))?;
+    let provider = DoProvider::new(&creds);
+    deploy_command(app, &provider, deployment_id, region, size, auto_destroy_hours).await
+}
+
+#[tauri::command]
+pub async fn deploy_hetzner(
+    app: AppHandle,
+    deployment_id: Option<String>,
+    location: String,
+    server_type: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let creds = store::load_hetzner_credentials()?
+        .ok_or_else(|| AppError::Credential("No Hetzner credentials saved".into()))?;
+    let provider = HetznerProvider::new(&creds);
+    deploy_command(
+        app,
+        &provider,
+        deployment_id,
+        location,
+        server_type,
+        auto_destroy_hours,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn deploy_vultr(
+    app: AppHandle,
+    deployment_id: Option<String>,
+    region: String,
+    plan: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let creds = store::load_vultr_credentials()?
+        .ok_or_else(|| AppError::Credential("No Vultr credentials saved".into()))?;
+    let provider = VultrProvider::new(&creds);
+    deploy_command(app, &provider, deployment_id, region, plan, auto_destroy_hours).await
+}
+
+#[tauri::command]
+pub async fn deploy_linode(
+    app: AppHandle,
+    deployment_id: Option<String>,
+    region: String,
+    linode_type: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let creds = store::load_linode_credentials()?
+        .ok_or_else(|| AppError::Credential("No Linode credentials saved".into()))?;
+    let provider = LinodeProvider::new(&creds);
+    deploy_command(
+        app,
+        &provider,
+        deployment_id,
+        region,
+        linode_type,
+        auto_destroy_hours,
+    )
+    .await
+}
+
+/// What every deploy command does around `deploy_on`: check the region and
+/// size are on offer, claim the id, roll a failed deploy back, and schedule
+/// auto-destroy. The server image is the one chosen in Settings.
+async fn deploy_command(
+    app: AppHandle,
+    provider: &dyn CloudProvider,
+    deployment_id: Option<String>,
+    region: String,
+    size: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let image = store::load_settings()?.server_image();
+    catalog::check_offered(provider, &region, &size).await?;
+    let id = store::claim_deployment_id(deployment_id)?;
+    let progress = TauriProgress::new(app.clone(), DEPLOY_EVENT);
+    let result = deploy_on(
+        provider,
+        &progress,
+        &id,
+        region,
+        size,
+        image,
+        auto_destroy_hours,
+    )
+    .await;
+    let state = match result {
+        Ok(state) => state,
+        Err(e) => {
+            let progress = TauriProgress::new(app, DESTROY_EVENT);
+            destroy::rollback_failed_deploy(&progress, &id, provider).await;
+            return Err(e);
+        }
     };
-    store::save_state(&state)?;
 
+    if let Some(at) = state.auto_destroy_at {
+        timer::spawn_auto_destroy_timer(app, id, at);
+    }
+    Ok(state)
+}
+
+/// Continue a failed AWS deployment from its last completed step.
+#[tauri::command]
+pub async fn resume_deployment(
+    app: AppHandle,
+    deployment_id: String,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
     let creds = store::load_credentials()?
         .ok_or_else(|| AppError::Credential("No credentials saved".into()))?;
+    let progress = TauriProgress::new(app.clone(), DEPLOY_EVENT);
+    let state = resume_aws(&progress, &deployment_id, &creds, auto_destroy_hours).await?;
+
+    if let Some(at) = state.auto_destroy_at {
+        timer::spawn_auto_destroy_timer(app, deployment_id, at);
+    }
+    Ok(state)
+}
+
+/// Deploy a WireGuard VPN on `provider` (7 steps) in `region`, on a server
+/// of `size` booting `image`, saved under `deployment_id` (see
+/// `store::claim_deployment_id`). The caller is responsible for rolling back
+/// a failed deploy and for scheduling auto-destroy, if requested.
+pub async fn deploy_on(
+    provider: &dyn CloudProvider,
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
+    region: String,
+    size: String,
+    image: ServerImage,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    // Secrets are saved as soon as they exist; a locked vault must fail first.
+    vault::ensure_unlocked()?;
     let settings = store::load_settings()?;
+    let tunnel = subnet::validate(&settings.tunnel_cidr)?;
+    provider.check_image(&image, &size)?;
+    if settings.deploy_without_ssh && !provider.runs_user_data() {
+        return Err(AppError::State(format!(
+            "{} servers are set up over SSH, so they can't be deployed without it. \
+             Turn off \"Deploy without SSH\" in Settings.",
+            provider.display_name()
+        )));
+    }
     tracing::info!(
-        "Loaded settings: instance_type={}, wireguard_port={}",
-        settings.instance_type,
-        settings.wireguard_port
+        "=== Starting {} deployment to region: {} ({}) ===",
+        provider.display_name(),
+        region,
+        image
     );
 
-    // Step 1: Build AWS config
-    emit_progress(&app, 1, total_steps, "Connecting to AWS...", "running");
-    tracing::info!("[Step 1/{}] Building AWS config for region {}", total_steps, region);
-    let config = client::build_config(&creds, &region).await?;
-    let ec2_client = aws_sdk_ec2::Client::new(&config);
-    tracing::info!("[Step 1/{}] AWS config ready", total_steps);
-
-    // Step 2: Lookup AMI
-    emit_progress(&app, 2, total_steps, "Finding Ubuntu AMI...", "running");
-    tracing::info!("[Step 2/{}] Looking up latest Ubuntu 22.04 AMI in {}", total_steps, region);
-    let ami_id = ami::lookup_ubuntu_ami(&config).await?;
-    tracing::info!("[Step 2/{}] Using AMI: {}", total_steps, ami_id);
-
-    // Step 3: Create VPC
-    emit_progress(&app, 3, total_steps, "Creating VPC...", "running");
-    tracing::info!("[Step 3/{}] Creating VPC", total_steps);
-    let vpc_id = vpc::create_vpc(&ec2_client).await?;
-    tracing::info!("[Step 3/{}] VPC created: {}", total_steps, vpc_id);
-    state.vpc_id = Some(vpc_id.clone());
-    store::save_state(&state)?;
-
-    // Step 4: Create IGW + Subnet + Route Table
-    emit_progress(&app, 4, total_steps, "Setting up networking...", "running");
-    tracing::info!("[Step 4/{}] Creating Internet Gateway", total_steps);
-    let igw_id = vpc::create_internet_gateway(&ec2_client, &vpc_id).await?;
-    tracing::info!("[Step 4/{}] IGW created: {}", total_steps, igw_id);
-    state.igw_id = Some(igw_id.clone());
-    store::save_state(&state)?;
-
-    tracing::info!("[Step 4/{}] Creating subnet in {}a", total_steps, region);
-    let subnet_id = vpc::create_subnet(&ec2_client, &vpc_id, &region).await?;
-    tracing::info!("[Step 4/{}] Subnet created: {}", total_steps, subnet_id);
-    state.subnet_id = Some(subnet_id.clone());
-    store::save_state(&state)?;
-
-    tracing::info!("[Step 4/{}] Creating route table", total_steps);
-    let rt_id = vpc::create_route_table(&ec2_client, &vpc_id, &igw_id, &subnet_id).await?;
-    tracing::info!("[Step 4/{}] Route table created: {}", total_steps, rt_id);
-    state.route_table_id = Some(rt_id);
-    store::save_state(&state)?;
-
-    // Step 5: Create Security Group
-    emit_progress(&app, 5, total_steps, "Creating firewall rules...", "running");
+    let hourly_cost = pricing::hourly_rate(provider.mode(), Some(provider), &region, &size).await;
+    if let Some(hourly) = hourly_cost {
+        let estimate = pricing::estimate(provider.mode(), hourly, auto_destroy_hours);
+        tracing::info!(
+            "Estimated cost: {}/hour, {}/month",
+            pricing::format_amount(&estimate.currency, estimate.hourly),
+            pricing::format_amount(&estimate.currency, estimate.monthly)
+        );
+    }
+
+    let state = DeploymentState {
+        id: deployment_id.to_string(),
+        name: deployment_name(Some(provider.mode()), Some(&region), None),
+        status: DeploymentStatus::Deploying,
+        deployment_mode: Some(provider.mode().to_string()),
+        region: Some(region),
+        os_release: Some(image.os),
+        architecture: Some(image.arch),
+        ssh_user: Some(provider.ssh_user(&image).to_string()),
+        // A deploy without SSH starts out locked down: no SSH rule is added.
+        ssh_locked: settings.deploy_without_ssh,
+        user_data_bootstrap: provider.runs_user_data(),
+        tunnel_cidr: Some(tunnel.to_string()),
+        hourly_cost,
+        cost_currency: hourly_cost.map(|_| pricing::currency(provider.mode()).to_string()),
+        ..Default::default()
+    };
+    store::save_deployment(&state)?;
+
+    let result = run_steps(provider, progress, state, &size, auto_destroy_hours).await;
+    if let Err(ref e) = result {
+        mark_failed(deployment_id, e);
+    }
+    result
+}
+
+/// Continue an interrupted or failed AWS deploy after its last completed
+/// step. Recorded resources are checked first; whatever still exists is
+/// reused, the rest is created again.
+pub async fn resume_aws(
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
+    creds: &AwsCredentials,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    vault::ensure_unlocked()?;
+    let mut state = store::load_deployment(deployment_id)?;
+    if !matches!(state.deployment_mode.as_deref(), None | Some("aws")) {
+        return Err(AppError::State(
+            "Only AWS deployments can be resumed — destroy this one and deploy again".into(),
+        ));
+    }
+    if state.status != DeploymentStatus::Failed {
+        return Err(AppError::State(format!(
+            "Deployment '{}' has not failed, so there is nothing to resume",
+            deployment_id
+        )));
+    }
+    let region = state
+        .region
+        .clone()
+        .ok_or_else(|| AppError::State("Deployment has no region recorded".into()))?;
     tracing::info!(
-        "[Step 5/{}] Creating security group (WireGuard port: {})",
-        total_steps,
-        settings.wireguard_port
+        "=== Resuming VPN deployment {} in {} after step {} ===",
+        deployment_id,
+        region,
+        state.completed_step
     );
-    let sg_id =
-        security_group::create_security_group(&ec2_client, &vpc_id, settings.wireguard_port)
-            .await?;
-    tracing::info!("[Step 5/{}] Security group created: {}", total_steps, sg_id);
-    state.security_group_id = Some(sg_id.clone());
-    store::save_state(&state)?;
-
-    // Step 6: Create Key Pair
-    emit_progress(&app, 6, total_steps, "Generating SSH keys...", "running");
-    tracing::info!("[Step 6/{}] Creating EC2 key pair", total_steps);
-    let (key_name, private_key) = ec2::create_key_pair(&ec2_client).await?;
-    tracing::info!("[Step 6/{}] Key pair created: {}", total_steps, key_name);
-    state.key_pair_name = Some(key_name.clone());
-    state.ssh_private_key = Some(private_key.clone());
-    store::save_state(&state)?;
-
-    // Step 7: Launch Instance
-    emit_progress(&app, 7, total_steps, "Launching server...", "running");
+
+    let config = client::build_config(creds, &region).await?;
+    let ec2_client = aws_sdk_ec2::Client::new(&config);
+    let existing = resume::existing_ids(&ec2_client, &state).await?;
+    let abandoned = resume::prune_missing(&mut state, &existing);
+    if !abandoned.is_empty() {
+        tracing::warn!(
+            "Resume: left behind {:?} — they depend on resources that are gone",
+            abandoned
+        );
+    }
+    tracing::info!("Resume: continuing after step {}", state.completed_step);
+    // A server that is still to be launched must match the recorded image.
+    let size = store::load_settings()?.instance_type;
+    if state.instance_id.is_none() {
+        state
+            .server_image()
+            .check_size(&size, instance_architecture(&size))?;
+    }
+
+    state.status = DeploymentStatus::Deploying;
+    state.error_message = None;
+    // Older deployments did not record the SSH user.
+    state.ssh_user.get_or_insert_with(|| "ubuntu".to_string());
+    store::save_deployment(&state)?;
+
+    let provider = AwsProvider::new(creds.clone(), &region);
+    if state.hourly_cost.is_none() {
+        state.hourly_cost = pricing::hourly_rate("aws", None, &region, &size).await;
+        state.cost_currency = state.hourly_cost.map(|_| pricing::currency("aws").to_string());
+    }
+    let result = run_steps(&provider, progress, state, &size, auto_destroy_hours).await;
+    if let Err(ref e) = result {
+        mark_failed(deployment_id, e);
+    }
+    result
+}
+
+/// Leave a deployment whose steps returned an error as `Failed`, with its
+/// recorded resources intact so it can be rolled back, resumed or destroyed.
+pub fn mark_failed(deployment_id: &str, error: &AppError) {
+    match store::load_deployment_metadata(deployment_id) {
+        Ok(mut state) => {
+            state.status = DeploymentStatus::Failed;
+            state.error_message = Some(error.to_string());
+            if let Err(e) = store::save_deployment(&state) {
+                tracing::warn!("Could not mark deployment {} failed: {}", deployment_id, e);
+            }
+        }
+        Err(e) => tracing::warn!("Could not mark deployment {} failed: {}", deployment_id, e),
+    }
+}
+
+/// Record that `step` finished and persist it, so a resume starts after it.
+/// Never moves backwards: a resume re-runs cheap early steps.
+fn complete_step(state: &mut DeploymentState, step: u32) -> Result<(), AppError> {
+    state.completed_step = state.completed_step.max(step);
+    store::save_deployment(state)
+}
+
+/// Close a step that may have recorded resources in `state` before failing:
+/// on success the step is completed, on failure whatever it recorded is
+/// still saved, so a rollback or destroy can find it.
+fn finish_step<T>(
+    state: &mut DeploymentState,
+    step: u32,
+    result: Result<T, AppError>,
+) -> Result<T, AppError> {
+    match result {
+        Ok(value) => {
+            complete_step(state, step)?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(save_err) = store::save_deployment(state) {
+                tracing::warn!("Could not save deployment {}: {}", state.id, save_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// The 7 deploy steps. Providers reuse every resource already recorded in
+/// `state`, so a fresh deploy and a resume share this path.
+async fn run_steps(
+    provider: &dyn CloudProvider,
+    progress: &dyn ProgressSink,
+    mut state: DeploymentState,
+    size: &str,
+    auto_destroy_hours: Option<u32>,
+) -> Result<DeploymentState, AppError> {
+    let total_steps = DEPLOY_STEPS;
+    let name = provider.display_name();
+    let settings = store::load_settings()?;
+    let tunnel = TunnelSubnet::of(state.tunnel_cidr.as_deref())?;
     tracing::info!(
-        "[Step 7/{}] Launching EC2 instance (ami={}, type={}, subnet={}, sg={})",
-        total_steps,
-        ami_id,
-        settings.instance_type,
-        subnet_id,
-        sg_id
+        "Deploying with size={}, wireguard_port={}",
+        size,
+        settings.wireguard_port
     );
-    let instance_id = ec2::launch_instance(
-        &ec2_client,
-        &ami_id,
-        &settings.instance_type,
-        &subnet_id,
-        &sg_id,
-        &key_name,
-    )
-    .await?;
-    tracing::info!("[Step 7/{}] Instance launched: {}", total_steps, instance_id);
-    state.instance_id = Some(instance_id.clone());
-    store::save_state(&state)?;
-
-    tracing::info!("[Step 7/{}] Waiting for instance {} to reach running state...", total_steps, instance_id);
-    ec2::wait_for_instance_running(&ec2_client, &instance_id).await?;
-    tracing::info!("[Step 7/{}] Instance {} is running", total_steps, instance_id);
-
-    // Step 8: Allocate EIP
-    emit_progress(&app, 8, total_steps, "Allocating static IP...", "running");
-    tracing::info!("[Step 8/{}] Allocating Elastic IP", total_steps);
-    let (alloc_id, assoc_id, elastic_ip) =
-        ec2::allocate_and_associate_eip(&ec2_client, &instance_id).await?;
+
+    // Step 1: Check credentials
+    progress.emit(1, total_steps, &format!("Connecting to {}...", name), "running");
+    tracing::info!("[Step 1/{}] Validating {} credentials", total_steps, name);
+    provider.validate_credentials().await?;
+    tracing::info!("[Step 1/{}] Credentials valid", total_steps);
+    complete_step(&mut state, 1)?;
+
+    // Step 2: Firewall first, so the server is never exposed. SSH is only
+    // let in from this computer, and only needed until step 6, if at all.
+    progress.emit(2, total_steps, "Creating firewall rules...", "running");
+    let ssh_source = if state.completed_step < CONFIGURE_STEP && !state.ssh_locked {
+        Some(public_ip::ssh_source(&settings.ip_lookup_url).await?)
+    } else {
+        None
+    };
+    if state.completed_step < 2 {
+        state.ssh_source = ssh_source.clone();
+    }
     tracing::info!(
-        "[Step 8/{}] EIP allocated: {} (alloc={}, assoc={})",
+        "[Step 2/{}] Creating firewall (WireGuard port: {}, SSH from {})",
         total_steps,
-        elastic_ip,
-        alloc_id,
-        assoc_id
+        settings.wireguard_port,
+        if state.ssh_locked {
+            "nowhere"
+        } else {
+            state.ssh_source.as_deref().unwrap_or("anywhere")
+        }
     );
-    state.allocation_id = Some(alloc_id);
-    state.association_id = Some(assoc_id);
-    state.elastic_ip = Some(elastic_ip.clone());
-    store::save_state(&state)?;
-
-    // Step 9: Generate WireGuard keys and configure via SSH
-    emit_progress(
-        &app,
-        9,
+    let result = provider
+        .create_firewall(&mut state, settings.wireguard_port)
+        .await;
+    finish_step(&mut state, 2, result)?;
+    // A resumed deploy's firewall may let SSH in from an address this
+    // computer no longer has.
+    if let Some(source) = ssh_source {
+        if state.ssh_source.as_deref() != Some(source.as_str()) {
+            ssh_access::open(provider, &mut state, settings.wireguard_port, source).await?;
+        }
+    }
+
+    // Step 3: Deploy SSH key
+    progress.emit(3, total_steps, "Generating SSH keys...", "running");
+    tracing::info!("[Step 3/{}] Creating SSH key", total_steps);
+    let result = provider.create_ssh_key(&mut state).await;
+    finish_step(&mut state, 3, result)?;
+
+    // Step 4: Server
+    progress.emit(4, total_steps, "Launching server...", "running");
+    tracing::info!("[Step 4/{}] Launching {} server ({})", total_steps, name, size);
+    // A server that sets WireGuard up from its user data needs the keys
+    // before it exists.
+    let user_data = if state.user_data_bootstrap && state.completed_step < 4 {
+        bootstrap_user_data(&mut state, settings.wireguard_port, &tunnel)?
+    } else {
+        None
+    };
+    let result = provider
+        .create_server(&mut state, size, user_data.as_deref())
+        .await;
+    finish_step(&mut state, 4, result)?;
+
+    // Step 5: Public address
+    progress.emit(5, total_steps, "Waiting for server to start...", "running");
+    tracing::info!("[Step 5/{}] Waiting for the server's public IP", total_steps);
+    let result = provider.wait_for_ip(&mut state).await;
+    if let Ok(ref ip) = result {
+        state.elastic_ip = Some(ip.clone());
+    }
+    let server_ip = finish_step(&mut state, 5, result)?;
+    tracing::info!("[Step 5/{}] Server running, IP: {}", total_steps, server_ip);
+
+    // Step 6: Configure WireGuard via SSH, or wait for the server to have
+    // done it from its user data
+    progress.emit(
+        6,
         total_steps,
-        "Configuring WireGuard (this may take a minute)...",
+        if state.user_data_bootstrap {
+            "Waiting for the server to set up WireGuard (this may take a minute)..."
+        } else {
+            "Configuring WireGuard (this may take a minute)..."
+        },
         "running",
     );
-    tracing::info!("[Step 9/{}] Generating WireGuard key pairs", total_steps);
+    if state.completed_step >= CONFIGURE_STEP {
+        tracing::info!("[Step 6/{}] WireGuard already configured, skipping", total_steps);
+    } else {
+        if state.user_data_bootstrap {
+            verify_bootstrap(
+                provider,
+                progress,
+                &mut state,
+                &server_ip,
+                settings.wireguard_port,
+            )
+            .await?;
+        } else {
+            configure_server(
+                provider,
+                progress,
+                &mut state,
+                &server_ip,
+                settings.wireguard_port,
+                &tunnel,
+            )
+            .await?;
+        }
+        complete_step(&mut state, CONFIGURE_STEP)?;
+    }
+    if settings.lock_down_ssh && !state.ssh_locked {
+        progress.emit(6, total_steps, "Locking down SSH...", "running");
+        tracing::info!("[Step 6/{}] Removing the SSH rule", total_steps);
+        // The VPN works either way; SSH stays open to this computer only.
+        if let Err(e) = ssh_access::close(provider, &mut state, settings.wireguard_port).await {
+            tracing::warn!("[Step 6/{}] Could not lock down SSH: {}", total_steps, e);
+        }
+    }
+
+    // Step 7: Generate client config
+    progress.emit(7, total_steps, "Generating client config...", "running");
+    tracing::info!("[Step 7/{}] Rendering WireGuard client config", total_steps);
+    let client_conf = client_config::render_client_config(
+        state
+            .client_private_key
+            .as_deref()
+            .ok_or_else(|| AppError::State("Client key missing after WireGuard setup".into()))?,
+        state
+            .server_public_key
+            .as_deref()
+            .ok_or_else(|| AppError::State("Server key missing after WireGuard setup".into()))?,
+        &server_ip,
+        settings.wireguard_port,
+        &tunnel,
+        state.carries_ipv6(),
+    );
+    state.client_config = Some(client_conf);
 
+    // Done!
+    state.status = DeploymentStatus::Deployed;
+    state.completed_step = total_steps;
+    state.deployed_at = Some(chrono::Utc::now());
+
+    if let Some(hours) = auto_destroy_hours {
+        let destroy_at = chrono::Utc::now() + chrono::Duration::hours(hours as i64);
+        state.auto_destroy_at = Some(destroy_at);
+        tracing::info!("Auto-destroy scheduled for {} (in {}h)", destroy_at, hours);
+    }
+
+    store::save_deployment(&state)?;
+
+    tracing::info!("=== {} deployment complete! Server IP: {} ===", name, server_ip);
+    progress.emit(total_steps, total_steps, "VPN deployed successfully!", "done");
+
+    Ok(state)
+}
+
+/// Generate the WireGuard key pairs for a server that sets itself up from
+/// its user data, record the ones the client needs in `state`, and render
+/// the script. The server's private key is only in the script.
+///
+/// None if the server was already created by an interrupted deploy: its
+/// user data holds the keys recorded then, which a resume must keep.
+fn bootstrap_user_data(
+    state: &mut DeploymentState,
+    wireguard_port: u16,
+    tunnel: &TunnelSubnet,
+) -> Result<Option<String>, AppError> {
+    if state.instance_id.is_some() || state.droplet_id.is_some() {
+        if state.server_public_key.is_none() || state.client_private_key.is_none() {
+            return Err(AppError::State(
+                "The server was created, but the WireGuard keys it was set up with were \
+                 not saved, so this deployment can't be resumed. Destroy it and deploy again."
+                    .into(),
+            ));
+        }
+        tracing::info!("Server already created, keeping the WireGuard keys in its user data");
+        return Ok(None);
+    }
     let server_keys = keys::generate_keypair();
     let client_keys = keys::generate_keypair();
-
-    let wg_server_conf = server_config::render_server_config(
+    let user_data = bootstrap::render_user_data(
         &server_keys.private_key,
+        &server_keys.public_key,
         &client_keys.public_key,
-        settings.wireguard_port,
+        wireguard_port,
+        tunnel,
     );
+    state.server_public_key = Some(server_keys.public_key);
+    state.client_private_key = Some(client_keys.private_key);
+    state.client_public_key = Some(client_keys.public_key);
+    Ok(Some(user_data))
+}
 
-    // Wait a bit for SSH to become available after instance starts
+/// Wait for SSH on a new server, connect and pin its host key.
+async fn connect_ssh(
+    provider: &dyn CloudProvider,
+    state: &mut DeploymentState,
+    server_ip: &str,
+) -> Result<ssh::client::SshSession, AppError> {
+    let total_steps = DEPLOY_STEPS;
+
+    // "Running" means booted, not that sshd is up yet
     tracing::info!(
-        "[Step 9/{}] Waiting 30s for SSH to become available on {}:22...",
+        "[Step 6/{}] Waiting 30s for SSH to become available on {}:22...",
         total_steps,
-        elastic_ip
+        server_ip
     );
     tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
 
+    // Pin the host key when the provider learned it out of band; otherwise
+    // fall back to trust-on-first-use.
+    let known_fingerprints = provider.host_key_fingerprints(state).await;
+
+    let private_key = state
+        .ssh_private_key
+        .clone()
+        .ok_or_else(|| AppError::State("SSH key missing before WireGuard setup".into()))?;
+    let ssh_user = provider.ssh_user(&state.server_image());
     tracing::info!(
-        "[Step 9/{}] Connecting via SSH to {} as ubuntu (timeout=120s)",
+        "[Step 6/{}] Connecting via SSH to {} as {} (timeout=120s)",
         total_steps,
-        elastic_ip
+        server_ip,
+        ssh_user
     );
-    let ssh_session =
-        ssh::client::SshSession::connect(&elastic_ip, 22, "ubuntu", &private_key, 120).await?;
-    tracing::info!("[Step 9/{}] SSH connected, configuring WireGuard...", total_steps);
+    let ssh_session = ssh::client::SshSession::connect(
+        server_ip,
+        22,
+        ssh_user,
+        &private_key,
+        120,
+        &known_fingerprints,
+    )
+    .await?;
+    state.ssh_host_key_fingerprint = Some(ssh_session.host_key_fingerprint().to_string());
+    store::save_deployment(state)?;
+    Ok(ssh_session)
+}
+
+/// Step 6: generate the WireGuard key pairs, wait for SSH, pin the host key
+/// and install WireGuard, streaming the server's output to `progress`. Keys
+/// are stored in `state` once the server is set up.
+async fn configure_server(
+    provider: &dyn CloudProvider,
+    progress: &dyn ProgressSink,
+    state: &mut DeploymentState,
+    server_ip: &str,
+    wireguard_port: u16,
+    tunnel: &TunnelSubnet,
+) -> Result<(), AppError> {
+    let total_steps = DEPLOY_STEPS;
+    tracing::info!("[Step 6/{}] Generating WireGuard key pairs", total_steps);
+
+    let server_keys = keys::generate_keypair();
+    let client_keys = keys::generate_keypair();
 
-    ssh::configure::configure_wireguard(&ssh_session, &wg_server_conf, &server_keys.public_key)
-        .await?;
-    tracing::info!("[Step 9/{}] WireGuard configured on server", total_steps);
+    let ssh_session = connect_ssh(provider, state, server_ip).await?;
+    tracing::info!("[Step 6/{}] SSH connected, configuring WireGuard...", total_steps);
 
+    let ipv6 = ssh::configure::configure_wireguard(
+        &ssh_session,
+        progress,
+        &server_keys,
+        &client_keys.public_key,
+        wireguard_port,
+        tunnel,
+    )
+    .await?;
+    tracing::info!("[Step 6/{}] WireGuard configured on server", total_steps);
+
+    state.tunnel_ipv6 = Some(ipv6);
     state.server_public_key = Some(server_keys.public_key.clone());
     state.client_private_key = Some(client_keys.private_key.clone());
     state.client_public_key = Some(client_keys.public_key.clone());
+    Ok(())
+}
 
-    // Step 10: Generate client config
-    emit_progress(&app, 10, total_steps, "Generating client config...", "running");
-    tracing::info!("[Step 10/{}] Rendering WireGuard client config", total_steps);
-    let client_conf = client_config::render_client_config(
-        &client_keys.private_key,
-        &server_keys.public_key,
-        &elastic_ip,
-        settings.wireguard_port,
-    );
-    state.client_config = Some(client_conf.clone());
-    store::save_client_config(&client_conf)?;
-
-    // Done!
-    state.status = DeploymentStatus::Deployed;
-    state.deployed_at = Some(chrono::Utc::now());
-
-    if let Some(hours) = auto_destroy_hours {
-        let destroy_at = chrono::Utc::now() + chrono::Duration::hours(hours as i64);
-        state.auto_destroy_at = Some(destroy_at);
-        tracing::info!("Auto-destroy scheduled for {} (in {}h)", destroy_at, hours);
+/// Step 6 for a server that set WireGuard up from its user data: wait for
+/// its first-boot script over SSH or, on a deploy without SSH, until it
+/// answers a WireGuard handshake.
+async fn verify_bootstrap(
+    provider: &dyn CloudProvider,
+    progress: &dyn ProgressSink,
+    state: &mut DeploymentState,
+    server_ip: &str,
+    wireguard_port: u16,
+) -> Result<(), AppError> {
+    let total_steps = DEPLOY_STEPS;
+    if !state.ssh_locked {
+        let ssh_session = connect_ssh(provider, state, server_ip).await?;
+        tracing::info!(
+            "[Step 6/{}] SSH connected, waiting for the server's first-boot setup...",
+            total_steps
+        );
+        let ipv6 = ssh::configure::wait_for_bootstrap(&ssh_session, progress).await?;
+        state.tunnel_ipv6 = Some(ipv6);
+        return Ok(());
     }
 
-    store::save_state(&state)?;
-
-    if let Some(at) = state.auto_destroy_at {
-        timer::spawn_auto_destroy_timer(app.clone(), at);
-    }
+    let client_private_key = state
+        .client_private_key
+        .as_deref()
+        .ok_or_else(|| AppError::State("Client key missing before WireGuard check".into()))?;
+    let server_public_key = state
+        .server_public_key
+        .as_deref()
+        .ok_or_else(|| AppError::State("Server key missing before WireGuard check".into()))?;
+    let ip: IpAddr = server_ip
+        .parse()
+        .map_err(|_| AppError::State(format!("Invalid server IP '{}'", server_ip)))?;
+    let endpoint = SocketAddr::new(ip, wireguard_port);
+    tracing::info!(
+        "[Step 6/{}] No SSH: waiting for WireGuard on {} to answer a handshake",
+        total_steps,
+        endpoint
+    );
+    probe::wait_for_handshake(
+        endpoint,
+        client_private_key,
+        server_public_key,
+        Duration::from_secs(HANDSHAKE_TIMEOUT_SECS),
+    )
+    .await
+}
 
-    tracing::info!("=== VPN deployment complete! Server IP: {} ===", elastic_ip);
-    emit_progress(&app, total_steps, total_steps, "VPN deployed successfully!", "done");
+#[tauri::command]
+pub async fn list_deployments() -> Result<Vec<DeploymentState>, AppError> {
+    store::list_deployments()
+}
 
-    Ok(state)
+#[tauri::command]
+pub async fn get_deployment_state(deployment_id: String) -> Result<DeploymentState, AppError> {
+    store::load_deployment_metadata(&deployment_id)
 }
 
 #[tauri::command]
-pub async fn get_deployment_state() -> Result<DeploymentState, AppError> {
-    store::load_state()
+pub async fn rename_deployment(
+    deployment_id: String,
+    name: String,
+) -> Result<DeploymentState, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::General("Deployment name cannot be empty".into()));
+    }
+    let mut state = store::load_deployment_metadata(&deployment_id)?;
+    state.name = name.to_string();
+    store::save_deployment(&state)?;
+    Ok(state)
 }
 
-/// Clears any stuck/failed deployment state so the user can start fresh.
+/// Forgets a stuck/failed deployment so the user can start fresh.
+/// Cloud resources are not touched — use `destroy_vpn` for that.
 #[tauri::command]
-pub async fn reset_deployment_state() -> Result<(), AppError> {
-    store::clear_state()
+pub async fn reset_deployment_state(deployment_id: String) -> Result<(), AppError> {
+    store::remove_deployment(&deployment_id)
 }
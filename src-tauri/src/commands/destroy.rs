@@ -1,84 +1,54 @@
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
-use crate::aws::{client, teardown};
-use crate::do_cloud;
 use crate::error::AppError;
 use crate::persistence::store;
+use crate::progress::{ProgressSink, TauriProgress, DESTROY_EVENT};
+use crate::provider::{for_deployment, CloudProvider};
 use crate::ssh;
-use crate::state::{DeploymentStatus, ProgressEvent};
+use crate::state::{DeploymentState, DeploymentStatus};
 
-fn emit_progress(app: &AppHandle, step: u32, total: u32, message: &str, status: &str) {
-    let event = ProgressEvent {
-        step,
-        total_steps: total,
-        message: message.to_string(),
-        status: status.to_string(),
-    };
-    let _ = app.emit("destroy-progress", event);
+/// Internal destroy logic — called by both the Tauri command and the auto-destroy timer.
+/// Uses the saved cloud credentials.
+pub async fn destroy_vpn_internal(
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
+) -> Result<(), AppError> {
+    destroy_with_provider(progress, deployment_id, None).await
 }
 
-/// Internal destroy logic — called by both the Tauri command and the auto-destroy timer.
-pub async fn destroy_vpn_internal(app: &AppHandle) -> Result<(), AppError> {
-    let mut state = store::load_state()?;
+/// Destroy one deployment. Without a `provider` the one it was deployed on
+/// is built from the saved credentials, so callers like the CLI can supply
+/// credentials from the environment instead.
+pub async fn destroy_with_provider(
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
+    provider: Option<&dyn CloudProvider>,
+) -> Result<(), AppError> {
+    let mut state = store::load_deployment(deployment_id)?;
 
     if state.status == DeploymentStatus::NotDeployed {
         return Err(AppError::State("No deployment to destroy".into()));
     }
 
-    // ── DigitalOcean: API teardown + clear local state ────────────────────────
-    if state.deployment_mode.as_deref() == Some("do") {
-        tracing::info!("Destroying DigitalOcean deployment");
-        state.status = DeploymentStatus::Destroying;
-        store::save_state(&state)?;
-
-        match store::load_do_credentials() {
-            Ok(Some(creds)) => {
-                let do_client = do_cloud::client::DoClient::new(&creds.api_token);
-
-                emit_progress(app, 1, 3, "Deleting firewall...", "running");
-                if let Some(ref firewall_id) = state.do_firewall_id {
-                    let result = do_cloud::firewall::delete_firewall(&do_client, firewall_id).await;
-                    if let Err(e) = result {
-                        tracing::warn!("Failed to delete DO firewall: {}", e);
-                    }
-                }
-
-                emit_progress(app, 2, 3, "Deleting server...", "running");
-                if let Some(droplet_id) = state.droplet_id {
-                    let result = do_cloud::droplet::delete_droplet(&do_client, droplet_id).await;
-                    if let Err(e) = result {
-                        tracing::warn!("Failed to delete DO droplet: {}", e);
-                    }
-                }
-
-                emit_progress(app, 3, 3, "Cleaning up...", "running");
-                if let Some(key_id) = state.do_ssh_key_id {
-                    let result = do_cloud::key::delete_ssh_key(&do_client, key_id).await;
-                    if let Err(e) = result {
-                        tracing::warn!("Failed to delete DO SSH key: {}", e);
-                    }
-                }
-            }
-            _ => {
-                tracing::warn!(
-                    "No DO credentials found during destroy — clearing local state only"
-                );
-            }
-        }
-
-        store::clear_state()?;
-        emit_progress(app, 3, 3, "All resources destroyed", "done");
-        return Ok(());
-    }
-
     // ── BYO VPS: SSH cleanup + clear local state (no AWS calls) ──────────────
     if state.deployment_mode.as_deref() == Some("byo") {
-        tracing::info!("Destroying BYO VPS deployment");
-        emit_progress(app, 1, 2, "Stopping WireGuard on server...", "running");
+        tracing::info!("Destroying BYO VPS deployment {}", deployment_id);
+        progress.emit(1, 2, "Stopping WireGuard on server...", "running");
 
         if let (Some(ip), Some(key)) = (&state.elastic_ip, &state.ssh_private_key) {
             let ssh_user = state.ssh_user.as_deref().unwrap_or("ubuntu");
-            match ssh::client::SshSession::connect(ip, 22, ssh_user, key, 15).await {
+            let ssh_port = state.ssh_port.unwrap_or(22);
+            let known_fingerprints = state.ssh_host_key_fingerprint.as_slice();
+            match ssh::client::SshSession::connect(
+                ip,
+                ssh_port,
+                ssh_user,
+                key,
+                15,
+                known_fingerprints,
+            )
+            .await
+            {
                 Ok(ssh) => {
                     let _ = ssh.execute("sudo systemctl stop wg-quick@wg0").await;
                     let _ = ssh.execute("sudo systemctl disable wg-quick@wg0").await;
@@ -93,50 +63,148 @@ pub async fn destroy_vpn_internal(app: &AppHandle) -> Result<(), AppError> {
             }
         }
 
-        emit_progress(app, 2, 2, "Cleaning up local config...", "running");
-        store::clear_state()?;
-        emit_progress(app, 2, 2, "Server disconnected", "done");
+        progress.emit(2, 2, "Cleaning up local config...", "running");
+        store::remove_deployment(deployment_id)?;
+        progress.emit(2, 2, "Server disconnected", "done");
         return Ok(());
     }
 
-    // ── AWS teardown ──────────────────────────────────────────────────────────
-    let region = state
-        .region
-        .clone()
-        .ok_or_else(|| AppError::State("No region in state".into()))?;
-    tracing::info!("Destroying AWS deployment in region: {}", region);
-
-    let creds = store::load_credentials()?
-        .ok_or_else(|| AppError::Credential("No credentials saved".into()))?;
-
+    // ── Cloud providers: API teardown + clear local state ────────────────────
+    let saved;
+    let provider = match provider {
+        Some(provider) => provider,
+        None => {
+            saved = for_deployment(&state)?
+                .ok_or_else(|| AppError::State("No cloud provider for this deployment".into()))?;
+            &*saved
+        }
+    };
+    tracing::info!(
+        "Destroying {} deployment {}",
+        provider.display_name(),
+        deployment_id
+    );
+    if state.status != DeploymentStatus::Destroying {
+        state.status_before_destroy = Some(state.status.clone());
+    }
     state.status = DeploymentStatus::Destroying;
-    store::save_state(&state)?;
-    tracing::info!("State updated to Destroying");
-
-    emit_progress(app, 1, 3, "Connecting to AWS...", "running");
-    tracing::info!("[Destroy 1/3] Connecting to AWS...");
-    let config = client::build_config(&creds, &region).await?;
-    let ec2_client = aws_sdk_ec2::Client::new(&config);
-    tracing::info!("[Destroy 1/3] AWS connection established");
+    store::save_deployment(&state)?;
+
+    // On failure the deployment is kept, with the status it had: it may be
+    // the only record of resources that are still running and billing.
+    if let Err(e) = provider.destroy(progress, &state).await {
+        tracing::error!("Destroy of {} failed: {}", deployment_id, e);
+        progress.emit(3, 3, &format!("Destroy failed: {}", e), "error");
+        state.status = state
+            .status_before_destroy
+            .take()
+            .unwrap_or(DeploymentStatus::Deployed);
+        state.error_message = Some(format!(
+            "Destroy failed ({}). Some resources may still be running — destroy the \
+             deployment again once the problem is fixed.",
+            e
+        ));
+        store::save_deployment(&state)?;
+        return Err(e);
+    }
 
-    emit_progress(app, 2, 3, "Destroying infrastructure...", "running");
-    tracing::info!("[Destroy 2/3] Tearing down all AWS resources...");
-    teardown::teardown_all(&ec2_client, &state).await?;
-    tracing::info!("[Destroy 2/3] All AWS resources destroyed");
+    tracing::info!("Cleaning up local state...");
+    store::remove_deployment(deployment_id)?;
+    progress.emit(3, 3, "All resources destroyed", "done");
+    Ok(())
+}
 
-    emit_progress(app, 3, 3, "Cleaning up...", "running");
-    tracing::info!("[Destroy 3/3] Cleaning up local state...");
-    store::clear_state()?;
-    tracing::info!("[Destroy 3/3] Local state cleared");
+/// After a failed deploy, tear down whatever it created if the
+/// `rollback_on_failure` setting is on (the default), reporting on `progress`
+/// like a destroy. The deployment stays `Failed`, with an error message that
+/// says what was removed. With rollback off the resources are kept so the
+/// deploy can be resumed or destroyed later.
+pub async fn rollback_failed_deploy(
+    progress: &dyn ProgressSink,
+    deployment_id: &str,
+    provider: &dyn CloudProvider,
+) {
+    if !store::load_settings()
+        .map(|s| s.rollback_on_failure)
+        .unwrap_or(true)
+    {
+        tracing::info!(
+            "Rollback is off — keeping the resources of {}",
+            deployment_id
+        );
+        return;
+    }
+    // Nothing saved means the deploy failed before creating anything.
+    let Ok(mut state) = store::load_deployment(deployment_id) else {
+        return;
+    };
+    let created = state.cloud_resources();
+    if state.status != DeploymentStatus::Failed || created.is_empty() {
+        return;
+    }
+    let error = state
+        .error_message
+        .clone()
+        .unwrap_or_else(|| "Deployment failed".into());
+    tracing::info!(
+        "=== Rolling back failed deployment {}: {} ===",
+        deployment_id,
+        created.join(", ")
+    );
+
+    state.status_before_destroy = Some(DeploymentStatus::Failed);
+    state.status = DeploymentStatus::Destroying;
+    let result = match store::save_deployment(&state) {
+        Ok(()) => provider.destroy(progress, &state).await,
+        Err(e) => Err(e),
+    };
 
-    emit_progress(app, 3, 3, "All resources destroyed", "done");
-    Ok(())
+    let state = match result {
+        Ok(()) => {
+            tracing::info!("=== Rollback of {} complete ===", deployment_id);
+            progress.emit(3, 3, "Rolled back the failed deployment", "done");
+            DeploymentState {
+                id: state.id,
+                name: state.name,
+                status: DeploymentStatus::Failed,
+                deployment_mode: state.deployment_mode,
+                region: state.region,
+                error_message: Some(format!(
+                    "{} — rolled back: removed {}.",
+                    error,
+                    created.join(", ")
+                )),
+                ..Default::default()
+            }
+        }
+        Err(e) => {
+            tracing::error!("Rollback of {} failed: {}", deployment_id, e);
+            progress.emit(3, 3, &format!("Rollback failed: {}", e), "error");
+            DeploymentState {
+                status: DeploymentStatus::Failed,
+                status_before_destroy: None,
+                error_message: Some(format!(
+                    "{} — rollback failed ({}). Destroy the deployment to remove {}.",
+                    error,
+                    e,
+                    created.join(", ")
+                )),
+                ..state
+            }
+        }
+    };
+    if let Err(e) = store::save_deployment(&state) {
+        tracing::warn!("Could not save rollback state: {}", e);
+    }
 }
 
 #[tauri::command]
-pub async fn destroy_vpn(app: AppHandle) -> Result<(), AppError> {
-    tracing::info!("=== Starting VPN server destruction ===");
-    destroy_vpn_internal(&app).await?;
+pub async fn destroy_vpn(app: AppHandle, deployment_id: String) -> Result<(), AppError> {
+    tracing::info!(
+        "=== Starting VPN server destruction ({}) ===",
+        deployment_id
+    );
+    destroy_vpn_internal(&TauriProgress::new(app, DESTROY_EVENT), &deployment_id).await?;
     tracing::info!("=== VPN server destruction complete ===");
     Ok(())
 }
@@ -0,0 +1,217 @@
+use tauri::AppHandle;
+use tokio::task::JoinSet;
+
+use crate::aws::{client, orphans, teardown};
+use crate::commands::settings;
+use crate::do_cloud::client::DoClient;
+use crate::do_cloud::orphans as do_orphans;
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::progress::{ProgressSink, TauriProgress, CLEANUP_EVENT};
+use crate::state::{AwsCredentials, DeploymentStatus, DoCredentials, OrphanScan};
+
+/// Scan one region, minus everything a local deployment still tracks.
+async fn scan_aws_region(
+    creds: &AwsCredentials,
+    region: &str,
+) -> Result<orphans::AwsInventory, AppError> {
+    let config = client::build_config(creds, region).await?;
+    let ec2 = aws_sdk_ec2::Client::new(&config);
+    let mut inventory = orphans::scan_region(&ec2).await?;
+    let tracked = orphans::tracked_ids(&store::list_deployments()?);
+    inventory.retain(|id| !tracked.contains(id));
+    Ok(inventory)
+}
+
+/// Scan every region from `get_regions` in parallel. Regions that fail
+/// (e.g. opt-in regions not enabled for the account) are reported, not fatal.
+pub async fn scan_aws(creds: &AwsCredentials) -> Result<OrphanScan, AppError> {
+    let mut tasks = JoinSet::new();
+    for region in settings::aws_regions() {
+        let creds = creds.clone();
+        tasks.spawn(async move {
+            let result = scan_aws_region(&creds, &region.code).await;
+            (region.code, result)
+        });
+    }
+
+    let mut scan = OrphanScan::default();
+    while let Some(joined) = tasks.join_next().await {
+        let (region, result) = joined.map_err(|e| AppError::General(e.to_string()))?;
+        match result {
+            Ok(inventory) => scan.resources.extend(inventory.resources(&region)),
+            Err(e) => {
+                tracing::warn!("Orphan scan of {} failed: {}", region, e);
+                scan.errors.push(format!("{}: {}", region, e));
+            }
+        }
+    }
+    scan.resources
+        .sort_by(|a, b| (&a.region, &a.kind, &a.id).cmp(&(&b.region, &b.kind, &b.id)));
+    scan.errors.sort();
+    scan.monthly_cost_usd = scan.resources.iter().map(|r| r.monthly_cost_usd).sum();
+    tracing::info!(
+        "Orphan scan found {} AWS resources (~${:.2}/month)",
+        scan.resources.len(),
+        scan.monthly_cost_usd
+    );
+    Ok(scan)
+}
+
+/// Tear down the selected orphans in one region. The region is scanned again
+/// so only resources that are still untracked and tagged as ours are touched,
+/// whatever ids the caller passes.
+pub async fn cleanup_aws(
+    progress: &dyn ProgressSink,
+    creds: &AwsCredentials,
+    region: &str,
+    resource_ids: &[String],
+) -> Result<(), AppError> {
+    let busy = store::list_deployments()?.into_iter().any(|d| {
+        d.region.as_deref() == Some(region)
+            && matches!(
+                d.status,
+                DeploymentStatus::Deploying | DeploymentStatus::Destroying
+            )
+    });
+    if busy {
+        return Err(AppError::State(format!(
+            "A deployment in {} is being created or destroyed — try again when it has finished",
+            region
+        )));
+    }
+
+    progress.emit(1, 2, &format!("Scanning {}...", region), "running");
+    let mut inventory = scan_aws_region(creds, region).await?;
+    inventory.retain(|id| resource_ids.iter().any(|r| r == id));
+    if inventory.is_empty() {
+        progress.emit(2, 2, "Nothing to clean up", "done");
+        return Ok(());
+    }
+
+    let plan = inventory.teardown_plan();
+    let total = plan.len() as u32 + 1;
+    let config = client::build_config(creds, region).await?;
+    let ec2 = aws_sdk_ec2::Client::new(&config);
+    for (i, step) in plan.iter().enumerate() {
+        progress.emit(
+            i as u32 + 2,
+            total,
+            &format!("Removing resources ({}/{})...", i + 1, plan.len()),
+            "running",
+        );
+        match step {
+            orphans::TeardownStep::Resources(state) => teardown::teardown_all(&ec2, state).await?,
+            orphans::TeardownStep::Gateway { igw_id, vpc_id } => {
+                teardown::teardown_gateway(&ec2, igw_id, vpc_id.as_deref()).await?
+            }
+        }
+    }
+    progress.emit(total, total, "Leftover resources removed", "done");
+    Ok(())
+}
+
+/// Scan the DigitalOcean account, minus everything a local deployment still
+/// tracks.
+async fn scan_do_account(client: &DoClient) -> Result<do_orphans::DoInventory, AppError> {
+    let mut inventory = do_orphans::scan(client).await?;
+    let tracked = do_orphans::tracked_ids(&store::list_deployments()?);
+    inventory.retain(|id| !tracked.contains(id));
+    Ok(inventory)
+}
+
+pub async fn scan_do(creds: &DoCredentials) -> Result<OrphanScan, AppError> {
+    let inventory = scan_do_account(&DoClient::new(&creds.api_token)).await?;
+    let resources = inventory.resources();
+    let monthly_cost_usd = resources.iter().map(|r| r.monthly_cost_usd).sum();
+    tracing::info!(
+        "Orphan scan found {} DigitalOcean resources (~${:.2}/month)",
+        resources.len(),
+        monthly_cost_usd
+    );
+    Ok(OrphanScan {
+        resources,
+        monthly_cost_usd,
+        errors: Vec::new(),
+    })
+}
+
+/// Delete the selected DigitalOcean orphans. Like `cleanup_aws`, the account
+/// is scanned again so only untracked resources of ours are touched.
+pub async fn cleanup_do(
+    progress: &dyn ProgressSink,
+    creds: &DoCredentials,
+    resource_ids: &[String],
+) -> Result<(), AppError> {
+    let busy = store::list_deployments()?.into_iter().any(|d| {
+        d.deployment_mode.as_deref() == Some("do")
+            && matches!(
+                d.status,
+                DeploymentStatus::Deploying | DeploymentStatus::Destroying
+            )
+    });
+    if busy {
+        return Err(AppError::State(
+            "A DigitalOcean deployment is being created or destroyed — try again when it has finished"
+                .into(),
+        ));
+    }
+
+    progress.emit(1, 3, "Scanning DigitalOcean...", "running");
+    let client = DoClient::new(&creds.api_token);
+    let mut inventory = scan_do_account(&client).await?;
+    inventory.retain(|id| resource_ids.iter().any(|r| r == id));
+    if inventory.is_empty() {
+        progress.emit(3, 3, "Nothing to clean up", "done");
+        return Ok(());
+    }
+
+    progress.emit(2, 3, "Removing resources...", "running");
+    inventory.delete_all(&client).await?;
+    progress.emit(3, 3, "Leftover resources removed", "done");
+    Ok(())
+}
+
+fn saved_aws_credentials() -> Result<AwsCredentials, AppError> {
+    store::load_credentials()?.ok_or_else(|| AppError::Credential("No credentials saved".into()))
+}
+
+#[tauri::command]
+pub async fn scan_aws_orphans() -> Result<OrphanScan, AppError> {
+    scan_aws(&saved_aws_credentials()?).await
+}
+
+#[tauri::command]
+pub async fn cleanup_aws_orphans(
+    app: AppHandle,
+    region: String,
+    resource_ids: Vec<String>,
+) -> Result<(), AppError> {
+    tracing::info!(
+        "=== Cleaning up {} orphaned resources in {} ===",
+        resource_ids.len(),
+        region
+    );
+    let progress = TauriProgress::new(app, CLEANUP_EVENT);
+    cleanup_aws(&progress, &saved_aws_credentials()?, &region, &resource_ids).await
+}
+
+fn saved_do_credentials() -> Result<DoCredentials, AppError> {
+    store::load_do_credentials()?
+        .ok_or_else(|| AppError::Credential("No DigitalOcean token saved".into()))
+}
+
+#[tauri::command]
+pub async fn scan_do_orphans() -> Result<OrphanScan, AppError> {
+    scan_do(&saved_do_credentials()?).await
+}
+
+#[tauri::command]
+pub async fn cleanup_do_orphans(app: AppHandle, resource_ids: Vec<String>) -> Result<(), AppError> {
+    tracing::info!(
+        "=== Cleaning up {} orphaned DigitalOcean resources ===",
+        resource_ids.len()
+    );
+    let progress = TauriProgress::new(app, CLEANUP_EVENT);
+    cleanup_do(&progress, &saved_do_credentials()?, &resource_ids).await
+}
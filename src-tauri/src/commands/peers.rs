@@ -0,0 +1,184 @@
+use crate::commands::ssh_access;
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::ssh::client::{FileMode, SshSession};
+use crate::state::{DeploymentState, DeploymentStatus, PeerInfo};
+use crate::wireguard::config_parser::ParsedClientConfig;
+use crate::wireguard::subnet::TunnelSubnet;
+use crate::wireguard::{client_config, keys, peers, server_config};
+
+const WG_CONF_PATH: &str = "/etc/wireguard/wg0.conf";
+/// Bring the live interface in line with wg0.conf without dropping the
+/// sessions of peers that stay.
+const SYNC_CONF: &str = "sudo bash -c 'wg syncconf wg0 <(wg-quick strip wg0)'";
+
+/// Open an SSH session to the deployed server using the credentials in state.
+async fn connect_to_server(state: &DeploymentState) -> Result<SshSession, AppError> {
+    let ip = state
+        .elastic_ip
+        .as_deref()
+        .ok_or_else(|| AppError::State("No server IP in state".into()))?;
+    let key = state
+        .ssh_private_key
+        .as_deref()
+        .ok_or_else(|| AppError::State("No SSH key in state".into()))?;
+    let user = state.ssh_user.as_deref().unwrap_or("ubuntu");
+    let port = state.ssh_port.unwrap_or(22);
+    SshSession::connect(
+        ip,
+        port,
+        user,
+        key,
+        30,
+        state.ssh_host_key_fingerprint.as_slice(),
+    )
+    .await
+}
+
+/// Add a peer to wg0.conf, with an IPv6 address too when the tunnel is
+/// dual-stack (`ipv6`), then to the running interface. If the interface
+/// can't take it the old wg0.conf is put back, so a failed add leaves no
+/// peer on the server whose key was never handed out.
+async fn install_peer(
+    state: &DeploymentState,
+    name: &str,
+    public_key: &str,
+    address: &str,
+    ipv6: bool,
+) -> Result<(), AppError> {
+    let ssh = connect_to_server(state).await?;
+    let original = read_server_config(&ssh).await?;
+    let config = format!(
+        "{}{}",
+        original,
+        server_config::render_peer_block(name, public_key, address, ipv6)
+    );
+    ssh.upload_file(WG_CONF_PATH, config.as_bytes(), FileMode::PRIVATE)
+        .await?;
+
+    if let Err(e) = ssh.execute(SYNC_CONF).await {
+        if let Err(restore) = ssh
+            .upload_file(WG_CONF_PATH, original.as_bytes(), FileMode::PRIVATE)
+            .await
+        {
+            tracing::warn!("Could not restore {}: {}", WG_CONF_PATH, restore);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// The server's wg0.conf.
+async fn read_server_config(ssh: &SshSession) -> Result<String, AppError> {
+    let content = ssh.download_file(WG_CONF_PATH).await?;
+    String::from_utf8(content)
+        .map_err(|_| AppError::Ssh(format!("{} is not valid UTF-8", WG_CONF_PATH)))
+}
+
+/// Remove a peer from the running interface and from wg0.conf.
+async fn remove_peer(state: &DeploymentState, public_key: &str) -> Result<(), AppError> {
+    let ssh = connect_to_server(state).await?;
+    ssh.execute(&format!("sudo wg set wg0 peer '{}' remove", public_key))
+        .await?;
+
+    let current = read_server_config(&ssh).await?;
+    let pruned = server_config::remove_peer_block(&current, public_key);
+    let pruned = format!("{}\n", pruned.trim_end());
+    ssh.upload_file(WG_CONF_PATH, pruned.as_bytes(), FileMode::PRIVATE)
+        .await
+}
+
+fn load_deployed_state(deployment_id: &str) -> Result<DeploymentState, AppError> {
+    let state = store::load_deployment(deployment_id)?;
+    if state.status != DeploymentStatus::Deployed {
+        return Err(AppError::State(
+            "Peers can only be managed on a deployed server".into(),
+        ));
+    }
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn list_peers(deployment_id: String) -> Result<Vec<PeerInfo>, AppError> {
+    Ok(store::load_deployment_metadata(&deployment_id)?.peers)
+}
+
+/// Register a new client device on the live server and return it, including
+/// its client config. Existing sessions are not interrupted: the peer is
+/// appended to wg0.conf, which is then synced to the running interface.
+#[tauri::command]
+pub async fn add_peer(deployment_id: String, name: String) -> Result<PeerInfo, AppError> {
+    tracing::info!("=== Adding peer '{}' to {} ===", name, deployment_id);
+    let mut state = load_deployed_state(&deployment_id)?;
+    peers::validate_peer_name(&name, &state.peers)?;
+
+    let server_public_key = state
+        .server_public_key
+        .clone()
+        .ok_or_else(|| AppError::State("No server public key in state".into()))?;
+    let primary_config = state
+        .client_config
+        .as_deref()
+        .ok_or_else(|| AppError::State("No client config available".into()))?;
+    let endpoint = ParsedClientConfig::parse(primary_config)?.endpoint;
+
+    let subnet = TunnelSubnet::of(state.tunnel_cidr.as_deref())?;
+    let address = peers::next_free_address(&subnet, &state.peers)?;
+    let peer_keys = keys::generate_keypair();
+    tracing::info!("Assigning {} to peer '{}'", address, name);
+
+    let reopened = ssh_access::open_for_maintenance(&mut state).await?;
+    let result = install_peer(
+        &state,
+        &name,
+        &peer_keys.public_key,
+        &address,
+        state.carries_ipv6(),
+    )
+    .await;
+    ssh_access::relock(&mut state, reopened).await;
+    result?;
+    tracing::info!("Peer '{}' added on server", name);
+
+    let peer = PeerInfo {
+        name,
+        public_key: peer_keys.public_key,
+        address: address.clone(),
+        created_at: chrono::Utc::now(),
+        client_config: Some(client_config::render_peer_client_config(
+            &peer_keys.private_key,
+            &address,
+            &server_public_key,
+            &endpoint.ip().to_string(),
+            endpoint.port(),
+            state.carries_ipv6(),
+        )),
+    };
+    state.peers.push(peer.clone());
+    store::save_deployment(&state)?;
+
+    Ok(peer)
+}
+
+/// Remove a peer from the live server and from wg0.conf, then forget it locally.
+#[tauri::command]
+pub async fn revoke_peer(deployment_id: String, name: String) -> Result<(), AppError> {
+    tracing::info!("=== Revoking peer '{}' on {} ===", name, deployment_id);
+    let mut state = load_deployed_state(&deployment_id)?;
+    let peer = state
+        .peers
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| AppError::State(format!("No peer named '{}'", name)))?;
+
+    let reopened = ssh_access::open_for_maintenance(&mut state).await?;
+    let result = remove_peer(&state, &peer.public_key).await;
+    ssh_access::relock(&mut state, reopened).await;
+    result?;
+    tracing::info!("Peer '{}' removed from server", name);
+
+    state.peers.retain(|p| p.name != name);
+    store::save_deployment(&state)?;
+    Ok(())
+}
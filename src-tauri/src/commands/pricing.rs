@@ -0,0 +1,25 @@
+use crate::commands::catalog::saved_provider;
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::pricing::{self, AccruedCost, CostEstimate};
+
+/// What a server of `size` in `region` costs on `provider` (AWS sizes are
+/// instance types), or `None` when its price isn't known.
+#[tauri::command]
+pub async fn estimate_cost(
+    provider: String,
+    region: String,
+    size: String,
+    auto_destroy_hours: Option<u32>,
+) -> Option<CostEstimate> {
+    let cloud = saved_provider(&provider, &region);
+    let hourly = pricing::hourly_rate(&provider, cloud.as_deref(), &region, &size).await?;
+    Some(pricing::estimate(&provider, hourly, auto_destroy_hours))
+}
+
+/// What a deployment has cost so far, if its rate was recorded.
+#[tauri::command]
+pub async fn get_deployment_cost(deployment_id: String) -> Result<Option<AccruedCost>, AppError> {
+    let state = store::load_deployment_metadata(&deployment_id)?;
+    Ok(pricing::accrued(&state, chrono::Utc::now()))
+}
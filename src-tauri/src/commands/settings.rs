@@ -1,20 +1,22 @@
+use crate::commands::catalog;
 use crate::error::AppError;
 use crate::persistence::store;
+use crate::provider::Region;
+use crate::public_ip;
+use crate::server_image::{Architecture, OsRelease};
 use crate::state::AppSettings;
+use crate::wireguard::subnet;
 use serde::Serialize;
 
 /// Saves the WireGuard client config to the user's Downloads folder.
 /// Returns the full path of the saved file.
 #[tauri::command]
-pub async fn export_client_config() -> Result<String, AppError> {
-    let config_path = store::client_config_path()?;
-    if !config_path.exists() {
-        return Err(AppError::State(
-            "No VPN config found. Deploy a server first.".into(),
-        ));
-    }
-    let content = std::fs::read_to_string(&config_path)?;
-    let path = store::save_to_downloads(&content, "createmyvpn-client.conf")?;
+pub async fn export_client_config(deployment_id: String) -> Result<String, AppError> {
+    let content = store::load_deployment(&deployment_id)?
+        .client_config
+        .ok_or_else(|| AppError::State("No VPN config found. Deploy a server first.".into()))?;
+    let filename = format!("createmyvpn-client-{}.conf", deployment_id);
+    let path = store::save_to_downloads(&content, &filename)?;
     Ok(path.to_string_lossy().into_owned())
 }
 
@@ -24,8 +26,16 @@ pub struct AwsRegion {
     pub name: String,
 }
 
+/// The AWS regions enabled for the account, as `commands::catalog::list_regions`
+/// returns them for "aws".
 #[tauri::command]
-pub async fn get_regions() -> Vec<AwsRegion> {
+pub async fn get_regions() -> Vec<Region> {
+    catalog::list_regions("aws".into()).await.items
+}
+
+/// The AWS regions scanned for leftovers, and offered for deployment when
+/// the enabled regions can't be fetched. Opt-in regions are included.
+pub fn aws_regions() -> Vec<AwsRegion> {
     vec![
         AwsRegion { code: "us-east-1".into(), name: "US East (N. Virginia)".into() },
         AwsRegion { code: "us-east-2".into(), name: "US East (Ohio)".into() },
@@ -51,12 +61,44 @@ pub async fn get_settings() -> Result<AppSettings, AppError> {
     store::load_settings()
 }
 
+/// Optional arguments keep their currently saved value when omitted.
 #[tauri::command]
-pub async fn update_settings(region: String, instance_type: String, wireguard_port: u16) -> Result<(), AppError> {
+pub async fn update_settings(
+    region: String,
+    instance_type: String,
+    wireguard_port: u16,
+    kill_switch: Option<bool>,
+    kill_switch_allow_lan: Option<bool>,
+    rollback_on_failure: Option<bool>,
+    tunnel_cidr: Option<String>,
+    ip_lookup_url: Option<String>,
+    lock_down_ssh: Option<bool>,
+    deploy_without_ssh: Option<bool>,
+    os_release: Option<OsRelease>,
+    architecture: Option<Architecture>,
+) -> Result<(), AppError> {
+    let current = store::load_settings()?;
+    let tunnel_cidr = match tunnel_cidr {
+        Some(cidr) => subnet::validate(&cidr)?.to_string(),
+        None => current.tunnel_cidr,
+    };
+    let ip_lookup_url = match ip_lookup_url {
+        Some(url) => public_ip::validate_url(&url)?,
+        None => current.ip_lookup_url,
+    };
     let settings = AppSettings {
         region,
         instance_type,
         wireguard_port,
+        kill_switch: kill_switch.unwrap_or(current.kill_switch),
+        kill_switch_allow_lan: kill_switch_allow_lan.unwrap_or(current.kill_switch_allow_lan),
+        rollback_on_failure: rollback_on_failure.unwrap_or(current.rollback_on_failure),
+        tunnel_cidr,
+        ip_lookup_url,
+        lock_down_ssh: lock_down_ssh.unwrap_or(current.lock_down_ssh),
+        deploy_without_ssh: deploy_without_ssh.unwrap_or(current.deploy_without_ssh),
+        os_release: os_release.unwrap_or(current.os_release),
+        architecture: architecture.unwrap_or(current.architecture),
     };
     store::save_settings(&settings)
 }
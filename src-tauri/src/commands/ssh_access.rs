@@ -0,0 +1,177 @@
+//! Opening and closing SSH to a deployed server. Its firewall lets SSH in
+//! from the computer that deployed it only; with lock-down on, the rule is
+//! removed once WireGuard is configured, and opened again to this computer's
+//! current address for as long as a maintenance command (adding or revoking
+//! a peer) needs it.
+use crate::error::AppError;
+use crate::persistence::store;
+use crate::provider::{self, CloudProvider};
+use crate::public_ip;
+use crate::state::{DeploymentState, DeploymentStatus};
+use crate::wireguard::config_parser::ParsedClientConfig;
+
+/// Let SSH in from `source` only and record it.
+pub async fn open(
+    provider: &dyn CloudProvider,
+    state: &mut DeploymentState,
+    wireguard_port: u16,
+    source: String,
+) -> Result<(), AppError> {
+    provider
+        .set_ssh_access(state, wireguard_port, Some(&source))
+        .await?;
+    tracing::info!("SSH to {} opened to {}", state.id, source);
+    state.ssh_source = Some(source);
+    state.ssh_locked = false;
+    store::save_deployment(state)
+}
+
+/// Remove the SSH rule and record it.
+pub async fn close(
+    provider: &dyn CloudProvider,
+    state: &mut DeploymentState,
+    wireguard_port: u16,
+) -> Result<(), AppError> {
+    provider.set_ssh_access(state, wireguard_port, None).await?;
+    tracing::info!("SSH to {} locked down", state.id);
+    state.ssh_locked = true;
+    store::save_deployment(state)
+}
+
+/// The WireGuard port that must stay open on a deployed server's firewall.
+fn wireguard_port(state: &DeploymentState) -> Result<u16, AppError> {
+    if state.status != DeploymentStatus::Deployed {
+        return Err(AppError::State(
+            "SSH access can only be changed on a deployed server".into(),
+        ));
+    }
+    let config = state
+        .client_config
+        .as_deref()
+        .ok_or_else(|| AppError::State("No client config available".into()))?;
+    Ok(ParsedClientConfig::parse(config)?.endpoint.port())
+}
+
+/// The provider with the app's saved credentials.
+fn saved_provider(state: &DeploymentState) -> Result<Box<dyn CloudProvider>, AppError> {
+    provider::for_deployment(state)?.ok_or_else(|| {
+        AppError::State("Your own server has no cloud firewall to change SSH access on".into())
+    })
+}
+
+/// Let SSH in from this computer's current public address only. `provider`
+/// defaults to the one with the app's saved credentials.
+pub async fn unlock(
+    state: &mut DeploymentState,
+    provider: Option<&dyn CloudProvider>,
+) -> Result<(), AppError> {
+    let port = wireguard_port(state)?;
+    let saved;
+    let provider = match provider {
+        Some(provider) => provider,
+        None => {
+            saved = saved_provider(state)?;
+            saved.as_ref()
+        }
+    };
+    let source = public_ip::ssh_source(&store::load_settings()?.ip_lookup_url).await?;
+    open(provider, state, port, source).await
+}
+
+/// Remove the SSH rule from the server's firewall. `provider` defaults to
+/// the one with the app's saved credentials.
+pub async fn lock(
+    state: &mut DeploymentState,
+    provider: Option<&dyn CloudProvider>,
+) -> Result<(), AppError> {
+    let port = wireguard_port(state)?;
+    let saved;
+    let provider = match provider {
+        Some(provider) => provider,
+        None => {
+            saved = saved_provider(state)?;
+            saved.as_ref()
+        }
+    };
+    close(provider, state, port).await
+}
+
+/// Before a maintenance command connects: open SSH if the server is locked
+/// down, returning whether it was. The SSH connect retries while the
+/// firewall change takes effect.
+pub async fn open_for_maintenance(state: &mut DeploymentState) -> Result<bool, AppError> {
+    if !state.ssh_locked {
+        return Ok(false);
+    }
+    tracing::info!("SSH to {} is locked down, opening it", state.id);
+    unlock(state, None).await?;
+    Ok(true)
+}
+
+/// After a maintenance command: lock SSH down again if
+/// `open_for_maintenance` opened it.
+pub async fn relock(state: &mut DeploymentState, reopened: bool) {
+    if !reopened {
+        return;
+    }
+    if let Err(e) = lock(state, None).await {
+        tracing::warn!(
+            "Could not lock SSH to {} down again, it stays open: {}",
+            state.id,
+            e
+        );
+    }
+}
+
+#[tauri::command]
+pub async fn lock_ssh(deployment_id: String) -> Result<DeploymentState, AppError> {
+    let mut state = store::load_deployment(&deployment_id)?;
+    lock(&mut state, None).await?;
+    Ok(state)
+}
+
+/// Open SSH to this computer, e.g. to log in for maintenance, or to follow
+/// it to a new address.
+#[tauri::command]
+pub async fn unlock_ssh(deployment_id: String) -> Result<DeploymentState, AppError> {
+    let mut state = store::load_deployment(&deployment_id)?;
+    unlock(&mut state, None).await?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wireguard::subnet::TunnelSubnet;
+    use crate::wireguard::{client_config, keys};
+
+    fn deployed(port: u16) -> DeploymentState {
+        let config = client_config::render_client_config(
+            &keys::generate_keypair().private_key,
+            &keys::generate_keypair().public_key,
+            "203.0.113.7",
+            port,
+            &TunnelSubnet::of(None).unwrap(),
+            true,
+        );
+        DeploymentState {
+            status: DeploymentStatus::Deployed,
+            client_config: Some(config),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn wireguard_port_comes_from_the_client_config() {
+        assert_eq!(wireguard_port(&deployed(51821)).unwrap(), 51821);
+    }
+
+    #[test]
+    fn only_deployed_servers_change_access() {
+        let state = DeploymentState {
+            status: DeploymentStatus::Failed,
+            ..deployed(51820)
+        };
+        assert!(wireguard_port(&state).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 use chrono::{DateTime, Utc};
 use tauri::AppHandle;
 
-use crate::persistence::store;
+use crate::persistence::{store, vault};
+use crate::pricing;
+use crate::progress::{ProgressSink, TauriProgress, DESTROY_EVENT};
 use crate::state::DeploymentStatus;
 
-/// Spawns a background task that automatically destroys the VPN deployment
+/// Spawns a background task that automatically destroys one deployment
 /// when `at` is reached. If the app restarts before firing, `lib.rs` re-spawns
-/// this timer from the persisted `auto_destroy_at` field in state.
-pub fn spawn_auto_destroy_timer(app: AppHandle, at: DateTime<Utc>) {
+/// this timer from the persisted `auto_destroy_at` field of each deployment.
+pub fn spawn_auto_destroy_timer(app: AppHandle, deployment_id: String, at: DateTime<Utc>) {
     tokio::spawn(async move {
         let now = Utc::now();
         let delay = if at > now {
@@ -16,19 +18,49 @@ pub fn spawn_auto_destroy_timer(app: AppHandle, at: DateTime<Utc>) {
             std::time::Duration::ZERO
         };
 
-        tracing::info!("Auto-destroy timer set: fires in {:?}", delay);
+        tracing::info!(
+            "Auto-destroy timer for {} set: fires in {:?}",
+            deployment_id,
+            delay
+        );
         tokio::time::sleep(delay).await;
 
-        match store::load_state() {
-            Ok(state) if state.status == DeploymentStatus::Deployed => {
-                tracing::info!("Auto-destroy timer fired — destroying deployment...");
-                if let Err(e) = crate::commands::destroy::destroy_vpn_internal(&app).await {
-                    tracing::error!("Auto-destroy failed: {}", e);
+        // Teardown needs the stored secrets. After a restart the passphrase
+        // vault stays locked until the user unlocks it.
+        if vault::status().locked {
+            tracing::warn!("Auto-destroy is due but secrets are locked — waiting for unlock");
+        }
+        while vault::status().locked {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        }
+
+        // The deployment may have been destroyed or rescheduled meanwhile.
+        match store::load_deployment(&deployment_id) {
+            Ok(state)
+                if state.status == DeploymentStatus::Deployed
+                    && state.auto_destroy_at == Some(at) =>
+            {
+                tracing::info!("Auto-destroy timer fired — destroying {}...", deployment_id);
+                let progress = TauriProgress::new(app, DESTROY_EVENT);
+                if let Some(cost) = pricing::accrued(&state, Utc::now()) {
+                    let message = format!(
+                        "Auto-destroy after {:.1} hours, about {} accrued",
+                        cost.hours,
+                        pricing::format_amount(&cost.currency, cost.amount)
+                    );
+                    tracing::info!("{}: {}", deployment_id, message);
+                    progress.emit(0, 3, &message, "running");
+                }
+                let result =
+                    crate::commands::destroy::destroy_vpn_internal(&progress, &deployment_id).await;
+                if let Err(e) = result {
+                    tracing::error!("Auto-destroy of {} failed: {}", deployment_id, e);
                 }
             }
             _ => {
                 tracing::info!(
-                    "Auto-destroy timer fired but no active deployment found, skipping"
+                    "Auto-destroy timer for {} fired but the deployment is gone or rescheduled, skipping",
+                    deployment_id
                 );
             }
         }
@@ -0,0 +1,22 @@
+use crate::error::AppError;
+use crate::persistence::vault::{self, VaultStatus};
+
+#[tauri::command]
+pub async fn get_vault_status() -> Result<VaultStatus, AppError> {
+    Ok(vault::status())
+}
+
+/// Unlock the passphrase vault for this session. On first use this sets the
+/// passphrase. Argon2 takes a moment, so it runs off the async runtime.
+#[tauri::command]
+pub async fn unlock_vault(passphrase: String) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || vault::unlock(&passphrase))
+        .await
+        .map_err(|e| AppError::General(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn lock_vault() -> Result<(), AppError> {
+    vault::lock();
+    Ok(())
+}
@@ -1,95 +1,54 @@
+use crate::api_client::ApiClient;
 use crate::error::AppError;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::ops::Deref;
 
 const BASE_URL: &str = "https://api.digitalocean.com/v2";
+/// The largest page size list endpoints accept.
+const PER_PAGE: u32 = 200;
 
-pub struct DoClient {
-    http: reqwest::Client,
-    token: String,
+pub struct DoClient(ApiClient);
+
+/// Error body returned by the DigitalOcean API.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    id: String,
+    message: String,
 }
 
 impl DoClient {
     pub fn new(token: &str) -> Self {
-        DoClient {
-            http: reqwest::Client::new(),
-            token: token.to_string(),
-        }
+        DoClient::with_base_url(token, BASE_URL)
     }
 
-    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, AppError> {
-        let url = format!("{}{}", BASE_URL, path);
-        let resp = self
-            .http
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await
-            .map_err(|e| AppError::General(format!("DO API request failed: {}", e)))?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            return Err(AppError::General(format!(
-                "DO API error {}: {}",
-                status, body
-            )));
-        }
-
-        resp.json::<T>()
-            .await
-            .map_err(|e| AppError::General(format!("DO API response parse error: {}", e)))
+    pub fn with_base_url(token: &str, base_url: &str) -> Self {
+        DoClient(ApiClient::new("DO", base_url, token, error_message))
     }
 
-    pub async fn post<B: Serialize, T: DeserializeOwned>(
+    /// Fetch every page of a list endpoint (e.g. `/droplets`) and collect the
+    /// array under `key`, following `links.pages.next` to the last page.
+    pub async fn get_all<T: DeserializeOwned>(
         &self,
         path: &str,
-        body: &B,
-    ) -> Result<T, AppError> {
-        let url = format!("{}{}", BASE_URL, path);
-        let resp = self
-            .http
-            .post(&url)
-            .bearer_auth(&self.token)
-            .json(body)
-            .send()
-            .await
-            .map_err(|e| AppError::General(format!("DO API request failed: {}", e)))?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            return Err(AppError::General(format!(
-                "DO API error {}: {}",
-                status, body
-            )));
+        key: &str,
+    ) -> Result<Vec<T>, AppError> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        let mut page = 1;
+        loop {
+            let body: serde_json::Value = self
+                .get(&format!(
+                    "{}{}page={}&per_page={}",
+                    path, separator, page, PER_PAGE
+                ))
+                .await?;
+            let (batch, more) = parse_page(body, key)?;
+            items.extend(batch);
+            if !more {
+                return Ok(items);
+            }
+            page += 1;
         }
-
-        resp.json::<T>()
-            .await
-            .map_err(|e| AppError::General(format!("DO API response parse error: {}", e)))
-    }
-
-    pub async fn delete(&self, path: &str) -> Result<(), AppError> {
-        let url = format!("{}{}", BASE_URL, path);
-        let resp = self
-            .http
-            .delete(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await
-            .map_err(|e| AppError::General(format!("DO API request failed: {}", e)))?;
-
-        let status = resp.status();
-        // DELETE returns 204 No Content on success
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            return Err(AppError::General(format!(
-                "DO API error {}: {}",
-                status, body
-            )));
-        }
-
-        Ok(())
     }
 
     /// Validate a DigitalOcean API token by calling GET /v2/account.
@@ -111,3 +70,73 @@ impl DoClient {
         Ok(resp.account.email)
     }
 }
+
+impl Deref for DoClient {
+    type Target = ApiClient;
+
+    fn deref(&self) -> &ApiClient {
+        &self.0
+    }
+}
+
+/// `"<id>: <message>"` from an error body.
+fn error_message(body: &str) -> Option<String> {
+    let resp: ErrorResponse = serde_json::from_str(body).ok()?;
+    Some(format!("{}: {}", resp.id, resp.message))
+}
+
+/// Split one page of a list response into its items and whether a next
+/// page exists.
+fn parse_page<T: DeserializeOwned>(
+    mut body: serde_json::Value,
+    key: &str,
+) -> Result<(Vec<T>, bool), AppError> {
+    let more = body["links"]["pages"]["next"].is_string();
+    let items = serde_json::from_value(body[key].take())
+        .map_err(|e| AppError::General(format!("DO API response parse error: {}", e)))?;
+    Ok((items, more))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_reads_id_and_message() {
+        let body = r#"{"id": "unauthorized", "message": "Unable to authenticate you"}"#;
+        assert_eq!(
+            error_message(body).as_deref(),
+            Some("unauthorized: Unable to authenticate you")
+        );
+        assert_eq!(error_message("Bad Gateway"), None);
+    }
+
+    #[test]
+    fn parse_page_follows_next_link() {
+        let body = serde_json::json!({
+            "ssh_keys": [{"id": 1}, {"id": 2}],
+            "links": {"pages": {"next": "https://api.digitalocean.com/v2/account/keys?page=2"}},
+            "meta": {"total": 3}
+        });
+        let (items, more) = parse_page::<serde_json::Value>(body, "ssh_keys").unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(more);
+    }
+
+    #[test]
+    fn parse_page_stops_on_last_page() {
+        let body = serde_json::json!({
+            "droplets": [{"id": 3}],
+            "links": {"pages": {"prev": "https://api.digitalocean.com/v2/droplets?page=1"}}
+        });
+        let (items, more) = parse_page::<serde_json::Value>(body, "droplets").unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(!more);
+    }
+
+    #[test]
+    fn parse_page_rejects_missing_key() {
+        let body = serde_json::json!({"links": {}});
+        assert!(parse_page::<serde_json::Value>(body, "firewalls").is_err());
+    }
+}
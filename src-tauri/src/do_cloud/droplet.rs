@@ -1,6 +1,16 @@
 use crate::do_cloud::client::DoClient;
+use crate::do_cloud::RESOURCE_TAG;
 use crate::error::AppError;
+use crate::server_image::OsRelease;
 use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// How long to wait between status polls (shortened in tests).
+const POLL_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
 
 #[derive(Serialize)]
 struct CreateDropletRequest {
@@ -9,6 +19,11 @@ struct CreateDropletRequest {
     size: String,
     image: String,
     ssh_keys: Vec<u64>,
+    /// Gives the Droplet a public IPv6 address for egress from the tunnel.
+    ipv6: bool,
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_data: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -35,21 +50,36 @@ struct NetworkV4 {
     network_type: String,
 }
 
-/// Create a DigitalOcean Droplet and return its ID.
+/// The image slug of `os`. Droplets are x64 only.
+pub fn image_slug(os: OsRelease) -> &'static str {
+    match os {
+        OsRelease::Ubuntu2204 => "ubuntu-22-04-x64",
+        OsRelease::Ubuntu2404 => "ubuntu-24-04-x64",
+        OsRelease::Debian12 => "debian-12-x64",
+    }
+}
+
+/// Create a DigitalOcean Droplet booting `image` and return its ID.
+/// cloud-init runs `user_data`, if given, on first boot.
 /// POST /v2/droplets
 pub async fn create_droplet(
     client: &DoClient,
     name: &str,
     region: &str,
     size: &str,
+    image: &str,
     ssh_key_id: u64,
+    user_data: Option<&str>,
 ) -> Result<u64, AppError> {
     let body = CreateDropletRequest {
         name: name.to_string(),
         region: region.to_string(),
         size: size.to_string(),
-        image: "ubuntu-22-04-x64".to_string(),
+        image: image.to_string(),
         ssh_keys: vec![ssh_key_id],
+        ipv6: true,
+        tags: vec![RESOURCE_TAG.to_string()],
+        user_data: user_data.map(str::to_string),
     };
 
     let resp: DropletResponse = client.post("/droplets", &body).await?;
@@ -62,11 +92,9 @@ pub async fn wait_for_active(client: &DoClient, droplet_id: u64) -> Result<Strin
     let max_attempts = 60; // 60 × 5s = 5 minutes
 
     for attempt in 0..max_attempts {
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        tokio::time::sleep(POLL_INTERVAL).await;
 
-        let resp: DropletResponse = client
-            .get(&format!("/droplets/{}", droplet_id))
-            .await?;
+        let resp: DropletResponse = client.get(&format!("/droplets/{}", droplet_id)).await?;
 
         tracing::debug!(
             "[DO wait_for_active] attempt {}/{}: status={:?}",
@@ -97,3 +125,82 @@ pub async fn wait_for_active(client: &DoClient, droplet_id: u64) -> Result<Strin
 pub async fn delete_droplet(client: &DoClient, droplet_id: u64) -> Result<(), AppError> {
     client.delete(&format!("/droplets/{}", droplet_id)).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn droplet_json(status: &str, ip: Option<&str>) -> String {
+        let v4 = match ip {
+            Some(ip) => format!(r#"[{{"ip_address": "{}", "type": "public"}}]"#, ip),
+            None => "[]".to_string(),
+        };
+        format!(
+            r#"{{"droplet": {{"id": 3164444, "name": "createmyvpn-server-ab12",
+                "status": "{}", "networks": {{"v4": {}, "v6": []}}}}}}"#,
+            status, v4
+        )
+    }
+
+    #[tokio::test]
+    async fn creates_tagged_droplet_with_key() {
+        let server = MockServer::start(|_| (202, droplet_json("new", None))).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        let id = create_droplet(
+            &client,
+            "createmyvpn-server-ab12",
+            "ams3",
+            "s-1vcpu-1gb",
+            image_slug(OsRelease::Debian12),
+            512190,
+            Some("#cloud-config"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, 3164444);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/droplets");
+        let body = request.json();
+        assert_eq!(body["region"], "ams3");
+        assert_eq!(body["size"], "s-1vcpu-1gb");
+        assert_eq!(body["image"], "debian-12-x64");
+        assert_eq!(body["ssh_keys"], serde_json::json!([512190]));
+        assert_eq!(body["ipv6"], true);
+        assert_eq!(body["tags"], serde_json::json!([RESOURCE_TAG]));
+        assert_eq!(body["user_data"], "#cloud-config");
+    }
+
+    #[tokio::test]
+    async fn waits_until_active_with_a_public_address() {
+        let polls = AtomicU32::new(0);
+        let server = MockServer::start(move |_| {
+            let body = match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => droplet_json("new", None),
+                1 => droplet_json("active", None),
+                _ => droplet_json("active", Some("203.0.113.7")),
+            };
+            (200, body)
+        })
+        .await;
+        let client = DoClient::with_base_url("token", &server.url);
+        let ip = wait_for_active(&client, 3164444).await.unwrap();
+        assert_eq!(ip, "203.0.113.7");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.path == "/droplets/3164444"));
+    }
+
+    #[tokio::test]
+    async fn deletes_droplet_by_id() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        delete_droplet(&client, 3164444).await.unwrap();
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "DELETE");
+        assert_eq!(request.path, "/droplets/3164444");
+    }
+}
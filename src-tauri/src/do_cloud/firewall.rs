@@ -26,6 +26,16 @@ struct OutboundRule {
     destinations: FirewallDestinations,
 }
 
+#[derive(Serialize)]
+struct DropletIds {
+    droplet_ids: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct InboundRules {
+    inbound_rules: Vec<InboundRule>,
+}
+
 #[derive(Serialize)]
 struct CreateFirewallRequest {
     name: String,
@@ -44,34 +54,40 @@ struct FirewallInfo {
     id: String,
 }
 
-/// Create a firewall allowing SSH (TCP 22) and WireGuard (UDP port) inbound,
-/// all traffic outbound, and attach it to the given droplet.
+fn ssh_rule(sources: Vec<String>) -> InboundRule {
+    InboundRule {
+        protocol: "tcp".to_string(),
+        ports: "22".to_string(),
+        sources: FirewallSources { addresses: sources },
+    }
+}
+
+/// Create a firewall allowing SSH (TCP 22) from `ssh_source` (unless it is
+/// `None`) and WireGuard (UDP port) from anywhere inbound, and all traffic
+/// outbound. It applies to no Droplet until `add_droplet`.
 /// POST /v2/firewalls — returns firewall UUID.
 pub async fn create_firewall(
     client: &DoClient,
-    droplet_id: u64,
+    name: &str,
     wireguard_port: u16,
+    ssh_source: Option<&str>,
 ) -> Result<String, AppError> {
     let all_addrs = vec!["0.0.0.0/0".to_string(), "::/0".to_string()];
 
+    let mut inbound_rules = vec![InboundRule {
+        protocol: "udp".to_string(),
+        ports: wireguard_port.to_string(),
+        sources: FirewallSources {
+            addresses: all_addrs.clone(),
+        },
+    }];
+    if let Some(source) = ssh_source {
+        inbound_rules.push(ssh_rule(vec![source.to_string()]));
+    }
+
     let body = CreateFirewallRequest {
-        name: "createmyvpn-firewall".to_string(),
-        inbound_rules: vec![
-            InboundRule {
-                protocol: "tcp".to_string(),
-                ports: "22".to_string(),
-                sources: FirewallSources {
-                    addresses: all_addrs.clone(),
-                },
-            },
-            InboundRule {
-                protocol: "udp".to_string(),
-                ports: wireguard_port.to_string(),
-                sources: FirewallSources {
-                    addresses: all_addrs.clone(),
-                },
-            },
-        ],
+        name: name.to_string(),
+        inbound_rules,
         outbound_rules: vec![
             OutboundRule {
                 protocol: "tcp".to_string(),
@@ -95,15 +111,178 @@ pub async fn create_firewall(
                 },
             },
         ],
-        droplet_ids: vec![droplet_id],
+        droplet_ids: Vec::new(),
     };
 
     let resp: CreateFirewallResponse = client.post("/firewalls", &body).await?;
     Ok(resp.firewall.id)
 }
 
+/// Apply a firewall to a Droplet.
+/// POST /v2/firewalls/{id}/droplets
+pub async fn add_droplet(
+    client: &DoClient,
+    firewall_id: &str,
+    droplet_id: u64,
+) -> Result<(), AppError> {
+    let body = DropletIds {
+        droplet_ids: vec![droplet_id],
+    };
+    client
+        .post_empty(&format!("/firewalls/{}/droplets", firewall_id), &body)
+        .await
+}
+
+/// Remove the SSH rule letting in `current` (`None` if there is none), then
+/// let SSH in from `ssh_source` unless it is `None`. DigitalOcean removes
+/// only a rule that matches exactly.
+/// DELETE, then POST /v2/firewalls/{id}/rules
+pub async fn set_ssh_access(
+    client: &DoClient,
+    firewall_id: &str,
+    current: Option<Vec<String>>,
+    ssh_source: Option<&str>,
+) -> Result<(), AppError> {
+    let path = format!("/firewalls/{}/rules", firewall_id);
+    if let Some(sources) = current {
+        let body = InboundRules {
+            inbound_rules: vec![ssh_rule(sources)],
+        };
+        client.delete_with_body(&path, &body).await?;
+    }
+    if let Some(source) = ssh_source {
+        let body = InboundRules {
+            inbound_rules: vec![ssh_rule(vec![source.to_string()])],
+        };
+        client.post_empty(&path, &body).await?;
+    }
+    Ok(())
+}
+
 /// Delete a DigitalOcean firewall.
 /// DELETE /v2/firewalls/{id}
 pub async fn delete_firewall(client: &DoClient, firewall_id: &str) -> Result<(), AppError> {
     client.delete(&format!("/firewalls/{}", firewall_id)).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn opens_wireguard_port_and_ssh() {
+        let server = MockServer::start(|_| {
+            (
+                202,
+                r#"{"firewall": {"id": "bb4b2611-3d72-467b-8602-280330ecd65c",
+                    "name": "createmyvpn-firewall-ab12", "status": "waiting"}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = DoClient::with_base_url("token", &server.url);
+        let id = create_firewall(
+            &client,
+            "createmyvpn-firewall-ab12",
+            51821,
+            Some("203.0.113.7/32"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, "bb4b2611-3d72-467b-8602-280330ecd65c");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/firewalls");
+        let body = request.json();
+        let inbound = body["inbound_rules"].as_array().unwrap();
+        assert_eq!(inbound.len(), 2);
+        assert_eq!(inbound[0]["protocol"], "udp");
+        assert_eq!(inbound[0]["ports"], "51821");
+        assert_eq!(inbound[1]["ports"], "22");
+        assert_eq!(
+            inbound[1]["sources"]["addresses"],
+            serde_json::json!(["203.0.113.7/32"])
+        );
+        assert_eq!(body["outbound_rules"].as_array().unwrap().len(), 3);
+        assert_eq!(body["droplet_ids"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn applies_firewall_to_droplet() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        add_droplet(&client, "fw", 3164444).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/firewalls/fw/droplets")
+        );
+        assert_eq!(request.json()["droplet_ids"], serde_json::json!([3164444]));
+    }
+
+    #[tokio::test]
+    async fn moving_ssh_removes_the_old_rule_first() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        set_ssh_access(
+            &client,
+            "fw",
+            Some(vec!["198.51.100.4/32".into()]),
+            Some("203.0.113.7/32"),
+        )
+        .await
+        .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            (requests[0].method.as_str(), requests[0].path.as_str()),
+            ("DELETE", "/firewalls/fw/rules")
+        );
+        assert_eq!(
+            requests[0].json()["inbound_rules"][0]["sources"]["addresses"],
+            serde_json::json!(["198.51.100.4/32"])
+        );
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(
+            requests[1].json()["inbound_rules"][0]["sources"]["addresses"],
+            serde_json::json!(["203.0.113.7/32"])
+        );
+    }
+
+    #[tokio::test]
+    async fn closing_ssh_only_removes_the_rule() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        set_ssh_access(&client, "fw", Some(vec!["198.51.100.4/32".into()]), None)
+            .await
+            .unwrap();
+
+        let methods: Vec<String> = server.requests().into_iter().map(|r| r.method).collect();
+        assert_eq!(methods, vec!["DELETE"]);
+    }
+
+    #[tokio::test]
+    async fn firewall_error_carries_do_message() {
+        let server = MockServer::start(|_| {
+            (
+                422,
+                r#"{"id": "unprocessable_entity", "message": "invalid rule"}"#.into(),
+            )
+        })
+        .await;
+        let client = DoClient::with_base_url("token", &server.url);
+        let err = delete_firewall(&client, "fw")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("unprocessable_entity: invalid rule"),
+            "{}",
+            err
+        );
+        assert_eq!(server.requests()[0].path, "/firewalls/fw");
+    }
+}
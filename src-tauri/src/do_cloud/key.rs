@@ -39,3 +39,46 @@ pub async fn upload_ssh_key(
 pub async fn delete_ssh_key(client: &DoClient, key_id: u64) -> Result<(), AppError> {
     client.delete(&format!("/account/keys/{}", key_id)).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn uploads_key() {
+        let server = MockServer::start(|_| {
+            (
+                201,
+                r#"{"ssh_key": {"id": 512190, "name": "createmyvpn-key-ab12",
+                    "fingerprint": "3b:16", "public_key": "ssh-ed25519 AAAA"}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = DoClient::with_base_url("token", &server.url);
+        let id = upload_ssh_key(&client, "createmyvpn-key-ab12", "ssh-ed25519 AAAA")
+            .await
+            .unwrap();
+        assert_eq!(id, 512190);
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/account/keys")
+        );
+        let body = request.json();
+        assert_eq!(body["name"], "createmyvpn-key-ab12");
+        assert_eq!(body["public_key"], "ssh-ed25519 AAAA");
+    }
+
+    #[tokio::test]
+    async fn deletes_key_by_id() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = DoClient::with_base_url("token", &server.url);
+        delete_ssh_key(&client, 512190).await.unwrap();
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "DELETE");
+        assert_eq!(request.path, "/account/keys/512190");
+    }
+}
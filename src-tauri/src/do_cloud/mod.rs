@@ -1,6 +0,0 @@
-pub mod client;
-pub mod droplet;
-pub mod firewall;
-pub mod key;
-
-pub use client::DoClient;
@@ -0,0 +1,283 @@
+//! Finds DigitalOcean resources this app created that no local deployment
+//! tracks any more, and deletes the ones the user picks.
+//!
+//! Droplets are found by the `createmyvpn` tag. Firewalls are not tagged: a
+//! tag on a firewall applies its rules to every Droplet with that tag, which
+//! would open each deployment's ports on all the others. Firewalls and SSH
+//! keys are matched by their `createmyvpn-` names instead, as are Droplets
+//! created before tagging.
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::do_cloud::client::DoClient;
+use crate::do_cloud::{droplet, firewall, key, RESOURCE_TAG};
+use crate::error::AppError;
+use crate::state::{DeploymentState, OrphanResource};
+
+const DROPLET_PREFIX: &str = "createmyvpn-server";
+const FIREWALL_PREFIX: &str = "createmyvpn-firewall";
+const KEY_PREFIX: &str = "createmyvpn-key";
+/// Firewalls and SSH keys belong to the account, not a region.
+const GLOBAL_REGION: &str = "global";
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Slug {
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Size {
+    pub price_monthly: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Droplet {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub status: Option<String>,
+    pub size_slug: Option<String>,
+    pub size: Option<Size>,
+    pub region: Option<Slug>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Firewall {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub droplet_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct SshKey {
+    pub id: u64,
+    pub name: String,
+}
+
+/// Everything of ours in the account.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DoInventory {
+    pub droplets: Vec<Droplet>,
+    pub firewalls: Vec<Firewall>,
+    pub ssh_keys: Vec<SshKey>,
+}
+
+/// List every Droplet, firewall and SSH key in the account and keep ours.
+pub async fn scan(client: &DoClient) -> Result<DoInventory, AppError> {
+    let droplets = client.get_all("/droplets", "droplets").await?;
+    let firewalls = client.get_all("/firewalls", "firewalls").await?;
+    let ssh_keys = client.get_all("/account/keys", "ssh_keys").await?;
+    Ok(DoInventory::ours(droplets, firewalls, ssh_keys))
+}
+
+/// Ids the local deployments still own, formatted as `OrphanResource::id`.
+pub fn tracked_ids(deployments: &[DeploymentState]) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    for d in deployments {
+        ids.extend(d.droplet_id.map(|id| id.to_string()));
+        ids.extend(d.do_firewall_id.clone());
+        ids.extend(d.do_ssh_key_id.map(|id| id.to_string()));
+    }
+    ids
+}
+
+impl DoInventory {
+    pub fn ours(droplets: Vec<Droplet>, firewalls: Vec<Firewall>, ssh_keys: Vec<SshKey>) -> Self {
+        DoInventory {
+            droplets: droplets
+                .into_iter()
+                .filter(|d| {
+                    d.tags.iter().any(|t| t == RESOURCE_TAG) || d.name.starts_with(DROPLET_PREFIX)
+                })
+                .collect(),
+            firewalls: firewalls
+                .into_iter()
+                .filter(|f| f.name.starts_with(FIREWALL_PREFIX))
+                .collect(),
+            ssh_keys: ssh_keys
+                .into_iter()
+                .filter(|k| k.name.starts_with(KEY_PREFIX))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.droplets.is_empty() && self.firewalls.is_empty() && self.ssh_keys.is_empty()
+    }
+
+    /// Keep only the resources whose id passes `keep`.
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.droplets.retain(|d| keep(&d.id.to_string()));
+        self.firewalls.retain(|f| keep(&f.id));
+        self.ssh_keys.retain(|k| keep(&k.id.to_string()));
+    }
+
+    /// Flatten into report rows. Droplets cost what DigitalOcean bills for
+    /// their size, whether powered on or not.
+    pub fn resources(&self) -> Vec<OrphanResource> {
+        let mut rows = Vec::new();
+        for d in &self.droplets {
+            let detail = [d.size_slug.as_deref(), d.status.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+            rows.push(OrphanResource {
+                kind: "droplet".into(),
+                id: d.id.to_string(),
+                region: d
+                    .region
+                    .as_ref()
+                    .map(|r| r.slug.clone())
+                    .unwrap_or_else(|| GLOBAL_REGION.into()),
+                name: Some(d.name.clone()),
+                detail: (!detail.is_empty()).then_some(detail),
+                monthly_cost_usd: d.size.as_ref().map(|s| s.price_monthly).unwrap_or(0.0),
+            });
+        }
+        for f in &self.firewalls {
+            rows.push(OrphanResource {
+                kind: "firewall".into(),
+                id: f.id.clone(),
+                region: GLOBAL_REGION.into(),
+                name: Some(f.name.clone()),
+                detail: Some(format!("{} droplets attached", f.droplet_ids.len())),
+                monthly_cost_usd: 0.0,
+            });
+        }
+        for k in &self.ssh_keys {
+            rows.push(OrphanResource {
+                kind: "ssh_key".into(),
+                id: k.id.to_string(),
+                region: GLOBAL_REGION.into(),
+                name: Some(k.name.clone()),
+                detail: None,
+                monthly_cost_usd: 0.0,
+            });
+        }
+        rows
+    }
+
+    /// Delete Droplets first, then firewalls and SSH keys, mirroring a
+    /// regular destroy.
+    pub async fn delete_all(&self, client: &DoClient) -> Result<(), AppError> {
+        for d in &self.droplets {
+            tracing::info!("Deleting orphaned Droplet {} ({})", d.name, d.id);
+            droplet::delete_droplet(client, d.id).await?;
+        }
+        for f in &self.firewalls {
+            tracing::info!("Deleting orphaned firewall {} ({})", f.name, f.id);
+            firewall::delete_firewall(client, &f.id).await?;
+        }
+        for k in &self.ssh_keys {
+            tracing::info!("Deleting orphaned SSH key {} ({})", k.name, k.id);
+            key::delete_ssh_key(client, k.id).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn droplet(id: u64, name: &str, tags: &[&str]) -> Droplet {
+        Droplet {
+            id,
+            name: name.into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            status: Some("active".into()),
+            size_slug: Some("s-1vcpu-1gb".into()),
+            size: Some(Size { price_monthly: 6.0 }),
+            region: Some(Slug {
+                slug: "fra1".into(),
+            }),
+        }
+    }
+
+    fn inventory() -> DoInventory {
+        DoInventory::ours(
+            vec![
+                droplet(1, "createmyvpn-server-ab12", &["createmyvpn"]),
+                droplet(2, "createmyvpn-server", &[]),
+                droplet(3, "renamed-by-hand", &["createmyvpn"]),
+                droplet(4, "my-blog", &["web"]),
+            ],
+            vec![
+                Firewall {
+                    id: "fw-1".into(),
+                    name: "createmyvpn-firewall-ab12".into(),
+                    droplet_ids: vec![1],
+                },
+                Firewall {
+                    id: "fw-2".into(),
+                    name: "web".into(),
+                    droplet_ids: vec![4],
+                },
+            ],
+            vec![
+                SshKey {
+                    id: 10,
+                    name: "createmyvpn-key-ab12".into(),
+                },
+                SshKey {
+                    id: 11,
+                    name: "laptop".into(),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn matches_by_tag_or_name() {
+        let inv = inventory();
+        let ids: Vec<u64> = inv.droplets.iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(inv.firewalls.len(), 1);
+        assert_eq!(inv.ssh_keys.len(), 1);
+    }
+
+    #[test]
+    fn tracked_resources_are_left_alone() {
+        let tracked = tracked_ids(&[DeploymentState {
+            droplet_id: Some(1),
+            do_firewall_id: Some("fw-1".into()),
+            do_ssh_key_id: Some(10),
+            ..Default::default()
+        }]);
+        let mut inv = inventory();
+        inv.retain(|id| !tracked.contains(id));
+        let ids: Vec<u64> = inv.droplets.iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(inv.firewalls.is_empty());
+        assert!(inv.ssh_keys.is_empty());
+        assert!(!inv.is_empty());
+    }
+
+    #[test]
+    fn reports_droplet_price_and_global_resources() {
+        let rows = inventory().resources();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0].region, "fra1");
+        assert_eq!(rows[0].monthly_cost_usd, 6.0);
+        assert_eq!(rows[0].detail.as_deref(), Some("s-1vcpu-1gb, active"));
+        let key = rows.iter().find(|r| r.kind == "ssh_key").unwrap();
+        assert_eq!(key.id, "10");
+        assert_eq!(key.region, "global");
+        assert_eq!(key.monthly_cost_usd, 0.0);
+    }
+
+    #[test]
+    fn droplet_list_item_deserializes() {
+        let json = r#"{"id": 42, "name": "createmyvpn-server-ab12", "status": "active",
+            "tags": ["createmyvpn"], "size_slug": "s-1vcpu-1gb",
+            "size": {"slug": "s-1vcpu-1gb", "price_monthly": 6.0},
+            "region": {"slug": "nyc3", "name": "New York 3"}}"#;
+        let d: Droplet = serde_json::from_str(json).unwrap();
+        assert_eq!(d.region.unwrap().slug, "nyc3");
+        assert_eq!(d.size.unwrap().price_monthly, 6.0);
+    }
+}
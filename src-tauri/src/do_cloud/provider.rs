@@ -0,0 +1,253 @@
+//! `CloudProvider` for DigitalOcean: a Droplet behind a cloud firewall, with
+//! an SSH key uploaded to the account.
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::do_cloud::{client::DoClient, droplet, firewall, key};
+use crate::error::AppError;
+use crate::progress::ProgressSink;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+use crate::server_image::ServerImage;
+use crate::ssh::keygen;
+use crate::state::{DeploymentState, DoCredentials};
+
+pub struct DoProvider {
+    client: DoClient,
+    token: String,
+}
+
+impl DoProvider {
+    pub fn new(creds: &DoCredentials) -> Self {
+        DoProvider {
+            client: DoClient::new(&creds.api_token),
+            token: creds.api_token.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RegionInfo {
+    slug: String,
+    name: String,
+    available: bool,
+}
+
+#[derive(Deserialize)]
+struct SizeInfo {
+    slug: String,
+    memory: u64,
+    vcpus: u32,
+    price_monthly: f64,
+    regions: Vec<String>,
+    available: bool,
+}
+
+#[async_trait]
+impl CloudProvider for DoProvider {
+    fn mode(&self) -> &'static str {
+        "do"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "DigitalOcean"
+    }
+
+    fn check_image(&self, image: &ServerImage, _size: &str) -> Result<(), AppError> {
+        image.require_amd64(self.display_name())
+    }
+
+    async fn validate_credentials(&self) -> Result<String, AppError> {
+        DoClient::validate(&self.token).await
+    }
+
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError> {
+        let regions: Vec<RegionInfo> = self.client.get_all("/regions", "regions").await?;
+        Ok(regions
+            .into_iter()
+            .filter(|r| r.available)
+            .map(|r| Region {
+                code: r.slug,
+                name: r.name,
+            })
+            .collect())
+    }
+
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError> {
+        let sizes: Vec<SizeInfo> = self.client.get_all("/sizes", "sizes").await?;
+        Ok(sizes
+            .into_iter()
+            .filter(|s| s.available && s.regions.iter().any(|r| r == region))
+            .map(|s| ServerSize {
+                description: format!("{} vCPU, {}MB RAM", s.vcpus, s.memory),
+                code: s.slug,
+                monthly_price: Some(s.price_monthly),
+            })
+            .collect())
+    }
+
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError> {
+        if state.do_firewall_id.is_some() {
+            return Ok(());
+        }
+        // Names carry the deployment id so parallel deployments are told apart.
+        let name = format!("createmyvpn-firewall-{}", state.id);
+        let firewall_id = firewall::create_firewall(
+            &self.client,
+            &name,
+            wireguard_port,
+            provider::firewall_ssh_source(state)?,
+        )
+        .await?;
+        tracing::info!("[DO] Firewall created: {}", firewall_id);
+        state.do_firewall_id = Some(firewall_id);
+        Ok(())
+    }
+
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        _wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError> {
+        let firewall_id = state
+            .do_firewall_id
+            .as_deref()
+            .ok_or_else(|| AppError::State("No firewall recorded for this deployment".into()))?;
+        firewall::set_ssh_access(
+            &self.client,
+            firewall_id,
+            provider::current_ssh_sources(state),
+            source,
+        )
+        .await
+    }
+
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError> {
+        if state.do_ssh_key_id.is_some() && state.ssh_private_key.is_some() {
+            return Ok(());
+        }
+        // A recorded key whose private half was lost is no use; delete it
+        // rather than leave it in the account untracked.
+        if let Some(key_id) = state.do_ssh_key_id {
+            key::delete_ssh_key(&self.client, key_id).await?;
+            tracing::info!("[DO] Deleted SSH key {} with no saved private key", key_id);
+            state.do_ssh_key_id = None;
+        }
+        let (private_key, public_key) = keygen::generate_ed25519()?;
+        state.ssh_private_key = Some(private_key);
+
+        let name = format!("createmyvpn-key-{}", state.id);
+        let key_id = key::upload_ssh_key(&self.client, &name, &public_key).await?;
+        tracing::info!("[DO] SSH key uploaded, key_id={}", key_id);
+        state.do_ssh_key_id = Some(key_id);
+        Ok(())
+    }
+
+    fn runs_user_data(&self) -> bool {
+        true
+    }
+
+    /// DigitalOcean applies a firewall to existing Droplets only, so it is
+    /// added right after the Droplet is created.
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        user_data: Option<&str>,
+    ) -> Result<(), AppError> {
+        let firewall_id = state
+            .do_firewall_id
+            .clone()
+            .ok_or_else(|| AppError::State("No firewall recorded for this deployment".into()))?;
+        let droplet_id = match state.droplet_id {
+            Some(id) => id,
+            None => {
+                let key_id = state.do_ssh_key_id.ok_or_else(|| {
+                    AppError::State("No SSH key recorded for this deployment".into())
+                })?;
+                let region = state.region.clone().unwrap_or_default();
+                let name = format!("createmyvpn-server-{}", state.id);
+                let image = droplet::image_slug(state.server_image().os);
+                let droplet_id = droplet::create_droplet(
+                    &self.client,
+                    &name,
+                    &region,
+                    size,
+                    image,
+                    key_id,
+                    user_data,
+                )
+                .await?;
+                tracing::info!("[DO] Droplet created: droplet_id={}", droplet_id);
+                state.droplet_id = Some(droplet_id);
+                droplet_id
+            }
+        };
+        firewall::add_droplet(&self.client, &firewall_id, droplet_id).await?;
+        tracing::info!(
+            "[DO] Firewall {} applied to Droplet {}",
+            firewall_id,
+            droplet_id
+        );
+        Ok(())
+    }
+
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError> {
+        let droplet_id = state
+            .droplet_id
+            .ok_or_else(|| AppError::State("No Droplet recorded for this deployment".into()))?;
+        tracing::info!(
+            "[DO] Waiting for Droplet {} to become active...",
+            droplet_id
+        );
+        droplet::wait_for_active(&self.client, droplet_id).await
+    }
+
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError> {
+        let mut failures = Vec::new();
+
+        progress.emit(1, 3, "Deleting firewall...", "running");
+        if let Some(ref firewall_id) = state.do_firewall_id {
+            let result = firewall::delete_firewall(&self.client, firewall_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete DO firewall: {}", e);
+                failures.push(format!("firewall {}: {}", firewall_id, e));
+            }
+        }
+
+        progress.emit(2, 3, "Deleting server...", "running");
+        if let Some(droplet_id) = state.droplet_id {
+            let result = droplet::delete_droplet(&self.client, droplet_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete DO droplet: {}", e);
+                failures.push(format!("Droplet {}: {}", droplet_id, e));
+            }
+        }
+
+        progress.emit(3, 3, "Cleaning up...", "running");
+        if let Some(key_id) = state.do_ssh_key_id {
+            let result = key::delete_ssh_key(&self.client, key_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete DO SSH key: {}", e);
+                failures.push(format!("SSH key {}: {}", key_id, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::General(format!(
+                "Could not delete {}",
+                failures.join("; ")
+            )))
+        }
+    }
+}
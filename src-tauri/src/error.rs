@@ -8,6 +8,17 @@ pub enum AppError {
     #[error("SSH error: {0}")]
     Ssh(String),
 
+    #[error(
+        "SSH host key mismatch for {host}: expected {expected}, but the server presented {actual}. \
+         The server's identity has changed since it was first trusted. This can mean someone \
+         is intercepting the connection, or that the server was rebuilt. Refusing to connect."
+    )]
+    HostKeyMismatch {
+        host: String,
+        expected: String,
+        actual: String,
+    },
+
     #[error("WireGuard error: {0}")]
     WireGuard(String),
 
@@ -61,6 +72,19 @@ mod tests {
         assert_eq!(err.to_string(), "SSH error: connection refused");
     }
 
+    #[test]
+    fn error_display_host_key_mismatch() {
+        let err = AppError::HostKeyMismatch {
+            host: "1.2.3.4".into(),
+            expected: "SHA256:old".into(),
+            actual: "SHA256:new".into(),
+        };
+        let msg = err.to_string();
+        assert!(msg.starts_with("SSH host key mismatch for 1.2.3.4"));
+        assert!(msg.contains("expected SHA256:old"));
+        assert!(msg.contains("presented SHA256:new"));
+    }
+
     #[test]
     fn error_display_wireguard() {
         let err = AppError::WireGuard("bad key".into());
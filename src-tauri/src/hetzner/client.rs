@@ -0,0 +1,80 @@
+use crate::api_client::ApiClient;
+use crate::error::AppError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+const BASE_URL: &str = "https://api.hetzner.cloud/v1";
+
+/// Label put on every resource this app creates, so it can be found again
+/// without local state.
+const RESOURCE_LABEL: (&str, &str) = ("managed-by", "createmyvpn");
+
+pub struct HetznerClient(ApiClient);
+
+/// Error body returned by the Hetzner Cloud API.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ApiError,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: String,
+    message: String,
+}
+
+impl HetznerClient {
+    pub fn new(token: &str) -> Self {
+        HetznerClient::with_base_url(token, BASE_URL)
+    }
+
+    pub fn with_base_url(token: &str, base_url: &str) -> Self {
+        HetznerClient(ApiClient::new("Hetzner", base_url, token, error_message))
+    }
+
+    /// Validate the API token with a cheap authenticated call. Hetzner tokens
+    /// belong to a project, not an account, so there is no identity to return.
+    pub async fn validate(&self) -> Result<(), AppError> {
+        #[derive(Deserialize)]
+        struct SshKeysResponse {}
+
+        let _: SshKeysResponse = self.get("/ssh_keys?per_page=1").await?;
+        Ok(())
+    }
+}
+
+impl Deref for HetznerClient {
+    type Target = ApiClient;
+
+    fn deref(&self) -> &ApiClient {
+        &self.0
+    }
+}
+
+/// Labels for a resource this app creates.
+pub fn resource_labels() -> BTreeMap<String, String> {
+    let (key, value) = RESOURCE_LABEL;
+    BTreeMap::from([(key.to_string(), value.to_string())])
+}
+
+/// `"<code>: <message>"` from an error body.
+fn error_message(body: &str) -> Option<String> {
+    let resp: ErrorResponse = serde_json::from_str(body).ok()?;
+    Some(format!("{}: {}", resp.error.code, resp.error.message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_reads_code_and_message() {
+        let body = r#"{"error": {"code": "unauthorized", "message": "unable to authenticate"}}"#;
+        assert_eq!(
+            error_message(body).as_deref(),
+            Some("unauthorized: unable to authenticate")
+        );
+        assert_eq!(error_message("Bad Gateway"), None);
+    }
+}
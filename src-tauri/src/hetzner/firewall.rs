@@ -0,0 +1,179 @@
+use crate::error::AppError;
+use crate::hetzner::client::{resource_labels, HetznerClient};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+struct Rule {
+    direction: String,
+    protocol: String,
+    port: String,
+    source_ips: Vec<String>,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct CreateFirewallRequest {
+    name: String,
+    labels: BTreeMap<String, String>,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct SetRulesRequest {
+    rules: Vec<Rule>,
+}
+
+#[derive(Deserialize)]
+struct CreateFirewallResponse {
+    firewall: FirewallInfo,
+}
+
+#[derive(Deserialize)]
+struct FirewallInfo {
+    id: u64,
+}
+
+/// Inbound rules: SSH (TCP 22) from `ssh_source` unless it is `None`, and
+/// WireGuard (UDP port) from anywhere.
+fn rules(wireguard_port: u16, ssh_source: Option<&str>) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    if let Some(source) = ssh_source {
+        rules.push(Rule {
+            direction: "in".to_string(),
+            protocol: "tcp".to_string(),
+            port: "22".to_string(),
+            source_ips: vec![source.to_string()],
+            description: "SSH".to_string(),
+        });
+    }
+    rules.push(Rule {
+        direction: "in".to_string(),
+        protocol: "udp".to_string(),
+        port: wireguard_port.to_string(),
+        source_ips: vec!["0.0.0.0/0".to_string(), "::/0".to_string()],
+        description: "WireGuard".to_string(),
+    });
+    rules
+}
+
+/// Create a cloud firewall allowing SSH (TCP 22) from `ssh_source` and
+/// WireGuard (UDP port) from anywhere inbound. Hetzner firewalls allow all
+/// outbound traffic unless outbound rules exist, so none are added. Apply it
+/// when creating the server.
+/// POST /v1/firewalls — returns the firewall ID.
+pub async fn create_firewall(
+    client: &HetznerClient,
+    name: &str,
+    wireguard_port: u16,
+    ssh_source: &str,
+) -> Result<u64, AppError> {
+    let body = CreateFirewallRequest {
+        name: name.to_string(),
+        labels: resource_labels(),
+        rules: rules(wireguard_port, Some(ssh_source)),
+    };
+
+    let resp: CreateFirewallResponse = client.post("/firewalls", &body).await?;
+    Ok(resp.firewall.id)
+}
+
+/// Replace the firewall's rules so SSH is let in from `ssh_source` only, or
+/// not at all when it is `None`.
+/// POST /v1/firewalls/{id}/actions/set_rules
+pub async fn set_ssh_access(
+    client: &HetznerClient,
+    firewall_id: u64,
+    wireguard_port: u16,
+    ssh_source: Option<&str>,
+) -> Result<(), AppError> {
+    let body = SetRulesRequest {
+        rules: rules(wireguard_port, ssh_source),
+    };
+    let _: serde_json::Value = client
+        .post(
+            &format!("/firewalls/{}/actions/set_rules", firewall_id),
+            &body,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Delete a cloud firewall. It must no longer be applied to a server, so
+/// delete the server first (see `server::wait_for_deleted`).
+/// DELETE /v1/firewalls/{id}
+pub async fn delete_firewall(client: &HetznerClient, firewall_id: u64) -> Result<(), AppError> {
+    client.delete(&format!("/firewalls/{}", firewall_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn opens_ssh_and_wireguard_port() {
+        let server = MockServer::start(|_| {
+            (
+                201,
+                r#"{"firewall": {"id": 38, "name": "createmyvpn-firewall-ab12", "rules": []},
+                    "actions": []}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        let id = create_firewall(
+            &client,
+            "createmyvpn-firewall-ab12",
+            51821,
+            "203.0.113.7/32",
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, 38);
+
+        let body = server.requests()[0].json();
+        let rules = body["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["protocol"], "tcp");
+        assert_eq!(rules[0]["port"], "22");
+        assert_eq!(
+            rules[0]["source_ips"],
+            serde_json::json!(["203.0.113.7/32"])
+        );
+        assert_eq!(rules[1]["protocol"], "udp");
+        assert_eq!(rules[1]["port"], "51821");
+        assert_eq!(rules[1]["direction"], "in");
+        assert_eq!(body["labels"]["managed-by"], "createmyvpn");
+    }
+
+    #[tokio::test]
+    async fn closing_ssh_keeps_only_wireguard() {
+        let server = MockServer::start(|_| (201, r#"{"actions": []}"#.into())).await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        set_ssh_access(&client, 38, 51820, None).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/firewalls/38/actions/set_rules");
+        let rules = request.json()["rules"].as_array().unwrap().clone();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["port"], "51820");
+    }
+
+    #[tokio::test]
+    async fn firewall_in_use_is_an_error() {
+        let server = MockServer::start(|_| {
+            (
+                409,
+                r#"{"error": {"code": "resource_in_use", "message": "firewall still applied"}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        let err = delete_firewall(&client, 38).await.unwrap_err().to_string();
+        assert!(err.contains("resource_in_use"), "{}", err);
+        assert_eq!(server.requests()[0].path, "/firewalls/38");
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error::AppError;
+use crate::hetzner::client::{resource_labels, HetznerClient};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+struct CreateKeyRequest {
+    name: String,
+    public_key: String,
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct CreateKeyResponse {
+    ssh_key: SshKeyInfo,
+}
+
+#[derive(Deserialize)]
+struct SshKeyInfo {
+    id: u64,
+}
+
+/// Upload an SSH public key to the project and return the key ID.
+/// POST /v1/ssh_keys
+pub async fn upload_ssh_key(
+    client: &HetznerClient,
+    name: &str,
+    public_key: &str,
+) -> Result<u64, AppError> {
+    let body = CreateKeyRequest {
+        name: name.to_string(),
+        public_key: public_key.to_string(),
+        labels: resource_labels(),
+    };
+
+    let resp: CreateKeyResponse = client.post("/ssh_keys", &body).await?;
+    Ok(resp.ssh_key.id)
+}
+
+/// Delete an SSH key from the project.
+/// DELETE /v1/ssh_keys/{id}
+pub async fn delete_ssh_key(client: &HetznerClient, key_id: u64) -> Result<(), AppError> {
+    client.delete(&format!("/ssh_keys/{}", key_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn uploads_labelled_key() {
+        let server = MockServer::start(|_| {
+            (
+                201,
+                r#"{"ssh_key": {"id": 2323, "name": "createmyvpn-key-ab12",
+                    "fingerprint": "b7:2f", "public_key": "ssh-ed25519 AAAA", "labels": {}}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        let id = upload_ssh_key(&client, "createmyvpn-key-ab12", "ssh-ed25519 AAAA")
+            .await
+            .unwrap();
+        assert_eq!(id, 2323);
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/ssh_keys")
+        );
+        let body = request.json();
+        assert_eq!(body["public_key"], "ssh-ed25519 AAAA");
+        assert_eq!(body["labels"]["managed-by"], "createmyvpn");
+    }
+
+    #[tokio::test]
+    async fn deletes_key_by_id() {
+        let server = MockServer::start(|_| (204, String::new())).await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        delete_ssh_key(&client, 2323).await.unwrap();
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "DELETE");
+        assert_eq!(request.path, "/ssh_keys/2323");
+    }
+}
@@ -0,0 +1,347 @@
+//! `CloudProvider` for Hetzner Cloud: a server created with its firewall and
+//! SSH key already attached.
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::hetzner::{client::HetznerClient, firewall, key, server};
+use crate::progress::ProgressSink;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+use crate::server_image::ServerImage;
+use crate::ssh::keygen;
+use crate::state::{DeploymentState, HetznerCredentials};
+
+pub struct HetznerProvider {
+    client: HetznerClient,
+}
+
+impl HetznerProvider {
+    pub fn new(creds: &HetznerCredentials) -> Self {
+        HetznerProvider {
+            client: HetznerClient::new(&creds.api_token),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_client(client: HetznerClient) -> Self {
+        HetznerProvider { client }
+    }
+}
+
+#[derive(Deserialize)]
+struct LocationsResponse {
+    locations: Vec<Location>,
+}
+
+#[derive(Deserialize)]
+struct Location {
+    name: String,
+    city: String,
+    country: String,
+}
+
+#[derive(Deserialize)]
+struct ServerTypesResponse {
+    server_types: Vec<ServerType>,
+}
+
+#[derive(Deserialize)]
+struct ServerType {
+    name: String,
+    cores: u32,
+    memory: f64,
+    #[serde(default)]
+    deprecated: Option<bool>,
+    prices: Vec<LocationPrice>,
+}
+
+#[derive(Deserialize)]
+struct LocationPrice {
+    location: String,
+    price_monthly: Price,
+}
+
+/// Hetzner sends prices as decimal strings, e.g. "4.5101000000".
+#[derive(Deserialize)]
+struct Price {
+    gross: String,
+}
+
+#[async_trait]
+impl CloudProvider for HetznerProvider {
+    fn mode(&self) -> &'static str {
+        "hetzner"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Hetzner"
+    }
+
+    /// Every image has x86 and arm builds; the server type decides.
+    fn check_image(&self, image: &ServerImage, size: &str) -> Result<(), AppError> {
+        image.check_size(size, server::server_type_architecture(size))
+    }
+
+    async fn validate_credentials(&self) -> Result<String, AppError> {
+        self.client.validate().await?;
+        Ok(String::new())
+    }
+
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError> {
+        let resp: LocationsResponse = self.client.get("/locations").await?;
+        Ok(resp
+            .locations
+            .into_iter()
+            .map(|l| Region {
+                name: format!("{}, {}", l.city, l.country),
+                code: l.name,
+            })
+            .collect())
+    }
+
+    /// Server types sold in `region`, priced there (gross, in EUR).
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError> {
+        let resp: ServerTypesResponse = self.client.get("/server_types?per_page=50").await?;
+        Ok(resp
+            .server_types
+            .into_iter()
+            .filter(|t| t.deprecated != Some(true))
+            .filter_map(|t| {
+                let price = t.prices.iter().find(|p| p.location == region)?;
+                Some(ServerSize {
+                    description: format!("{} vCPU, {}GB RAM", t.cores, t.memory),
+                    monthly_price: price.price_monthly.gross.parse().ok(),
+                    code: t.name,
+                })
+            })
+            .collect())
+    }
+
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError> {
+        if state.hetzner_firewall_id.is_some() {
+            return Ok(());
+        }
+        // Names carry the deployment id so parallel deployments are told apart.
+        let name = format!("createmyvpn-firewall-{}", state.id);
+        let firewall_id = firewall::create_firewall(
+            &self.client,
+            &name,
+            wireguard_port,
+            provider::ssh_source(state)?,
+        )
+        .await?;
+        tracing::info!("[Hetzner] Firewall created: {}", firewall_id);
+        state.hetzner_firewall_id = Some(firewall_id);
+        Ok(())
+    }
+
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError> {
+        let firewall_id = state
+            .hetzner_firewall_id
+            .ok_or_else(|| AppError::State("No firewall recorded for this deployment".into()))?;
+        firewall::set_ssh_access(&self.client, firewall_id, wireguard_port, source).await
+    }
+
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError> {
+        if state.hetzner_ssh_key_id.is_some() && state.ssh_private_key.is_some() {
+            return Ok(());
+        }
+        // A recorded key whose private half was lost is no use; delete it
+        // rather than leave it in the account untracked.
+        if let Some(key_id) = state.hetzner_ssh_key_id {
+            key::delete_ssh_key(&self.client, key_id).await?;
+            tracing::info!(
+                "[Hetzner] Deleted SSH key {} with no saved private key",
+                key_id
+            );
+            state.hetzner_ssh_key_id = None;
+        }
+        let (private_key, public_key) = keygen::generate_ed25519()?;
+        state.ssh_private_key = Some(private_key);
+
+        let name = format!("createmyvpn-key-{}", state.id);
+        let key_id = key::upload_ssh_key(&self.client, &name, &public_key).await?;
+        tracing::info!("[Hetzner] SSH key uploaded, key_id={}", key_id);
+        state.hetzner_ssh_key_id = Some(key_id);
+        Ok(())
+    }
+
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        _user_data: Option<&str>,
+    ) -> Result<(), AppError> {
+        if state.hetzner_server_id.is_some() {
+            return Ok(());
+        }
+        let (Some(key_id), Some(firewall_id)) =
+            (state.hetzner_ssh_key_id, state.hetzner_firewall_id)
+        else {
+            return Err(AppError::State(
+                "The SSH key and firewall must exist before the server".into(),
+            ));
+        };
+        let location = state.region.clone().unwrap_or_default();
+        let name = format!("createmyvpn-server-{}", state.id);
+        let image = server::image_name(state.server_image().os);
+        let server_id = server::create_server(
+            &self.client,
+            &name,
+            &location,
+            size,
+            image,
+            key_id,
+            firewall_id,
+        )
+        .await?;
+        tracing::info!("[Hetzner] Server created: server_id={}", server_id);
+        state.hetzner_server_id = Some(server_id);
+        Ok(())
+    }
+
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError> {
+        let server_id = state
+            .hetzner_server_id
+            .ok_or_else(|| AppError::State("No server recorded for this deployment".into()))?;
+        tracing::info!(
+            "[Hetzner] Waiting for server {} to start running...",
+            server_id
+        );
+        server::wait_for_running(&self.client, server_id).await
+    }
+
+    /// The firewall can only go once the server is gone.
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError> {
+        let mut failures = Vec::new();
+
+        progress.emit(1, 3, "Deleting server...", "running");
+        if let Some(server_id) = state.hetzner_server_id {
+            let result = match server::delete_server(&self.client, server_id).await {
+                Ok(()) => server::wait_for_deleted(&self.client, server_id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Hetzner server: {}", e);
+                failures.push(format!("server {}: {}", server_id, e));
+            }
+        }
+
+        progress.emit(2, 3, "Deleting firewall...", "running");
+        if let Some(firewall_id) = state.hetzner_firewall_id {
+            let result = firewall::delete_firewall(&self.client, firewall_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Hetzner firewall: {}", e);
+                failures.push(format!("firewall {}: {}", firewall_id, e));
+            }
+        }
+
+        progress.emit(3, 3, "Cleaning up...", "running");
+        if let Some(key_id) = state.hetzner_ssh_key_id {
+            let result = key::delete_ssh_key(&self.client, key_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Hetzner SSH key: {}", e);
+                failures.push(format!("SSH key {}: {}", key_id, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::General(format!(
+                "Could not delete {}",
+                failures.join("; ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn sizes_are_priced_for_the_region() {
+        let server = MockServer::start(|_| {
+            (
+                200,
+                r#"{"server_types": [
+                    {"name": "cx22", "cores": 2, "memory": 4.0, "deprecated": false,
+                     "prices": [{"location": "fsn1",
+                                 "price_monthly": {"net": "3.79", "gross": "4.51"}}]},
+                    {"name": "cpx11", "cores": 2, "memory": 2.0, "deprecated": false,
+                     "prices": [{"location": "ash",
+                                 "price_monthly": {"net": "4.35", "gross": "4.35"}}]}
+                ]}"#
+                .into(),
+            )
+        })
+        .await;
+        let provider =
+            HetznerProvider::with_client(HetznerClient::with_base_url("token", &server.url));
+        let sizes = provider.list_sizes("fsn1").await.unwrap();
+        assert_eq!(
+            sizes,
+            vec![ServerSize {
+                code: "cx22".into(),
+                description: "2 vCPU, 4GB RAM".into(),
+                monthly_price: Some(4.51),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn lost_ssh_key_is_deleted_before_uploading_a_new_one() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "DELETE" => (204, String::new()),
+            _ => (201, r#"{"ssh_key": {"id": 8}}"#.into()),
+        })
+        .await;
+        let provider =
+            HetznerProvider::with_client(HetznerClient::with_base_url("token", &server.url));
+        let mut state = DeploymentState {
+            hetzner_ssh_key_id: Some(7),
+            ..Default::default()
+        };
+        provider.create_ssh_key(&mut state).await.unwrap();
+
+        let calls: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(calls, vec!["DELETE /ssh_keys/7", "POST /ssh_keys"]);
+        assert_eq!(state.hetzner_ssh_key_id, Some(8));
+        assert!(state.ssh_private_key.is_some());
+    }
+
+    #[tokio::test]
+    async fn server_needs_key_and_firewall_first() {
+        let server = MockServer::start(|_| (500, String::new())).await;
+        let provider =
+            HetznerProvider::with_client(HetznerClient::with_base_url("token", &server.url));
+        let mut state = DeploymentState {
+            hetzner_ssh_key_id: Some(7),
+            ..Default::default()
+        };
+        assert!(provider
+            .create_server(&mut state, "cx22", None)
+            .await
+            .is_err());
+        assert!(server.requests().is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::error::AppError;
+use crate::hetzner::client::{resource_labels, HetznerClient};
+use crate::server_image::{Architecture, OsRelease};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::time::Duration;
+
+/// How long to wait between status polls (shortened in tests).
+const POLL_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+const MAX_POLLS: u32 = 60; // 60 × 5s = 5 minutes
+
+#[derive(Serialize)]
+struct FirewallRef {
+    firewall: u64,
+}
+
+#[derive(Serialize)]
+struct CreateServerRequest {
+    name: String,
+    server_type: String,
+    image: String,
+    location: String,
+    ssh_keys: Vec<u64>,
+    firewalls: Vec<FirewallRef>,
+    labels: BTreeMap<String, String>,
+    start_after_create: bool,
+}
+
+#[derive(Deserialize)]
+struct ServerResponse {
+    server: ServerInfo,
+}
+
+#[derive(Deserialize)]
+struct ServerInfo {
+    id: u64,
+    status: Option<String>,
+    public_net: Option<PublicNet>,
+}
+
+#[derive(Deserialize)]
+struct PublicNet {
+    ipv4: Option<Ipv4>,
+}
+
+#[derive(Deserialize)]
+struct Ipv4 {
+    ip: String,
+}
+
+/// The image name of `os`. Hetzner picks the x86 or arm build to match the
+/// server type.
+pub fn image_name(os: OsRelease) -> &'static str {
+    match os {
+        OsRelease::Ubuntu2204 => "ubuntu-22.04",
+        OsRelease::Ubuntu2404 => "ubuntu-24.04",
+        OsRelease::Debian12 => "debian-12",
+    }
+}
+
+/// The CPUs of `server_type`: the CAX line is Ampere arm64, the rest x86.
+pub fn server_type_architecture(server_type: &str) -> Architecture {
+    if server_type.starts_with("cax") {
+        Architecture::Arm64
+    } else {
+        Architecture::Amd64
+    }
+}
+
+/// Create a server booting `image` with the SSH key and firewall already
+/// attached, and return its ID.
+/// POST /v1/servers
+pub async fn create_server(
+    client: &HetznerClient,
+    name: &str,
+    location: &str,
+    server_type: &str,
+    image: &str,
+    ssh_key_id: u64,
+    firewall_id: u64,
+) -> Result<u64, AppError> {
+    let body = CreateServerRequest {
+        name: name.to_string(),
+        server_type: server_type.to_string(),
+        image: image.to_string(),
+        location: location.to_string(),
+        ssh_keys: vec![ssh_key_id],
+        firewalls: vec![FirewallRef {
+            firewall: firewall_id,
+        }],
+        labels: resource_labels(),
+        start_after_create: true,
+    };
+
+    let resp: ServerResponse = client.post("/servers", &body).await?;
+    Ok(resp.server.id)
+}
+
+/// Poll the server until it reaches `running` status, then return its
+/// public IPv4. Polls every 5 seconds for up to 5 minutes.
+pub async fn wait_for_running(client: &HetznerClient, server_id: u64) -> Result<String, AppError> {
+    for attempt in 0..MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        let resp: ServerResponse = client.get(&format!("/servers/{}", server_id)).await?;
+
+        tracing::debug!(
+            "[Hetzner wait_for_running] attempt {}/{}: status={:?}",
+            attempt + 1,
+            MAX_POLLS,
+            resp.server.status
+        );
+
+        if resp.server.status.as_deref() == Some("running") {
+            if let Some(ipv4) = resp.server.public_net.and_then(|net| net.ipv4) {
+                return Ok(ipv4.ip);
+            }
+            // Running but no public IP yet — continue polling
+        }
+    }
+
+    Err(AppError::General(
+        "Server did not start running within 5 minutes".into(),
+    ))
+}
+
+/// Delete a server. Hetzner deletes it in the background.
+/// DELETE /v1/servers/{id}
+pub async fn delete_server(client: &HetznerClient, server_id: u64) -> Result<(), AppError> {
+    client.delete(&format!("/servers/{}", server_id)).await
+}
+
+/// Poll until the server is gone, so the firewall it used can be deleted.
+pub async fn wait_for_deleted(client: &HetznerClient, server_id: u64) -> Result<(), AppError> {
+    for attempt in 0..MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        let resp: Option<ServerResponse> =
+            client.get_opt(&format!("/servers/{}", server_id)).await?;
+        if resp.is_none() {
+            return Ok(());
+        }
+    }
+
+    Err(AppError::General(format!(
+        "Server {} was not deleted within 5 minutes",
+        server_id
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn server_json(status: &str, ip: Option<&str>) -> String {
+        let ipv4 = match ip {
+            Some(ip) => format!(r#"{{"ip": "{}", "blocked": false}}"#, ip),
+            None => "null".to_string(),
+        };
+        format!(
+            r#"{{"server": {{"id": 42, "name": "createmyvpn-server-ab12", "status": "{}",
+                "public_net": {{"ipv4": {}, "ipv6": null}}}}}}"#,
+            status, ipv4
+        )
+    }
+
+    #[tokio::test]
+    async fn creates_server_with_key_and_firewall() {
+        let server = MockServer::start(|_| (201, server_json("initializing", None))).await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        let id = create_server(
+            &client,
+            "createmyvpn-server-ab12",
+            "fsn1",
+            "cax11",
+            image_name(OsRelease::Debian12),
+            7,
+            38,
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, 42);
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["location"], "fsn1");
+        assert_eq!(body["server_type"], "cax11");
+        assert_eq!(body["image"], "debian-12");
+        assert_eq!(body["ssh_keys"], serde_json::json!([7]));
+        assert_eq!(body["firewalls"], serde_json::json!([{"firewall": 38}]));
+        assert_eq!(body["labels"]["managed-by"], "createmyvpn");
+    }
+
+    #[tokio::test]
+    async fn waits_until_running_with_an_address() {
+        let polls = AtomicU32::new(0);
+        let server = MockServer::start(move |_| {
+            let body = match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => server_json("initializing", None),
+                1 => server_json("running", None),
+                _ => server_json("running", Some("203.0.113.7")),
+            };
+            (200, body)
+        })
+        .await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        let ip = wait_for_running(&client, 42).await.unwrap();
+        assert_eq!(ip, "203.0.113.7");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.path == "/servers/42"));
+    }
+
+    #[tokio::test]
+    async fn deleted_server_is_waited_out() {
+        let polls = AtomicU32::new(0);
+        let server = MockServer::start(move |request| {
+            if request.method == "DELETE" {
+                return (
+                    200,
+                    r#"{"action": {"id": 13, "command": "delete_server"}}"#.into(),
+                );
+            }
+            match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, server_json("running", Some("203.0.113.7"))),
+                _ => (
+                    404,
+                    r#"{"error": {"code": "not_found", "message": "server not found"}}"#.into(),
+                ),
+            }
+        })
+        .await;
+        let client = HetznerClient::with_base_url("token", &server.url);
+        delete_server(&client, 42).await.unwrap();
+        wait_for_deleted(&client, 42).await.unwrap();
+
+        let methods: Vec<String> = server.requests().into_iter().map(|r| r.method).collect();
+        assert_eq!(methods, vec!["DELETE", "GET", "GET"]);
+    }
+}
@@ -0,0 +1,126 @@
+//! A minimal HTTP/1.1 server for testing the REST API clients (DigitalOcean,
+//! Hetzner, Vultr, Linode) against canned responses, without network access or
+//! extra dependencies.
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request the mock server received.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path and query, e.g. "/servers/42".
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+type Handler = dyn Fn(&Request) -> (u16, String) + Send + Sync;
+
+pub struct MockServer {
+    /// Base URL to hand to a client's `with_base_url`.
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Answer every request with the `(status, JSON body)` from `handler`.
+    pub async fn start(
+        handler: impl Fn(&Request) -> (u16, String) + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let seen = seen.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, handler.as_ref(), &seen).await;
+                });
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Serve requests on one connection until the client closes it.
+async fn serve(
+    stream: TcpStream,
+    handler: &Handler,
+    seen: &Mutex<Vec<Request>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut content_length = 0;
+        let mut authorization = None;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.parse().unwrap_or(0);
+                } else if name.eq_ignore_ascii_case("authorization") {
+                    authorization = Some(value.to_string());
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await?;
+
+        let request = Request {
+            method,
+            path,
+            authorization,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+        let (status, response) = handler(&request);
+        seen.lock().unwrap().push(request);
+
+        // 204 No Content must not carry a body or a length.
+        let head = if status == 204 {
+            "HTTP/1.1 204 No Content\r\n\r\n".to_string()
+        } else {
+            format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+                status,
+                response.len()
+            )
+        };
+        let stream = reader.get_mut();
+        stream.write_all(head.as_bytes()).await?;
+        if status != 204 {
+            stream.write_all(response.as_bytes()).await?;
+        }
+        stream.flush().await?;
+    }
+}
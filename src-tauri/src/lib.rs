@@ -2,6 +2,9 @@ pub mod aws {
     pub mod ami;
     pub mod client;
     pub mod ec2;
+    pub mod orphans;
+    pub mod provider;
+    pub mod resume;
     pub mod security_group;
     pub mod teardown;
     pub mod vpc;
@@ -12,42 +15,98 @@ pub mod do_cloud {
     pub mod droplet;
     pub mod firewall;
     pub mod key;
+    pub mod orphans;
+    pub mod provider;
+
+    /// Tag put on every Droplet this app creates, so it can be found again
+    /// without local state.
+    pub const RESOURCE_TAG: &str = "createmyvpn";
+}
+
+pub mod hetzner {
+    pub mod client;
+    pub mod firewall;
+    pub mod key;
+    pub mod provider;
+    pub mod server;
+}
+
+pub mod vultr {
+    pub mod client;
+    pub mod firewall;
+    pub mod instance;
+    pub mod key;
+    pub mod provider;
+}
+
+pub mod linode {
+    pub mod client;
+    pub mod firewall;
+    pub mod instance;
+    pub mod provider;
 }
 
 pub mod commands {
     pub mod byo;
+    pub mod catalog;
     pub mod connect;
     pub mod credentials;
     pub mod credentials_do;
+    pub mod credentials_hetzner;
+    pub mod credentials_linode;
+    pub mod credentials_vultr;
     pub mod deploy;
-    pub mod deploy_do;
     pub mod destroy;
     pub mod logs;
+    pub mod orphans;
+    pub mod peers;
+    pub mod pricing;
     pub mod settings;
+    pub mod ssh_access;
     pub mod timer;
+    pub mod vault;
 }
 
 pub mod persistence {
     pub mod store;
+    pub mod vault;
 }
 
 pub mod ssh {
     pub mod client;
     pub mod configure;
+    pub mod host_key;
+    pub mod keygen;
 }
 
 pub mod wireguard {
+    pub mod bootstrap;
     pub mod client_config;
     pub mod config_parser;
+    pub mod dns;
     pub mod keys;
+    pub mod killswitch;
+    pub mod peers;
+    pub mod probe;
     pub mod server_config;
+    pub mod subnet;
     pub mod userspace;
     pub mod tunnel;
 }
 
+pub mod api_client;
+pub mod catalog;
 pub mod error;
+pub mod pricing;
+pub mod progress;
+pub mod provider;
+pub mod public_ip;
+pub mod server_image;
 pub mod state;
 
+#[cfg(test)]
+mod http_mock;
+
 pub fn run() {
     // ── Logging: stderr + file ─────────────────────────────────────────────
     let log_dir = dirs::home_dir()
@@ -61,17 +120,24 @@ pub fn run() {
     // ── Fresh-session cleanup ─────────────────────────────────────────────
     // Truncate the log file so each run starts with a clean slate.
     let _ = std::fs::write(&log_path, "");
-    // Delete stored AWS credentials — they are entered fresh each session.
+    // Delete stored cloud credentials — they are entered fresh each session.
     // Exception: if an auto-destroy timer is pending, keep credentials so the
-    // timer can call destroy_vpn (which needs them for AWS teardown).
-    let has_pending_timer = persistence::store::load_state()
-        .ok()
-        .and_then(|s| s.auto_destroy_at)
-        .map(|t| t > chrono::Utc::now())
-        .unwrap_or(false);
+    // timer can call destroy_vpn (which needs them for the provider teardown).
+    // Deployments are listed even while the passphrase vault is locked; a
+    // state file that can't be read at all keeps the credentials too.
+    let has_pending_timer = match persistence::store::list_deployments() {
+        Ok(deployments) => deployments
+            .iter()
+            .filter_map(|s| s.auto_destroy_at)
+            .any(|t| t > chrono::Utc::now()),
+        Err(_) => true,
+    };
     if !has_pending_timer {
         let _ = persistence::store::delete_credentials();
         let _ = persistence::store::delete_do_credentials();
+        let _ = persistence::store::delete_hetzner_credentials();
+        let _ = persistence::store::delete_vultr_credentials();
+        let _ = persistence::store::delete_linode_credentials();
     }
 
     // Write a session separator
@@ -116,38 +182,66 @@ pub fn run() {
         registry.init();
     }
 
+    // ── Kill switch / DNS recovery ────────────────────────────────────────
+    // No tunnel is up yet, so kill switch rules still loaded in the kernel or a
+    // rewritten /etc/resolv.conf were left behind by a crashed or killed session.
+    wireguard::killswitch::cleanup_stale();
+    wireguard::dns::restore_stale();
+
     // ── Startup state recovery ────────────────────────────────────────────
     // If the app was closed mid-deploy or mid-destroy, the persisted status
     // will be stuck at "Deploying" or "Destroying". Correct those on startup
     // so the UI can offer a sensible recovery path instead of spinning forever.
-    if let Ok(mut st) = persistence::store::load_state() {
+    for mut st in persistence::store::list_deployments().unwrap_or_default() {
         match st.status {
             state::DeploymentStatus::Deploying => {
-                tracing::warn!("Startup: found stuck 'Deploying' state — resetting to Failed");
+                tracing::warn!(
+                    "Startup: found stuck 'Deploying' state for {} — resetting to Failed",
+                    st.id
+                );
                 st.status = state::DeploymentStatus::Failed;
-                st.error_message = Some(
+                // Only AWS deployments can be resumed.
+                let resumable = st.completed_step > 0
+                    && matches!(st.deployment_mode.as_deref(), None | Some("aws"));
+                st.error_message = Some(if resumable {
+                    "The deployment was interrupted (app was closed mid-deploy). \
+                     Resume it to continue where it stopped, or destroy it."
+                        .into()
+                } else {
                     "The deployment was interrupted (app was closed mid-deploy). \
                      You can retry from the Deploy page."
-                        .into(),
-                );
-                let _ = persistence::store::save_state(&st);
+                        .into()
+                });
+                let _ = persistence::store::save_deployment(&st);
             }
             state::DeploymentStatus::Destroying => {
+                // A rollback of a failed deploy goes back to Failed, a
+                // destroy to Deployed (old state files only know destroys).
+                let previous = st
+                    .status_before_destroy
+                    .take()
+                    .unwrap_or(state::DeploymentStatus::Deployed);
                 tracing::warn!(
-                    "Startup: found stuck 'Destroying' state — \
-                     resetting to Deployed so the user can retry"
+                    "Startup: found stuck 'Destroying' state for {} — \
+                     resetting to {:?} so the user can retry",
+                    st.id,
+                    previous
                 );
-                st.status = state::DeploymentStatus::Deployed;
                 // Clear the auto-destroy timer — the resources were already being
                 // deleted, so we don't want to re-spawn a timer that fires and
                 // tries to destroy partially-removed infrastructure.
                 st.auto_destroy_at = None;
-                st.error_message = Some(
+                st.error_message = Some(if previous == state::DeploymentStatus::Failed {
+                    "Rolling back the failed deployment was interrupted. \
+                     Some of its resources may still exist — destroy it to remove them."
+                        .into()
+                } else {
                     "A previous destroy attempt was interrupted. \
                      Your server may still be running — please destroy it again."
-                        .into(),
-                );
-                let _ = persistence::store::save_state(&st);
+                        .into()
+                });
+                st.status = previous;
+                let _ = persistence::store::save_deployment(&st);
             }
             state::DeploymentStatus::Deployed => {
                 // Re-spawn auto-destroy timer if it was set and is still in the future.
@@ -165,7 +259,7 @@ pub fn run() {
                                 .into(),
                         );
                         st.auto_destroy_at = None;
-                        let _ = persistence::store::save_state(&st);
+                        let _ = persistence::store::save_deployment(&st);
                     }
                 }
             }
@@ -176,12 +270,16 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .setup(|app| {
-            // Re-spawn auto-destroy timer if it survived an app restart.
-            if let Ok(st) = persistence::store::load_state() {
+            // Re-spawn auto-destroy timers that survived an app restart.
+            for st in persistence::store::list_deployments().unwrap_or_default() {
                 if st.status == state::DeploymentStatus::Deployed {
                     if let Some(at) = st.auto_destroy_at {
                         if at > chrono::Utc::now() {
-                            commands::timer::spawn_auto_destroy_timer(app.handle().clone(), at);
+                            commands::timer::spawn_auto_destroy_timer(
+                                app.handle().clone(),
+                                st.id,
+                                at,
+                            );
                         }
                     }
                 }
@@ -197,23 +295,59 @@ pub fn run() {
             commands::credentials_do::save_do_credentials,
             commands::credentials_do::load_do_credentials,
             commands::credentials_do::delete_do_credentials,
+            commands::credentials_hetzner::validate_hetzner_credentials,
+            commands::credentials_hetzner::save_hetzner_credentials,
+            commands::credentials_hetzner::load_hetzner_credentials,
+            commands::credentials_hetzner::delete_hetzner_credentials,
+            commands::credentials_vultr::validate_vultr_credentials,
+            commands::credentials_vultr::save_vultr_credentials,
+            commands::credentials_vultr::load_vultr_credentials,
+            commands::credentials_vultr::delete_vultr_credentials,
+            commands::credentials_linode::validate_linode_credentials,
+            commands::credentials_linode::save_linode_credentials,
+            commands::credentials_linode::load_linode_credentials,
+            commands::credentials_linode::delete_linode_credentials,
             commands::deploy::deploy_vpn,
+            commands::deploy::list_deployments,
             commands::deploy::get_deployment_state,
+            commands::deploy::rename_deployment,
             commands::deploy::reset_deployment_state,
-            commands::deploy_do::deploy_do,
+            commands::deploy::resume_deployment,
+            commands::deploy::deploy_do,
+            commands::deploy::deploy_hetzner,
+            commands::deploy::deploy_vultr,
+            commands::deploy::deploy_linode,
             commands::destroy::destroy_vpn,
             commands::byo::deploy_byo_vps,
             commands::connect::connect_vpn,
             commands::connect::disconnect_vpn,
             commands::connect::get_vpn_status,
+            commands::connect::get_tunnel_stats,
+            commands::connect::get_connected_deployment,
             commands::connect::get_client_config,
+            commands::peers::list_peers,
+            commands::peers::add_peer,
+            commands::peers::revoke_peer,
+            commands::ssh_access::lock_ssh,
+            commands::ssh_access::unlock_ssh,
             commands::settings::get_regions,
+            commands::catalog::list_regions,
+            commands::catalog::list_sizes,
+            commands::pricing::estimate_cost,
+            commands::pricing::get_deployment_cost,
+            commands::orphans::scan_aws_orphans,
+            commands::orphans::cleanup_aws_orphans,
+            commands::orphans::scan_do_orphans,
+            commands::orphans::cleanup_do_orphans,
             commands::settings::get_settings,
             commands::settings::update_settings,
             commands::logs::get_logs,
             commands::logs::export_logs,
             commands::logs::clear_logs,
             commands::settings::export_client_config,
+            commands::vault::get_vault_status,
+            commands::vault::unlock_vault,
+            commands::vault::lock_vault,
         ])
         .run(tauri::generate_context!())
         .expect("error while running createmyvpn");
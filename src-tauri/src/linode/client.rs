@@ -0,0 +1,79 @@
+use crate::api_client::ApiClient;
+use crate::error::AppError;
+use serde::Deserialize;
+use std::ops::Deref;
+
+const BASE_URL: &str = "https://api.linode.com/v4";
+
+/// Tag put on every resource this app creates, so it can be found again
+/// without local state.
+pub const RESOURCE_TAG: &str = "createmyvpn";
+
+pub struct LinodeClient(ApiClient);
+
+/// Error body returned by the Linode API.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    errors: Vec<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    reason: String,
+}
+
+impl LinodeClient {
+    pub fn new(token: &str) -> Self {
+        LinodeClient::with_base_url(token, BASE_URL)
+    }
+
+    pub fn with_base_url(token: &str, base_url: &str) -> Self {
+        LinodeClient(ApiClient::new("Linode", base_url, token, error_message))
+    }
+
+    /// Validate the API token and return the profile's username.
+    pub async fn validate(&self) -> Result<String, AppError> {
+        #[derive(Deserialize)]
+        struct Profile {
+            username: String,
+        }
+
+        let profile: Profile = self.get("/profile").await?;
+        Ok(profile.username)
+    }
+}
+
+impl Deref for LinodeClient {
+    type Target = ApiClient;
+
+    fn deref(&self) -> &ApiClient {
+        &self.0
+    }
+}
+
+/// The reasons from an error body joined by "; ".
+fn error_message(body: &str) -> Option<String> {
+    let resp: ErrorResponse = serde_json::from_str(body).ok()?;
+    if resp.errors.is_empty() {
+        return None;
+    }
+    let reasons: Vec<String> = resp.errors.into_iter().map(|e| e.reason).collect();
+    Some(reasons.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_joins_reasons() {
+        let body = r#"{"errors": [{"reason": "Invalid Token"},
+                                  {"field": "region", "reason": "region is not valid"}]}"#;
+        assert_eq!(
+            error_message(body).as_deref(),
+            Some("Invalid Token; region is not valid")
+        );
+        assert_eq!(error_message(r#"{"errors": []}"#), None);
+        assert_eq!(error_message("Bad Gateway"), None);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::AppError;
+use crate::linode::client::{LinodeClient, RESOURCE_TAG};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+struct Addresses {
+    ipv4: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ipv6: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Rule {
+    action: String,
+    protocol: String,
+    ports: String,
+    addresses: Addresses,
+    label: String,
+}
+
+#[derive(Serialize)]
+struct Rules {
+    inbound_policy: String,
+    outbound_policy: String,
+    inbound: Vec<Rule>,
+    outbound: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct CreateFirewallRequest {
+    label: String,
+    rules: Rules,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct FirewallInfo {
+    id: u64,
+}
+
+fn accept_from_anywhere(protocol: &str, port: u16, label: &str) -> Rule {
+    Rule {
+        action: "ACCEPT".to_string(),
+        protocol: protocol.to_string(),
+        ports: port.to_string(),
+        addresses: Addresses {
+            ipv4: vec!["0.0.0.0/0".to_string()],
+            ipv6: vec!["::/0".to_string()],
+        },
+        label: label.to_string(),
+    }
+}
+
+/// Drop inbound traffic except SSH (TCP 22) from `ssh_source`, unless it is
+/// `None`, and WireGuard (UDP port) from anywhere; accept all outbound.
+fn rules(wireguard_port: u16, ssh_source: Option<&str>) -> Rules {
+    let mut inbound = Vec::new();
+    if let Some(source) = ssh_source {
+        inbound.push(Rule {
+            action: "ACCEPT".to_string(),
+            protocol: "TCP".to_string(),
+            ports: "22".to_string(),
+            addresses: Addresses {
+                ipv4: vec![source.to_string()],
+                ipv6: Vec::new(),
+            },
+            label: "ssh".to_string(),
+        });
+    }
+    inbound.push(accept_from_anywhere("UDP", wireguard_port, "wireguard"));
+    Rules {
+        inbound_policy: "DROP".to_string(),
+        outbound_policy: "ACCEPT".to_string(),
+        inbound,
+        outbound: Vec::new(),
+    }
+}
+
+/// Create a cloud firewall that drops inbound traffic except SSH (TCP 22)
+/// from `ssh_source` and WireGuard (UDP port), and accepts all outbound.
+/// Assign it when creating the Linode.
+/// POST /v4/networking/firewalls — returns the firewall ID.
+pub async fn create_firewall(
+    client: &LinodeClient,
+    label: &str,
+    wireguard_port: u16,
+    ssh_source: &str,
+) -> Result<u64, AppError> {
+    let body = CreateFirewallRequest {
+        label: label.to_string(),
+        rules: rules(wireguard_port, Some(ssh_source)),
+        tags: vec![RESOURCE_TAG.to_string()],
+    };
+
+    let resp: FirewallInfo = client.post("/networking/firewalls", &body).await?;
+    Ok(resp.id)
+}
+
+/// Replace the firewall's rules so SSH is let in from `ssh_source` only, or
+/// not at all when it is `None`.
+/// PUT /v4/networking/firewalls/{id}/rules
+pub async fn set_ssh_access(
+    client: &LinodeClient,
+    firewall_id: u64,
+    wireguard_port: u16,
+    ssh_source: Option<&str>,
+) -> Result<(), AppError> {
+    let _: serde_json::Value = client
+        .put(
+            &format!("/networking/firewalls/{}/rules", firewall_id),
+            &rules(wireguard_port, ssh_source),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Delete a cloud firewall, detaching it from any Linode still using it.
+/// DELETE /v4/networking/firewalls/{id}
+pub async fn delete_firewall(client: &LinodeClient, firewall_id: u64) -> Result<(), AppError> {
+    client
+        .delete(&format!("/networking/firewalls/{}", firewall_id))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn drops_inbound_except_ssh_and_wireguard() {
+        let server = MockServer::start(|_| {
+            (
+                200,
+                r#"{"id": 123, "label": "createmyvpn-ab12", "status": "enabled"}"#.into(),
+            )
+        })
+        .await;
+        let client = LinodeClient::with_base_url("token", &server.url);
+        let id = create_firewall(&client, "createmyvpn-ab12", 51821, "203.0.113.7/32")
+            .await
+            .unwrap();
+        assert_eq!(id, 123);
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["rules"]["inbound_policy"], "DROP");
+        assert_eq!(body["rules"]["outbound_policy"], "ACCEPT");
+        let inbound = body["rules"]["inbound"].as_array().unwrap();
+        assert_eq!(inbound.len(), 2);
+        assert_eq!(inbound[0]["ports"], "22");
+        assert_eq!(
+            inbound[0]["addresses"],
+            serde_json::json!({"ipv4": ["203.0.113.7/32"]})
+        );
+        assert_eq!(inbound[1]["protocol"], "UDP");
+        assert_eq!(inbound[1]["ports"], "51821");
+        assert_eq!(body["tags"], serde_json::json!(["createmyvpn"]));
+    }
+
+    #[tokio::test]
+    async fn locking_down_drops_the_ssh_rule() {
+        let server =
+            MockServer::start(|_| (200, r#"{"inbound_policy": "DROP", "inbound": []}"#.into()))
+                .await;
+        let client = LinodeClient::with_base_url("token", &server.url);
+        set_ssh_access(&client, 123, 51820, None).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.path, "/networking/firewalls/123/rules");
+        let inbound = request.json()["inbound"].as_array().unwrap().clone();
+        assert_eq!(inbound.len(), 1);
+        assert_eq!(inbound[0]["label"], "wireguard");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::AppError;
+use crate::linode::client::{LinodeClient, RESOURCE_TAG};
+use crate::server_image::OsRelease;
+use rand::distributions::Alphanumeric;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// How long to wait between status polls (shortened in tests).
+const POLL_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+const MAX_POLLS: u32 = 60; // 60 × 5s = 5 minutes
+
+#[derive(Serialize)]
+struct CreateInstanceRequest {
+    region: String,
+    #[serde(rename = "type")]
+    linode_type: String,
+    image: String,
+    label: String,
+    root_pass: String,
+    authorized_keys: Vec<String>,
+    firewall_id: u64,
+    tags: Vec<String>,
+    booted: bool,
+}
+
+#[derive(Deserialize)]
+struct InstanceInfo {
+    id: u64,
+    status: Option<String>,
+    #[serde(default)]
+    ipv4: Vec<String>,
+}
+
+/// The image id of `os`. Linodes are x86 only.
+pub fn image_id(os: OsRelease) -> &'static str {
+    match os {
+        OsRelease::Ubuntu2204 => "linode/ubuntu22.04",
+        OsRelease::Ubuntu2404 => "linode/ubuntu24.04",
+        OsRelease::Debian12 => "linode/debian12",
+    }
+}
+
+/// Create a Linode booting `image` that lets `public_key` log in as root,
+/// behind the firewall, and return its ID. Linode insists on a root password; a random
+/// one is set and thrown away, since only the key is ever used.
+/// POST /v4/linode/instances
+pub async fn create_instance(
+    client: &LinodeClient,
+    label: &str,
+    region: &str,
+    linode_type: &str,
+    image: &str,
+    public_key: &str,
+    firewall_id: u64,
+) -> Result<u64, AppError> {
+    let root_pass: String = OsRng
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    let body = CreateInstanceRequest {
+        region: region.to_string(),
+        linode_type: linode_type.to_string(),
+        image: image.to_string(),
+        label: label.to_string(),
+        root_pass,
+        authorized_keys: vec![public_key.to_string()],
+        firewall_id,
+        tags: vec![RESOURCE_TAG.to_string()],
+        booted: true,
+    };
+
+    let resp: InstanceInfo = client.post("/linode/instances", &body).await?;
+    Ok(resp.id)
+}
+
+/// Poll the Linode until it is `running`, then return its public IPv4.
+/// Polls every 5 seconds for up to 5 minutes.
+pub async fn wait_for_running(client: &LinodeClient, linode_id: u64) -> Result<String, AppError> {
+    for attempt in 0..MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        let resp: InstanceInfo = client
+            .get(&format!("/linode/instances/{}", linode_id))
+            .await?;
+
+        tracing::debug!(
+            "[Linode wait_for_running] attempt {}/{}: status={:?}",
+            attempt + 1,
+            MAX_POLLS,
+            resp.status
+        );
+
+        if resp.status.as_deref() == Some("running") {
+            if let Some(ip) = resp.ipv4.into_iter().next() {
+                return Ok(ip);
+            }
+        }
+    }
+
+    Err(AppError::General(
+        "Linode did not start running within 5 minutes".into(),
+    ))
+}
+
+/// Delete a Linode.
+/// DELETE /v4/linode/instances/{id}
+pub async fn delete_instance(client: &LinodeClient, linode_id: u64) -> Result<(), AppError> {
+    client
+        .delete(&format!("/linode/instances/{}", linode_id))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn instance_json(status: &str) -> String {
+        format!(
+            r#"{{"id": 7001, "label": "createmyvpn-server-ab12", "status": "{}",
+                "ipv4": ["192.0.2.10"], "ipv6": "2600:3c00::1/128"}}"#,
+            status
+        )
+    }
+
+    #[tokio::test]
+    async fn creates_tagged_linode_with_key_and_firewall() {
+        let server = MockServer::start(|_| (200, instance_json("provisioning"))).await;
+        let client = LinodeClient::with_base_url("token", &server.url);
+        let id = create_instance(
+            &client,
+            "createmyvpn-server-ab12",
+            "us-east",
+            "g6-nanode-1",
+            image_id(OsRelease::Ubuntu2204),
+            "ssh-ed25519 AAAA",
+            123,
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, 7001);
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["region"], "us-east");
+        assert_eq!(body["type"], "g6-nanode-1");
+        assert_eq!(body["image"], "linode/ubuntu22.04");
+        assert_eq!(
+            body["authorized_keys"],
+            serde_json::json!(["ssh-ed25519 AAAA"])
+        );
+        assert_eq!(body["firewall_id"], 123);
+        assert_eq!(body["tags"], serde_json::json!(["createmyvpn"]));
+        assert_eq!(body["root_pass"].as_str().unwrap().len(), 40);
+    }
+
+    #[tokio::test]
+    async fn waits_until_running() {
+        let polls = AtomicU32::new(0);
+        let server = MockServer::start(move |_| {
+            let status = match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => "provisioning",
+                1 => "booting",
+                _ => "running",
+            };
+            (200, instance_json(status))
+        })
+        .await;
+        let client = LinodeClient::with_base_url("token", &server.url);
+        let ip = wait_for_running(&client, 7001).await.unwrap();
+        assert_eq!(ip, "192.0.2.10");
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -0,0 +1,312 @@
+//! `CloudProvider` for Linode (Akamai): a Linode behind a cloud firewall.
+//! Linode takes the SSH public key at creation, so no key is kept in the
+//! account.
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::linode::{client::LinodeClient, firewall, instance};
+use crate::progress::ProgressSink;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+use crate::server_image::ServerImage;
+use crate::ssh::keygen;
+use crate::state::{DeploymentState, LinodeCredentials};
+
+/// Linode firewall labels are limited to 32 characters.
+const MAX_FIREWALL_LABEL: usize = 32;
+
+pub struct LinodeProvider {
+    client: LinodeClient,
+}
+
+impl LinodeProvider {
+    pub fn new(creds: &LinodeCredentials) -> Self {
+        LinodeProvider {
+            client: LinodeClient::new(&creds.api_token),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_client(client: LinodeClient) -> Self {
+        LinodeProvider { client }
+    }
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    data: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct RegionInfo {
+    id: String,
+    label: String,
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct TypeInfo {
+    id: String,
+    class: String,
+    vcpus: u32,
+    memory: u64,
+    price: Price,
+    #[serde(default)]
+    region_prices: Vec<RegionPrice>,
+}
+
+#[derive(Deserialize)]
+struct Price {
+    monthly: Option<f64>,
+}
+
+/// Some regions are priced differently from the base price.
+#[derive(Deserialize)]
+struct RegionPrice {
+    id: String,
+    monthly: Option<f64>,
+}
+
+#[async_trait]
+impl CloudProvider for LinodeProvider {
+    fn mode(&self) -> &'static str {
+        "linode"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Linode"
+    }
+
+    fn check_image(&self, image: &ServerImage, _size: &str) -> Result<(), AppError> {
+        image.require_amd64(self.display_name())
+    }
+
+    async fn validate_credentials(&self) -> Result<String, AppError> {
+        self.client.validate().await
+    }
+
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError> {
+        let page: Page<RegionInfo> = self.client.get("/regions?page_size=500").await?;
+        Ok(page
+            .data
+            .into_iter()
+            .filter(|r| r.status == "ok")
+            .map(|r| Region {
+                code: r.id,
+                name: r.label,
+            })
+            .collect())
+    }
+
+    /// Shared and Nanode plans, priced in USD for `region`.
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError> {
+        let page: Page<TypeInfo> = self.client.get("/linode/types?page_size=500").await?;
+        Ok(page
+            .data
+            .into_iter()
+            .filter(|t| t.class == "nanode" || t.class == "standard")
+            .map(|t| {
+                let monthly_price = t
+                    .region_prices
+                    .iter()
+                    .find(|p| p.id == region)
+                    .map_or(t.price.monthly, |p| p.monthly);
+                ServerSize {
+                    description: format!("{} vCPU, {}MB RAM", t.vcpus, t.memory),
+                    code: t.id,
+                    monthly_price,
+                }
+            })
+            .collect())
+    }
+
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError> {
+        if state.linode_firewall_id.is_some() {
+            return Ok(());
+        }
+        // Labels carry the deployment id so parallel deployments are told apart.
+        let mut label = format!("createmyvpn-{}", state.id);
+        label.truncate(MAX_FIREWALL_LABEL);
+        let firewall_id = firewall::create_firewall(
+            &self.client,
+            &label,
+            wireguard_port,
+            provider::ssh_source(state)?,
+        )
+        .await?;
+        tracing::info!("[Linode] Firewall created: {}", firewall_id);
+        state.linode_firewall_id = Some(firewall_id);
+        Ok(())
+    }
+
+    /// Only generated here; the public key is handed to `create_server`.
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError> {
+        let firewall_id = state
+            .linode_firewall_id
+            .ok_or_else(|| AppError::State("No firewall recorded for this deployment".into()))?;
+        firewall::set_ssh_access(&self.client, firewall_id, wireguard_port, source).await
+    }
+
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError> {
+        if state.ssh_private_key.is_some() {
+            return Ok(());
+        }
+        // The key is only given to the Linode when it is created, so a new
+        // one would never let us in to an existing Linode.
+        if let Some(linode_id) = state.linode_instance_id {
+            return Err(AppError::State(format!(
+                "Linode {} was created with an SSH key that is no longer saved — destroy \
+                 the deployment and deploy again",
+                linode_id
+            )));
+        }
+        let (private_key, _) = keygen::generate_ed25519()?;
+        state.ssh_private_key = Some(private_key);
+        Ok(())
+    }
+
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        _user_data: Option<&str>,
+    ) -> Result<(), AppError> {
+        if state.linode_instance_id.is_some() {
+            return Ok(());
+        }
+        let (Some(private_key), Some(firewall_id)) =
+            (&state.ssh_private_key, state.linode_firewall_id)
+        else {
+            return Err(AppError::State(
+                "The SSH key and firewall must exist before the server".into(),
+            ));
+        };
+        let public_key = keygen::public_key_of(private_key)?;
+        let region = state.region.clone().unwrap_or_default();
+        let label = format!("createmyvpn-server-{}", state.id);
+        let linode_id = instance::create_instance(
+            &self.client,
+            &label,
+            &region,
+            size,
+            instance::image_id(state.server_image().os),
+            &public_key,
+            firewall_id,
+        )
+        .await?;
+        tracing::info!("[Linode] Linode created: {}", linode_id);
+        state.linode_instance_id = Some(linode_id);
+        Ok(())
+    }
+
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError> {
+        let linode_id = state
+            .linode_instance_id
+            .ok_or_else(|| AppError::State("No Linode recorded for this deployment".into()))?;
+        tracing::info!(
+            "[Linode] Waiting for Linode {} to start running...",
+            linode_id
+        );
+        instance::wait_for_running(&self.client, linode_id).await
+    }
+
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError> {
+        let mut failures = Vec::new();
+
+        progress.emit(1, 3, "Deleting server...", "running");
+        if let Some(linode_id) = state.linode_instance_id {
+            let result = instance::delete_instance(&self.client, linode_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Linode: {}", e);
+                failures.push(format!("Linode {}: {}", linode_id, e));
+            }
+        }
+
+        progress.emit(2, 3, "Deleting firewall...", "running");
+        if let Some(firewall_id) = state.linode_firewall_id {
+            let result = firewall::delete_firewall(&self.client, firewall_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Linode firewall: {}", e);
+                failures.push(format!("firewall {}: {}", firewall_id, e));
+            }
+        }
+
+        progress.emit(3, 3, "Cleaning up...", "running");
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::General(format!(
+                "Could not delete {}",
+                failures.join("; ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn sizes_use_region_prices() {
+        let server = MockServer::start(|_| {
+            (
+                200,
+                r#"{"data": [
+                    {"id": "g6-nanode-1", "class": "nanode", "vcpus": 1, "memory": 1024,
+                     "price": {"hourly": 0.0075, "monthly": 5.0},
+                     "region_prices": [{"id": "id-cgk", "hourly": 0.009, "monthly": 6.0}]},
+                    {"id": "g6-dedicated-2", "class": "dedicated", "vcpus": 2, "memory": 4096,
+                     "price": {"hourly": 0.054, "monthly": 36.0}, "region_prices": []}
+                ], "page": 1, "pages": 1, "results": 2}"#
+                    .into(),
+            )
+        })
+        .await;
+        let provider =
+            LinodeProvider::with_client(LinodeClient::with_base_url("token", &server.url));
+
+        let sizes = provider.list_sizes("id-cgk").await.unwrap();
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].code, "g6-nanode-1");
+        assert_eq!(sizes[0].monthly_price, Some(6.0));
+
+        let sizes = provider.list_sizes("us-east").await.unwrap();
+        assert_eq!(sizes[0].monthly_price, Some(5.0));
+    }
+
+    #[tokio::test]
+    async fn firewall_label_fits_linode_limit() {
+        let server = MockServer::start(|_| (200, r#"{"id": 123}"#.into())).await;
+        let provider =
+            LinodeProvider::with_client(LinodeClient::with_base_url("token", &server.url));
+        let mut state = DeploymentState {
+            id: "a".repeat(32),
+            ssh_source: Some("203.0.113.7/32".into()),
+            ..Default::default()
+        };
+        provider.create_firewall(&mut state, 51820).await.unwrap();
+        assert_eq!(state.linode_firewall_id, Some(123));
+
+        let label = server.requests()[0].json()["label"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(label.len(), MAX_FIREWALL_LABEL);
+        assert!(label.starts_with("createmyvpn-"));
+    }
+}
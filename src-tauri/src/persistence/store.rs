@@ -1,7 +1,17 @@
 use crate::error::AppError;
-use crate::state::{AppSettings, AwsCredentials, DeploymentState, DoCredentials};
+use crate::persistence::vault;
+use crate::state::{
+    deployment_name, AppSettings, AwsCredentials, DeploymentState, DeploymentStatus, DoCredentials,
+    HetznerCredentials, LinodeCredentials, VultrCredentials,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 fn config_dir() -> Result<PathBuf, AppError> {
     let dir = dirs::home_dir()
@@ -27,106 +37,593 @@ fn do_credentials_path() -> Result<PathBuf, AppError> {
     Ok(config_dir()?.join("do_credentials.json"))
 }
 
-// --- Deployment State ---
+/// Region and size lists fetched from the providers (see `catalog`).
+pub fn catalog_cache_path() -> Result<PathBuf, AppError> {
+    Ok(config_dir()?.join("catalog.json"))
+}
 
-pub fn load_state() -> Result<DeploymentState, AppError> {
-    let path = state_path()?;
-    if !path.exists() {
-        return Ok(DeploymentState::default());
+// --- Secrets ---
+//
+// Private keys, client configs and cloud credentials never touch the JSON
+// files; they live in the vault under these names.
+
+/// Per-deployment entries are "state/<id>"; schema 2 used plain "state".
+const STATE_SECRETS: &str = "state";
+const AWS_CREDENTIALS: &str = "aws_credentials";
+const DO_CREDENTIALS: &str = "do_credentials";
+const HETZNER_CREDENTIALS: &str = "hetzner_credentials";
+const VULTR_CREDENTIALS: &str = "vultr_credentials";
+const LINODE_CREDENTIALS: &str = "linode_credentials";
+
+/// The secret fields of a `DeploymentState`, stored as one vault entry.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+struct StateSecrets {
+    ssh_private_key: Option<String>,
+    client_private_key: Option<String>,
+    client_config: Option<String>,
+    /// Extra peers' client configs, keyed by public key.
+    #[serde(default)]
+    peer_configs: BTreeMap<String, String>,
+}
+
+impl StateSecrets {
+    /// Move the secrets out of `state`, leaving only what is safe to write as JSON.
+    fn take(state: &mut DeploymentState) -> Self {
+        StateSecrets {
+            ssh_private_key: state.ssh_private_key.take(),
+            client_private_key: state.client_private_key.take(),
+            client_config: state.client_config.take(),
+            peer_configs: state
+                .peers
+                .iter_mut()
+                .filter_map(|p| Some((p.public_key.clone(), p.client_config.take()?)))
+                .collect(),
+        }
     }
-    let data = fs::read_to_string(&path)?;
-    match serde_json::from_str(&data) {
-        Ok(state) => Ok(state),
-        Err(_) => {
-            // Corrupt or schema-incompatible state file — delete it and start fresh.
-            let _ = fs::remove_file(&path);
-            Ok(DeploymentState::default())
+
+    fn restore(mut self, state: &mut DeploymentState) {
+        state.ssh_private_key = self.ssh_private_key;
+        state.client_private_key = self.client_private_key;
+        state.client_config = self.client_config;
+        for peer in &mut state.peers {
+            peer.client_config = self.peer_configs.remove(&peer.public_key);
         }
     }
+
+    fn is_empty(&self) -> bool {
+        *self == StateSecrets::default()
+    }
 }
 
-pub fn save_state(state: &DeploymentState) -> Result<(), AppError> {
-    let path = state_path()?;
-    let data = serde_json::to_string_pretty(state)?;
-    fs::write(&path, data)?;
+// --- Atomic writes ---
+
+/// Replace `path` without ever leaving a half-written file: write a temp file
+/// next to it, fsync, then rename over the original. Files are created 0600
+/// on Unix since everything under `~/.createmyvpn` is private.
+pub(crate) fn write_atomic(path: &Path, data: &str) -> Result<(), AppError> {
+    let tmp = path.with_extension("tmp");
+    let _ = fs::remove_file(&tmp);
+    {
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let mut file = opts.open(&tmp)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // Make the rename itself durable.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
     Ok(())
 }
 
-pub fn clear_state() -> Result<(), AppError> {
-    let path = state_path()?;
-    if path.exists() {
-        fs::remove_file(&path)?;
+/// Copy `path` into `dir` as `<name>.1`, shifting older copies up and
+/// dropping anything past `keep`.
+fn rotate_backups(path: &Path, dir: &Path, keep: usize) -> Result<(), AppError> {
+    if !path.exists() || keep == 0 {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let backup = |i: usize| dir.join(format!("{}.{}", name, i));
+
+    let _ = fs::remove_file(backup(keep));
+    for i in (1..keep).rev() {
+        if backup(i).exists() {
+            fs::rename(backup(i), backup(i + 1))?;
+        }
     }
+    fs::copy(path, backup(1))?;
     Ok(())
 }
 
-// --- Credentials ---
+// --- Deployments ---
+//
+// state.json holds every deployment, keyed by id, and carries a
+// `schema_version`. Files from older versions are migrated step by step on
+// load and rewritten in the current format. A file that can't be parsed is
+// never deleted: it may be the only record of cloud resources that are still
+// running and billing.
 
-pub fn load_credentials() -> Result<Option<AwsCredentials>, AppError> {
-    let path = credentials_path()?;
+const STATE_SCHEMA_VERSION: u64 = 3;
+/// Previous versions of state.json kept in `~/.createmyvpn/backups`.
+const STATE_BACKUPS: usize = 5;
+
+/// Serialises read-modify-write cycles, so two deploys running side by side
+/// don't overwrite each other's progress.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+fn backups_dir() -> Result<PathBuf, AppError> {
+    Ok(config_dir()?.join("backups"))
+}
+
+fn secrets_name(id: &str) -> String {
+    format!("{}/{}", STATE_SECRETS, id)
+}
+
+/// The id a schema 2 deployment gets when it moves into the map. Fixed,
+/// since the file is migrated on every load until it can be rewritten.
+const MIGRATED_DEPLOYMENT_ID: &str = "default";
+
+/// A short random id, e.g. "3f9c2a1b".
+pub fn new_deployment_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Bring a parsed state file up to `STATE_SCHEMA_VERSION`. Files written
+/// before versioning have no `schema_version` and count as version 1.
+/// Returns the version the file was at.
+fn migrate_state(mut value: Value) -> Result<(Value, u64), AppError> {
+    let version = value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    if version > STATE_SCHEMA_VERSION {
+        return Err(AppError::State(format!(
+            "state.json was written by a newer version of CreateMyVpn (schema {}, this version \
+             understands up to {}). Update the app to manage this deployment.",
+            version, STATE_SCHEMA_VERSION
+        )));
+    }
+
+    for from in version..STATE_SCHEMA_VERSION {
+        match from {
+            1 => migrate_v1_to_v2(&mut value),
+            2 => migrate_v2_to_v3(&mut value),
+            _ => unreachable!("no migration from state schema {}", from),
+        }
+        tracing::info!("Migrated state.json from schema {} to {}", from, from + 1);
+    }
+    value["schema_version"] = STATE_SCHEMA_VERSION.into();
+    Ok((value, version))
+}
+
+/// v1 left `deployment_mode` unset for AWS deployments made before the other
+/// providers existed. Make it explicit. (v1 files may also hold plaintext
+/// secrets; `read_deployments` moves those into the vault.)
+fn migrate_v1_to_v2(value: &mut Value) {
+    let deployed = value
+        .get("status")
+        .and_then(Value::as_str)
+        .map(|s| s != "not_deployed")
+        .unwrap_or(false);
+    let has_mode = value
+        .get("deployment_mode")
+        .map(|m| !m.is_null())
+        .unwrap_or(false);
+    if deployed && !has_mode {
+        value["deployment_mode"] = "aws".into();
+    }
+}
+
+/// v2 held a single deployment at the top level. Wrap it in the
+/// `deployments` map as `MIGRATED_DEPLOYMENT_ID`, unless there was nothing
+/// deployed.
+fn migrate_v2_to_v3(value: &mut Value) {
+    let mut deployment = std::mem::take(value);
+    *value = serde_json::json!({ "deployments": {} });
+
+    if let Some(obj) = deployment.as_object_mut() {
+        obj.remove("schema_version");
+    }
+    let status = deployment.get("status").and_then(Value::as_str);
+    if status.unwrap_or("not_deployed") == "not_deployed" {
+        return;
+    }
+    let field = |key: &str| deployment.get(key).and_then(Value::as_str);
+    let name = deployment_name(
+        field("deployment_mode"),
+        field("region"),
+        field("elastic_ip"),
+    );
+    deployment["id"] = MIGRATED_DEPLOYMENT_ID.into();
+    deployment["name"] = name.into();
+    value["deployments"][MIGRATED_DEPLOYMENT_ID] = deployment;
+}
+
+/// Move an unreadable state.json aside and leave a `Failed` deployment in
+/// its place that says where it went, so the UI keeps pointing at the problem.
+fn quarantine_state(path: &Path, reason: &str) -> AppError {
+    let dest = path.with_file_name(format!(
+        "state.json.corrupt-{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    if let Err(e) = fs::rename(path, &dest) {
+        return AppError::State(format!("state.json is unreadable ({}): {}", reason, e));
+    }
+    let backups = backups_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_default();
+    let message = format!(
+        "The saved deployment state could not be read ({}) and was moved to {}. Cloud \
+         resources it described may still be running — check your provider's console, or \
+         restore a previous copy from {}.",
+        reason,
+        dest.display(),
+        backups
+    );
+    tracing::error!("{}", message);
+
+    let placeholder = DeploymentState {
+        id: new_deployment_id(),
+        name: "Unreadable state".into(),
+        status: DeploymentStatus::Failed,
+        error_message: Some(message.clone()),
+        ..Default::default()
+    };
+    // Written directly: saving through the vault would drop the secrets,
+    // which belong to the quarantined file and may be needed to recover it.
+    let deployments = BTreeMap::from([(placeholder.id.clone(), placeholder)]);
+    if let Err(e) = write_state_file(&deployments) {
+        tracing::warn!("Could not write placeholder state: {}", e);
+    }
+    AppError::State(message)
+}
+
+/// Every deployment with its secrets filled in. While the passphrase vault
+/// is locked the secrets are left out, so deployments can still be listed,
+/// recovered and timed at startup. Caller holds `STATE_LOCK`.
+fn read_deployments() -> Result<BTreeMap<String, DeploymentState>, AppError> {
+    read_deployments_at(&state_path()?, vault::status().locked)
+}
+
+fn read_deployments_at(
+    path: &Path,
+    vault_locked: bool,
+) -> Result<BTreeMap<String, DeploymentState>, AppError> {
     if !path.exists() {
-        return Ok(None);
+        return Ok(BTreeMap::new());
     }
-    let data = fs::read_to_string(&path)?;
-    let creds: AwsCredentials = serde_json::from_str(&data)?;
-    Ok(Some(creds))
+    let data = fs::read_to_string(path)?;
+    let value: Value = match serde_json::from_str(&data) {
+        Ok(value @ Value::Object(_)) => value,
+        Ok(_) => return Err(quarantine_state(path, "not a JSON object")),
+        Err(e) => return Err(quarantine_state(path, &e.to_string())),
+    };
+    let (mut value, from_version) = migrate_state(value)?;
+    let mut deployments: BTreeMap<String, DeploymentState> =
+        match serde_json::from_value(value["deployments"].take()) {
+            Ok(deployments) => deployments,
+            Err(e) => return Err(quarantine_state(path, &e.to_string())),
+        };
+
+    // v1 files carry secrets in plaintext; v2 kept them in a single vault entry.
+    let mut plaintext_secrets = false;
+    for (id, state) in deployments.iter_mut() {
+        state.id = id.clone();
+        let mut public = state.clone();
+        if !StateSecrets::take(&mut public).is_empty() {
+            plaintext_secrets = true;
+            continue;
+        }
+        if vault_locked {
+            continue;
+        }
+        let name = if from_version < 3 {
+            STATE_SECRETS.to_string()
+        } else {
+            secrets_name(id)
+        };
+        if let Some(data) = vault::get(&name)? {
+            let secrets: StateSecrets = serde_json::from_str(&data)?;
+            secrets.restore(state);
+        }
+    }
+
+    // Rewrite old files in the current format, moving secrets to their
+    // per-deployment vault entries. If the vault is locked this waits for
+    // the next load: the secrets it holds were not read.
+    if vault_locked && (from_version < STATE_SCHEMA_VERSION || plaintext_secrets) {
+        tracing::info!("state.json left in its old format until secrets are unlocked");
+    } else if from_version < STATE_SCHEMA_VERSION || plaintext_secrets {
+        let upgraded = deployments
+            .values()
+            .try_for_each(store_secrets)
+            .and_then(|()| write_state_file(&public_deployments(&deployments)));
+        match upgraded {
+            Ok(()) => {
+                let _ = vault::remove(STATE_SECRETS);
+                let _ = fs::remove_file(client_config_path()?);
+                tracing::info!("Rewrote state.json in the current format");
+            }
+            Err(e) => tracing::warn!("state.json left in its old format for now: {}", e),
+        }
+    }
+    Ok(deployments)
 }
 
-pub fn save_credentials(creds: &AwsCredentials) -> Result<(), AppError> {
-    let path = credentials_path()?;
-    let data = serde_json::to_string_pretty(creds)?;
-    fs::write(&path, data)?;
+/// Save `state`'s secrets to its vault entry.
+fn store_secrets(state: &DeploymentState) -> Result<(), AppError> {
+    let mut public = state.clone();
+    let secrets = StateSecrets::take(&mut public);
+    if secrets.is_empty() && vault::status().locked {
+        // Loaded without its secrets; the vault entry still holds them.
+        return Ok(());
+    }
+    if secrets.is_empty() {
+        vault::remove(&secrets_name(&state.id))
+    } else {
+        vault::put(&secrets_name(&state.id), &serde_json::to_string(&secrets)?)
+    }
+}
 
-    // Restrict permissions on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+fn public_deployments(
+    deployments: &BTreeMap<String, DeploymentState>,
+) -> BTreeMap<String, DeploymentState> {
+    deployments
+        .iter()
+        .map(|(id, state)| {
+            let mut public = state.clone();
+            StateSecrets::take(&mut public);
+            (id.clone(), public)
+        })
+        .collect()
+}
+
+/// Write the (secret-free) deployments, keeping the previous file as a backup.
+fn write_state_file(public: &BTreeMap<String, DeploymentState>) -> Result<(), AppError> {
+    let path = state_path()?;
+    let value = serde_json::json!({
+        "schema_version": STATE_SCHEMA_VERSION,
+        "deployments": public,
+    });
+    let data = serde_json::to_string_pretty(&value)?;
+
+    // Deploys save after every step; only back up real changes.
+    if fs::read_to_string(&path).ok().as_deref() == Some(data.as_str()) {
+        return Ok(());
     }
+    rotate_backups(&path, &backups_dir()?, STATE_BACKUPS)?;
+    write_atomic(&path, &data)
+}
 
-    Ok(())
+/// All deployments, oldest first. Without their secrets while the vault is
+/// locked.
+pub fn list_deployments() -> Result<Vec<DeploymentState>, AppError> {
+    let _guard = STATE_LOCK.lock().unwrap();
+    let mut deployments: Vec<_> = read_deployments()?.into_values().collect();
+    deployments.sort_by(|a, b| (a.deployed_at, &a.id).cmp(&(b.deployed_at, &b.id)));
+    Ok(deployments)
 }
 
-pub fn delete_credentials() -> Result<(), AppError> {
-    let path = credentials_path()?;
-    if path.exists() {
-        fs::remove_file(&path)?;
+/// The deployment with `id` and its secrets. Fails while the vault is
+/// locked if it has any, rather than leaving a caller to find a key missing.
+pub fn load_deployment(id: &str) -> Result<DeploymentState, AppError> {
+    let state = load_deployment_metadata(id)?;
+    if vault::status().locked {
+        vault::get(&secrets_name(id))?;
+        vault::get(STATE_SECRETS)?;
+    }
+    Ok(state)
+}
+
+/// The deployment with `id`, without its secrets while the vault is locked:
+/// for callers that only read or update its status and names.
+pub fn load_deployment_metadata(id: &str) -> Result<DeploymentState, AppError> {
+    let _guard = STATE_LOCK.lock().unwrap();
+    read_deployments()?
+        .remove(id)
+        .ok_or_else(|| AppError::State(format!("No deployment with id '{}'", id)))
+}
+
+/// Insert or update the deployment with `state.id`.
+pub fn save_deployment(state: &DeploymentState) -> Result<(), AppError> {
+    if state.id.is_empty() {
+        return Err(AppError::State("Deployment has no id".into()));
+    }
+    let _guard = STATE_LOCK.lock().unwrap();
+    let mut deployments = read_deployments()?;
+    store_secrets(state)?;
+    deployments.insert(state.id.clone(), state.clone());
+    write_state_file(&public_deployments(&deployments))
+}
+
+/// Forget a deployment and its secrets. Cloud resources are not touched.
+pub fn remove_deployment(id: &str) -> Result<(), AppError> {
+    let _guard = STATE_LOCK.lock().unwrap();
+    let mut deployments = read_deployments()?;
+    deployments.remove(id);
+    vault::remove(&secrets_name(id))?;
+    write_state_file(&public_deployments(&deployments))
+}
+
+/// Pick the id for a new deployment. A requested id may reuse the slot of a
+/// deployment that failed or was torn down with nothing left running, but
+/// never one that is live.
+pub fn claim_deployment_id(requested: Option<String>) -> Result<String, AppError> {
+    let _guard = STATE_LOCK.lock().unwrap();
+    let deployments = read_deployments()?;
+    match requested {
+        Some(id) => {
+            let valid = !id.is_empty()
+                && id.len() <= 32
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(AppError::State(format!(
+                    "Invalid deployment id '{}': use up to 32 letters, digits, '-' or '_'",
+                    id
+                )));
+            }
+            check_reusable(&id, deployments.get(&id))?;
+            Ok(id)
+        }
+        None => loop {
+            let id = new_deployment_id();
+            if !deployments.contains_key(&id) {
+                return Ok(id);
+            }
+        },
+    }
+}
+
+/// Whether a new deployment may be saved over `existing` under `id`: only
+/// when it failed or was torn down, and records no cloud resources that a
+/// fresh state would forget while they are still running.
+fn check_reusable(id: &str, existing: Option<&DeploymentState>) -> Result<(), AppError> {
+    let Some(existing) = existing else {
+        return Ok(());
+    };
+    if !matches!(
+        existing.status,
+        DeploymentStatus::NotDeployed | DeploymentStatus::Failed
+    ) {
+        return Err(AppError::State(format!(
+            "Deployment '{}' already exists — destroy it first or pick another id",
+            id
+        )));
+    }
+    let resources = existing.cloud_resources();
+    if !resources.is_empty() {
+        return Err(AppError::State(format!(
+            "Deployment '{}' still records {} — resume or destroy it first, or pick another id",
+            id,
+            resources.join(", ")
+        )));
     }
     Ok(())
 }
 
+// --- Credentials ---
+
+/// Read credentials from the vault, first moving over a plaintext file left
+/// by versions before the vault.
+fn load_secret<T: DeserializeOwned + Serialize>(
+    name: &str,
+    legacy_path: &Path,
+) -> Result<Option<T>, AppError> {
+    if legacy_path.exists() {
+        let data = fs::read_to_string(legacy_path)?;
+        let creds: T = serde_json::from_str(&data)?;
+        vault::put(name, &serde_json::to_string(&creds)?)?;
+        fs::remove_file(legacy_path)?;
+        tracing::info!("Moved {} into the vault", legacy_path.display());
+        return Ok(Some(creds));
+    }
+    match vault::get(name)? {
+        Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+        None => Ok(None),
+    }
+}
+
+fn delete_secret(name: &str, legacy_path: &Path) -> Result<(), AppError> {
+    if legacy_path.exists() {
+        fs::remove_file(legacy_path)?;
+    }
+    vault::remove(name)
+}
+
+pub fn load_credentials() -> Result<Option<AwsCredentials>, AppError> {
+    load_secret(AWS_CREDENTIALS, &credentials_path()?)
+}
+
+pub fn save_credentials(creds: &AwsCredentials) -> Result<(), AppError> {
+    vault::put(AWS_CREDENTIALS, &serde_json::to_string(creds)?)
+}
+
+pub fn delete_credentials() -> Result<(), AppError> {
+    delete_secret(AWS_CREDENTIALS, &credentials_path()?)
+}
+
 // --- DigitalOcean Credentials ---
 
 pub fn load_do_credentials() -> Result<Option<DoCredentials>, AppError> {
-    let path = do_credentials_path()?;
-    if !path.exists() {
-        return Ok(None);
-    }
-    let data = fs::read_to_string(&path)?;
-    let creds: DoCredentials = serde_json::from_str(&data)?;
-    Ok(Some(creds))
+    load_secret(DO_CREDENTIALS, &do_credentials_path()?)
 }
 
 pub fn save_do_credentials(creds: &DoCredentials) -> Result<(), AppError> {
-    let path = do_credentials_path()?;
-    let data = serde_json::to_string_pretty(creds)?;
-    fs::write(&path, data)?;
+    vault::put(DO_CREDENTIALS, &serde_json::to_string(creds)?)
+}
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+pub fn delete_do_credentials() -> Result<(), AppError> {
+    delete_secret(DO_CREDENTIALS, &do_credentials_path()?)
+}
+
+// --- Hetzner Credentials ---
+//
+// Added after the vault, so there is no plaintext file to migrate.
+
+pub fn load_hetzner_credentials() -> Result<Option<HetznerCredentials>, AppError> {
+    match vault::get(HETZNER_CREDENTIALS)? {
+        Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+        None => Ok(None),
     }
+}
 
-    Ok(())
+pub fn save_hetzner_credentials(creds: &HetznerCredentials) -> Result<(), AppError> {
+    vault::put(HETZNER_CREDENTIALS, &serde_json::to_string(creds)?)
 }
 
-pub fn delete_do_credentials() -> Result<(), AppError> {
-    let path = do_credentials_path()?;
-    if path.exists() {
-        fs::remove_file(&path)?;
+pub fn delete_hetzner_credentials() -> Result<(), AppError> {
+    vault::remove(HETZNER_CREDENTIALS)
+}
+
+// --- Vultr and Linode Credentials ---
+//
+// Vault only, like Hetzner.
+
+pub fn load_vultr_credentials() -> Result<Option<VultrCredentials>, AppError> {
+    match vault::get(VULTR_CREDENTIALS)? {
+        Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+        None => Ok(None),
     }
-    Ok(())
+}
+
+pub fn save_vultr_credentials(creds: &VultrCredentials) -> Result<(), AppError> {
+    vault::put(VULTR_CREDENTIALS, &serde_json::to_string(creds)?)
+}
+
+pub fn delete_vultr_credentials() -> Result<(), AppError> {
+    vault::remove(VULTR_CREDENTIALS)
+}
+
+pub fn load_linode_credentials() -> Result<Option<LinodeCredentials>, AppError> {
+    match vault::get(LINODE_CREDENTIALS)? {
+        Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn save_linode_credentials(creds: &LinodeCredentials) -> Result<(), AppError> {
+    vault::put(LINODE_CREDENTIALS, &serde_json::to_string(creds)?)
+}
+
+pub fn delete_linode_credentials() -> Result<(), AppError> {
+    vault::remove(LINODE_CREDENTIALS)
 }
 
 // --- Settings ---
@@ -144,8 +641,7 @@ pub fn load_settings() -> Result<AppSettings, AppError> {
 pub fn save_settings(settings: &AppSettings) -> Result<(), AppError> {
     let path = settings_path()?;
     let data = serde_json::to_string_pretty(settings)?;
-    fs::write(&path, data)?;
-    Ok(())
+    write_atomic(&path, &data)
 }
 
 // --- Logs ---
@@ -170,12 +666,285 @@ pub fn save_to_downloads(content: &str, filename: &str) -> Result<std::path::Pat
 
 // --- Client Config File ---
 
-pub fn save_client_config(config: &str) -> Result<PathBuf, AppError> {
-    let path = config_dir()?.join("client.conf");
-    fs::write(&path, config)?;
-    Ok(path)
+/// Plaintext copy of the client config written by versions before the vault.
+/// Only read to delete it; the config now lives in the vault with the state.
+fn client_config_path() -> Result<PathBuf, AppError> {
+    Ok(config_dir()?.join("client.conf"))
 }
 
-pub fn client_config_path() -> Result<PathBuf, AppError> {
-    Ok(config_dir()?.join("client.conf"))
+// --- CLI ---
+
+/// PID of a running `createmyvpn-cli connect`, so `disconnect` can stop it.
+pub fn cli_pid_path() -> Result<PathBuf, AppError> {
+    Ok(config_dir()?.join("cli-tunnel.pid"))
+}
+
+// --- DNS ---
+
+/// Where the original /etc/resolv.conf is kept while the VPN's DNS is applied.
+pub fn dns_backup_path() -> Result<PathBuf, AppError> {
+    Ok(config_dir()?.join("resolv.conf.backup"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::PeerInfo;
+
+    fn deployed_state() -> DeploymentState {
+        DeploymentState {
+            elastic_ip: Some("1.2.3.4".into()),
+            ssh_private_key: Some("SSH".into()),
+            client_private_key: Some("PRIV".into()),
+            client_public_key: Some("PUB".into()),
+            client_config: Some("[Interface]".into()),
+            peers: vec![PeerInfo {
+                name: "phone".into(),
+                public_key: "PEERPUB".into(),
+                address: "10.8.0.3".into(),
+                created_at: chrono::Utc::now(),
+                client_config: Some("[Interface] phone".into()),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn take_strips_every_secret() {
+        let mut state = deployed_state();
+        let secrets = StateSecrets::take(&mut state);
+        assert!(!secrets.is_empty());
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("SSH"));
+        assert!(!json.contains("PRIV"));
+        assert!(!json.contains("[Interface]"));
+        // Public data stays.
+        assert_eq!(state.client_public_key.as_deref(), Some("PUB"));
+        assert_eq!(state.peers[0].public_key, "PEERPUB");
+    }
+
+    #[test]
+    fn restore_puts_secrets_back() {
+        let original = deployed_state();
+        let mut state = original.clone();
+        let secrets = StateSecrets::take(&mut state);
+
+        let json = serde_json::to_string(&secrets).unwrap();
+        let secrets: StateSecrets = serde_json::from_str(&json).unwrap();
+        secrets.restore(&mut state);
+
+        assert_eq!(state.ssh_private_key, original.ssh_private_key);
+        assert_eq!(state.client_config, original.client_config);
+        assert_eq!(state.peers, original.peers);
+    }
+
+    #[test]
+    fn state_without_secrets_is_empty() {
+        let mut state = DeploymentState::default();
+        assert!(StateSecrets::take(&mut state).is_empty());
+    }
+
+    #[test]
+    fn failed_deployment_id_is_reusable_only_without_resources() {
+        let rolled_back = DeploymentState {
+            status: DeploymentStatus::Failed,
+            ..Default::default()
+        };
+        assert!(check_reusable("vpn", Some(&rolled_back)).is_ok());
+        assert!(check_reusable("vpn", None).is_ok());
+
+        let leftover = DeploymentState {
+            status: DeploymentStatus::Failed,
+            vpc_id: Some("vpc-1".into()),
+            instance_id: Some("i-1".into()),
+            ..Default::default()
+        };
+        let err = check_reusable("vpn", Some(&leftover))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("VPC vpc-1, instance i-1"), "{}", err);
+
+        let live = DeploymentState {
+            status: DeploymentStatus::Deployed,
+            ..Default::default()
+        };
+        assert!(check_reusable("vpn", Some(&live)).is_err());
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("createmyvpn-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The single deployment a migrated file ended up with.
+    fn only_deployment(value: &Value) -> DeploymentState {
+        let deployments = value["deployments"].as_object().unwrap();
+        assert_eq!(deployments.len(), 1);
+        let (id, state) = deployments.iter().next().unwrap();
+        let state: DeploymentState = serde_json::from_value(state.clone()).unwrap();
+        assert_eq!(&state.id, id);
+        state
+    }
+
+    #[test]
+    fn unversioned_aws_state_migrates_to_current() {
+        let v1 =
+            serde_json::json!({"status": "deployed", "vpc_id": "vpc-1", "region": "eu-west-1"});
+        let (value, from) = migrate_state(v1).unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(value["schema_version"], STATE_SCHEMA_VERSION);
+
+        let state = only_deployment(&value);
+        assert_eq!(state.deployment_mode.as_deref(), Some("aws"));
+        assert_eq!(state.vpc_id.as_deref(), Some("vpc-1"));
+        assert_eq!(state.name, "AWS eu-west-1");
+    }
+
+    #[test]
+    fn v2_state_moves_into_deployments_map() {
+        let v2 = serde_json::json!({
+            "schema_version": 2,
+            "status": "deployed",
+            "deployment_mode": "do",
+            "region": "nyc3",
+            "droplet_id": 42
+        });
+        let (value, from) = migrate_state(v2).unwrap();
+        assert_eq!(from, 2);
+        assert!(value.get("status").is_none());
+
+        let state = only_deployment(&value);
+        assert_eq!(state.droplet_id, Some(42));
+        assert_eq!(state.name, "DigitalOcean nyc3");
+    }
+
+    #[test]
+    fn unrewritten_v2_state_keeps_its_id_across_loads() {
+        let path = temp_dir().join("state.json");
+        let v2 = serde_json::json!({"schema_version": 2, "status": "deployed", "region": "nyc3"});
+        fs::write(&path, v2.to_string()).unwrap();
+
+        let first = read_deployments_at(&path, true).unwrap();
+        let second = read_deployments_at(&path, true).unwrap();
+        let ids: Vec<_> = first.keys().collect();
+        assert_eq!(ids, vec![MIGRATED_DEPLOYMENT_ID]);
+        assert_eq!(
+            first.keys().collect::<Vec<_>>(),
+            second.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(second[MIGRATED_DEPLOYMENT_ID].id, MIGRATED_DEPLOYMENT_ID);
+    }
+
+    #[test]
+    fn empty_old_state_migrates_to_no_deployments() {
+        let v1 = serde_json::json!({"status": "not_deployed"});
+        let (value, _) = migrate_state(v1).unwrap();
+        assert!(value["deployments"].as_object().unwrap().is_empty());
+    }
+
+    #[test]
+    fn current_schema_is_not_migrated() {
+        let current =
+            serde_json::json!({"schema_version": STATE_SCHEMA_VERSION, "deployments": {}});
+        let (value, from) = migrate_state(current.clone()).unwrap();
+        assert_eq!(from, STATE_SCHEMA_VERSION);
+        assert_eq!(value, current);
+    }
+
+    #[test]
+    fn locked_vault_still_lists_deployments() {
+        let mut state = deployed_state();
+        state.id = "a".into();
+        state.name = "AWS us-east-1".into();
+        state.status = DeploymentStatus::Deployed;
+        let all = BTreeMap::from([("a".to_string(), state)]);
+        let path = temp_dir().join("state.json");
+        let data = serde_json::json!({
+            "schema_version": STATE_SCHEMA_VERSION,
+            "deployments": public_deployments(&all),
+        });
+        fs::write(&path, data.to_string()).unwrap();
+
+        let listed = read_deployments_at(&path, true).unwrap();
+        let state = &listed["a"];
+        assert_eq!(state.status, DeploymentStatus::Deployed);
+        assert_eq!(state.name, "AWS us-east-1");
+        assert!(state.ssh_private_key.is_none());
+        assert!(state.client_config.is_none());
+    }
+
+    #[test]
+    fn locked_vault_leaves_old_files_alone() {
+        let path = temp_dir().join("state.json");
+        let v2 = serde_json::json!({"schema_version": 2, "status": "deployed", "region": "nyc3"});
+        fs::write(&path, v2.to_string()).unwrap();
+
+        assert_eq!(read_deployments_at(&path, true).unwrap().len(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), v2.to_string());
+    }
+
+    #[test]
+    fn public_deployments_strip_secrets() {
+        let mut state = deployed_state();
+        state.id = "a".into();
+        let all = BTreeMap::from([("a".to_string(), state)]);
+        let json = serde_json::to_string(&public_deployments(&all)).unwrap();
+        assert!(!json.contains("PRIV"));
+        assert!(json.contains("PUB"));
+    }
+
+    #[test]
+    fn new_deployment_ids_are_short_and_unique() {
+        let a = new_deployment_id();
+        assert_eq!(a.len(), 8);
+        assert_ne!(a, new_deployment_id());
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let newer = serde_json::json!({"schema_version": STATE_SCHEMA_VERSION + 1});
+        let err = migrate_state(newer).unwrap_err();
+        assert!(err.to_string().contains("newer version"));
+    }
+
+    #[test]
+    fn write_atomic_replaces_file_and_leaves_no_temp() {
+        let dir = temp_dir();
+        let path = dir.join("state.json");
+        write_atomic(&path, "one").unwrap();
+        write_atomic(&path, "two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert!(!path.with_extension("tmp").exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotate_backups_keeps_newest_n() {
+        let dir = temp_dir();
+        let path = dir.join("state.json");
+        let backups = dir.join("backups");
+        for i in 1..=4 {
+            fs::write(&path, format!("v{}", i)).unwrap();
+            rotate_backups(&path, &backups, 3).unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(backups.join("state.json.1")).unwrap(),
+            "v4"
+        );
+        assert_eq!(
+            fs::read_to_string(backups.join("state.json.3")).unwrap(),
+            "v2"
+        );
+        assert!(!backups.join("state.json.4").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,397 @@
+//! Encrypted storage for secrets (cloud credentials, SSH and WireGuard private
+//! keys, client configs), kept out of the plain JSON files in `~/.createmyvpn`.
+//!
+//! Uses the OS keyring (Secret Service, macOS Keychain, Windows Credential
+//! Manager) when one is reachable. Otherwise secrets live in `vault.json`,
+//! each entry sealed with XChaCha20-Poly1305 under a key derived from a user
+//! passphrase with Argon2id. That vault starts locked on every launch and is
+//! opened with `unlock`; entry names are stored in the clear so entries can
+//! be listed and removed while locked, but not read or written.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::persistence::store;
+
+const KEYRING_SERVICE: &str = "createmyvpn";
+/// Set to "passphrase" to skip the OS keyring, e.g. on a headless server.
+const BACKEND_ENV: &str = "CREATEMYVPN_VAULT";
+/// Encrypted under the derived key so a wrong passphrase is detected on unlock.
+const CHECK_PLAINTEXT: &str = "createmyvpn-vault";
+const CHECK_NAME: &str = "__check__";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Keyring,
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub backend: Backend,
+    /// False until a passphrase has been chosen. Always true for the keyring.
+    pub initialized: bool,
+    /// True while the passphrase vault is waiting for `unlock`.
+    pub locked: bool,
+}
+
+/// Argon2id parameters, stored with the vault so they can be raised later
+/// without breaking existing files.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KdfParams {
+    pub salt: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    fn generate() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        KdfParams {
+            salt: B64.encode(salt),
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SealedEntry {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// On-disk format of `vault.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultFile {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub check: SealedEntry,
+    #[serde(default)]
+    pub entries: BTreeMap<String, SealedEntry>,
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+static KEY: OnceLock<Mutex<Option<[u8; 32]>>> = OnceLock::new();
+
+fn key_slot() -> &'static Mutex<Option<[u8; 32]>> {
+    KEY.get_or_init(|| Mutex::new(None))
+}
+
+fn vault_path() -> Result<PathBuf, AppError> {
+    let dir = dirs::home_dir()
+        .ok_or_else(|| AppError::State("Cannot find home directory".into()))?
+        .join(".createmyvpn");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join("vault.json"))
+}
+
+fn locked_error() -> AppError {
+    AppError::Credential("Secrets are locked — unlock them with your passphrase first".into())
+}
+
+/// Which backend holds secrets. Decided once per process: an existing
+/// passphrase vault always wins so its contents stay reachable.
+pub fn backend() -> Backend {
+    *BACKEND.get_or_init(|| {
+        if vault_path().map(|p| p.exists()).unwrap_or(false) {
+            return Backend::Passphrase;
+        }
+        if std::env::var(BACKEND_ENV).as_deref() == Ok("passphrase") {
+            return Backend::Passphrase;
+        }
+        if keyring_available() {
+            tracing::info!("Secrets: using the OS keyring");
+            Backend::Keyring
+        } else {
+            tracing::info!("Secrets: OS keyring unavailable, using the passphrase vault");
+            Backend::Passphrase
+        }
+    })
+}
+
+pub fn status() -> VaultStatus {
+    match backend() {
+        Backend::Keyring => VaultStatus {
+            backend: Backend::Keyring,
+            initialized: true,
+            locked: false,
+        },
+        Backend::Passphrase => VaultStatus {
+            backend: Backend::Passphrase,
+            initialized: vault_path().map(|p| p.exists()).unwrap_or(false),
+            locked: key_slot().lock().unwrap().is_none(),
+        },
+    }
+}
+
+/// Open the passphrase vault for this session, creating it with `passphrase`
+/// if it doesn't exist yet. A no-op for the keyring backend.
+pub fn unlock(passphrase: &str) -> Result<(), AppError> {
+    if backend() == Backend::Keyring {
+        return Ok(());
+    }
+    if passphrase.is_empty() {
+        return Err(AppError::Credential("Passphrase cannot be empty".into()));
+    }
+
+    let key = match read_vault_file()? {
+        Some(vault) => {
+            let key = derive_key(passphrase, &vault.kdf)?;
+            match open(&key, CHECK_NAME, &vault.check) {
+                Ok(text) if text == CHECK_PLAINTEXT => key,
+                _ => return Err(AppError::Credential("Wrong passphrase".into())),
+            }
+        }
+        None => {
+            let kdf = KdfParams::generate();
+            let key = derive_key(passphrase, &kdf)?;
+            let vault = VaultFile {
+                version: 1,
+                check: seal(&key, CHECK_NAME, CHECK_PLAINTEXT)?,
+                kdf,
+                entries: BTreeMap::new(),
+            };
+            write_vault_file(&vault)?;
+            tracing::info!("Secrets: created a new passphrase vault");
+            key
+        }
+    };
+    *key_slot().lock().unwrap() = Some(key);
+    tracing::info!("Secrets: vault unlocked");
+    Ok(())
+}
+
+/// Forget the derived key. Secrets stay on disk.
+pub fn lock() {
+    *key_slot().lock().unwrap() = None;
+}
+
+/// Fails early with a clear message instead of halfway through a deploy.
+pub fn ensure_unlocked() -> Result<(), AppError> {
+    if status().locked {
+        return Err(locked_error());
+    }
+    Ok(())
+}
+
+pub fn get(name: &str) -> Result<Option<String>, AppError> {
+    match backend() {
+        Backend::Keyring => {
+            match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|e| e.get_password()) {
+                Ok(value) => Ok(Some(value)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(AppError::Credential(format!("Keyring read failed: {}", e))),
+            }
+        }
+        Backend::Passphrase => {
+            let Some(vault) = read_vault_file()? else {
+                return Ok(None);
+            };
+            let Some(entry) = vault.entries.get(name) else {
+                return Ok(None);
+            };
+            let key = key_slot().lock().unwrap().ok_or_else(locked_error)?;
+            open(&key, name, entry).map(Some)
+        }
+    }
+}
+
+pub fn put(name: &str, value: &str) -> Result<(), AppError> {
+    match backend() {
+        Backend::Keyring => keyring::Entry::new(KEYRING_SERVICE, name)
+            .and_then(|e| e.set_password(value))
+            .map_err(|e| AppError::Credential(format!("Keyring write failed: {}", e))),
+        Backend::Passphrase => {
+            let key = key_slot().lock().unwrap().ok_or_else(locked_error)?;
+            let mut vault = read_vault_file()?.ok_or_else(locked_error)?;
+            vault
+                .entries
+                .insert(name.to_string(), seal(&key, name, value)?);
+            write_vault_file(&vault)
+        }
+    }
+}
+
+/// Works while locked: entries are removed without being decrypted.
+pub fn remove(name: &str) -> Result<(), AppError> {
+    match backend() {
+        Backend::Keyring => {
+            match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|e| e.delete_credential()) {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(AppError::Credential(format!(
+                    "Keyring delete failed: {}",
+                    e
+                ))),
+            }
+        }
+        Backend::Passphrase => {
+            let Some(mut vault) = read_vault_file()? else {
+                return Ok(());
+            };
+            if vault.entries.remove(name).is_some() {
+                write_vault_file(&vault)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Round-trips a throwaway entry; a missing or locked Secret Service daemon
+/// fails here rather than on the first real write.
+fn keyring_available() -> bool {
+    let probe = match keyring::Entry::new(KEYRING_SERVICE, "__probe__") {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+    let ok = probe.set_password("ok").is_ok()
+        && probe.get_password().map(|v| v == "ok").unwrap_or(false);
+    let _ = probe.delete_credential();
+    ok
+}
+
+fn read_vault_file() -> Result<Option<VaultFile>, AppError> {
+    let path = vault_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+fn write_vault_file(vault: &VaultFile) -> Result<(), AppError> {
+    store::write_atomic(&vault_path()?, &serde_json::to_string_pretty(vault)?)
+}
+
+pub fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; 32], AppError> {
+    let salt = B64
+        .decode(&kdf.salt)
+        .map_err(|e| AppError::Credential(format!("Corrupt vault salt: {}", e)))?;
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| AppError::Credential(format!("Bad vault KDF parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| AppError::Credential(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Encrypt `value`. The entry name is bound in as associated data, so an
+/// entry copied under another name fails to open.
+pub fn seal(key: &[u8; 32], name: &str, value: &str) -> Result<SealedEntry, AppError> {
+    let mut nonce = [0u8; 24];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: value.as_bytes(),
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| AppError::Credential("Encryption failed".into()))?;
+    Ok(SealedEntry {
+        nonce: B64.encode(nonce),
+        ciphertext: B64.encode(ciphertext),
+    })
+}
+
+pub fn open(key: &[u8; 32], name: &str, entry: &SealedEntry) -> Result<String, AppError> {
+    let corrupt = || AppError::Credential(format!("Cannot decrypt secret '{}'", name));
+    let nonce = B64.decode(&entry.nonce).map_err(|_| corrupt())?;
+    let ciphertext = B64.decode(&entry.ciphertext).map_err(|_| corrupt())?;
+    if nonce.len() != 24 {
+        return Err(corrupt());
+    }
+    let plaintext = XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| corrupt())?;
+    String::from_utf8(plaintext).map_err(|_| corrupt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_kdf() -> KdfParams {
+        // Small memory cost keeps the tests fast; the format is the same.
+        KdfParams {
+            salt: B64.encode([7u8; 16]),
+            m_cost: 1024,
+            t_cost: 1,
+            p_cost: 1,
+        }
+    }
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let key = derive_key("correct horse", &test_kdf()).unwrap();
+        let sealed = seal(&key, "aws_credentials", "secret").unwrap();
+        assert_ne!(sealed.ciphertext, B64.encode("secret"));
+        assert_eq!(open(&key, "aws_credentials", &sealed).unwrap(), "secret");
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_to_open() {
+        let key = derive_key("correct horse", &test_kdf()).unwrap();
+        let other = derive_key("battery staple", &test_kdf()).unwrap();
+        let sealed = seal(&key, "state", "secret").unwrap();
+        assert!(open(&other, "state", &sealed).is_err());
+    }
+
+    #[test]
+    fn entry_is_bound_to_its_name() {
+        let key = derive_key("pw", &test_kdf()).unwrap();
+        let sealed = seal(&key, "state", "secret").unwrap();
+        assert!(open(&key, "do_credentials", &sealed).is_err());
+    }
+
+    #[test]
+    fn derive_key_is_deterministic_per_salt() {
+        let a = derive_key("pw", &test_kdf()).unwrap();
+        let b = derive_key("pw", &test_kdf()).unwrap();
+        assert_eq!(a, b);
+
+        let mut kdf = test_kdf();
+        kdf.salt = B64.encode([8u8; 16]);
+        assert_ne!(derive_key("pw", &kdf).unwrap(), a);
+    }
+
+    #[test]
+    fn nonces_are_unique() {
+        let key = derive_key("pw", &test_kdf()).unwrap();
+        let a = seal(&key, "state", "same").unwrap();
+        let b = seal(&key, "state", "same").unwrap();
+        assert_ne!(a.nonce, b.nonce);
+        assert_ne!(a.ciphertext, b.ciphertext);
+    }
+
+    #[test]
+    fn vault_file_without_entries_deserializes() {
+        let json = r#"{"version":1,"kdf":{"salt":"AAAA","m_cost":1024,"t_cost":1,"p_cost":1},
+            "check":{"nonce":"n","ciphertext":"c"}}"#;
+        let vault: VaultFile = serde_json::from_str(json).unwrap();
+        assert!(vault.entries.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! What a deployment costs: an estimate before deploying, and what it has
+//! run up since. AWS prices are bundled (on-demand Linux in us-east-1; other
+//! regions differ by a few percent). The other providers publish theirs with
+//! their sizes, so those come from the size catalog, which refreshes from
+//! the API (see `catalog`) and falls back to bundled list prices.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::catalog;
+use crate::provider::CloudProvider;
+use crate::state::DeploymentState;
+
+pub const HOURS_PER_MONTH: f64 = 730.0;
+/// Public IPv4 addresses, attached or not, cost $0.005/hour.
+pub const PUBLIC_IPV4_HOURLY_USD: f64 = 0.005;
+/// gp3 storage, per GB-month.
+pub const GP3_GB_MONTHLY_USD: f64 = 0.08;
+/// The root volume `ec2::launch_instance` gives every instance.
+pub const ROOT_VOLUME_GB: f64 = 20.0;
+
+/// On-demand Linux prices in us-east-1.
+const INSTANCE_HOURLY_USD: &[(&str, f64)] = &[
+    ("t2.nano", 0.0058),
+    ("t2.micro", 0.0116),
+    ("t2.small", 0.023),
+    ("t3.nano", 0.0052),
+    ("t3.micro", 0.0104),
+    ("t3.small", 0.0208),
+    ("t3a.micro", 0.0094),
+    ("t4g.nano", 0.0042),
+    ("t4g.micro", 0.0084),
+    ("t4g.small", 0.0168),
+];
+
+pub fn aws_instance_hourly(instance_type: &str) -> Option<f64> {
+    INSTANCE_HOURLY_USD
+        .iter()
+        .find(|(t, _)| *t == instance_type)
+        .map(|(_, hourly)| *hourly)
+}
+
+/// The currency `mode` bills in.
+pub fn currency(mode: &str) -> &'static str {
+    match mode {
+        "hetzner" => "EUR",
+        _ => "USD",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CostEstimate {
+    /// "USD" or "EUR".
+    pub currency: String,
+    pub hourly: f64,
+    pub monthly: f64,
+    /// The total until auto-destroy fires, if it is set.
+    pub until_auto_destroy: Option<f64>,
+}
+
+/// What a deployment has cost since it finished deploying.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AccruedCost {
+    pub currency: String,
+    pub hourly: f64,
+    pub hours: f64,
+    pub amount: f64,
+}
+
+/// The hourly price of a server of `size` in `region`, with what it needs
+/// to run (on AWS its public IPv4 address and root volume). `None` when the
+/// size has no known price.
+pub async fn hourly_rate(
+    mode: &str,
+    provider: Option<&dyn CloudProvider>,
+    region: &str,
+    size: &str,
+) -> Option<f64> {
+    if mode == "aws" {
+        let storage = GP3_GB_MONTHLY_USD * ROOT_VOLUME_GB / HOURS_PER_MONTH;
+        return aws_instance_hourly(size).map(|h| h + PUBLIC_IPV4_HOURLY_USD + storage);
+    }
+    let sizes = catalog::sizes(mode, provider, region).await;
+    let monthly = sizes.items.iter().find(|s| s.code == size)?.monthly_price?;
+    Some(monthly / HOURS_PER_MONTH)
+}
+
+pub fn estimate(mode: &str, hourly: f64, auto_destroy_hours: Option<u32>) -> CostEstimate {
+    CostEstimate {
+        currency: currency(mode).to_string(),
+        hourly,
+        monthly: hourly * HOURS_PER_MONTH,
+        until_auto_destroy: auto_destroy_hours.map(|h| hourly * f64::from(h)),
+    }
+}
+
+/// The cost of `state` from `deployed_at` until `now`, if its rate was
+/// recorded when it was deployed.
+pub fn accrued(state: &DeploymentState, now: DateTime<Utc>) -> Option<AccruedCost> {
+    let hourly = state.hourly_cost?;
+    let deployed_at = state.deployed_at?;
+    let hours = ((now - deployed_at).num_seconds().max(0) as f64) / 3600.0;
+    Some(AccruedCost {
+        currency: state
+            .cost_currency
+            .clone()
+            .unwrap_or_else(|| "USD".to_string()),
+        hourly,
+        hours,
+        amount: hourly * hours,
+    })
+}
+
+/// e.g. "$0.42" or "€1.03".
+pub fn format_amount(currency: &str, amount: f64) -> String {
+    let symbol = match currency {
+        "EUR" => "€",
+        _ => "$",
+    };
+    format!("{}{:.2}", symbol, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn aws_rate_includes_address_and_storage() {
+        let rate = hourly_rate("aws", None, "eu-west-1", "t3.micro")
+            .await
+            .unwrap();
+        assert!((rate - (0.0104 + 0.005 + 1.6 / 730.0)).abs() < 1e-9);
+        assert!(hourly_rate("aws", None, "eu-west-1", "m7i.48xlarge")
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn estimate_covers_auto_destroy_window() {
+        let e = estimate("hetzner", 0.01, Some(4));
+        assert_eq!(e.currency, "EUR");
+        assert!((e.monthly - 7.3).abs() < 1e-9);
+        assert!((e.until_auto_destroy.unwrap() - 0.04).abs() < 1e-9);
+        assert_eq!(estimate("do", 0.01, None).until_auto_destroy, None);
+    }
+
+    #[test]
+    fn accrues_from_deployed_at() {
+        let now = Utc::now();
+        let state = DeploymentState {
+            deployed_at: Some(now - Duration::minutes(90)),
+            hourly_cost: Some(0.02),
+            cost_currency: Some("USD".into()),
+            ..Default::default()
+        };
+        let cost = accrued(&state, now).unwrap();
+        assert!((cost.hours - 1.5).abs() < 1e-9);
+        assert!((cost.amount - 0.03).abs() < 1e-9);
+        assert_eq!(format_amount(&cost.currency, cost.amount), "$0.03");
+    }
+
+    #[test]
+    fn nothing_accrues_without_a_recorded_rate() {
+        let state = DeploymentState {
+            deployed_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        assert!(accrued(&state, Utc::now()).is_none());
+    }
+}
@@ -0,0 +1,53 @@
+//! Progress reporting for long-running operations (deploy, destroy).
+//!
+//! The orchestration in `commands::*` only talks to a `ProgressSink`, so the
+//! same code drives the desktop app (events to the frontend) and the
+//! `createmyvpn-cli` binary (lines on the terminal).
+use tauri::{AppHandle, Emitter};
+
+use crate::state::{DeployLogEvent, ProgressEvent};
+
+pub const DEPLOY_EVENT: &str = "deploy-progress";
+pub const DESTROY_EVENT: &str = "destroy-progress";
+pub const CLEANUP_EVENT: &str = "cleanup-progress";
+/// Output from commands running on the server during a deploy.
+pub const DEPLOY_LOG_EVENT: &str = "deploy-log";
+
+/// Receives step-by-step progress. `status` is "running", "done" or "error".
+pub trait ProgressSink: Send + Sync {
+    fn emit(&self, step: u32, total: u32, message: &str, status: &str);
+
+    /// A line of output from a command running on the server.
+    fn log(&self, _line: &str) {}
+}
+
+/// Forwards progress to the frontend as a Tauri event.
+pub struct TauriProgress {
+    app: AppHandle,
+    event: &'static str,
+}
+
+impl TauriProgress {
+    pub fn new(app: AppHandle, event: &'static str) -> Self {
+        Self { app, event }
+    }
+}
+
+impl ProgressSink for TauriProgress {
+    fn emit(&self, step: u32, total: u32, message: &str, status: &str) {
+        let event = ProgressEvent {
+            step,
+            total_steps: total,
+            message: message.to_string(),
+            status: status.to_string(),
+        };
+        let _ = self.app.emit(self.event, event);
+    }
+
+    fn log(&self, line: &str) {
+        let event = DeployLogEvent {
+            line: line.to_string(),
+        };
+        let _ = self.app.emit(DEPLOY_LOG_EVENT, event);
+    }
+}
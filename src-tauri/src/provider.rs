@@ -0,0 +1,193 @@
+//! One interface over the clouds a VPN server can be deployed on.
+//!
+//! `commands::deploy::deploy_on` runs the same steps for every provider
+//! (firewall, SSH key, server, address, then WireGuard, from the server's
+//! user data where the provider runs it, otherwise over SSH). A provider
+//! only creates and deletes its own resources, recording their ids in the
+//! `DeploymentState` it is handed so a failed deploy can be rolled back.
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::aws::provider::AwsProvider;
+use crate::do_cloud::provider::DoProvider;
+use crate::error::AppError;
+use crate::hetzner::provider::HetznerProvider;
+use crate::linode::provider::LinodeProvider;
+use crate::persistence::store;
+use crate::progress::ProgressSink;
+use crate::server_image::ServerImage;
+use crate::state::DeploymentState;
+use crate::vultr::provider::VultrProvider;
+
+/// A region (location, datacenter) servers can be created in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Region {
+    pub code: String,
+    pub name: String,
+}
+
+/// A server size (instance type, plan) on offer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerSize {
+    pub code: String,
+    pub description: String,
+    /// Monthly list price in the provider's billing currency, if known.
+    pub monthly_price: Option<f64>,
+}
+
+/// The create methods record what they create in `state` as soon as it
+/// exists, so the caller can save it even when a later call fails, and reuse
+/// whatever `state` already records, so a resumed deploy creates nothing twice.
+#[async_trait]
+pub trait CloudProvider: Send + Sync {
+    /// The `deployment_mode` its deployments are saved with, e.g. "do".
+    fn mode(&self) -> &'static str;
+
+    /// Name used in progress messages, e.g. "DigitalOcean".
+    fn display_name(&self) -> &'static str;
+
+    /// The user the deploy SSH key logs in as on `image`.
+    fn ssh_user(&self, _image: &ServerImage) -> &'static str {
+        "root"
+    }
+
+    /// Fail unless `image` is on offer and boots on a server of `size`.
+    /// Checked before anything is created.
+    fn check_image(&self, image: &ServerImage, size: &str) -> Result<(), AppError>;
+
+    /// Whether `create_server` hands its `user_data` to the server's
+    /// cloud-init, so WireGuard is set up at first boot instead of over SSH.
+    fn runs_user_data(&self) -> bool {
+        false
+    }
+
+    /// Check the credentials and return the account they belong to, or an
+    /// empty string when the provider has no account to report.
+    async fn validate_credentials(&self) -> Result<String, AppError>;
+
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError>;
+
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError>;
+
+    /// Allow SSH from `state.ssh_source` (see `firewall_ssh_source`) and the
+    /// WireGuard port from anywhere in, before there is a server to expose.
+    /// On AWS this includes the VPC the server will live in.
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError>;
+
+    /// Let SSH in from `source` only, or from nowhere when it is `None`,
+    /// replacing the SSH rule the firewall has now (see
+    /// `current_ssh_sources`). The WireGuard port stays open.
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError>;
+
+    /// Create the deploy SSH key, keeping the private half in
+    /// `state.ssh_private_key`.
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError>;
+
+    /// Launch a server of `size` behind the firewall, reachable with the
+    /// key, booting `state.server_image()`. `user_data` is a first-boot
+    /// script, only given when `runs_user_data`.
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        user_data: Option<&str>,
+    ) -> Result<(), AppError>;
+
+    /// Wait until the server runs and return its public IPv4 address.
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError>;
+
+    /// Host key fingerprints learned out of band, to pin on the first SSH
+    /// connect. Empty means the key seen first is trusted.
+    async fn host_key_fingerprints(&self, _state: &DeploymentState) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Delete everything `state` records, reporting steps 1-3 of 3 on
+    /// `progress`. Every resource is attempted; failures are reported
+    /// together at the end.
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError>;
+}
+
+/// The address a new firewall lets SSH in from.
+pub fn ssh_source(state: &DeploymentState) -> Result<&str, AppError> {
+    state
+        .ssh_source
+        .as_deref()
+        .ok_or_else(|| AppError::State("No SSH source recorded for this deployment".into()))
+}
+
+/// The address a new firewall lets SSH in from, or `None` for a deploy
+/// without SSH, whose firewall never has an SSH rule.
+pub fn firewall_ssh_source(state: &DeploymentState) -> Result<Option<&str>, AppError> {
+    if state.ssh_locked {
+        return Ok(None);
+    }
+    ssh_source(state).map(Some)
+}
+
+/// What the firewall's SSH rule lets in now: nothing once it is locked down,
+/// and anywhere for deployments made before SSH was restricted.
+pub fn current_ssh_sources(state: &DeploymentState) -> Option<Vec<String>> {
+    if state.ssh_locked {
+        return None;
+    }
+    Some(match &state.ssh_source {
+        Some(source) => vec![source.clone()],
+        None => vec!["0.0.0.0/0".to_string(), "::/0".to_string()],
+    })
+}
+
+/// The provider `state` was deployed on, with the credentials saved in the
+/// app. `None` for BYO servers, which no provider manages.
+pub fn for_deployment(state: &DeploymentState) -> Result<Option<Box<dyn CloudProvider>>, AppError> {
+    for_mode(state.deployment_mode.as_deref(), state.region.as_deref())
+}
+
+/// The provider for `deployment_mode` (`None` is AWS, as in old state
+/// files), with the credentials saved in the app. AWS clients are bound to
+/// `region`. `None` for "byo".
+pub fn for_mode(
+    mode: Option<&str>,
+    region: Option<&str>,
+) -> Result<Option<Box<dyn CloudProvider>>, AppError> {
+    let missing =
+        |provider: &str| AppError::Credential(format!("No {} credentials saved", provider));
+    let provider: Box<dyn CloudProvider> = match mode {
+        Some("byo") => return Ok(None),
+        Some("do") => {
+            let creds = store::load_do_credentials()?.ok_or_else(|| missing("DigitalOcean"))?;
+            Box::new(DoProvider::new(&creds))
+        }
+        Some("hetzner") => {
+            let creds = store::load_hetzner_credentials()?.ok_or_else(|| missing("Hetzner"))?;
+            Box::new(HetznerProvider::new(&creds))
+        }
+        Some("vultr") => {
+            let creds = store::load_vultr_credentials()?.ok_or_else(|| missing("Vultr"))?;
+            Box::new(VultrProvider::new(&creds))
+        }
+        Some("linode") => {
+            let creds = store::load_linode_credentials()?.ok_or_else(|| missing("Linode"))?;
+            Box::new(LinodeProvider::new(&creds))
+        }
+        _ => {
+            let region = region.ok_or_else(|| AppError::State("No region in state".into()))?;
+            let creds = store::load_credentials()?.ok_or_else(|| missing("AWS"))?;
+            Box::new(AwsProvider::new(creds, region))
+        }
+    };
+    Ok(Some(provider))
+}
@@ -0,0 +1,138 @@
+//! The public IPv4 address this computer reaches the internet from, as a
+//! server sees it. New firewalls only let SSH in from there, so a freshly
+//! booted server isn't open to the whole internet while it is configured.
+//!
+//! It is asked of a lookup service that answers with the bare address (or
+//! `{"ip": "..."}`), configurable in Settings for networks that block the
+//! default one.
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::AppError;
+
+pub const DEFAULT_LOOKUP_URL: &str = "https://checkip.amazonaws.com";
+const LOOKUP_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Deserialize)]
+struct JsonAnswer {
+    ip: String,
+}
+
+/// `url` trimmed, if it is an http(s) URL.
+pub fn validate_url(url: &str) -> Result<String, AppError> {
+    let url = url.trim();
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url.to_string()),
+        _ => Err(AppError::General(format!(
+            "Invalid IP lookup URL '{}': expected an http(s) URL such as {}",
+            url, DEFAULT_LOOKUP_URL
+        ))),
+    }
+}
+
+/// The address in a lookup service's answer.
+fn parse_answer(body: &str) -> Result<Ipv4Addr, String> {
+    let body = body.trim();
+    let text = match serde_json::from_str::<JsonAnswer>(body) {
+        Ok(answer) => answer.ip,
+        Err(_) => body.to_string(),
+    };
+    match text.trim().parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip))
+            if ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() =>
+        {
+            Err(format!("{} is not a public address", ip))
+        }
+        Ok(IpAddr::V4(ip)) => Ok(ip),
+        Ok(IpAddr::V6(ip)) => Err(format!(
+            "got the IPv6 address {}, but SSH reaches the server over IPv4; \
+             use a lookup that answers over IPv4",
+            ip
+        )),
+        Err(_) => Err(format!(
+            "unexpected answer '{}'",
+            body.chars().take(64).collect::<String>()
+        )),
+    }
+}
+
+/// Ask the lookup service at `url` for this computer's public address.
+pub async fn lookup(url: &str) -> Result<Ipv4Addr, AppError> {
+    let failed = |why: String| {
+        AppError::General(format!(
+            "Could not look up this computer's public IP address at {}: {}. \
+             Set another IP lookup URL in Settings.",
+            url, why
+        ))
+    };
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(LOOKUP_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| failed(e.to_string()))?;
+    let body = http
+        .get(url)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| failed(e.to_string()))?
+        .text()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    parse_answer(&body).map_err(failed)
+}
+
+/// The firewall source for SSH from this computer, e.g. "203.0.113.7/32".
+pub async fn ssh_source(url: &str) -> Result<String, AppError> {
+    let ip = lookup(url).await?;
+    tracing::info!("This computer's public IP address is {}", ip);
+    Ok(format!("{}/32", ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[test]
+    fn parses_plain_and_json_answers() {
+        let ip = Ipv4Addr::new(203, 0, 113, 7);
+        assert_eq!(parse_answer("203.0.113.7\n"), Ok(ip));
+        assert_eq!(parse_answer(r#"{"ip": "203.0.113.7"}"#), Ok(ip));
+    }
+
+    #[test]
+    fn rejects_answers_ssh_cannot_use() {
+        assert!(parse_answer("2001:db8::1").unwrap_err().contains("IPv6"));
+        assert!(parse_answer("192.168.1.20")
+            .unwrap_err()
+            .contains("not a public address"));
+        assert!(parse_answer("<html>blocked</html>")
+            .unwrap_err()
+            .contains("unexpected answer"));
+    }
+
+    #[test]
+    fn lookup_url_must_be_http() {
+        assert_eq!(
+            validate_url(" https://api.ipify.org ").unwrap(),
+            "https://api.ipify.org"
+        );
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("checkip.amazonaws.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn source_is_a_host_cidr() {
+        let server = MockServer::start(|_| (200, "198.51.100.23\n".into())).await;
+        assert_eq!(ssh_source(&server.url).await.unwrap(), "198.51.100.23/32");
+    }
+
+    #[tokio::test]
+    async fn failed_lookup_points_at_settings() {
+        let server = MockServer::start(|_| (503, String::new())).await;
+        let err = lookup(&server.url).await.unwrap_err().to_string();
+        assert!(err.contains("Settings"), "{}", err);
+    }
+}
@@ -0,0 +1,169 @@
+//! The operating system and CPU architecture a new server is created with.
+//! Each provider maps them onto its own images (an AMI, a Droplet image
+//! slug, a Vultr OS id, ...) and checks that the chosen size runs that
+//! architecture before anything is created.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OsRelease {
+    #[serde(rename = "ubuntu-22.04")]
+    Ubuntu2204,
+    #[serde(rename = "ubuntu-24.04")]
+    Ubuntu2404,
+    #[serde(rename = "debian-12")]
+    Debian12,
+}
+
+impl Default for OsRelease {
+    fn default() -> Self {
+        OsRelease::Ubuntu2204
+    }
+}
+
+impl OsRelease {
+    pub const ALL: [OsRelease; 3] = [
+        OsRelease::Ubuntu2204,
+        OsRelease::Ubuntu2404,
+        OsRelease::Debian12,
+    ];
+
+    /// As saved in settings and state, e.g. "ubuntu-24.04".
+    pub fn code(&self) -> &'static str {
+        match self {
+            OsRelease::Ubuntu2204 => "ubuntu-22.04",
+            OsRelease::Ubuntu2404 => "ubuntu-24.04",
+            OsRelease::Debian12 => "debian-12",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OsRelease::Ubuntu2204 => "Ubuntu 22.04 LTS",
+            OsRelease::Ubuntu2404 => "Ubuntu 24.04 LTS",
+            OsRelease::Debian12 => "Debian 12",
+        }
+    }
+
+    pub fn parse(code: &str) -> Result<Self, AppError> {
+        Self::ALL
+            .into_iter()
+            .find(|os| os.code() == code)
+            .ok_or_else(|| {
+                AppError::General(format!(
+                    "Unknown OS '{}': expected ubuntu-22.04, ubuntu-24.04 or debian-12",
+                    code
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Architecture {
+    Amd64,
+    Arm64,
+}
+
+impl Default for Architecture {
+    fn default() -> Self {
+        Architecture::Amd64
+    }
+}
+
+impl Architecture {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Architecture::Amd64 => "amd64",
+            Architecture::Arm64 => "arm64",
+        }
+    }
+
+    pub fn parse(code: &str) -> Result<Self, AppError> {
+        match code {
+            "amd64" | "x86_64" => Ok(Architecture::Amd64),
+            "arm64" | "aarch64" => Ok(Architecture::Arm64),
+            _ => Err(AppError::General(format!(
+                "Unknown architecture '{}': expected amd64 or arm64",
+                code
+            ))),
+        }
+    }
+}
+
+/// What a new server boots. Old state files predate the choice and were
+/// deployed with the default, Ubuntu 22.04 on amd64.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServerImage {
+    pub os: OsRelease,
+    pub arch: Architecture,
+}
+
+impl fmt::Display for ServerImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.os.name(), self.arch.code())
+    }
+}
+
+impl ServerImage {
+    /// Fail unless a server of `size`, whose CPUs are `size_arch`, can boot
+    /// this image.
+    pub fn check_size(&self, size: &str, size_arch: Architecture) -> Result<(), AppError> {
+        if self.arch == size_arch {
+            return Ok(());
+        }
+        Err(AppError::State(format!(
+            "{} is an {} server, so it can't boot {}. Pick an {} size, or change \
+             the architecture in Settings.",
+            size,
+            size_arch.code(),
+            self,
+            self.arch.code()
+        )))
+    }
+
+    /// For providers without arm64 servers.
+    pub fn require_amd64(&self, provider: &str) -> Result<(), AppError> {
+        match self.arch {
+            Architecture::Amd64 => Ok(()),
+            Architecture::Arm64 => Err(AppError::State(format!(
+                "{} has no arm64 servers. Choose amd64 in Settings.",
+                provider
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for os in OsRelease::ALL {
+            assert_eq!(OsRelease::parse(os.code()).unwrap(), os);
+            assert_eq!(serde_json::to_value(os).unwrap(), os.code());
+        }
+        assert_eq!(Architecture::parse("aarch64").unwrap(), Architecture::Arm64);
+        assert_eq!(serde_json::to_value(Architecture::Arm64).unwrap(), "arm64");
+        assert!(OsRelease::parse("centos-7").is_err());
+    }
+
+    #[test]
+    fn size_must_match_the_architecture() {
+        let image = ServerImage {
+            os: OsRelease::Ubuntu2404,
+            arch: Architecture::Arm64,
+        };
+        assert!(image.check_size("t4g.micro", Architecture::Arm64).is_ok());
+        let err = image
+            .check_size("t2.micro", Architecture::Amd64)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("t2.micro is an amd64 server"), "{}", err);
+        assert!(image.require_amd64("Linode").is_err());
+    }
+}
@@ -1,10 +1,137 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use russh::client;
+use russh_sftp::client::SftpSession;
+use russh_sftp::protocol::OpenFlags;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
 
 use crate::error::AppError;
+use crate::ssh::host_key;
 
-struct SshHandler;
+/// Permissions and owner a file written by `upload_file` ends up with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMode {
+    pub mode: u32,
+    pub owner: &'static str,
+    pub group: &'static str,
+}
+
+impl FileMode {
+    /// Root's, readable by root only, e.g. wg0.conf with the server's private key.
+    pub const PRIVATE: FileMode = FileMode {
+        mode: 0o600,
+        owner: "root",
+        group: "root",
+    };
+    /// Root's, readable by everyone.
+    pub const PUBLIC: FileMode = FileMode {
+        mode: 0o644,
+        owner: "root",
+        group: "root",
+    };
+}
+
+/// `value` in single quotes for a remote shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Lowercase hex SHA-256 of `content`, as `sha256sum` prints it.
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The digest at the start of `sha256sum` output.
+fn parse_sha256sum(output: &str) -> Option<&str> {
+    let digest = output.split_whitespace().next()?;
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+/// Copy the uploaded `staging` file next to `remote_path` with `mode`, rename
+/// it into place (atomic within one directory), clean up, and print the
+/// digest of what is now at `remote_path`.
+fn install_command(staging: &str, remote_path: &str, mode: FileMode) -> String {
+    let next = shell_quote(&format!("{}.createmyvpn-new", remote_path));
+    let staging = shell_quote(staging);
+    let target = shell_quote(remote_path);
+    format!(
+        "sudo install -m {:o} -o {} -g {} {staging} {next} && sudo mv -f {next} {target}; \
+         s=$?; rm -f {staging}; sudo rm -f {next}; [ $s -eq 0 ] && sudo sha256sum {target}",
+        mode.mode,
+        mode.owner,
+        mode.group,
+        staging = staging,
+        next = next,
+        target = target,
+    )
+}
+
+/// Fail unless `sha256sum` output for `path` shows `expected`.
+fn check_digest(path: &str, output: &str, expected: &str) -> Result<(), AppError> {
+    match parse_sha256sum(output) {
+        Some(actual) if actual.eq_ignore_ascii_case(expected) => Ok(()),
+        _ => Err(AppError::Ssh(format!(
+            "{} did not transfer intact: SHA-256 should be {}, sha256sum said: {}",
+            path,
+            expected,
+            output.trim()
+        ))),
+    }
+}
+
+/// Which stream a line of command output came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Lines of output shown when a streamed command fails.
+const FAILURE_TAIL_LINES: usize = 20;
+
+/// Splits a stream of output into lines as it arrives. A carriage return
+/// ends a line too, so progress bars redrawn in place come through one
+/// update at a time.
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Add `data` and return the non-empty lines it completed.
+    fn push(&mut self, data: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for &byte in data {
+            if byte == b'\n' || byte == b'\r' {
+                lines.extend(self.take());
+            } else {
+                self.pending.push(byte);
+            }
+        }
+        lines
+    }
+
+    /// The unterminated last line, if any.
+    fn take(&mut self) -> Option<String> {
+        let line = String::from_utf8_lossy(&self.pending)
+            .trim_end()
+            .to_string();
+        self.pending.clear();
+        (!line.is_empty()).then_some(line)
+    }
+}
+
+struct SshHandler {
+    /// Fingerprints the server is allowed to present. Empty means trust on first use.
+    known_fingerprints: Vec<String>,
+    /// Fingerprint the server actually presented, read back after the handshake.
+    seen_fingerprint: Arc<Mutex<Option<String>>>,
+}
 
 #[async_trait::async_trait]
 impl client::Handler for SshHandler {
@@ -12,25 +139,38 @@ impl client::Handler for SshHandler {
 
     async fn check_server_key(
         &mut self,
-        _server_public_key: &russh_keys::key::PublicKey,
+        server_public_key: &russh_keys::key::PublicKey,
     ) -> Result<bool, Self::Error> {
-        // Accept all server keys (like StrictHostKeyChecking=no)
-        Ok(true)
+        let actual = host_key::normalize_fingerprint(&server_public_key.fingerprint());
+        *self.seen_fingerprint.lock().unwrap() = Some(actual.clone());
+
+        if self.known_fingerprints.is_empty() {
+            tracing::info!("Trusting SSH host key on first use: {}", actual);
+            return Ok(true);
+        }
+        Ok(host_key::matches_any(&self.known_fingerprints, &actual))
     }
 }
 
 pub struct SshSession {
     session: client::Handle<SshHandler>,
+    host_key_fingerprint: String,
 }
 
 impl SshSession {
     /// Connect to an SSH server with retry up to `timeout_secs`.
+    ///
+    /// `known_fingerprints` pins the server's host key: if non-empty, the server
+    /// must present one of them or the connection fails immediately with
+    /// `AppError::HostKeyMismatch`. If empty, any key is accepted and can be read
+    /// back with `host_key_fingerprint()` to pin it for later connections.
     pub async fn connect(
         host: &str,
         port: u16,
         user: &str,
         private_key_pem: &str,
         timeout_secs: u64,
+        known_fingerprints: &[String],
     ) -> Result<Self, AppError> {
         let config = Arc::new(client::Config::default());
 
@@ -39,9 +179,14 @@ impl SshSession {
 
         let start = std::time::Instant::now();
         let deadline = std::time::Duration::from_secs(timeout_secs);
+        let seen_fingerprint = Arc::new(Mutex::new(None));
 
         loop {
-            match client::connect(config.clone(), (host, port), SshHandler).await {
+            let handler = SshHandler {
+                known_fingerprints: known_fingerprints.to_vec(),
+                seen_fingerprint: seen_fingerprint.clone(),
+            };
+            match client::connect(config.clone(), (host, port), handler).await {
                 Ok(mut handle) => {
                     let auth_ok = handle
                         .authenticate_publickey(user, Arc::new(key_pair.clone()))
@@ -52,10 +197,39 @@ impl SshSession {
                         return Err(AppError::Ssh("SSH authentication rejected".into()));
                     }
 
-                    tracing::info!("SSH connected to {}:{}", host, port);
-                    return Ok(SshSession { session: handle });
+                    let host_key_fingerprint =
+                        seen_fingerprint.lock().unwrap().clone().unwrap_or_default();
+                    tracing::info!(
+                        "SSH connected to {}:{} (host key {})",
+                        host,
+                        port,
+                        host_key_fingerprint
+                    );
+                    return Ok(SshSession {
+                        session: handle,
+                        host_key_fingerprint,
+                    });
                 }
                 Err(e) => {
+                    // A rejected host key will not fix itself by retrying.
+                    let seen = seen_fingerprint.lock().unwrap().clone();
+                    if let Some(actual) = seen {
+                        if !known_fingerprints.is_empty()
+                            && !host_key::matches_any(known_fingerprints, &actual)
+                        {
+                            tracing::error!(
+                                "SSH host key mismatch for {}: got {}, expected one of {:?}",
+                                host,
+                                actual,
+                                known_fingerprints
+                            );
+                            return Err(AppError::HostKeyMismatch {
+                                host: host.to_string(),
+                                expected: known_fingerprints.join(", "),
+                                actual,
+                            });
+                        }
+                    }
                     if start.elapsed() > deadline {
                         return Err(AppError::Ssh(format!(
                             "SSH connection timeout after {}s: {}",
@@ -69,6 +243,11 @@ impl SshSession {
         }
     }
 
+    /// The server's host key fingerprint (`SHA256:...`) seen during connect.
+    pub fn host_key_fingerprint(&self) -> &str {
+        &self.host_key_fingerprint
+    }
+
     /// Execute a command and return stdout as a string.
     pub async fn execute(&self, command: &str) -> Result<String, AppError> {
         let mut channel = self
@@ -109,12 +288,238 @@ impl SshSession {
         Ok(String::from_utf8_lossy(&output).to_string())
     }
 
-    /// Upload file content to a remote path.
-    pub async fn upload_file(&self, remote_path: &str, content: &str) -> Result<(), AppError> {
-        // Write via echo command to avoid needing SFTP
-        let escaped = content.replace('\\', "\\\\").replace('\'', "'\\''");
-        let cmd = format!("echo '{}' | sudo tee {} > /dev/null", escaped, remote_path);
-        self.execute(&cmd).await?;
+    /// Execute a command, passing each line of its stdout and stderr to
+    /// `on_line` as it arrives. Fails if the command runs longer than
+    /// `timeout`. Returns all of the output, like `execute`.
+    pub async fn execute_streaming(
+        &self,
+        command: &str,
+        timeout: Duration,
+        on_line: &mut (dyn FnMut(OutputStream, &str) + Send),
+    ) -> Result<String, AppError> {
+        let mut channel = self
+            .session
+            .channel_open_session()
+            .await
+            .map_err(|e| AppError::Ssh(format!("Failed to open channel: {}", e)))?;
+
+        channel
+            .exec(true, command)
+            .await
+            .map_err(|e| AppError::Ssh(format!("Failed to exec command: {}", e)))?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let (mut stdout, mut stderr) = (LineBuffer::default(), LineBuffer::default());
+        let mut lines: Vec<String> = Vec::new();
+        let mut exit_status = None;
+        let mut forward = |stream: OutputStream, new_lines: Vec<String>| {
+            for line in new_lines {
+                on_line(stream, &line);
+                lines.push(line);
+            }
+        };
+
+        loop {
+            let msg = match tokio::time::timeout_at(deadline, channel.wait()).await {
+                Ok(msg) => msg,
+                Err(_) => {
+                    let _ = channel.close().await;
+                    return Err(AppError::Ssh(format!(
+                        "Command '{}' did not finish within {}s",
+                        command,
+                        timeout.as_secs()
+                    )));
+                }
+            };
+            match msg {
+                Some(russh::ChannelMsg::Data { ref data }) => {
+                    forward(OutputStream::Stdout, stdout.push(data));
+                }
+                Some(russh::ChannelMsg::ExtendedData { ref data, .. }) => {
+                    forward(OutputStream::Stderr, stderr.push(data));
+                }
+                Some(russh::ChannelMsg::ExitStatus {
+                    exit_status: status,
+                }) => {
+                    exit_status = Some(status);
+                }
+                None => break,
+                _ => {}
+            }
+        }
+        forward(OutputStream::Stdout, stdout.take().into_iter().collect());
+        forward(OutputStream::Stderr, stderr.take().into_iter().collect());
+
+        match exit_status {
+            Some(status) if status != 0 => {
+                let tail = &lines[lines.len().saturating_sub(FAILURE_TAIL_LINES)..];
+                Err(AppError::Ssh(format!(
+                    "Command '{}' exited with status {}: {}",
+                    command,
+                    status,
+                    tail.join("\n")
+                )))
+            }
+            _ => Ok(lines.join("\n")),
+        }
+    }
+
+    /// Open an SFTP session over a new channel.
+    async fn sftp(&self) -> Result<SftpSession, AppError> {
+        let channel = self
+            .session
+            .channel_open_session()
+            .await
+            .map_err(|e| AppError::Ssh(format!("Failed to open channel: {}", e)))?;
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .map_err(|e| AppError::Ssh(format!("Failed to start SFTP: {}", e)))?;
+        SftpSession::new(channel.into_stream())
+            .await
+            .map_err(|e| AppError::Ssh(format!("Failed to start SFTP: {}", e)))
+    }
+
+    /// A new empty file only the login user can read, for staging a transfer.
+    async fn staging_file(&self) -> Result<String, AppError> {
+        let path = self
+            .execute("mktemp /tmp/createmyvpn.XXXXXXXXXX")
+            .await?
+            .trim()
+            .to_string();
+        if path.is_empty() {
+            return Err(AppError::Ssh("mktemp printed no path".into()));
+        }
+        Ok(path)
+    }
+
+    /// Write `content` to `remote_path` with `mode`. The content goes over
+    /// SFTP to a private staging file, is moved into place in one rename, and
+    /// the result is checked against its SHA-256.
+    pub async fn upload_file(
+        &self,
+        remote_path: &str,
+        content: &[u8],
+        mode: FileMode,
+    ) -> Result<(), AppError> {
+        let staging = self.staging_file().await?;
+        let sent = async {
+            let sftp = self.sftp().await?;
+            let mut file = sftp
+                .open_with_flags(staging.as_str(), OpenFlags::WRITE | OpenFlags::TRUNCATE)
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to open {}: {}", staging, e)))?;
+            file.write_all(content).await?;
+            file.shutdown().await?;
+            let _ = sftp.close().await;
+            Ok::<(), AppError>(())
+        }
+        .await;
+        if let Err(e) = sent {
+            tracing::error!("Failed to upload {}: {}", remote_path, e);
+            let _ = self
+                .execute(&format!("rm -f {}", shell_quote(&staging)))
+                .await;
+            return Err(e);
+        }
+
+        let output = self
+            .execute(&install_command(&staging, remote_path, mode))
+            .await?;
+        check_digest(remote_path, &output, &sha256_hex(content))?;
+        tracing::debug!(
+            "Uploaded {} ({} bytes, mode {:o})",
+            remote_path,
+            content.len(),
+            mode.mode
+        );
         Ok(())
     }
+
+    /// Read `remote_path`, which may be root's. It is copied to a private
+    /// staging file, fetched over SFTP and checked against its SHA-256.
+    pub async fn download_file(&self, remote_path: &str) -> Result<Vec<u8>, AppError> {
+        let staging = self.staging_file().await?;
+        let quoted = shell_quote(&staging);
+        let fetched = async {
+            let output = self
+                .execute(&format!(
+                    "sudo cat {} > {staging} && sha256sum {staging}",
+                    shell_quote(remote_path),
+                    staging = quoted
+                ))
+                .await?;
+            let content = self
+                .sftp()
+                .await?
+                .read(staging.as_str())
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to read {}: {}", remote_path, e)))?;
+            check_digest(remote_path, &output, &sha256_hex(&content))?;
+            Ok::<Vec<u8>, AppError>(content)
+        }
+        .await;
+        let _ = self.execute(&format!("rm -f {}", quoted)).await;
+        let content = fetched?;
+        tracing::debug!("Downloaded {} ({} bytes)", remote_path, content.len());
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_come_out_as_they_complete() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"Reading package").is_empty());
+        assert_eq!(
+            buffer.push(b" lists...\nBuilding\n\n"),
+            vec!["Reading package lists...", "Building"]
+        );
+        assert_eq!(buffer.push(b"10%\r20%\rdone"), vec!["10%", "20%"]);
+        assert_eq!(buffer.take().as_deref(), Some("done"));
+        assert_eq!(buffer.take(), None);
+    }
+
+    #[test]
+    fn quotes_survive_the_shell() {
+        assert_eq!(
+            shell_quote("/etc/wireguard/wg0.conf"),
+            "'/etc/wireguard/wg0.conf'"
+        );
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn digest_matches_sha256sum() {
+        let empty = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(sha256_hex(b""), empty);
+        let output = format!("{}  /etc/wireguard/wg0.conf\n", empty);
+        assert_eq!(parse_sha256sum(&output), Some(empty));
+        assert!(check_digest("/etc/wireguard/wg0.conf", &output, empty).is_ok());
+        assert!(check_digest("/etc/wireguard/wg0.conf", &output, &sha256_hex(b"x")).is_err());
+        assert_eq!(parse_sha256sum("sha256sum: /nope: No such file"), None);
+    }
+
+    #[test]
+    fn install_moves_the_file_into_place_with_its_mode() {
+        let cmd = install_command(
+            "/tmp/createmyvpn.abc",
+            "/etc/wireguard/wg0.conf",
+            FileMode::PRIVATE,
+        );
+        let install = cmd
+            .find("sudo install -m 600 -o root -g root '/tmp/createmyvpn.abc' '/etc/wireguard/wg0.conf.createmyvpn-new'")
+            .unwrap();
+        let rename = cmd
+            .find("sudo mv -f '/etc/wireguard/wg0.conf.createmyvpn-new' '/etc/wireguard/wg0.conf'")
+            .unwrap();
+        let digest = cmd
+            .find("sudo sha256sum '/etc/wireguard/wg0.conf'")
+            .unwrap();
+        assert!(install < rename && rename < digest);
+        assert!(cmd.contains("rm -f '/tmp/createmyvpn.abc'"));
+    }
 }
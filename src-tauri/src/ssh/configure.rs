@@ -1,44 +1,190 @@
+use std::time::Duration;
+
 use crate::error::AppError;
-use crate::ssh::client::SshSession;
+use crate::progress::ProgressSink;
+use crate::ssh::client::{FileMode, OutputStream, SshSession};
+use crate::wireguard::bootstrap::{self, BootstrapStatus};
+use crate::wireguard::keys::WgKeyPair;
+use crate::wireguard::server_config;
+use crate::wireguard::subnet::TunnelSubnet;
+
+pub const SYSCTL_CONF: &str = "net.ipv4.ip_forward=1
+net.ipv6.conf.all.forwarding=1
+net.ipv6.conf.all.accept_ra=2
+net.ipv6.conf.default.accept_ra=2
+";
+
+/// Succeeds, as root, on a server that can carry the tunnel's IPv6 side: it
+/// has a global IPv6 address and ip6tables can NAT.
+pub const IPV6_CHECK: &str = "ip -6 addr show scope global | grep -q inet6 && \
+     ip6tables -t nat -L POSTROUTING -n >/dev/null 2>&1";
+
+/// How long cloud-init may take to finish first boot.
+const CLOUD_INIT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// How long one apt-get run may take.
+const APT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Run a slow command, passing its output to the log and to `progress` as
+/// it arrives.
+async fn execute_logged(
+    ssh: &SshSession,
+    progress: &dyn ProgressSink,
+    command: &str,
+    timeout: Duration,
+) -> Result<String, AppError> {
+    ssh.execute_streaming(command, timeout, &mut |stream, line| {
+        match stream {
+            OutputStream::Stdout => tracing::info!("server: {}", line),
+            OutputStream::Stderr => tracing::info!("server (stderr): {}", line),
+        }
+        progress.log(line);
+    })
+    .await
+}
+
+/// The interface named after `dev` in `ip route` output, e.g. "ens5" in
+/// "default via 172.31.0.1 dev ens5 proto dhcp src 172.31.5.10 metric 100".
+/// Only plain interface names are accepted; it ends up in shell commands.
+pub fn parse_route_interface(output: &str) -> Option<String> {
+    let words: Vec<&str> = output.split_whitespace().collect();
+    let name = words.windows(2).find(|w| w[0] == "dev")?[1];
+    let plain = name.len() <= 15
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    plain.then(|| name.to_string())
+}
+
+/// The interface the server's default route leaves through. Not always
+/// `eth0`: `ens5` on AWS Nitro, `enp1s0` or `ens3` on many other hosts.
+pub async fn detect_egress_interface(ssh: &SshSession) -> Result<String, AppError> {
+    let output = ssh.execute("ip -4 route show default").await?;
+    if let Some(name) = parse_route_interface(&output) {
+        return Ok(name);
+    }
+    // No default route listed (e.g. policy routing): ask for the route out.
+    let output = ssh.execute("ip -4 route get 1.1.1.1").await?;
+    parse_route_interface(&output).ok_or_else(|| {
+        AppError::Ssh(format!(
+            "Could not find the server's outgoing network interface. ip route said: {}",
+            output.trim()
+        ))
+    })
+}
+
+/// Whether the server has IPv6 to masquerade the tunnel's IPv6 side out of.
+/// Not every host does: IPv6 is optional on DigitalOcean and on many VPSs.
+pub async fn detect_ipv6(ssh: &SshSession) -> Result<bool, AppError> {
+    let output = ssh
+        .execute(&format!(
+            "sudo sh -c '{}' && echo yes || echo no",
+            IPV6_CHECK
+        ))
+        .await?;
+    Ok(output.trim() == "yes")
+}
 
 /// Full WireGuard server configuration sequence (replaces Ansible playbook).
+/// wg0.conf is rendered here, once the server's egress interface is known.
+/// Output of the slow steps (cloud-init, apt) goes to `progress` line by line.
+/// Returns whether the tunnel carries IPv6 (see `detect_ipv6`).
 pub async fn configure_wireguard(
     ssh: &SshSession,
-    server_config: &str,
-    server_public_key: &str,
-) -> Result<(), AppError> {
+    progress: &dyn ProgressSink,
+    server_keys: &WgKeyPair,
+    client_public_key: &str,
+    listen_port: u16,
+    subnet: &TunnelSubnet,
+) -> Result<bool, AppError> {
     tracing::info!("Starting WireGuard configuration...");
 
     // 0. Wait for cloud-init to finish so it releases the apt lock.
     //    Ubuntu instances run cloud-init on first boot which holds apt for
     //    several minutes. We must wait before touching apt at all.
+    //    cloud-init 23.4+ (Ubuntu 24.04) exits 2 when it finished with
+    //    recoverable errors, which still means done.
+    //    Minimal Debian images log in as root without sudo installed.
     tracing::info!("Waiting for cloud-init to complete (this can take 1-2 min)...");
-    ssh.execute("sudo cloud-init status --wait").await?;
+    execute_logged(
+        ssh,
+        progress,
+        "command -v sudo >/dev/null || { cloud-init status --wait; \
+         DEBIAN_FRONTEND=noninteractive apt-get update -y && \
+         DEBIAN_FRONTEND=noninteractive apt-get install -y sudo; }",
+        CLOUD_INIT_TIMEOUT + APT_TIMEOUT,
+    )
+    .await?;
+    execute_logged(
+        ssh,
+        progress,
+        "sudo cloud-init status --wait >/dev/null; s=$?; [ $s -eq 0 ] || [ $s -eq 2 ]",
+        CLOUD_INIT_TIMEOUT,
+    )
+    .await?;
     tracing::info!("cloud-init complete, proceeding with package installation");
 
-    // 1. Install WireGuard
+    // 1. Install WireGuard, and iptables for its NAT rules (not preinstalled
+    //    on Debian 12)
     tracing::info!("Installing WireGuard packages...");
-    ssh.execute("sudo DEBIAN_FRONTEND=noninteractive apt-get update -y")
-        .await?;
-    ssh.execute("sudo DEBIAN_FRONTEND=noninteractive apt-get install -y wireguard wireguard-tools")
-        .await?;
+    execute_logged(
+        ssh,
+        progress,
+        "sudo DEBIAN_FRONTEND=noninteractive apt-get update -y",
+        APT_TIMEOUT,
+    )
+    .await?;
+    execute_logged(
+        ssh,
+        progress,
+        "sudo DEBIAN_FRONTEND=noninteractive apt-get install -y wireguard wireguard-tools iptables",
+        APT_TIMEOUT,
+    )
+    .await?;
 
-    // 2. Ensure IP forwarding is enabled (backup in case user_data didn't run)
-    tracing::info!("Enabling IP forwarding...");
-    ssh.execute("echo 'net.ipv4.ip_forward=1' | sudo tee /etc/sysctl.d/99-vpn.conf")
-        .await?;
+    // 2. Ensure IP forwarding is enabled (backup in case user_data didn't run).
+    //    accept_ra=2 keeps the IPv6 default route learned from router
+    //    advertisements once forwarding is on.
+    tracing::info!("Enabling IPv4/IPv6 forwarding...");
+    ssh.upload_file(
+        "/etc/sysctl.d/99-vpn.conf",
+        SYSCTL_CONF.as_bytes(),
+        FileMode::PUBLIC,
+    )
+    .await?;
     ssh.execute("sudo sysctl -p /etc/sysctl.d/99-vpn.conf")
         .await?;
 
-    // 3. Deploy WireGuard server config
-    tracing::info!("Deploying wg0.conf...");
-    ssh.upload_file("/etc/wireguard/wg0.conf", server_config)
-        .await?;
-    ssh.execute("sudo chmod 600 /etc/wireguard/wg0.conf").await?;
+    // 3. Deploy WireGuard server config, NATed out of the default route
+    let egress_interface = detect_egress_interface(ssh).await?;
+    let ipv6 = detect_ipv6(ssh).await?;
+    tracing::info!(
+        "Deploying wg0.conf (tunnel {}, egress via {}, {})...",
+        subnet,
+        egress_interface,
+        if ipv6 { "dual-stack" } else { "IPv4 only" }
+    );
+    let server_config = server_config::render_server_config(
+        &server_keys.private_key,
+        client_public_key,
+        listen_port,
+        subnet,
+        &egress_interface,
+        ipv6,
+    );
+    ssh.upload_file(
+        "/etc/wireguard/wg0.conf",
+        server_config.as_bytes(),
+        FileMode::PRIVATE,
+    )
+    .await?;
 
     // 4. Save server public key for reference
-    ssh.upload_file("/etc/wireguard/server_public.key", server_public_key)
-        .await?;
+    ssh.upload_file(
+        "/etc/wireguard/server_public.key",
+        format!("{}\n", server_keys.public_key).as_bytes(),
+        FileMode::PUBLIC,
+    )
+    .await?;
 
     // 5. Enable and start WireGuard
     tracing::info!("Starting WireGuard service...");
@@ -57,5 +203,89 @@ pub async fn configure_wireguard(
         )));
     }
 
-    Ok(())
+    Ok(ipv6)
+}
+
+/// Wait for the first-boot script in the server's user data (see
+/// `wireguard::bootstrap`) to finish, passing its log to `progress` as it is
+/// written, and fail with the end of the log unless it got WireGuard
+/// running. Returns whether the tunnel carries IPv6, which the script
+/// decides on the server. Its marker and log are world-readable, so nothing
+/// here needs sudo.
+pub async fn wait_for_bootstrap(
+    ssh: &SshSession,
+    progress: &dyn ProgressSink,
+) -> Result<bool, AppError> {
+    tracing::info!("Waiting for cloud-init to run the bootstrap script (this can take 1-2 min)...");
+    // Follow the log until the script writes its marker, or cloud-init is
+    // done without it having done so (the marker check below then fails).
+    let follow = format!(
+        "tail -n +1 -F {log} 2>/dev/null & t=$!; \
+         until [ -s {marker} ] || cloud-init status 2>/dev/null | grep -qE '^status: (done|error|disabled)'; \
+         do sleep 2; done; sleep 1; kill $t 2>/dev/null; true",
+        log = bootstrap::LOG_PATH,
+        marker = bootstrap::MARKER_PATH,
+    );
+    if let Err(e) = execute_logged(ssh, progress, &follow, CLOUD_INIT_TIMEOUT).await {
+        // Most likely the timeout; the marker still says how far it got.
+        tracing::warn!("Stopped waiting for the bootstrap script: {}", e);
+    }
+    let marker = ssh
+        .execute(&format!("cat {} 2>/dev/null; true", bootstrap::MARKER_PATH))
+        .await?;
+    match bootstrap::parse_marker(&marker) {
+        BootstrapStatus::Ready => {
+            tracing::info!("Bootstrap complete, WireGuard is running");
+            let ipv4_only = ssh
+                .execute(&format!(
+                    "grep -qF '{}' {} && echo yes || echo no",
+                    bootstrap::IPV4_ONLY_NOTICE,
+                    bootstrap::LOG_PATH
+                ))
+                .await?;
+            Ok(ipv4_only.trim() != "yes")
+        }
+        status => {
+            let log = ssh
+                .execute(&format!("tail -n 20 {} 2>/dev/null; true", bootstrap::LOG_PATH))
+                .await
+                .unwrap_or_default();
+            Err(AppError::Ssh(format!(
+                "The server's first-boot setup {}. Last lines of {}:\n{}",
+                if status == BootstrapStatus::Failed {
+                    "failed"
+                } else {
+                    "never finished"
+                },
+                bootstrap::LOG_PATH,
+                log.trim_end()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interface_from_default_route() {
+        let out = "default via 172.31.0.1 dev ens5 proto dhcp src 172.31.5.10 metric 100\n";
+        assert_eq!(parse_route_interface(out).as_deref(), Some("ens5"));
+    }
+
+    #[test]
+    fn interface_from_route_get() {
+        let out = "1.1.1.1 via 10.0.0.1 dev enp1s0 src 10.0.0.5 uid 0\n    cache\n";
+        assert_eq!(parse_route_interface(out).as_deref(), Some("enp1s0"));
+    }
+
+    #[test]
+    fn no_route_or_odd_names_are_rejected() {
+        assert_eq!(parse_route_interface(""), None);
+        assert_eq!(
+            parse_route_interface("default via 10.0.0.1 dev eth0;reboot"),
+            None
+        );
+    }
 }
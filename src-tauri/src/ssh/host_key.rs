@@ -0,0 +1,135 @@
+//! SSH host key fingerprint helpers for trust-on-first-use pinning.
+//!
+//! Fingerprints are stored in OpenSSH's `SHA256:<base64-nopad>` form, the same
+//! string `ssh-keygen -lf /etc/ssh/ssh_host_ed25519_key.pub` prints, so users
+//! can compare them by eye.
+
+use crate::error::AppError;
+
+const CONSOLE_BEGIN: &str = "-----BEGIN SSH HOST KEY FINGERPRINTS-----";
+const CONSOLE_END: &str = "-----END SSH HOST KEY FINGERPRINTS-----";
+
+/// Normalise a fingerprint pasted by a user or read from a console log:
+/// trims whitespace, adds the `SHA256:` prefix if missing and drops base64 padding.
+pub fn normalize_fingerprint(fp: &str) -> String {
+    let fp = fp.trim();
+    let body = fp.strip_prefix("SHA256:").unwrap_or(fp);
+    format!("SHA256:{}", body.trim_end_matches('='))
+}
+
+/// Check a fingerprint the user supplied and normalise it. Only SHA-256
+/// fingerprints are accepted, as `SHA256:` plus 43 base64 characters or the
+/// bare 43 characters; anything else, e.g. an `MD5:` one, could never match
+/// and would fail the connect as a host key mismatch.
+pub fn parse_user_fingerprint(fp: &str) -> Result<String, AppError> {
+    let normalized = normalize_fingerprint(fp);
+    let body = &normalized["SHA256:".len()..];
+    let valid = body.len() == 43
+        && body
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/');
+    if !valid {
+        return Err(AppError::Ssh(format!(
+            "Invalid host key fingerprint '{}': expected SHA256: followed by 43 base64 \
+             characters, as printed by `ssh-keygen -lf`",
+            fp.trim()
+        )));
+    }
+    Ok(normalized)
+}
+
+/// True if `actual` matches any of the pinned fingerprints.
+pub fn matches_any(known: &[String], actual: &str) -> bool {
+    let actual = normalize_fingerprint(actual);
+    known.iter().any(|k| normalize_fingerprint(k) == actual)
+}
+
+/// Extract host key fingerprints from an EC2 console log. cloud-init prints a
+/// block like:
+/// ```text
+/// ec2: -----BEGIN SSH HOST KEY FINGERPRINTS-----
+/// ec2: 256 SHA256:AbC... root@ip-10-0-1-5 (ED25519)
+/// ec2: -----END SSH HOST KEY FINGERPRINTS-----
+/// ```
+pub fn parse_console_fingerprints(console: &str) -> Vec<String> {
+    let mut inside = false;
+    let mut fingerprints = Vec::new();
+    for line in console.lines() {
+        if line.contains(CONSOLE_BEGIN) {
+            inside = true;
+            continue;
+        }
+        if line.contains(CONSOLE_END) {
+            inside = false;
+            continue;
+        }
+        if inside {
+            if let Some(token) = line.split_whitespace().find(|t| t.starts_with("SHA256:")) {
+                fingerprints.push(normalize_fingerprint(token));
+            }
+        }
+    }
+    fingerprints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_adds_prefix_and_strips_padding() {
+        assert_eq!(normalize_fingerprint("  abc123=  "), "SHA256:abc123");
+        assert_eq!(normalize_fingerprint("SHA256:abc123"), "SHA256:abc123");
+    }
+
+    #[test]
+    fn user_fingerprint_must_be_sha256() {
+        let body = "nThbg6kXUpJWGl7E1IGOCspRomTxdCARLviKw6E5SY8";
+        let expected = format!("SHA256:{}", body);
+        assert_eq!(parse_user_fingerprint(body).unwrap(), expected);
+        assert_eq!(parse_user_fingerprint(&expected).unwrap(), expected);
+        assert_eq!(
+            parse_user_fingerprint(&format!(" SHA256:{}= ", body)).unwrap(),
+            expected
+        );
+
+        let md5 = "MD5:16:27:ac:a5:76:28:2d:36:63:1b:56:4d:eb:df:a6:48";
+        let err = parse_user_fingerprint(md5).unwrap_err().to_string();
+        assert!(err.contains("Invalid host key fingerprint"), "{}", err);
+        assert!(parse_user_fingerprint("SHA256:tooshort").is_err());
+        assert!(parse_user_fingerprint(&format!("SHA256:{}!", &body[1..])).is_err());
+    }
+
+    #[test]
+    fn matches_any_ignores_formatting_differences() {
+        let known = vec!["SHA256:abc123".to_string()];
+        assert!(matches_any(&known, "abc123"));
+        assert!(!matches_any(&known, "SHA256:zzz"));
+        assert!(!matches_any(&[], "SHA256:abc123"));
+    }
+
+    #[test]
+    fn parse_console_fingerprints_reads_cloud_init_block() {
+        let console = "\
+[   10.1] cloud-init[800]: Cloud-init v. 23.1 finished
+ec2: #############################################################
+ec2: -----BEGIN SSH HOST KEY FINGERPRINTS-----
+ec2: 256 SHA256:EcDsAkEy root@ip-10-0-1-5 (ECDSA)
+ec2: 256 SHA256:Ed25519kEy root@ip-10-0-1-5 (ED25519)
+ec2: 3072 SHA256:RsAkEy root@ip-10-0-1-5 (RSA)
+ec2: -----END SSH HOST KEY FINGERPRINTS-----
+ec2: #############################################################
+SHA256:OutsideTheBlock
+";
+        let fps = parse_console_fingerprints(console);
+        assert_eq!(
+            fps,
+            vec!["SHA256:EcDsAkEy", "SHA256:Ed25519kEy", "SHA256:RsAkEy"]
+        );
+    }
+
+    #[test]
+    fn parse_console_fingerprints_empty_when_block_missing() {
+        assert!(parse_console_fingerprints("booting...\n").is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::server_image::{Architecture, OsRelease, ServerImage};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum DeploymentStatus {
@@ -32,14 +34,42 @@ impl Default for VpnConnectionStatus {
     }
 }
 
-/// Tracks every AWS/DO resource created so we can tear down safely.
+/// Live counters for the active tunnel. Byte and packet counts are for
+/// encrypted UDP datagrams on the wire, keepalives and handshakes included,
+/// matching what `wg show` reports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TunnelStats {
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+    /// When the last handshake completed. None until the server first answers.
+    pub last_handshake_at: Option<DateTime<Utc>>,
+    /// Datagrams from the server that failed to decrypt or authenticate.
+    pub decapsulate_errors: u64,
+    /// Round-trip time estimated by boringtun from the last handshake.
+    pub rtt_ms: Option<u32>,
+}
+
+/// Tracks every cloud resource created so we can tear down safely.
 /// Each field is set immediately after the resource is created.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeploymentState {
+    /// Key of this deployment in the store, e.g. "3f9c2a1b" or "eu-exit".
+    #[serde(default)]
+    pub id: String,
+    /// Display name, e.g. "AWS eu-west-1".
+    #[serde(default)]
+    pub name: String,
     pub status: DeploymentStatus,
-    /// "aws", "byo", or "do". None in old state files is treated as "aws".
+    /// "aws", "byo", "do", "hetzner", "vultr" or "linode". None in old state
+    /// files is treated as "aws".
     pub deployment_mode: Option<String>,
     pub region: Option<String>,
+    /// What the server was created with. None in old state files and for
+    /// BYO servers; see `server_image`.
+    pub os_release: Option<OsRelease>,
+    pub architecture: Option<Architecture>,
     // AWS-specific fields
     pub vpc_id: Option<String>,
     pub igw_id: Option<String>,
@@ -54,17 +84,172 @@ pub struct DeploymentState {
     pub elastic_ip: Option<String>,
     pub ssh_private_key: Option<String>,
     pub ssh_user: Option<String>,
+    /// SSH port used to reach the server. None means 22.
+    pub ssh_port: Option<u16>,
+    /// Pinned SSH host key fingerprint (`SHA256:...`), recorded on first connect.
+    pub ssh_host_key_fingerprint: Option<String>,
+    /// The address SSH is let in from, e.g. "203.0.113.7/32": the public IP
+    /// of the computer that deployed or last opened it. None in old state
+    /// files, whose firewalls let SSH in from anywhere.
+    pub ssh_source: Option<String>,
+    /// The firewall has no SSH rule: it was removed (see
+    /// `commands::ssh_access`), or never added on a deploy without SSH.
+    #[serde(default)]
+    pub ssh_locked: bool,
+    /// WireGuard was set up by the server's first-boot script (see
+    /// `wireguard::bootstrap`) rather than over SSH.
+    #[serde(default)]
+    pub user_data_bootstrap: bool,
     pub server_public_key: Option<String>,
     pub client_private_key: Option<String>,
     pub client_public_key: Option<String>,
     pub client_config: Option<String>,
+    /// Tunnel network the server was set up with, e.g. "10.8.0.0/24". None in
+    /// old state files means that default.
+    pub tunnel_cidr: Option<String>,
+    /// Whether the tunnel carries IPv6 too, which needs IPv6 on the server
+    /// (see `ssh::configure::detect_ipv6`). None in old state files, or after
+    /// a deploy without SSH that couldn't check, means it does.
+    pub tunnel_ipv6: Option<bool>,
     pub deployed_at: Option<DateTime<Utc>>,
     pub auto_destroy_at: Option<DateTime<Utc>>,
+    /// Price per hour of the server and what it needs, recorded at deploy
+    /// time (see `pricing::hourly_rate`). None if unknown or in old state files.
+    pub hourly_cost: Option<f64>,
+    /// "USD" or "EUR", the currency `hourly_cost` is in.
+    pub cost_currency: Option<String>,
     pub error_message: Option<String>,
+    /// What `status` was before it became `Destroying`, so a destroy or
+    /// rollback cut short by closing the app can go back to it.
+    pub status_before_destroy: Option<DeploymentStatus>,
+    /// Last deploy step that finished (1-7), so an interrupted deploy can
+    /// resume after it. 0 in old state files.
+    #[serde(default)]
+    pub completed_step: u32,
     // DigitalOcean-specific fields
     pub droplet_id: Option<u64>,
     pub do_firewall_id: Option<String>,
     pub do_ssh_key_id: Option<u64>,
+    // Hetzner-specific fields
+    pub hetzner_server_id: Option<u64>,
+    pub hetzner_firewall_id: Option<u64>,
+    pub hetzner_ssh_key_id: Option<u64>,
+    // Vultr-specific fields
+    pub vultr_instance_id: Option<String>,
+    pub vultr_firewall_id: Option<String>,
+    pub vultr_ssh_key_id: Option<String>,
+    // Linode-specific fields (the SSH key is not stored in the account)
+    pub linode_instance_id: Option<u64>,
+    pub linode_firewall_id: Option<u64>,
+    /// Additional client devices registered on the server, on top of the
+    /// primary client at 10.8.0.2. Missing in old state files.
+    #[serde(default)]
+    pub peers: Vec<PeerInfo>,
+}
+
+impl DeploymentState {
+    /// The image the server was created with; old state files predate the
+    /// choice and used the default.
+    pub fn server_image(&self) -> ServerImage {
+        ServerImage {
+            os: self.os_release.unwrap_or_default(),
+            arch: self.architecture.unwrap_or_default(),
+        }
+    }
+
+    /// Whether client configs route IPv6 into the tunnel.
+    pub fn carries_ipv6(&self) -> bool {
+        self.tunnel_ipv6.unwrap_or(true)
+    }
+
+    /// The cloud resources this deployment has recorded, e.g. "VPC vpc-1" or
+    /// "Droplet 42", in creation order. BYO servers are not ours to remove and
+    /// are never listed.
+    pub fn cloud_resources(&self) -> Vec<String> {
+        if self.deployment_mode.as_deref() == Some("byo") {
+            return Vec::new();
+        }
+        let labelled = [
+            ("VPC", self.vpc_id.clone()),
+            ("internet gateway", self.igw_id.clone()),
+            ("subnet", self.subnet_id.clone()),
+            ("route table", self.route_table_id.clone()),
+            ("security group", self.security_group_id.clone()),
+            ("key pair", self.key_pair_name.clone()),
+            ("instance", self.instance_id.clone()),
+            ("Elastic IP", self.allocation_id.clone()),
+            ("firewall", self.do_firewall_id.clone()),
+            ("SSH key", self.do_ssh_key_id.map(|id| id.to_string())),
+            ("Droplet", self.droplet_id.map(|id| id.to_string())),
+            ("firewall", self.hetzner_firewall_id.map(|id| id.to_string())),
+            ("SSH key", self.hetzner_ssh_key_id.map(|id| id.to_string())),
+            ("server", self.hetzner_server_id.map(|id| id.to_string())),
+            ("firewall group", self.vultr_firewall_id.clone()),
+            ("SSH key", self.vultr_ssh_key_id.clone()),
+            ("instance", self.vultr_instance_id.clone()),
+            ("firewall", self.linode_firewall_id.map(|id| id.to_string())),
+            ("Linode", self.linode_instance_id.map(|id| id.to_string())),
+        ];
+        labelled
+            .into_iter()
+            .filter_map(|(kind, id)| id.map(|id| format!("{} {}", kind, id)))
+            .collect()
+    }
+}
+
+/// Default display name for a deployment.
+pub fn deployment_name(
+    mode: Option<&str>,
+    region: Option<&str>,
+    server_ip: Option<&str>,
+) -> String {
+    let (provider, place) = match mode {
+        Some("byo") => ("Own server", server_ip),
+        Some("do") => ("DigitalOcean", region),
+        Some("hetzner") => ("Hetzner", region),
+        Some("vultr") => ("Vultr", region),
+        Some("linode") => ("Linode", region),
+        _ => ("AWS", region),
+    };
+    match place {
+        Some(place) => format!("{} {}", provider, place),
+        None => provider.to_string(),
+    }
+}
+
+/// An extra WireGuard client (laptop, phone, teammate) added after deploy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerInfo {
+    pub name: String,
+    pub public_key: String,
+    /// Tunnel address without prefix, e.g. "10.8.0.3".
+    pub address: String,
+    pub created_at: DateTime<Utc>,
+    pub client_config: Option<String>,
+}
+
+/// A cloud resource tagged as created by this app that no local deployment
+/// tracks, e.g. after state.json was lost.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrphanResource {
+    /// "instance", "elastic_ip", "vpc", "droplet", ...
+    pub kind: String,
+    pub id: String,
+    pub region: String,
+    pub name: Option<String>,
+    /// Short human-readable facts, e.g. "t2.micro, running" or "54.1.2.3".
+    pub detail: Option<String>,
+    pub monthly_cost_usd: f64,
+}
+
+/// Result of scanning a provider for orphaned resources.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OrphanScan {
+    pub resources: Vec<OrphanResource>,
+    pub monthly_cost_usd: f64,
+    /// Regions that could not be scanned (e.g. not enabled for the account),
+    /// as "region: reason".
+    pub errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -72,6 +257,49 @@ pub struct AppSettings {
     pub region: String,
     pub instance_type: String,
     pub wireguard_port: u16,
+    /// Block all non-tunnel traffic while connected (Linux only).
+    #[serde(default)]
+    pub kill_switch: bool,
+    /// Let LAN traffic (printers, NAS, ...) bypass the kill switch.
+    #[serde(default)]
+    pub kill_switch_allow_lan: bool,
+    /// Tear down whatever a failed deploy created instead of leaving it
+    /// billing. On unless turned off; missing in old settings files.
+    #[serde(default = "default_true")]
+    pub rollback_on_failure: bool,
+    /// Tunnel network for new deployments (see `wireguard::subnet`).
+    #[serde(default = "default_tunnel_cidr")]
+    pub tunnel_cidr: String,
+    /// Returns this computer's public IPv4 address as plain text; SSH to a new
+    /// server is only let in from there (see `public_ip`).
+    #[serde(default = "default_ip_lookup_url")]
+    pub ip_lookup_url: String,
+    /// Remove the SSH rule once WireGuard is configured. It is opened again
+    /// for as long as a maintenance command needs it.
+    #[serde(default)]
+    pub lock_down_ssh: bool,
+    /// Never let SSH in: the server sets WireGuard up from its user data
+    /// and the deploy checks it with a WireGuard handshake. AWS and
+    /// DigitalOcean only.
+    #[serde(default)]
+    pub deploy_without_ssh: bool,
+    /// The OS and architecture new cloud servers are created with.
+    #[serde(default)]
+    pub os_release: OsRelease,
+    #[serde(default)]
+    pub architecture: Architecture,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tunnel_cidr() -> String {
+    crate::wireguard::subnet::DEFAULT_TUNNEL_CIDR.to_string()
+}
+
+fn default_ip_lookup_url() -> String {
+    crate::public_ip::DEFAULT_LOOKUP_URL.to_string()
 }
 
 impl AppSettings {
@@ -80,6 +308,22 @@ impl AppSettings {
             region: "us-east-1".to_string(),
             instance_type: "t2.micro".to_string(),
             wireguard_port: 51820,
+            kill_switch: false,
+            kill_switch_allow_lan: false,
+            rollback_on_failure: true,
+            tunnel_cidr: default_tunnel_cidr(),
+            ip_lookup_url: default_ip_lookup_url(),
+            lock_down_ssh: false,
+            deploy_without_ssh: false,
+            os_release: OsRelease::default(),
+            architecture: Architecture::default(),
+        }
+    }
+
+    pub fn server_image(&self) -> ServerImage {
+        ServerImage {
+            os: self.os_release,
+            arch: self.architecture,
         }
     }
 }
@@ -95,6 +339,21 @@ pub struct DoCredentials {
     pub api_token: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HetznerCredentials {
+    pub api_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VultrCredentials {
+    pub api_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinodeCredentials {
+    pub api_token: String,
+}
+
 /// Progress event sent to the frontend during deploy/destroy
 #[derive(Debug, Clone, Serialize)]
 pub struct ProgressEvent {
@@ -104,6 +363,12 @@ pub struct ProgressEvent {
     pub status: String, // "running", "done", "error"
 }
 
+/// A line of server command output sent to the frontend during deploy
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployLogEvent {
+    pub line: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +393,123 @@ mod tests {
         assert!(state.client_config.is_none());
         assert!(state.droplet_id.is_none());
         assert!(state.deployment_mode.is_none());
+        assert!(state.peers.is_empty());
+    }
+
+    #[test]
+    fn deployment_state_without_peers_field_deserializes() {
+        let json = r#"{"status":"deployed","deployment_mode":"aws"}"#;
+        let state: DeploymentState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.status, DeploymentStatus::Deployed);
+        assert!(state.peers.is_empty());
+        assert_eq!(state.server_image(), ServerImage::default());
+    }
+
+    #[test]
+    fn deployment_names_by_mode() {
+        assert_eq!(
+            deployment_name(Some("aws"), Some("eu-west-1"), None),
+            "AWS eu-west-1"
+        );
+        assert_eq!(
+            deployment_name(None, Some("us-east-1"), None),
+            "AWS us-east-1"
+        );
+        assert_eq!(
+            deployment_name(Some("do"), Some("nyc3"), None),
+            "DigitalOcean nyc3"
+        );
+        assert_eq!(
+            deployment_name(Some("byo"), None, Some("1.2.3.4")),
+            "Own server 1.2.3.4"
+        );
+        assert_eq!(
+            deployment_name(Some("hetzner"), Some("fsn1"), None),
+            "Hetzner fsn1"
+        );
+        assert_eq!(
+            deployment_name(Some("vultr"), Some("ams"), None),
+            "Vultr ams"
+        );
+        assert_eq!(
+            deployment_name(Some("linode"), Some("us-east"), None),
+            "Linode us-east"
+        );
+        assert_eq!(deployment_name(Some("byo"), None, None), "Own server");
+    }
+
+    #[test]
+    fn cloud_resources_lists_recorded_ids() {
+        let aws = DeploymentState {
+            vpc_id: Some("vpc-1".into()),
+            instance_id: Some("i-1".into()),
+            ..Default::default()
+        };
+        assert_eq!(aws.cloud_resources(), vec!["VPC vpc-1", "instance i-1"]);
+
+        let digital_ocean = DeploymentState {
+            deployment_mode: Some("do".into()),
+            droplet_id: Some(42),
+            do_ssh_key_id: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(
+            digital_ocean.cloud_resources(),
+            vec!["SSH key 7", "Droplet 42"]
+        );
+
+        let hetzner = DeploymentState {
+            deployment_mode: Some("hetzner".into()),
+            hetzner_ssh_key_id: Some(7),
+            hetzner_firewall_id: Some(38),
+            hetzner_server_id: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(
+            hetzner.cloud_resources(),
+            vec!["firewall 38", "SSH key 7", "server 42"]
+        );
+
+        let linode = DeploymentState {
+            deployment_mode: Some("linode".into()),
+            linode_firewall_id: Some(123),
+            linode_instance_id: Some(7001),
+            ..Default::default()
+        };
+        assert_eq!(
+            linode.cloud_resources(),
+            vec!["firewall 123", "Linode 7001"]
+        );
+
+        let byo = DeploymentState {
+            deployment_mode: Some("byo".into()),
+            ssh_private_key: Some("PEM".into()),
+            ..Default::default()
+        };
+        assert!(byo.cloud_resources().is_empty());
+        assert!(DeploymentState::default().cloud_resources().is_empty());
+    }
+
+    #[test]
+    fn deployment_state_without_id_deserializes() {
+        let json = r#"{"status":"deployed"}"#;
+        let state: DeploymentState = serde_json::from_str(json).unwrap();
+        assert!(state.id.is_empty());
+        assert!(state.name.is_empty());
+    }
+
+    #[test]
+    fn peer_info_serde_roundtrip() {
+        let peer = PeerInfo {
+            name: "phone".to_string(),
+            public_key: "PUB".to_string(),
+            address: "10.8.0.3".to_string(),
+            created_at: Utc::now(),
+            client_config: None,
+        };
+        let json = serde_json::to_string(&peer).unwrap();
+        let restored: PeerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, peer);
     }
 
     #[test]
@@ -170,12 +552,39 @@ mod tests {
         assert_eq!(json, "\"connected\"");
     }
 
+    #[test]
+    fn tunnel_stats_default_has_no_handshake() {
+        let stats = TunnelStats::default();
+        assert_eq!(stats.tx_bytes, 0);
+        assert!(stats.last_handshake_at.is_none());
+        assert!(stats.rtt_ms.is_none());
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert!(json["last_handshake_at"].is_null());
+    }
+
     #[test]
     fn app_settings_default_values() {
         let settings = AppSettings::new();
         assert_eq!(settings.region, "us-east-1");
         assert_eq!(settings.instance_type, "t2.micro");
         assert_eq!(settings.wireguard_port, 51820);
+        assert!(!settings.kill_switch);
+        assert!(!settings.kill_switch_allow_lan);
+        assert!(settings.rollback_on_failure);
+    }
+
+    #[test]
+    fn app_settings_without_kill_switch_fields_deserializes() {
+        let json = r#"{"region":"us-east-1","instance_type":"t2.micro","wireguard_port":51820}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert!(!settings.kill_switch);
+        assert!(settings.rollback_on_failure);
+        assert_eq!(settings.tunnel_cidr, "10.8.0.0/24");
+        assert_eq!(settings.ip_lookup_url, "https://checkip.amazonaws.com");
+        assert!(!settings.lock_down_ssh);
+        assert!(!settings.deploy_without_ssh);
+        assert_eq!(settings.server_image(), ServerImage::default());
     }
 
     #[test]
@@ -184,6 +593,7 @@ mod tests {
             region: "eu-west-1".to_string(),
             instance_type: "t3.micro".to_string(),
             wireguard_port: 9999,
+            ..AppSettings::new()
         };
         let json = serde_json::to_string(&settings).unwrap();
         let restored: AppSettings = serde_json::from_str(&json).unwrap();
@@ -0,0 +1,70 @@
+use crate::api_client::ApiClient;
+use crate::error::AppError;
+use serde::Deserialize;
+use std::ops::Deref;
+
+const BASE_URL: &str = "https://api.vultr.com/v2";
+
+/// Tag put on every instance this app creates, so it can be found again
+/// without local state. Vultr firewall groups and SSH keys take no tags.
+pub const RESOURCE_TAG: &str = "createmyvpn";
+
+pub struct VultrClient(ApiClient);
+
+/// Error body returned by the Vultr API.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+impl VultrClient {
+    pub fn new(api_key: &str) -> Self {
+        VultrClient::with_base_url(api_key, BASE_URL)
+    }
+
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Self {
+        VultrClient(ApiClient::new("Vultr", base_url, api_key, error_message))
+    }
+
+    /// Validate the API key and return the account email.
+    pub async fn validate(&self) -> Result<String, AppError> {
+        #[derive(Deserialize)]
+        struct AccountResponse {
+            account: Account,
+        }
+        #[derive(Deserialize)]
+        struct Account {
+            email: String,
+        }
+
+        let resp: AccountResponse = self.get("/account").await?;
+        Ok(resp.account.email)
+    }
+}
+
+impl Deref for VultrClient {
+    type Target = ApiClient;
+
+    fn deref(&self) -> &ApiClient {
+        &self.0
+    }
+}
+
+/// The message from an error body.
+fn error_message(body: &str) -> Option<String> {
+    serde_json::from_str::<ErrorResponse>(body)
+        .ok()
+        .map(|resp| resp.error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_reads_error() {
+        let body = r#"{"error": "Invalid API token.", "status": 401}"#;
+        assert_eq!(error_message(body).as_deref(), Some("Invalid API token."));
+        assert_eq!(error_message("Bad Gateway"), None);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::error::AppError;
+use crate::vultr::client::VultrClient;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+struct CreateGroupRequest {
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct CreateGroupResponse {
+    firewall_group: FirewallGroup,
+}
+
+#[derive(Deserialize)]
+struct FirewallGroup {
+    id: String,
+}
+
+#[derive(Serialize)]
+struct CreateRuleRequest {
+    ip_type: String,
+    protocol: String,
+    subnet: String,
+    subnet_size: u8,
+    port: String,
+    notes: String,
+}
+
+#[derive(Deserialize)]
+struct RulesResponse {
+    firewall_rules: Vec<FirewallRule>,
+}
+
+#[derive(Deserialize)]
+struct FirewallRule {
+    id: u64,
+    protocol: String,
+    port: String,
+}
+
+/// Create a firewall group allowing SSH (TCP 22) from `ssh_source` and
+/// WireGuard (UDP port) from anywhere over IPv4 and IPv6. Vultr firewall
+/// groups drop everything inbound that no rule accepts and leave outbound
+/// traffic alone. Attach it when creating the instance.
+/// POST /v2/firewalls, then POST /v2/firewalls/{id}/rules per rule —
+/// returns the firewall group ID.
+pub async fn create_firewall(
+    client: &VultrClient,
+    name: &str,
+    wireguard_port: u16,
+    ssh_source: &str,
+) -> Result<String, AppError> {
+    let body = CreateGroupRequest {
+        description: name.to_string(),
+    };
+    let resp: CreateGroupResponse = client.post("/firewalls", &body).await?;
+    let group_id = resp.firewall_group.id;
+
+    // Rules are added one at a time; the group is recorded by the caller
+    // only once it is complete, so a failure here deletes it again.
+    if let Err(e) = add_rules(client, &group_id, wireguard_port, ssh_source).await {
+        if let Err(cleanup) = delete_firewall(client, &group_id).await {
+            tracing::warn!(
+                "Failed to delete incomplete Vultr firewall group: {}",
+                cleanup
+            );
+        }
+        return Err(e);
+    }
+    Ok(group_id)
+}
+
+async fn add_rules(
+    client: &VultrClient,
+    group_id: &str,
+    wireguard_port: u16,
+    ssh_source: &str,
+) -> Result<(), AppError> {
+    add_ssh_rule(client, group_id, ssh_source).await?;
+    for (ip_type, subnet) in [("v4", "0.0.0.0"), ("v6", "::")] {
+        let rule = CreateRuleRequest {
+            ip_type: ip_type.to_string(),
+            protocol: "udp".to_string(),
+            subnet: subnet.to_string(),
+            subnet_size: 0,
+            port: wireguard_port.to_string(),
+            notes: "WireGuard".to_string(),
+        };
+        let _: serde_json::Value = client
+            .post(&format!("/firewalls/{}/rules", group_id), &rule)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Accept SSH from `source`, an IPv4 CIDR such as "203.0.113.7/32".
+async fn add_ssh_rule(client: &VultrClient, group_id: &str, source: &str) -> Result<(), AppError> {
+    let (subnet, size) = source.split_once('/').unwrap_or((source, "32"));
+    let rule = CreateRuleRequest {
+        ip_type: "v4".to_string(),
+        protocol: "tcp".to_string(),
+        subnet: subnet.to_string(),
+        subnet_size: size
+            .parse()
+            .map_err(|_| AppError::General(format!("Invalid SSH source '{}'", source)))?,
+        port: "22".to_string(),
+        notes: "SSH".to_string(),
+    };
+    let _: serde_json::Value = client
+        .post(&format!("/firewalls/{}/rules", group_id), &rule)
+        .await?;
+    Ok(())
+}
+
+/// Delete the group's SSH rules, then accept SSH from `ssh_source` again
+/// unless it is `None`.
+/// GET /v2/firewalls/{id}/rules, DELETE /v2/firewalls/{id}/rules/{rule-id}
+pub async fn set_ssh_access(
+    client: &VultrClient,
+    group_id: &str,
+    ssh_source: Option<&str>,
+) -> Result<(), AppError> {
+    let resp: RulesResponse = client
+        .get(&format!("/firewalls/{}/rules?per_page=500", group_id))
+        .await?;
+    for rule in resp
+        .firewall_rules
+        .iter()
+        .filter(|r| r.protocol == "tcp" && r.port == "22")
+    {
+        client
+            .delete(&format!("/firewalls/{}/rules/{}", group_id, rule.id))
+            .await?;
+    }
+    match ssh_source {
+        Some(source) => add_ssh_rule(client, group_id, source).await,
+        None => Ok(()),
+    }
+}
+
+/// Delete a firewall group. Delete the instance using it first (see
+/// `instance::wait_for_deleted`).
+/// DELETE /v2/firewalls/{id}
+pub async fn delete_firewall(client: &VultrClient, group_id: &str) -> Result<(), AppError> {
+    client.delete(&format!("/firewalls/{}", group_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn opens_ssh_to_the_source_and_wireguard_to_everyone() {
+        let server = MockServer::start(|request| {
+            if request.path == "/firewalls" {
+                (201, r#"{"firewall_group": {"id": "1234abcd"}}"#.into())
+            } else {
+                (201, r#"{"firewall_rule": {"id": 1}}"#.into())
+            }
+        })
+        .await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        let id = create_firewall(
+            &client,
+            "createmyvpn-firewall-ab12",
+            51821,
+            "203.0.113.7/32",
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, "1234abcd");
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].json()["description"],
+            "createmyvpn-firewall-ab12"
+        );
+        let rules: Vec<serde_json::Value> = requests[1..].iter().map(|r| r.json()).collect();
+        assert_eq!(rules.len(), 3);
+        assert!(requests[1..]
+            .iter()
+            .all(|r| r.path == "/firewalls/1234abcd/rules"));
+        assert_eq!(rules[0]["port"], "22");
+        assert_eq!(rules[0]["subnet"], "203.0.113.7");
+        assert_eq!(rules[0]["subnet_size"], 32);
+        assert_eq!(rules[1]["protocol"], "udp");
+        assert_eq!(rules[1]["port"], "51821");
+        assert_eq!(rules[1]["subnet"], "0.0.0.0");
+        assert_eq!(rules[2]["ip_type"], "v6");
+        assert_eq!(rules[2]["subnet"], "::");
+    }
+
+    #[tokio::test]
+    async fn failed_rule_deletes_the_group() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "DELETE" => (204, String::new()),
+            _ if request.path == "/firewalls" => {
+                (201, r#"{"firewall_group": {"id": "1234abcd"}}"#.into())
+            }
+            _ => (400, r#"{"error": "Invalid port", "status": 400}"#.into()),
+        })
+        .await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        let err = create_firewall(
+            &client,
+            "createmyvpn-firewall-ab12",
+            51821,
+            "203.0.113.7/32",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Invalid port"), "{}", err);
+
+        let last = server.requests().pop().unwrap();
+        assert_eq!(
+            (last.method.as_str(), last.path.as_str()),
+            ("DELETE", "/firewalls/1234abcd")
+        );
+    }
+
+    #[tokio::test]
+    async fn reopening_ssh_replaces_the_old_rule() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => (
+                200,
+                r#"{"firewall_rules": [
+                    {"id": 1, "ip_type": "v4", "protocol": "tcp", "port": "22",
+                     "subnet": "203.0.113.7", "subnet_size": 32},
+                    {"id": 2, "ip_type": "v4", "protocol": "udp", "port": "51820",
+                     "subnet": "0.0.0.0", "subnet_size": 0}
+                ], "meta": {"total": 2}}"#
+                    .into(),
+            ),
+            "DELETE" => (204, String::new()),
+            _ => (201, r#"{"firewall_rule": {"id": 3}}"#.into()),
+        })
+        .await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        set_ssh_access(&client, "1234abcd", Some("198.51.100.23/32"))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].path, "/firewalls/1234abcd/rules/1");
+        assert_eq!(requests[2].json()["subnet"], "198.51.100.23");
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::AppError;
+use crate::server_image::OsRelease;
+use crate::vultr::client::{VultrClient, RESOURCE_TAG};
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// Vultr's OS id for `os`, x64 (GET /v2/os).
+pub fn os_id(os: OsRelease) -> u32 {
+    match os {
+        OsRelease::Ubuntu2204 => 1743,
+        OsRelease::Ubuntu2404 => 2284,
+        OsRelease::Debian12 => 2136,
+    }
+}
+
+/// How long to wait between status polls (shortened in tests).
+const POLL_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+const MAX_POLLS: u32 = 60; // 60 × 5s = 5 minutes
+
+#[derive(Serialize)]
+struct CreateInstanceRequest {
+    region: String,
+    plan: String,
+    os_id: u32,
+    label: String,
+    sshkey_id: Vec<String>,
+    firewall_group_id: String,
+    tags: Vec<String>,
+    enable_ipv6: bool,
+}
+
+#[derive(Deserialize)]
+struct InstanceResponse {
+    instance: InstanceInfo,
+}
+
+#[derive(Deserialize)]
+struct InstanceInfo {
+    id: String,
+    status: Option<String>,
+    main_ip: Option<String>,
+}
+
+/// Create an instance running `os_id` with the SSH key and firewall group
+/// attached, and return its ID.
+/// POST /v2/instances
+pub async fn create_instance(
+    client: &VultrClient,
+    label: &str,
+    region: &str,
+    plan: &str,
+    os_id: u32,
+    ssh_key_id: &str,
+    firewall_group_id: &str,
+) -> Result<String, AppError> {
+    let body = CreateInstanceRequest {
+        region: region.to_string(),
+        plan: plan.to_string(),
+        os_id,
+        label: label.to_string(),
+        sshkey_id: vec![ssh_key_id.to_string()],
+        firewall_group_id: firewall_group_id.to_string(),
+        tags: vec![RESOURCE_TAG.to_string()],
+        enable_ipv6: true,
+    };
+
+    let resp: InstanceResponse = client.post("/instances", &body).await?;
+    Ok(resp.instance.id)
+}
+
+/// Poll the instance until it is `active` with a public IPv4, and return
+/// the address. Vultr reports "0.0.0.0" until one is assigned. Polls every
+/// 5 seconds for up to 5 minutes.
+pub async fn wait_for_active(client: &VultrClient, instance_id: &str) -> Result<String, AppError> {
+    for attempt in 0..MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        let resp: InstanceResponse = client.get(&format!("/instances/{}", instance_id)).await?;
+
+        tracing::debug!(
+            "[Vultr wait_for_active] attempt {}/{}: status={:?}",
+            attempt + 1,
+            MAX_POLLS,
+            resp.instance.status
+        );
+
+        if resp.instance.status.as_deref() == Some("active") {
+            match resp.instance.main_ip {
+                Some(ip) if ip != "0.0.0.0" => return Ok(ip),
+                // Active but no public IP yet — continue polling
+                _ => {}
+            }
+        }
+    }
+
+    Err(AppError::General(
+        "Instance did not become active within 5 minutes".into(),
+    ))
+}
+
+/// Delete an instance. Vultr deletes it in the background.
+/// DELETE /v2/instances/{id}
+pub async fn delete_instance(client: &VultrClient, instance_id: &str) -> Result<(), AppError> {
+    client.delete(&format!("/instances/{}", instance_id)).await
+}
+
+/// Poll until the instance is gone, so its firewall group can be deleted.
+pub async fn wait_for_deleted(client: &VultrClient, instance_id: &str) -> Result<(), AppError> {
+    for attempt in 0..MAX_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        let resp: Option<InstanceResponse> = client
+            .get_opt(&format!("/instances/{}", instance_id))
+            .await?;
+        if resp.is_none() {
+            return Ok(());
+        }
+    }
+
+    Err(AppError::General(format!(
+        "Instance {} was not deleted within 5 minutes",
+        instance_id
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn instance_json(status: &str, ip: &str) -> String {
+        format!(
+            r#"{{"instance": {{"id": "cb676a46", "label": "createmyvpn-server-ab12",
+                "status": "{}", "main_ip": "{}", "power_status": "running"}}}}"#,
+            status, ip
+        )
+    }
+
+    #[tokio::test]
+    async fn creates_tagged_instance_with_key_and_firewall() {
+        let server = MockServer::start(|_| (202, instance_json("pending", "0.0.0.0"))).await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        let id = create_instance(
+            &client,
+            "createmyvpn-server-ab12",
+            "ams",
+            "vc2-1c-1gb",
+            os_id(OsRelease::Ubuntu2404),
+            "key-1",
+            "fw-1",
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, "cb676a46");
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["region"], "ams");
+        assert_eq!(body["plan"], "vc2-1c-1gb");
+        assert_eq!(body["os_id"], 2284);
+        assert_eq!(body["sshkey_id"], serde_json::json!(["key-1"]));
+        assert_eq!(body["firewall_group_id"], "fw-1");
+        assert_eq!(body["tags"], serde_json::json!(["createmyvpn"]));
+    }
+
+    #[tokio::test]
+    async fn waits_until_active_with_an_address() {
+        let polls = AtomicU32::new(0);
+        let server = MockServer::start(move |_| {
+            let body = match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => instance_json("pending", "0.0.0.0"),
+                1 => instance_json("active", "0.0.0.0"),
+                _ => instance_json("active", "198.51.100.4"),
+            };
+            (200, body)
+        })
+        .await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        let ip = wait_for_active(&client, "cb676a46").await.unwrap();
+        assert_eq!(ip, "198.51.100.4");
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::error::AppError;
+use crate::vultr::client::VultrClient;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+struct CreateKeyRequest {
+    name: String,
+    ssh_key: String,
+}
+
+#[derive(Deserialize)]
+struct CreateKeyResponse {
+    ssh_key: SshKeyInfo,
+}
+
+#[derive(Deserialize)]
+struct SshKeyInfo {
+    id: String,
+}
+
+/// Upload an SSH public key to the account and return the key ID.
+/// POST /v2/ssh-keys
+pub async fn upload_ssh_key(
+    client: &VultrClient,
+    name: &str,
+    public_key: &str,
+) -> Result<String, AppError> {
+    let body = CreateKeyRequest {
+        name: name.to_string(),
+        ssh_key: public_key.to_string(),
+    };
+
+    let resp: CreateKeyResponse = client.post("/ssh-keys", &body).await?;
+    Ok(resp.ssh_key.id)
+}
+
+/// Delete an SSH key from the account.
+/// DELETE /v2/ssh-keys/{id}
+pub async fn delete_ssh_key(client: &VultrClient, key_id: &str) -> Result<(), AppError> {
+    client.delete(&format!("/ssh-keys/{}", key_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    #[tokio::test]
+    async fn uploads_key() {
+        let server = MockServer::start(|_| {
+            (
+                201,
+                r#"{"ssh_key": {"id": "3b8066a7-b438-455a-9688-44afc9a3597f",
+                    "name": "createmyvpn-key-ab12", "ssh_key": "ssh-ed25519 AAAA"}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let client = VultrClient::with_base_url("key", &server.url);
+        let id = upload_ssh_key(&client, "createmyvpn-key-ab12", "ssh-ed25519 AAAA")
+            .await
+            .unwrap();
+        assert_eq!(id, "3b8066a7-b438-455a-9688-44afc9a3597f");
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/ssh-keys")
+        );
+        assert_eq!(request.json()["ssh_key"], "ssh-ed25519 AAAA");
+    }
+}
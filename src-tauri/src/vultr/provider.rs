@@ -0,0 +1,338 @@
+//! `CloudProvider` for Vultr: a Cloud Compute instance in a firewall group,
+//! with an SSH key uploaded to the account.
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::progress::ProgressSink;
+use crate::provider::{self, CloudProvider, Region, ServerSize};
+use crate::server_image::ServerImage;
+use crate::ssh::keygen;
+use crate::state::{DeploymentState, VultrCredentials};
+use crate::vultr::{client::VultrClient, firewall, instance, key};
+
+pub struct VultrProvider {
+    client: VultrClient,
+}
+
+impl VultrProvider {
+    pub fn new(creds: &VultrCredentials) -> Self {
+        VultrProvider {
+            client: VultrClient::new(&creds.api_key),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_client(client: VultrClient) -> Self {
+        VultrProvider { client }
+    }
+}
+
+#[derive(Deserialize)]
+struct RegionsResponse {
+    regions: Vec<RegionInfo>,
+}
+
+#[derive(Deserialize)]
+struct RegionInfo {
+    id: String,
+    city: String,
+    country: String,
+}
+
+#[derive(Deserialize)]
+struct PlansResponse {
+    plans: Vec<Plan>,
+}
+
+#[derive(Deserialize)]
+struct Plan {
+    id: String,
+    vcpu_count: u32,
+    ram: u64,
+    monthly_cost: f64,
+    locations: Vec<String>,
+}
+
+#[async_trait]
+impl CloudProvider for VultrProvider {
+    fn mode(&self) -> &'static str {
+        "vultr"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Vultr"
+    }
+
+    fn check_image(&self, image: &ServerImage, _size: &str) -> Result<(), AppError> {
+        image.require_amd64(self.display_name())
+    }
+
+    async fn validate_credentials(&self) -> Result<String, AppError> {
+        self.client.validate().await
+    }
+
+    async fn list_regions(&self) -> Result<Vec<Region>, AppError> {
+        let resp: RegionsResponse = self.client.get("/regions?per_page=500").await?;
+        Ok(resp
+            .regions
+            .into_iter()
+            .map(|r| Region {
+                name: format!("{}, {}", r.city, r.country),
+                code: r.id,
+            })
+            .collect())
+    }
+
+    /// Regular Cloud Compute plans sold in `region`, priced in USD.
+    async fn list_sizes(&self, region: &str) -> Result<Vec<ServerSize>, AppError> {
+        let resp: PlansResponse = self.client.get("/plans?type=vc2&per_page=500").await?;
+        Ok(resp
+            .plans
+            .into_iter()
+            .filter(|p| p.locations.iter().any(|l| l == region))
+            .map(|p| ServerSize {
+                description: format!("{} vCPU, {}MB RAM", p.vcpu_count, p.ram),
+                monthly_price: Some(p.monthly_cost),
+                code: p.id,
+            })
+            .collect())
+    }
+
+    async fn create_firewall(
+        &self,
+        state: &mut DeploymentState,
+        wireguard_port: u16,
+    ) -> Result<(), AppError> {
+        if state.vultr_firewall_id.is_some() {
+            return Ok(());
+        }
+        // Names carry the deployment id so parallel deployments are told apart.
+        let name = format!("createmyvpn-firewall-{}", state.id);
+        let group_id = firewall::create_firewall(
+            &self.client,
+            &name,
+            wireguard_port,
+            provider::ssh_source(state)?,
+        )
+        .await?;
+        tracing::info!("[Vultr] Firewall group created: {}", group_id);
+        state.vultr_firewall_id = Some(group_id);
+        Ok(())
+    }
+
+    async fn set_ssh_access(
+        &self,
+        state: &DeploymentState,
+        _wireguard_port: u16,
+        source: Option<&str>,
+    ) -> Result<(), AppError> {
+        let group_id = state.vultr_firewall_id.as_deref().ok_or_else(|| {
+            AppError::State("No firewall group recorded for this deployment".into())
+        })?;
+        firewall::set_ssh_access(&self.client, group_id, source).await
+    }
+
+    async fn create_ssh_key(&self, state: &mut DeploymentState) -> Result<(), AppError> {
+        if state.vultr_ssh_key_id.is_some() && state.ssh_private_key.is_some() {
+            return Ok(());
+        }
+        // A recorded key whose private half was lost is no use; delete it
+        // rather than leave it in the account untracked.
+        if let Some(ref key_id) = state.vultr_ssh_key_id {
+            key::delete_ssh_key(&self.client, key_id).await?;
+            tracing::info!(
+                "[Vultr] Deleted SSH key {} with no saved private key",
+                key_id
+            );
+            state.vultr_ssh_key_id = None;
+        }
+        let (private_key, public_key) = keygen::generate_ed25519()?;
+        state.ssh_private_key = Some(private_key);
+
+        let name = format!("createmyvpn-key-{}", state.id);
+        let key_id = key::upload_ssh_key(&self.client, &name, &public_key).await?;
+        tracing::info!("[Vultr] SSH key uploaded, key_id={}", key_id);
+        state.vultr_ssh_key_id = Some(key_id);
+        Ok(())
+    }
+
+    async fn create_server(
+        &self,
+        state: &mut DeploymentState,
+        size: &str,
+        _user_data: Option<&str>,
+    ) -> Result<(), AppError> {
+        if state.vultr_instance_id.is_some() {
+            return Ok(());
+        }
+        let (Some(key_id), Some(group_id)) = (&state.vultr_ssh_key_id, &state.vultr_firewall_id)
+        else {
+            return Err(AppError::State(
+                "The SSH key and firewall must exist before the server".into(),
+            ));
+        };
+        let region = state.region.clone().unwrap_or_default();
+        let label = format!("createmyvpn-server-{}", state.id);
+        let os_id = instance::os_id(state.server_image().os);
+        let instance_id =
+            instance::create_instance(&self.client, &label, &region, size, os_id, key_id, group_id)
+                .await?;
+        tracing::info!("[Vultr] Instance created: {}", instance_id);
+        state.vultr_instance_id = Some(instance_id);
+        Ok(())
+    }
+
+    async fn wait_for_ip(&self, state: &mut DeploymentState) -> Result<String, AppError> {
+        let instance_id = state
+            .vultr_instance_id
+            .clone()
+            .ok_or_else(|| AppError::State("No instance recorded for this deployment".into()))?;
+        tracing::info!(
+            "[Vultr] Waiting for instance {} to become active...",
+            instance_id
+        );
+        instance::wait_for_active(&self.client, &instance_id).await
+    }
+
+    /// The firewall group can only go once the instance is gone.
+    async fn destroy(
+        &self,
+        progress: &dyn ProgressSink,
+        state: &DeploymentState,
+    ) -> Result<(), AppError> {
+        let mut failures = Vec::new();
+
+        progress.emit(1, 3, "Deleting server...", "running");
+        if let Some(ref instance_id) = state.vultr_instance_id {
+            let result = match instance::delete_instance(&self.client, instance_id).await {
+                Ok(()) => instance::wait_for_deleted(&self.client, instance_id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Vultr instance: {}", e);
+                failures.push(format!("instance {}: {}", instance_id, e));
+            }
+        }
+
+        progress.emit(2, 3, "Deleting firewall...", "running");
+        if let Some(ref group_id) = state.vultr_firewall_id {
+            let result = firewall::delete_firewall(&self.client, group_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Vultr firewall group: {}", e);
+                failures.push(format!("firewall group {}: {}", group_id, e));
+            }
+        }
+
+        progress.emit(3, 3, "Cleaning up...", "running");
+        if let Some(ref key_id) = state.vultr_ssh_key_id {
+            let result = key::delete_ssh_key(&self.client, key_id).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to delete Vultr SSH key: {}", e);
+                failures.push(format!("SSH key {}: {}", key_id, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::General(format!(
+                "Could not delete {}",
+                failures.join("; ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_mock::MockServer;
+
+    struct NoProgress;
+
+    impl ProgressSink for NoProgress {
+        fn emit(&self, _step: u32, _total: u32, _message: &str, _status: &str) {}
+    }
+
+    #[tokio::test]
+    async fn sizes_are_limited_to_the_region() {
+        let server = MockServer::start(|_| {
+            (
+                200,
+                r#"{"plans": [
+                    {"id": "vc2-1c-1gb", "vcpu_count": 1, "ram": 1024, "disk": 25,
+                     "monthly_cost": 5, "type": "vc2", "locations": ["ams", "ewr"]},
+                    {"id": "vc2-1c-2gb", "vcpu_count": 1, "ram": 2048, "disk": 55,
+                     "monthly_cost": 10, "type": "vc2", "locations": ["ewr"]}
+                ], "meta": {"total": 2}}"#
+                    .into(),
+            )
+        })
+        .await;
+        let provider = VultrProvider::with_client(VultrClient::with_base_url("key", &server.url));
+        let sizes = provider.list_sizes("ams").await.unwrap();
+        assert_eq!(
+            sizes,
+            vec![ServerSize {
+                code: "vc2-1c-1gb".into(),
+                description: "1 vCPU, 1024MB RAM".into(),
+                monthly_price: Some(5.0),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn destroy_deletes_instance_before_firewall() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => (404, r#"{"error": "Not found", "status": 404}"#.into()),
+            _ => (204, String::new()),
+        })
+        .await;
+        let provider = VultrProvider::with_client(VultrClient::with_base_url("key", &server.url));
+        let state = DeploymentState {
+            vultr_instance_id: Some("inst".into()),
+            vultr_firewall_id: Some("fw".into()),
+            vultr_ssh_key_id: Some("key".into()),
+            ..Default::default()
+        };
+        provider.destroy(&NoProgress, &state).await.unwrap();
+
+        let calls: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                "DELETE /instances/inst",
+                "GET /instances/inst",
+                "DELETE /firewalls/fw",
+                "DELETE /ssh-keys/key",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn destroy_can_be_retried_after_resources_are_gone() {
+        // Each resource is deleted by the first destroy and 404s afterwards.
+        let deleted = std::sync::Mutex::new(std::collections::HashSet::new());
+        let server = MockServer::start(move |request| match request.method.as_str() {
+            "GET" => (404, r#"{"error": "Not found", "status": 404}"#.into()),
+            _ if deleted.lock().unwrap().insert(request.path.clone()) => (204, String::new()),
+            _ => (404, r#"{"error": "Not found", "status": 404}"#.into()),
+        })
+        .await;
+        let provider = VultrProvider::with_client(VultrClient::with_base_url("key", &server.url));
+        let state = DeploymentState {
+            vultr_instance_id: Some("inst".into()),
+            vultr_firewall_id: Some("fw".into()),
+            vultr_ssh_key_id: Some("key".into()),
+            ..Default::default()
+        };
+        provider.destroy(&NoProgress, &state).await.unwrap();
+        provider.destroy(&NoProgress, &state).await.unwrap();
+        assert_eq!(server.requests().len(), 8);
+    }
+}
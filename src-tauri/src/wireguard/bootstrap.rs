@@ -0,0 +1,165 @@
+//! The first-boot script that sets a server's WireGuard up from its user
+//! data, run once by cloud-init: packages, forwarding, wg0.conf, the
+//! service, then a readiness marker. SSH is then only needed to check the
+//! marker, or not at all (see `probe`).
+//!
+//! The script carries the server's private key. It stays on the server (and
+//! in the provider's copy of the user data); the app never saves it.
+use crate::ssh::configure::{IPV6_CHECK, SYSCTL_CONF};
+
+use super::server_config;
+use super::subnet::TunnelSubnet;
+
+const MARKER_DIR: &str = "/var/lib/createmyvpn";
+/// Holds "ready" once WireGuard runs, "failed" if the script stopped.
+pub const MARKER_PATH: &str = "/var/lib/createmyvpn/bootstrap";
+/// Everything the script printed.
+pub const LOG_PATH: &str = "/var/log/createmyvpn-bootstrap.log";
+/// Logged when the server has no IPv6 and the tunnel is set up without it.
+pub const IPV4_ONLY_NOTICE: &str = "No IPv6 on this server, the tunnel carries IPv4 only";
+/// Stands in for the egress interface in wg0.conf until the server has
+/// looked up its default route.
+const EGRESS_PLACEHOLDER: &str = "@EGRESS@";
+
+/// How the first-boot script ended, as its marker file says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapStatus {
+    Ready,
+    Failed,
+    /// No marker: the script has not finished, or never ran.
+    Unknown,
+}
+
+/// The marker file's content, e.g. `cat` output.
+pub fn parse_marker(content: &str) -> BootstrapStatus {
+    match content.trim() {
+        "ready" => BootstrapStatus::Ready,
+        "failed" => BootstrapStatus::Failed,
+        _ => BootstrapStatus::Unknown,
+    }
+}
+
+/// Render the user data script for a server with `server_private_key`,
+/// accepting `client_public_key` on `listen_port`.
+pub fn render_user_data(
+    server_private_key: &str,
+    server_public_key: &str,
+    client_public_key: &str,
+    listen_port: u16,
+    subnet: &TunnelSubnet,
+) -> String {
+    let wg0 = server_config::render_server_config(
+        server_private_key,
+        client_public_key,
+        listen_port,
+        subnet,
+        EGRESS_PLACEHOLDER,
+        true,
+    );
+    format!(
+        r#"#!/bin/bash
+# CreateMyVpn server bootstrap, run once by cloud-init on first boot.
+set -eu
+mkdir -p {marker_dir}
+exec > {log} 2>&1
+trap 'echo failed > {marker}; echo "createmyvpn: bootstrap failed" > /dev/console || true' ERR
+echo "=== CreateMyVpn VPN Server Bootstrap ==="
+export DEBIAN_FRONTEND=noninteractive
+apt-get update -y
+apt-get install -y wireguard wireguard-tools iptables
+cat > /etc/sysctl.d/99-vpn.conf <<'CREATEMYVPN_EOF'
+{sysctl}CREATEMYVPN_EOF
+sysctl -p /etc/sysctl.d/99-vpn.conf
+egress=$(ip -4 route show default | sed -n 's/.* dev \([^ ]*\).*/\1/p' | head -n 1)
+if [ -z "$egress" ]; then
+  egress=$(ip -4 route get 1.1.1.1 | sed -n 's/.* dev \([^ ]*\).*/\1/p' | head -n 1)
+fi
+[ -n "$egress" ]
+echo "Egress interface: $egress"
+mkdir -p /etc/wireguard
+(
+umask 077
+cat > /etc/wireguard/wg0.conf <<'CREATEMYVPN_EOF'
+{wg0}CREATEMYVPN_EOF
+)
+sed -i "s/{placeholder}/$egress/g" /etc/wireguard/wg0.conf
+if ! {{ {ipv6_check}; }}; then
+  echo "{ipv4_only_notice}"
+  sed -i -e '/ip6tables/d' -e 's/^\(Address = [^,]*\),.*/\1/' \
+    -e 's/^\(AllowedIPs = [^,]*\),.*/\1/' /etc/wireguard/wg0.conf
+fi
+echo '{server_public_key}' > /etc/wireguard/server_public.key
+systemctl enable --now wg-quick@wg0
+wg show wg0 > /dev/null
+echo ready > {marker}
+echo "createmyvpn: WireGuard ready" > /dev/console || true
+echo "=== Bootstrap Complete ==="
+"#,
+        marker_dir = MARKER_DIR,
+        marker = MARKER_PATH,
+        log = LOG_PATH,
+        sysctl = SYSCTL_CONF,
+        wg0 = wg0,
+        placeholder = EGRESS_PLACEHOLDER,
+        ipv6_check = IPV6_CHECK,
+        ipv4_only_notice = IPV4_ONLY_NOTICE,
+        server_public_key = server_public_key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_data() -> String {
+        render_user_data(
+            "SRV_PRIV",
+            "SRV_PUB",
+            "CLI_PUB",
+            51820,
+            &TunnelSubnet::of(None).unwrap(),
+        )
+    }
+
+    #[test]
+    fn user_data_writes_the_server_config() {
+        let script = user_data();
+        assert!(script.starts_with("#!/bin/bash\n"));
+        assert!(script.contains("PrivateKey = SRV_PRIV"));
+        assert!(script.contains("PublicKey = CLI_PUB"));
+        assert!(script.contains("ListenPort = 51820"));
+        assert!(script.contains("-o @EGRESS@ -j MASQUERADE"));
+        assert!(script.contains(r#"sed -i "s/@EGRESS@/$egress/g" /etc/wireguard/wg0.conf"#));
+        assert!(script.contains("net.ipv4.ip_forward=1"));
+        assert!(script.contains("systemctl enable --now wg-quick@wg0"));
+    }
+
+    #[test]
+    fn user_data_drops_ipv6_on_servers_without_it() {
+        let script = user_data();
+        let check = script.find("if ! { ip -6 addr show scope global").unwrap();
+        assert!(check > script.find("sed -i \"s/@EGRESS@").unwrap());
+        assert!(script.contains("sed -i -e '/ip6tables/d'"));
+        assert!(script.contains(r"-e 's/^\(AllowedIPs = [^,]*\),.*/\1/'"));
+        assert!(script.contains(&format!("echo \"{}\"", IPV4_ONLY_NOTICE)));
+        assert!(check < script.find("systemctl enable --now wg-quick@wg0").unwrap());
+    }
+
+    #[test]
+    fn user_data_ends_with_the_marker() {
+        let script = user_data();
+        assert!(script.contains("mkdir -p /var/lib/createmyvpn\n"));
+        assert!(script.contains("echo failed > /var/lib/createmyvpn/bootstrap"));
+        let ready = script
+            .find("echo ready > /var/lib/createmyvpn/bootstrap")
+            .unwrap();
+        assert!(ready > script.find("wg show wg0").unwrap());
+    }
+
+    #[test]
+    fn marker_states() {
+        assert_eq!(parse_marker("ready\n"), BootstrapStatus::Ready);
+        assert_eq!(parse_marker("failed\n"), BootstrapStatus::Failed);
+        assert_eq!(parse_marker(""), BootstrapStatus::Unknown);
+    }
+}
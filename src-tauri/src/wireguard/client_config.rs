@@ -1,23 +1,61 @@
-/// Render the WireGuard client config for connecting to the VPN server.
+use super::peers::tunnel_ipv6;
+use super::subnet::TunnelSubnet;
+
+/// Render the WireGuard client config for connecting to the VPN server, as
+/// the primary client of `subnet`.
 pub fn render_client_config(
     client_private_key: &str,
     server_public_key: &str,
     endpoint_ip: &str,
     listen_port: u16,
+    subnet: &TunnelSubnet,
+    ipv6: bool,
+) -> String {
+    render_peer_client_config(
+        client_private_key,
+        &subnet.client_address().to_string(),
+        server_public_key,
+        endpoint_ip,
+        listen_port,
+        ipv6,
+    )
+}
+
+/// Render a client config for a peer at the given tunnel address (no prefix).
+/// Both IPv4 and IPv6 are routed through the tunnel so neither leaks. With
+/// `ipv6` off, for a server whose side of the tunnel is IPv4 only (see
+/// `server_config::render_server_config`), the client gets no IPv6 address
+/// or resolver, so its IPv6 traffic goes into the tunnel and is dropped.
+pub fn render_peer_client_config(
+    client_private_key: &str,
+    client_address: &str,
+    server_public_key: &str,
+    endpoint_ip: &str,
+    listen_port: u16,
+    ipv6: bool,
 ) -> String {
+    let (address_v6, dns_v6) = if ipv6 {
+        (
+            format!(", {}/128", tunnel_ipv6(client_address)),
+            ", 2606:4700:4700::1111",
+        )
+    } else {
+        (String::new(), "")
+    };
     format!(
         r#"[Interface]
 PrivateKey = {client_private_key}
-Address = 10.8.0.2/32
-DNS = 1.1.1.1
+Address = {client_address}/32{address_v6}
+DNS = 1.1.1.1{dns_v6}
 
 [Peer]
 PublicKey = {server_public_key}
 Endpoint = {endpoint_ip}:{listen_port}
-AllowedIPs = 0.0.0.0/0
+AllowedIPs = 0.0.0.0/0, ::/0
 PersistentKeepalive = 25
 "#,
         client_private_key = client_private_key,
+        client_address = client_address,
         server_public_key = server_public_key,
         endpoint_ip = endpoint_ip,
         listen_port = listen_port,
@@ -28,9 +66,13 @@ PersistentKeepalive = 25
 mod tests {
     use super::*;
 
+    fn subnet() -> TunnelSubnet {
+        TunnelSubnet::of(None).unwrap()
+    }
+
     #[test]
     fn render_client_config_contains_interface_section() {
-        let config = render_client_config("PRIV_KEY", "PUB_KEY", "1.2.3.4", 51820);
+        let config = render_client_config("PRIV_KEY", "PUB_KEY", "1.2.3.4", 51820, &subnet(), true);
         assert!(config.contains("[Interface]"));
         assert!(config.contains("PrivateKey = PRIV_KEY"));
         assert!(config.contains("Address = 10.8.0.2/32"));
@@ -39,7 +81,7 @@ mod tests {
 
     #[test]
     fn render_client_config_contains_peer_section() {
-        let config = render_client_config("PRIV_KEY", "PUB_KEY", "1.2.3.4", 51820);
+        let config = render_client_config("PRIV_KEY", "PUB_KEY", "1.2.3.4", 51820, &subnet(), true);
         assert!(config.contains("[Peer]"));
         assert!(config.contains("PublicKey = PUB_KEY"));
         assert!(config.contains("Endpoint = 1.2.3.4:51820"));
@@ -49,13 +91,48 @@ mod tests {
 
     #[test]
     fn render_client_config_uses_custom_port() {
-        let config = render_client_config("KEY", "PUB", "10.0.0.1", 12345);
+        let config = render_client_config("KEY", "PUB", "10.0.0.1", 12345, &subnet(), true);
         assert!(config.contains("Endpoint = 10.0.0.1:12345"));
     }
 
     #[test]
     fn render_client_config_full_tunnel() {
-        let config = render_client_config("K", "P", "1.1.1.1", 51820);
-        assert!(config.contains("AllowedIPs = 0.0.0.0/0"), "should route all traffic");
+        let config = render_client_config("K", "P", "1.1.1.1", 51820, &subnet(), true);
+        assert!(
+            config.contains("AllowedIPs = 0.0.0.0/0"),
+            "should route all traffic"
+        );
+    }
+
+    #[test]
+    fn render_client_config_routes_ipv6_through_tunnel() {
+        let config = render_client_config("K", "P", "1.1.1.1", 51820, &subnet(), true);
+        assert!(config.contains("Address = 10.8.0.2/32, fd86:ea04:1115::2/128"));
+        assert!(config.contains("AllowedIPs = 0.0.0.0/0, ::/0"));
+        assert!(config.contains("DNS = 1.1.1.1, 2606:4700:4700::1111"));
+    }
+
+    #[test]
+    fn render_client_config_still_captures_ipv6_when_server_has_none() {
+        let config = render_client_config("K", "P", "1.1.1.1", 51820, &subnet(), false);
+        assert!(config.contains("Address = 10.8.0.2/32\n"));
+        assert!(config.contains("DNS = 1.1.1.1\n"));
+        // IPv6 is dropped in the tunnel rather than sent around it.
+        assert!(config.contains("AllowedIPs = 0.0.0.0/0, ::/0\n"));
+        assert!(!config.contains("fd86:"));
+    }
+
+    #[test]
+    fn render_client_config_uses_subnet_client_address() {
+        let subnet = TunnelSubnet::parse("192.168.200.0/24").unwrap();
+        let config = render_client_config("K", "P", "1.1.1.1", 51820, &subnet, true);
+        assert!(config.contains("Address = 192.168.200.2/32, fd86:ea04:1115::2/128"));
+    }
+
+    #[test]
+    fn render_peer_client_config_uses_given_address() {
+        let config = render_peer_client_config("K", "10.8.0.7", "P", "1.2.3.4", 51820, true);
+        assert!(config.contains("Address = 10.8.0.7/32"));
+        assert!(config.contains("Endpoint = 1.2.3.4:51820"));
     }
 }
@@ -8,7 +8,7 @@ use crate::error::AppError;
 /// ```ini
 /// [Interface]
 /// PrivateKey = <base64>
-/// Address = 10.0.0.2/32
+/// Address = 10.0.0.2/32, fd86:ea04:1115::2/128
 /// DNS = 1.1.1.1
 ///
 /// [Peer]
@@ -21,6 +21,7 @@ use crate::error::AppError;
 pub struct ParsedClientConfig {
     pub private_key_b64: String,
     pub vpn_address: String, // e.g. "10.0.0.2"
+    pub vpn_address_v6: Option<String>, // e.g. "fd86:ea04:1115::2"
     pub dns: Option<String>,
     pub server_public_key_b64: String,
     pub endpoint: SocketAddr,
@@ -32,6 +33,7 @@ impl ParsedClientConfig {
     pub fn parse(conf: &str) -> Result<Self, AppError> {
         let mut private_key = None;
         let mut address = None;
+        let mut address_v6 = None;
         let mut dns = None;
         let mut server_public_key = None;
         let mut endpoint_str = None;
@@ -59,9 +61,16 @@ impl ParsedClientConfig {
                 "[Interface]" => match key {
                     "PrivateKey" => private_key = Some(val.to_string()),
                     "Address" => {
-                        // Strip CIDR prefix, keep just the IP
-                        let ip = val.split('/').next().unwrap_or(val);
-                        address = Some(ip.to_string());
+                        // Strip CIDR prefixes, keep just the IPs. The first
+                        // IPv6 entry goes to vpn_address_v6.
+                        for entry in val.split(',') {
+                            let ip = entry.trim().split('/').next().unwrap_or_default();
+                            if ip.contains(':') {
+                                address_v6.get_or_insert_with(|| ip.to_string());
+                            } else {
+                                address.get_or_insert_with(|| ip.to_string());
+                            }
+                        }
                     }
                     "DNS" => dns = Some(val.to_string()),
                     _ => {}
@@ -99,6 +108,7 @@ impl ParsedClientConfig {
         Ok(ParsedClientConfig {
             private_key_b64,
             vpn_address,
+            vpn_address_v6: address_v6,
             dns,
             server_public_key_b64,
             endpoint,
@@ -155,6 +165,23 @@ PersistentKeepalive = 25
         assert_eq!(parsed.vpn_address, "10.0.0.5");
     }
 
+    #[test]
+    fn parse_dual_stack_address() {
+        let config = VALID_CONFIG.replace(
+            "Address = 10.8.0.2/32",
+            "Address = 10.8.0.2/32, fd86:ea04:1115::2/128",
+        );
+        let parsed = ParsedClientConfig::parse(&config).unwrap();
+        assert_eq!(parsed.vpn_address, "10.8.0.2");
+        assert_eq!(parsed.vpn_address_v6.as_deref(), Some("fd86:ea04:1115::2"));
+    }
+
+    #[test]
+    fn parse_ipv4_only_address_has_no_v6() {
+        let parsed = ParsedClientConfig::parse(VALID_CONFIG).unwrap();
+        assert_eq!(parsed.vpn_address_v6, None);
+    }
+
     #[test]
     fn parse_missing_private_key_errors() {
         let config = r#"[Interface]
@@ -0,0 +1,197 @@
+//! Applies the config's `DNS =` resolvers while the tunnel is up, so queries go
+//! through the VPN instead of leaking to the ISP resolver.
+//!
+//! Linux only. Uses systemd-resolved (`resolvectl`) when it is running, which
+//! scopes the change to the TUN interface and is undone automatically when the
+//! interface disappears. Otherwise `/etc/resolv.conf` is rewritten after saving
+//! a backup in `~/.createmyvpn`; the backup is restored on disconnect, or on the
+//! next start if the app was killed while connected.
+use std::net::IpAddr;
+#[cfg(target_os = "linux")]
+use std::process::Command;
+
+use crate::error::AppError;
+#[cfg(target_os = "linux")]
+use crate::persistence::store;
+
+#[cfg(target_os = "linux")]
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+/// Whether `apply` can change the resolver on this platform.
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// How the resolvers were applied, so `restore` knows what to undo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DnsMethod {
+    Resolved,
+    ResolvConf,
+}
+
+/// Parse the comma-separated `DNS =` value. Entries that aren't IP addresses
+/// (wg-quick treats those as search domains) are skipped.
+pub fn parse_dns_servers(dns: &str) -> Vec<IpAddr> {
+    dns.split(',')
+        .filter_map(|s| s.trim().parse::<IpAddr>().ok())
+        .collect()
+}
+
+/// Contents written to /etc/resolv.conf when systemd-resolved isn't available.
+pub fn render_resolv_conf(servers: &[IpAddr]) -> String {
+    let mut out = String::from("# Generated by CreateMyVpn while the VPN is connected.\n");
+    out.push_str("# The previous file is restored on disconnect.\n");
+    for server in servers {
+        out.push_str(&format!("nameserver {}\n", server));
+    }
+    out
+}
+
+/// Point the system at `servers` for as long as the tunnel is up.
+#[cfg(target_os = "linux")]
+pub fn apply(tun_name: &str, servers: &[IpAddr]) -> Result<DnsMethod, AppError> {
+    if resolved_available() {
+        let mut args = vec!["dns".to_string(), tun_name.to_string()];
+        args.extend(servers.iter().map(|s| s.to_string()));
+        run_resolvectl(&args)?;
+        // "~." makes this link the route for every domain, not just its own.
+        run_resolvectl(&["domain".into(), tun_name.into(), "~.".into()])?;
+        // Older systemd lacks default-route; the routing domain above is enough there.
+        let _ = run_resolvectl(&["default-route".into(), tun_name.into(), "true".into()]);
+        tracing::info!(
+            "DNS set to {:?} on {} via systemd-resolved",
+            servers,
+            tun_name
+        );
+        return Ok(DnsMethod::Resolved);
+    }
+
+    // Keep an existing backup: it holds the original file from a session that
+    // crashed before it could restore it.
+    let backup = store::dns_backup_path()?;
+    if !backup.exists() {
+        std::fs::copy(RESOLV_CONF, &backup)?;
+    }
+    std::fs::write(RESOLV_CONF, render_resolv_conf(servers)).map_err(|e| {
+        AppError::WireGuard(format!(
+            "Cannot apply VPN DNS — failed to write {}: {}",
+            RESOLV_CONF, e
+        ))
+    })?;
+    tracing::info!("DNS set to {:?} via {}", servers, RESOLV_CONF);
+    Ok(DnsMethod::ResolvConf)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_tun_name: &str, _servers: &[IpAddr]) -> Result<DnsMethod, AppError> {
+    Err(AppError::WireGuard(
+        "Applying VPN DNS is only supported on Linux".into(),
+    ))
+}
+
+/// Undo `apply`.
+pub fn restore(tun_name: &str, method: DnsMethod) {
+    #[cfg(target_os = "linux")]
+    match method {
+        DnsMethod::Resolved => {
+            let _ = run_resolvectl(&["revert".into(), tun_name.into()]);
+            tracing::info!("DNS settings reverted on {}", tun_name);
+        }
+        DnsMethod::ResolvConf => restore_resolv_conf(),
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = (tun_name, method);
+}
+
+/// Called on startup: a leftover backup means the app was killed while
+/// connected, and /etc/resolv.conf still points at the VPN resolvers.
+pub fn restore_stale() {
+    #[cfg(target_os = "linux")]
+    {
+        if store::dns_backup_path()
+            .map(|p| p.exists())
+            .unwrap_or(false)
+        {
+            tracing::warn!("Startup: restoring /etc/resolv.conf left over from a previous session");
+            restore_resolv_conf();
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn restore_resolv_conf() {
+    let backup = match store::dns_backup_path() {
+        Ok(p) if p.exists() => p,
+        _ => return,
+    };
+    match std::fs::copy(&backup, RESOLV_CONF) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&backup);
+            tracing::info!("Restored {}", RESOLV_CONF);
+        }
+        Err(e) => tracing::warn!(
+            "Failed to restore {} from {}: {}",
+            RESOLV_CONF,
+            backup.display(),
+            e
+        ),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn resolved_available() -> bool {
+    Command::new("resolvectl")
+        .arg("status")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn run_resolvectl(args: &[String]) -> Result<(), AppError> {
+    let out = Command::new("resolvectl")
+        .args(args)
+        .output()
+        .map_err(|e| AppError::WireGuard(format!("resolvectl {}: {}", args.join(" "), e)))?;
+    if !out.status.success() {
+        return Err(AppError::WireGuard(format!(
+            "resolvectl {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_server() {
+        assert_eq!(
+            parse_dns_servers("1.1.1.1"),
+            vec!["1.1.1.1".parse::<IpAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn parse_multiple_servers_and_skips_search_domains() {
+        let servers = parse_dns_servers("1.1.1.1, 2606:4700:4700::1111, corp.example");
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[1].to_string(), "2606:4700:4700::1111");
+    }
+
+    #[test]
+    fn parse_empty_is_empty() {
+        assert!(parse_dns_servers("").is_empty());
+    }
+
+    #[test]
+    fn render_resolv_conf_lists_nameservers() {
+        let servers = parse_dns_servers("1.1.1.1, 9.9.9.9");
+        let conf = render_resolv_conf(&servers);
+        assert!(conf.starts_with("# Generated by CreateMyVpn"));
+        assert!(conf.contains("nameserver 1.1.1.1\n"));
+        assert!(conf.contains("nameserver 9.9.9.9\n"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Kill switch: while enabled, an nftables table drops every packet that is not
+//! going through the tunnel, to loopback, or to the WireGuard endpoint itself.
+//!
+//! The rules live in the kernel, independent of the tunnel thread, so if the
+//! packet loop dies or the handshake stalls, traffic is blocked instead of
+//! leaking out of the real gateway. They are removed on disconnect, and any
+//! left over from a killed app are removed on the next start.
+//!
+//! Linux only. Requires the `nft` binary and CAP_NET_ADMIN.
+use std::net::IpAddr;
+#[cfg(target_os = "linux")]
+use std::process::Command;
+
+use crate::error::AppError;
+
+const TABLE: &str = "createmyvpn_killswitch";
+
+/// Private, link-local and multicast ranges allowed when LAN access is on.
+const LAN_V4: &[&str] = &[
+    "10.0.0.0/8",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    "169.254.0.0/16",
+    "224.0.0.0/4",
+];
+const LAN_V6: &[&str] = &["fc00::/7", "fe80::/10", "ff00::/8"];
+
+/// Render the nft script that (re)creates the kill switch table.
+pub fn render_ruleset(tun_name: &str, server_ip: IpAddr, port: u16, allow_lan: bool) -> String {
+    let family = if server_ip.is_ipv4() { "ip" } else { "ip6" };
+    let lan_rules = |dir: &str| -> String {
+        if !allow_lan {
+            return String::new();
+        }
+        format!(
+            "        ip {dir} {{ {v4} }} accept\n        ip6 {dir} {{ {v6} }} accept\n",
+            v4 = LAN_V4.join(", "),
+            v6 = LAN_V6.join(", "),
+        )
+    };
+
+    // Creating the table before deleting it makes the script idempotent:
+    // `delete table` on its own fails when the table doesn't exist.
+    format!(
+        r#"table inet {TABLE} {{}}
+delete table inet {TABLE}
+table inet {TABLE} {{
+    chain output {{
+        type filter hook output priority 0; policy drop;
+        oifname "lo" accept
+        oifname "{tun_name}" accept
+        {family} daddr {server_ip} udp dport {port} accept
+        udp sport 68 udp dport 67 accept
+{lan_out}    }}
+    chain input {{
+        type filter hook input priority 0; policy drop;
+        iifname "lo" accept
+        iifname "{tun_name}" accept
+        {family} saddr {server_ip} udp sport {port} accept
+        udp sport 67 udp dport 68 accept
+{lan_in}    }}
+}}
+"#,
+        lan_out = lan_rules("daddr"),
+        lan_in = lan_rules("saddr"),
+    )
+}
+
+/// Install the kill switch rules for the given endpoint.
+#[cfg(target_os = "linux")]
+pub fn enable(
+    tun_name: &str,
+    endpoint: std::net::SocketAddr,
+    allow_lan: bool,
+) -> Result<(), AppError> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let script = render_ruleset(tun_name, endpoint.ip(), endpoint.port(), allow_lan);
+    let mut child = Command::new("nft")
+        .args(["-f", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            AppError::WireGuard(format!(
+                "Cannot enable kill switch — failed to run nft: {e}\n\
+                 \n\
+                 Install nftables and try again:\n\
+                 \n\
+                 sudo apt install nftables"
+            ))
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(script.as_bytes())
+            .map_err(|e| AppError::WireGuard(format!("Failed to write nft rules: {e}")))?;
+    }
+    let out = child
+        .wait_with_output()
+        .map_err(|e| AppError::WireGuard(format!("nft did not complete: {e}")))?;
+    if !out.status.success() {
+        return Err(AppError::WireGuard(format!(
+            "Cannot enable kill switch — nft rejected the rules: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    tracing::info!(
+        "Kill switch enabled (endpoint {}, LAN {})",
+        endpoint,
+        if allow_lan { "allowed" } else { "blocked" }
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn enable(
+    _tun_name: &str,
+    _endpoint: std::net::SocketAddr,
+    _allow_lan: bool,
+) -> Result<(), AppError> {
+    tracing::warn!("Kill switch is only supported on Linux — continuing without it");
+    Ok(())
+}
+
+/// Remove the kill switch rules. A missing table is not an error.
+pub fn disable() {
+    #[cfg(target_os = "linux")]
+    {
+        let out = Command::new("nft")
+            .args(["delete", "table", "inet", TABLE])
+            .output();
+        if let Ok(o) = out {
+            if o.status.success() {
+                tracing::info!("Kill switch disabled");
+            }
+        }
+    }
+}
+
+/// True if the kill switch table is currently loaded in the kernel.
+pub fn is_enabled() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        Command::new("nft")
+            .args(["list", "table", "inet", TABLE])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+/// Called on startup: no tunnel can be up yet, so any kill switch rules still
+/// loaded were left behind by a crashed or killed session and would otherwise
+/// block all traffic.
+pub fn cleanup_stale() {
+    if is_enabled() {
+        tracing::warn!("Startup: removing kill switch rules left over from a previous session");
+        disable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ruleset_is_idempotent_and_drops_by_default() {
+        let rules = render_ruleset("createmyvpn0", "1.2.3.4".parse().unwrap(), 51820, false);
+        assert!(rules.starts_with(
+            "table inet createmyvpn_killswitch {}\ndelete table inet createmyvpn_killswitch\n"
+        ));
+        assert!(rules.contains("type filter hook output priority 0; policy drop;"));
+        assert!(rules.contains("type filter hook input priority 0; policy drop;"));
+    }
+
+    #[test]
+    fn ruleset_allows_tunnel_loopback_and_endpoint() {
+        let rules = render_ruleset("createmyvpn0", "1.2.3.4".parse().unwrap(), 51820, false);
+        assert!(rules.contains("oifname \"createmyvpn0\" accept"));
+        assert!(rules.contains("oifname \"lo\" accept"));
+        assert!(rules.contains("ip daddr 1.2.3.4 udp dport 51820 accept"));
+        assert!(rules.contains("ip saddr 1.2.3.4 udp sport 51820 accept"));
+    }
+
+    #[test]
+    fn ruleset_blocks_lan_unless_allowed() {
+        let blocked = render_ruleset("createmyvpn0", "1.2.3.4".parse().unwrap(), 51820, false);
+        assert!(!blocked.contains("192.168.0.0/16"));
+
+        let allowed = render_ruleset("createmyvpn0", "1.2.3.4".parse().unwrap(), 51820, true);
+        assert!(allowed.contains("ip daddr { 10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16"));
+        assert!(allowed.contains("ip6 saddr { fc00::/7, fe80::/10, ff00::/8 } accept"));
+    }
+
+    #[test]
+    fn ruleset_uses_ip6_for_v6_endpoint() {
+        let rules = render_ruleset("createmyvpn0", "2001:db8::1".parse().unwrap(), 51820, false);
+        assert!(rules.contains("ip6 daddr 2001:db8::1 udp dport 51820 accept"));
+    }
+}
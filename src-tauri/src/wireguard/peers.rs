@@ -0,0 +1,134 @@
+use crate::error::AppError;
+use crate::state::PeerInfo;
+
+use super::subnet::TunnelSubnet;
+
+/// .1 is the server, .2 is the primary client created at deploy time.
+const FIRST_PEER_HOST: u8 = 3;
+/// IPv6 ULA prefix of the tunnel (fd86:ea04:1115::/64). Each host gets the
+/// address whose last group matches its IPv4 host number.
+pub const TUNNEL_V6_PREFIX: &str = "fd86:ea04:1115::";
+pub const TUNNEL_V6_CIDR: &str = "fd86:ea04:1115::/64";
+
+/// IPv6 tunnel address paired with an IPv4 tunnel address, e.g.
+/// "10.8.0.3" → "fd86:ea04:1115::3".
+pub fn tunnel_ipv6(address_v4: &str) -> String {
+    let host = address_v4.rsplit('.').next().unwrap_or(address_v4);
+    format!("{}{}", TUNNEL_V6_PREFIX, host)
+}
+
+/// Pick the lowest unused host address in the tunnel subnet for a new peer.
+pub fn next_free_address(subnet: &TunnelSubnet, peers: &[PeerInfo]) -> Result<String, AppError> {
+    (FIRST_PEER_HOST..=subnet.last_host())
+        .map(|host| subnet.host(host).to_string())
+        .find(|addr| !peers.iter().any(|p| &p.address == addr))
+        .ok_or_else(|| AppError::WireGuard(format!("No free addresses left in {}", subnet)))
+}
+
+/// Peer names end up in wg0.conf comments and in file names on export, so keep
+/// them to a conservative character set.
+pub fn validate_peer_name(name: &str, peers: &[PeerInfo]) -> Result<(), AppError> {
+    if name.is_empty() || name.len() > 32 {
+        return Err(AppError::WireGuard(
+            "Peer name must be between 1 and 32 characters".into(),
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(AppError::WireGuard(
+            "Peer name may only contain letters, digits, '-' and '_'".into(),
+        ));
+    }
+    if peers.iter().any(|p| p.name == name) {
+        return Err(AppError::WireGuard(format!(
+            "A peer named '{}' already exists",
+            name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(name: &str, address: &str) -> PeerInfo {
+        PeerInfo {
+            name: name.to_string(),
+            public_key: format!("{}-pub", name),
+            address: address.to_string(),
+            created_at: chrono::Utc::now(),
+            client_config: None,
+        }
+    }
+
+    #[test]
+    fn tunnel_ipv6_matches_ipv4_host_number() {
+        assert_eq!(tunnel_ipv6("10.8.0.1"), "fd86:ea04:1115::1");
+        assert_eq!(tunnel_ipv6("10.8.0.42"), "fd86:ea04:1115::42");
+        assert!(tunnel_ipv6("10.8.0.254").parse::<std::net::Ipv6Addr>().is_ok());
+    }
+
+    fn default_subnet() -> TunnelSubnet {
+        TunnelSubnet::of(None).unwrap()
+    }
+
+    #[test]
+    fn first_peer_gets_dot_three() {
+        assert_eq!(
+            next_free_address(&default_subnet(), &[]).unwrap(),
+            "10.8.0.3"
+        );
+    }
+
+    #[test]
+    fn next_free_address_fills_gaps() {
+        let peers = vec![peer("a", "10.8.0.3"), peer("b", "10.8.0.5")];
+        assert_eq!(
+            next_free_address(&default_subnet(), &peers).unwrap(),
+            "10.8.0.4"
+        );
+    }
+
+    #[test]
+    fn next_free_address_errors_when_exhausted() {
+        let peers: Vec<PeerInfo> = (FIRST_PEER_HOST..=254)
+            .map(|h| peer(&format!("p{}", h), &format!("10.8.0.{}", h)))
+            .collect();
+        assert!(next_free_address(&default_subnet(), &peers).is_err());
+    }
+
+    #[test]
+    fn next_free_address_stays_in_a_custom_subnet() {
+        let subnet = TunnelSubnet::parse("192.168.77.0/29").unwrap();
+        assert_eq!(next_free_address(&subnet, &[]).unwrap(), "192.168.77.3");
+        let peers: Vec<PeerInfo> = (3..=6)
+            .map(|h| peer(&format!("p{}", h), &format!("192.168.77.{}", h)))
+            .collect();
+        let err = next_free_address(&subnet, &peers).unwrap_err();
+        assert!(err.to_string().contains("192.168.77.0/29"));
+    }
+
+    #[test]
+    fn validate_peer_name_accepts_simple_names() {
+        assert!(validate_peer_name("laptop", &[]).is_ok());
+        assert!(validate_peer_name("alice_phone-2", &[]).is_ok());
+    }
+
+    #[test]
+    fn validate_peer_name_rejects_bad_input() {
+        assert!(validate_peer_name("", &[]).is_err());
+        assert!(validate_peer_name("has space", &[]).is_err());
+        assert!(validate_peer_name("new\nline", &[]).is_err());
+        assert!(validate_peer_name(&"x".repeat(33), &[]).is_err());
+    }
+
+    #[test]
+    fn validate_peer_name_rejects_duplicates() {
+        let peers = vec![peer("phone", "10.8.0.3")];
+        let err = validate_peer_name("phone", &peers).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+    }
+}
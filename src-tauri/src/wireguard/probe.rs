@@ -0,0 +1,177 @@
+//! Checking a server's WireGuard from outside, with one handshake using the
+//! client's keys. A server deployed without SSH has no other way to say its
+//! first-boot script (see `bootstrap`) finished: it answers once wg0 is up
+//! with the client as its peer.
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use boringtun::noise::{Tunn, TunnResult};
+use boringtun::x25519::{PublicKey, StaticSecret};
+
+use super::config_parser::ParsedClientConfig;
+use crate::error::AppError;
+
+/// How long one handshake attempt waits for the server's response.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause between attempts while the server is still booting.
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Send a handshake initiation to `endpoint` and wait up to `timeout` for
+/// the response. Blocking.
+fn handshake_once(
+    endpoint: SocketAddr,
+    client_private: &[u8; 32],
+    server_public: &[u8; 32],
+    timeout: Duration,
+) -> Result<bool, AppError> {
+    let mut tunn = Tunn::new(
+        StaticSecret::from(*client_private),
+        PublicKey::from(*server_public),
+        None,
+        None,
+        0,
+        None,
+    );
+    let failed = |e: std::io::Error| AppError::WireGuard(format!("Handshake probe failed: {}", e));
+    let bind = if endpoint.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let udp = UdpSocket::bind(bind).map_err(failed)?;
+    udp.connect(endpoint).map_err(failed)?;
+
+    let mut out = vec![0u8; 2048];
+    match tunn.format_handshake_initiation(&mut out, true) {
+        TunnResult::WriteToNetwork(pkt) => {
+            udp.send(pkt).map_err(failed)?;
+        }
+        TunnResult::Err(e) => {
+            return Err(AppError::WireGuard(format!(
+                "Could not start a handshake: {:?}",
+                e
+            )))
+        }
+        _ => return Ok(false),
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = vec![0u8; 2048];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(false);
+        }
+        udp.set_read_timeout(Some(left)).map_err(failed)?;
+        let n = match udp.recv(&mut buf) {
+            Ok(n) => n,
+            // Timed out, or ICMP port unreachable while the server boots.
+            Err(_) => return Ok(false),
+        };
+        if let TunnResult::WriteToNetwork(pkt) = tunn.decapsulate(None, &buf[..n], &mut out) {
+            // The keepalive that confirms the session to the server.
+            let _ = udp.send(pkt);
+        }
+        if tunn.time_since_last_handshake().is_some() {
+            return Ok(true);
+        }
+    }
+}
+
+/// Retry a handshake with the server at `endpoint` until it answers, or
+/// fail after `timeout`.
+pub async fn wait_for_handshake(
+    endpoint: SocketAddr,
+    client_private_key: &str,
+    server_public_key: &str,
+    timeout: Duration,
+) -> Result<(), AppError> {
+    let client_private = ParsedClientConfig::decode_key(client_private_key)?;
+    let server_public = ParsedClientConfig::decode_key(server_public_key)?;
+    let deadline = Instant::now() + timeout;
+    for attempt in 1.. {
+        let answered = tokio::task::spawn_blocking(move || {
+            handshake_once(endpoint, &client_private, &server_public, ATTEMPT_TIMEOUT)
+        })
+        .await
+        .map_err(|e| AppError::WireGuard(format!("Handshake probe stopped: {}", e)))??;
+        if answered {
+            tracing::info!("WireGuard on {} answered (attempt {})", endpoint, attempt);
+            return Ok(());
+        }
+        tracing::debug!(
+            "No WireGuard answer from {} (attempt {})",
+            endpoint,
+            attempt
+        );
+        if Instant::now() + RETRY_INTERVAL > deadline {
+            break;
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+    Err(AppError::WireGuard(format!(
+        "WireGuard on {} did not answer within {} minutes. The server may still be \
+         installing it, or its first-boot setup failed; destroy it and deploy again.",
+        endpoint,
+        timeout.as_secs() / 60
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wireguard::keys;
+
+    /// A WireGuard responder on localhost for one handshake.
+    fn fake_server(server_private: [u8; 32], client_public: [u8; 32]) -> SocketAddr {
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = udp.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut tunn = Tunn::new(
+                StaticSecret::from(server_private),
+                PublicKey::from(client_public),
+                None,
+                None,
+                1,
+                None,
+            );
+            let (mut buf, mut out) = (vec![0u8; 2048], vec![0u8; 2048]);
+            let (n, from) = udp.recv_from(&mut buf).unwrap();
+            if let TunnResult::WriteToNetwork(pkt) = tunn.decapsulate(None, &buf[..n], &mut out) {
+                udp.send_to(pkt, from).unwrap();
+            }
+        });
+        addr
+    }
+
+    fn decode(key: &str) -> [u8; 32] {
+        ParsedClientConfig::decode_key(key).unwrap()
+    }
+
+    #[test]
+    fn server_with_the_client_as_peer_answers() {
+        let (server, client) = (keys::generate_keypair(), keys::generate_keypair());
+        let addr = fake_server(decode(&server.private_key), decode(&client.public_key));
+        let answered = handshake_once(
+            addr,
+            &decode(&client.private_key),
+            &decode(&server.public_key),
+            ATTEMPT_TIMEOUT,
+        );
+        assert!(answered.unwrap());
+    }
+
+    #[test]
+    fn unknown_client_gets_no_answer() {
+        let (server, client) = (keys::generate_keypair(), keys::generate_keypair());
+        let stranger = keys::generate_keypair();
+        let addr = fake_server(decode(&server.private_key), decode(&stranger.public_key));
+        let answered = handshake_once(
+            addr,
+            &decode(&client.private_key),
+            &decode(&server.public_key),
+            Duration::from_millis(500),
+        );
+        assert!(!answered.unwrap());
+    }
+}
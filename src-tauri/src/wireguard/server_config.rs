@@ -1,40 +1,110 @@
-/// Render the WireGuard server config (wg0.conf) with iptables NAT rules.
+use super::peers::tunnel_ipv6;
+use super::subnet::TunnelSubnet;
+
+/// Render the WireGuard server config (wg0.conf) with iptables NAT rules,
+/// masquerading `subnet` out of `egress_interface`, the server's default
+/// route. With `ipv6` the tunnel is dual-stack: a ULA /64 is added and
+/// NATed with ip6tables. Leave it off on servers without a global IPv6
+/// address or ip6tables NAT; a failing PostUp rule stops `wg-quick up`.
 pub fn render_server_config(
     server_private_key: &str,
     client_public_key: &str,
     listen_port: u16,
+    subnet: &TunnelSubnet,
+    egress_interface: &str,
+    ipv6: bool,
 ) -> String {
+    let server = subnet.server_address().to_string();
+    let client = subnet.client_address().to_string();
+    let (address_v6, rules_v6) = if ipv6 {
+        (
+            format!(", {}/64", tunnel_ipv6(&server)),
+            format!(
+                r#"PostUp = ip6tables -t nat -A POSTROUTING -o {egress_interface} -j MASQUERADE
+PostUp = ip6tables -A FORWARD -i wg0 -j ACCEPT
+PostUp = ip6tables -A FORWARD -o wg0 -j ACCEPT
+PostDown = ip6tables -t nat -D POSTROUTING -o {egress_interface} -j MASQUERADE
+PostDown = ip6tables -D FORWARD -i wg0 -j ACCEPT
+PostDown = ip6tables -D FORWARD -o wg0 -j ACCEPT
+"#
+            ),
+        )
+    } else {
+        (String::new(), String::new())
+    };
     format!(
         r#"[Interface]
-Address = 10.8.0.1/24
+Address = {server}/{prefix}{address_v6}
 ListenPort = {listen_port}
 PrivateKey = {server_private_key}
 
 # NAT masquerading rules
-PostUp = iptables -t nat -A POSTROUTING -o eth0 -j MASQUERADE
+PostUp = iptables -t nat -A POSTROUTING -o {egress_interface} -j MASQUERADE
 PostUp = iptables -A FORWARD -i wg0 -j ACCEPT
 PostUp = iptables -A FORWARD -o wg0 -j ACCEPT
-PostDown = iptables -t nat -D POSTROUTING -o eth0 -j MASQUERADE
+PostDown = iptables -t nat -D POSTROUTING -o {egress_interface} -j MASQUERADE
 PostDown = iptables -D FORWARD -i wg0 -j ACCEPT
 PostDown = iptables -D FORWARD -o wg0 -j ACCEPT
-
+{rules_v6}
 [Peer]
 PublicKey = {client_public_key}
-AllowedIPs = 10.8.0.2/32
+AllowedIPs = {allowed_ips}
 "#,
+        prefix = subnet.prefix(),
         listen_port = listen_port,
         server_private_key = server_private_key,
         client_public_key = client_public_key,
+        egress_interface = egress_interface,
+        allowed_ips = peer_allowed_ips(&client, ipv6),
+    )
+}
+
+/// A peer's AllowedIPs on the server: its tunnel address, plus the matching
+/// IPv6 one when the tunnel is dual-stack (`ipv6`).
+pub fn peer_allowed_ips(address: &str, ipv6: bool) -> String {
+    if ipv6 {
+        format!("{}/32, {}/128", address, tunnel_ipv6(address))
+    } else {
+        format!("{}/32", address)
+    }
+}
+
+/// Render a `[Peer]` block for an additional client, appended to wg0.conf so the
+/// peer survives a `wg-quick` restart. The name is kept as a comment only.
+pub fn render_peer_block(name: &str, public_key: &str, address: &str, ipv6: bool) -> String {
+    let allowed_ips = peer_allowed_ips(address, ipv6);
+    format!(
+        r#"
+# Name = {name}
+[Peer]
+PublicKey = {public_key}
+AllowedIPs = {allowed_ips}
+"#
     )
 }
 
+/// Remove the `[Peer]` block with the given public key from a wg0.conf,
+/// leaving every other section untouched.
+pub fn remove_peer_block(config: &str, public_key: &str) -> String {
+    let needle = format!("PublicKey = {}", public_key);
+    config
+        .split("\n\n")
+        .filter(|block| !(block.contains("[Peer]") && block.lines().any(|l| l.trim() == needle)))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn subnet() -> TunnelSubnet {
+        TunnelSubnet::of(None).unwrap()
+    }
+
     #[test]
     fn render_server_config_contains_interface() {
-        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820);
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
         assert!(config.contains("[Interface]"));
         assert!(config.contains("Address = 10.8.0.1/24"));
         assert!(config.contains("ListenPort = 51820"));
@@ -43,7 +113,7 @@ mod tests {
 
     #[test]
     fn render_server_config_contains_nat_rules() {
-        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820);
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
         assert!(config.contains("PostUp = iptables -t nat -A POSTROUTING -o eth0 -j MASQUERADE"));
         assert!(config.contains("PostDown = iptables -t nat -D POSTROUTING -o eth0 -j MASQUERADE"));
         assert!(config.contains("PostUp = iptables -A FORWARD -i wg0 -j ACCEPT"));
@@ -52,15 +122,86 @@ mod tests {
 
     #[test]
     fn render_server_config_contains_peer() {
-        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820);
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
         assert!(config.contains("[Peer]"));
         assert!(config.contains("PublicKey = CLI_PUB"));
         assert!(config.contains("AllowedIPs = 10.8.0.2/32"));
     }
 
+    #[test]
+    fn render_server_config_is_dual_stack() {
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
+        assert!(config.contains("Address = 10.8.0.1/24, fd86:ea04:1115::1/64"));
+        assert!(config.contains("PostUp = ip6tables -t nat -A POSTROUTING -o eth0 -j MASQUERADE"));
+        assert!(config.contains("PostDown = ip6tables -D FORWARD -o wg0 -j ACCEPT"));
+        assert!(config.contains("AllowedIPs = 10.8.0.2/32, fd86:ea04:1115::2/128"));
+    }
+
+    #[test]
+    fn render_server_config_without_ipv6() {
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", false);
+        assert!(config.contains("Address = 10.8.0.1/24\n"));
+        assert!(config.contains("PostUp = iptables -t nat -A POSTROUTING -o eth0 -j MASQUERADE"));
+        assert!(!config.contains("ip6tables"));
+        assert!(!config.contains("fd86:ea04:1115::1"));
+        assert!(config.contains("-j ACCEPT\n\n[Peer]"));
+        assert!(config.contains("AllowedIPs = 10.8.0.2/32\n"));
+        assert!(!config.contains("/128"));
+    }
+
     #[test]
     fn render_server_config_custom_port() {
-        let config = render_server_config("K", "P", 9999);
+        let config = render_server_config("K", "P", 9999, &subnet(), "eth0", true);
         assert!(config.contains("ListenPort = 9999"));
     }
+
+    #[test]
+    fn render_server_config_uses_subnet_and_interface() {
+        let subnet = TunnelSubnet::parse("172.29.4.0/26").unwrap();
+        let config = render_server_config("K", "P", 51820, &subnet, "ens5", true);
+        assert!(config.contains("Address = 172.29.4.1/26, fd86:ea04:1115::1/64"));
+        assert!(config.contains("AllowedIPs = 172.29.4.2/32, fd86:ea04:1115::2/128"));
+        assert!(config.contains("PostUp = iptables -t nat -A POSTROUTING -o ens5 -j MASQUERADE"));
+        assert!(config.contains("PostDown = ip6tables -t nat -D POSTROUTING -o ens5 -j MASQUERADE"));
+        assert!(!config.contains("eth0"));
+    }
+
+    #[test]
+    fn render_peer_block_contains_key_and_address() {
+        let block = render_peer_block("phone", "PEER_PUB", "10.8.0.3", true);
+        assert!(block.contains("# Name = phone"));
+        assert!(block.contains("[Peer]"));
+        assert!(block.contains("PublicKey = PEER_PUB"));
+        assert!(block.contains("AllowedIPs = 10.8.0.3/32, fd86:ea04:1115::3/128"));
+    }
+
+    #[test]
+    fn render_peer_block_without_ipv6() {
+        let block = render_peer_block("phone", "PEER_PUB", "10.8.0.3", false);
+        assert!(block.contains("AllowedIPs = 10.8.0.3/32\n"));
+        assert!(!block.contains("/128"));
+    }
+
+    #[test]
+    fn remove_peer_block_drops_only_matching_peer() {
+        let mut config =
+            render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
+        config.push('\n');
+        config.push_str(&render_peer_block("phone", "PHONE_PUB", "10.8.0.3", true));
+        config.push('\n');
+        config.push_str(&render_peer_block("laptop", "LAPTOP_PUB", "10.8.0.4", true));
+
+        let pruned = remove_peer_block(&config, "PHONE_PUB");
+        assert!(!pruned.contains("PHONE_PUB"));
+        assert!(!pruned.contains("# Name = phone"));
+        assert!(pruned.contains("PublicKey = LAPTOP_PUB"));
+        assert!(pruned.contains("PublicKey = CLI_PUB"));
+        assert!(pruned.contains("PostUp = iptables -A FORWARD -i wg0 -j ACCEPT"));
+    }
+
+    #[test]
+    fn remove_peer_block_unknown_key_is_noop() {
+        let config = render_server_config("SRV_PRIV", "CLI_PUB", 51820, &subnet(), "eth0", true);
+        assert_eq!(remove_peer_block(&config, "NOPE"), config);
+    }
 }
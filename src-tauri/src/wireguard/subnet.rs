@@ -0,0 +1,293 @@
+//! The IPv4 network a tunnel runs on. The server is host .1 and the primary
+//! client .2; extra peers get the hosts after that. It must not overlap a
+//! network the client already routes to (e.g. a corporate LAN), or traffic
+//! for one of them goes to the other.
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::process::Command;
+
+use crate::error::AppError;
+
+pub const DEFAULT_TUNNEL_CIDR: &str = "10.8.0.0/24";
+/// Host numbers stay below 256 so each maps onto its own IPv6 address (see
+/// `peers::tunnel_ipv6`); /29 leaves room for the server, the client and four
+/// peers.
+const MIN_PREFIX: u8 = 24;
+const MAX_PREFIX: u8 = 29;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TunnelSubnet {
+    network: Ipv4Addr,
+    prefix: u8,
+}
+
+impl TunnelSubnet {
+    /// Parse e.g. "10.8.0.0/24". The address must be the network address.
+    pub fn parse(cidr: &str) -> Result<Self, AppError> {
+        let invalid =
+            |why: &str| AppError::WireGuard(format!("Invalid tunnel subnet '{}': {}", cidr, why));
+        let (addr, prefix) = cidr
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| invalid("expected an IPv4 network such as 10.8.0.0/24"))?;
+        let network: Ipv4Addr = addr.parse().map_err(|_| invalid("not an IPv4 address"))?;
+        let prefix: u8 = prefix.parse().map_err(|_| invalid("bad prefix length"))?;
+        if !(MIN_PREFIX..=MAX_PREFIX).contains(&prefix) {
+            return Err(invalid(&format!(
+                "the prefix must be between /{} and /{}",
+                MIN_PREFIX, MAX_PREFIX
+            )));
+        }
+        if !network.is_private() {
+            return Err(invalid(
+                "use a private range (10/8, 172.16/12 or 192.168/16)",
+            ));
+        }
+        if mask(network, prefix) != network {
+            return Err(invalid(&format!(
+                "{} is not the network address, use {}/{}",
+                network,
+                mask(network, prefix),
+                prefix
+            )));
+        }
+        Ok(TunnelSubnet { network, prefix })
+    }
+
+    /// The subnet a deployment was made with; old state files predate the
+    /// setting and use the default.
+    pub fn of(cidr: Option<&str>) -> Result<Self, AppError> {
+        Self::parse(cidr.unwrap_or(DEFAULT_TUNNEL_CIDR))
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// The address of host number `n`, e.g. 3 → "10.8.0.3".
+    pub fn host(&self, n: u8) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.network) + u32::from(n))
+    }
+
+    pub fn server_address(&self) -> Ipv4Addr {
+        self.host(1)
+    }
+
+    pub fn client_address(&self) -> Ipv4Addr {
+        self.host(2)
+    }
+
+    /// The last host number before the broadcast address.
+    pub fn last_host(&self) -> u8 {
+        ((1u32 << (32 - self.prefix)) - 2) as u8
+    }
+
+    pub fn overlaps(&self, network: Ipv4Addr, prefix: u8) -> bool {
+        let shorter = self.prefix.min(prefix);
+        mask(self.network, shorter) == mask(network, shorter)
+    }
+
+    /// Fail if a network the client routes to overlaps this subnet.
+    pub fn check_local_routes(&self, routes: &[(Ipv4Addr, u8)]) -> Result<(), AppError> {
+        match routes
+            .iter()
+            .find(|(net, prefix)| self.overlaps(*net, *prefix))
+        {
+            Some((net, prefix)) => Err(AppError::WireGuard(format!(
+                "The tunnel subnet {} overlaps {}/{}, a network this computer already \
+                 routes to. Pick another subnet in Settings.",
+                self, net, prefix
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for TunnelSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+fn mask(addr: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    let bits = if prefix == 0 {
+        0
+    } else {
+        u32::MAX << (32 - prefix)
+    };
+    Ipv4Addr::from(u32::from(addr) & bits)
+}
+
+/// `cidr` parsed, checked against the client's routing table.
+pub fn validate(cidr: &str) -> Result<TunnelSubnet, AppError> {
+    let subnet = TunnelSubnet::parse(cidr)?;
+    subnet.check_local_routes(&local_routes())?;
+    Ok(subnet)
+}
+
+/// A route destination as the routing tables print it: "192.168.1.0/24", a
+/// bare host "192.168.1.7", or macOS's shortened "192.168.1" and "10/8".
+fn parse_destination(dest: &str) -> Option<(Ipv4Addr, u8)> {
+    let (addr, prefix) = match dest.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+        None => (dest, None),
+    };
+    let mut octets = [0u8; 4];
+    let mut count = 0;
+    for part in addr.split('.') {
+        if count == 4 {
+            return None;
+        }
+        octets[count] = part.parse().ok()?;
+        count += 1;
+    }
+    let prefix = prefix.unwrap_or(count as u8 * 8);
+    (prefix <= 32).then(|| (Ipv4Addr::from(octets), prefix))
+}
+
+/// Routes worth comparing against: not the default route or the two /1
+/// halves a connected tunnel adds, and not host routes (the tunnel's own
+/// address, the pinned route to a server).
+fn is_network_route(prefix: u8) -> bool {
+    (2..32).contains(&prefix)
+}
+
+/// The IPv4 networks the client routes to, outside any tunnel of ours.
+/// Empty if the routing table can't be read.
+pub fn local_routes() -> Vec<(Ipv4Addr, u8)> {
+    route_destinations()
+        .iter()
+        .filter_map(|d| parse_destination(d))
+        .filter(|(net, prefix)| {
+            is_network_route(*prefix) && !net.is_loopback() && !net.is_multicast()
+        })
+        .collect()
+}
+
+fn route_destinations() -> Vec<String> {
+    #[cfg(target_os = "linux")]
+    {
+        // "192.168.1.0/24 dev wlan0 proto kernel scope link src 192.168.1.5"
+        let Ok(out) = Command::new("ip").args(["-4", "route", "show"]).output() else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.contains("dev createmyvpn0"))
+            .filter_map(|l| l.split_whitespace().next().map(str::to_string))
+            .collect()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // "Destination  Gateway  Flags  Netif Expire", e.g.
+        // "192.168.1    link#6   UCS    en0"
+        let Ok(out) = Command::new("netstat").args(["-rn", "-f", "inet"]).output() else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.contains("utun"))
+            .filter_map(|l| l.split_whitespace().next().map(str::to_string))
+            .collect()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let out = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Get-NetRoute -AddressFamily IPv4 | \
+                  Where-Object { $_.InterfaceAlias -ne 'createmyvpn0' } | \
+                  ForEach-Object { $_.DestinationPrefix }",
+            ])
+            .output();
+        let Ok(out) = out else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .map(|l| l.trim().to_string())
+            .collect()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_subnet_hosts() {
+        let subnet = TunnelSubnet::parse(DEFAULT_TUNNEL_CIDR).unwrap();
+        assert_eq!(subnet.server_address().to_string(), "10.8.0.1");
+        assert_eq!(subnet.client_address().to_string(), "10.8.0.2");
+        assert_eq!(subnet.last_host(), 254);
+        assert_eq!(subnet.to_string(), "10.8.0.0/24");
+    }
+
+    #[test]
+    fn smaller_subnets_end_sooner() {
+        let subnet = TunnelSubnet::parse("172.20.9.64/27").unwrap();
+        assert_eq!(subnet.server_address().to_string(), "172.20.9.65");
+        assert_eq!(subnet.host(30).to_string(), "172.20.9.94");
+        assert_eq!(subnet.last_host(), 30);
+    }
+
+    #[test]
+    fn rejects_bad_subnets() {
+        assert!(TunnelSubnet::parse("10.8.0.0").is_err());
+        assert!(TunnelSubnet::parse("10.8.0.0/16").is_err());
+        assert!(TunnelSubnet::parse("10.8.0.0/30").is_err());
+        assert!(TunnelSubnet::parse("8.8.8.0/24").is_err());
+        let err = TunnelSubnet::parse("10.8.0.5/24").unwrap_err();
+        assert!(err.to_string().contains("use 10.8.0.0/24"));
+    }
+
+    #[test]
+    fn old_deployments_use_the_default() {
+        assert_eq!(
+            TunnelSubnet::of(None).unwrap(),
+            TunnelSubnet::parse("10.8.0.0/24").unwrap()
+        );
+    }
+
+    #[test]
+    fn overlap_with_local_routes() {
+        let subnet = TunnelSubnet::parse("10.8.0.0/24").unwrap();
+        let lan = (Ipv4Addr::new(192, 168, 1, 0), 24);
+        assert!(subnet.check_local_routes(&[lan]).is_ok());
+
+        let corporate = (Ipv4Addr::new(10, 0, 0, 0), 8);
+        let err = subnet.check_local_routes(&[lan, corporate]).unwrap_err();
+        assert!(err.to_string().contains("overlaps 10.0.0.0/8"));
+
+        let inside = (Ipv4Addr::new(10, 8, 0, 128), 25);
+        assert!(subnet.check_local_routes(&[inside]).is_err());
+    }
+
+    #[test]
+    fn parses_route_destinations() {
+        let net = |a, b, c, d, p| Some((Ipv4Addr::new(a, b, c, d), p));
+        assert_eq!(parse_destination("192.168.1.0/24"), net(192, 168, 1, 0, 24));
+        assert_eq!(parse_destination("192.168.1"), net(192, 168, 1, 0, 24));
+        assert_eq!(parse_destination("10/8"), net(10, 0, 0, 0, 8));
+        assert_eq!(parse_destination("192.168.1.7"), net(192, 168, 1, 7, 32));
+        assert_eq!(parse_destination("default"), None);
+        assert_eq!(parse_destination("1.2.3.4.5"), None);
+    }
+
+    #[test]
+    fn default_and_host_routes_are_ignored() {
+        assert!(!is_network_route(0));
+        assert!(!is_network_route(1));
+        assert!(!is_network_route(32));
+        assert!(is_network_route(24));
+    }
+}
@@ -1,11 +1,20 @@
 use crate::error::AppError;
-use crate::persistence::store;
+use crate::state::{AppSettings, TunnelStats};
+
+use super::userspace::{ConnectOptions, EventCallback};
 
 /// Activate the WireGuard tunnel using the built-in userspace engine.
 /// No `wg-quick`, no kernel module required. Works on Linux, WSL2, macOS, Windows.
-pub fn activate_tunnel(client_config: &str) -> Result<(), AppError> {
-    store::save_client_config(client_config)?;
-    super::userspace::connect(client_config)
+pub fn activate_tunnel(
+    client_config: &str,
+    settings: &AppSettings,
+    on_event: Option<EventCallback>,
+) -> Result<(), AppError> {
+    let opts = ConnectOptions {
+        kill_switch: settings.kill_switch,
+        kill_switch_allow_lan: settings.kill_switch_allow_lan,
+    };
+    super::userspace::connect(client_config, &opts, on_event)
 }
 
 /// Deactivate the WireGuard tunnel.
@@ -16,4 +25,9 @@ pub fn deactivate_tunnel() -> Result<(), AppError> {
 /// Returns true if the tunnel is currently active.
 pub fn is_tunnel_active() -> bool {
     super::userspace::is_active()
-}
\ No newline at end of file
+}
+
+/// Live counters for the active tunnel, or None if disconnected.
+pub fn tunnel_stats() -> Option<TunnelStats> {
+    super::userspace::stats()
+}
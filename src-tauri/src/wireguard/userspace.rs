@@ -7,10 +7,11 @@
 /// Privilege requirement: creating a TUN device needs CAP_NET_ADMIN on Linux
 /// or admin rights on Windows. Set once with:
 ///     sudo setcap cap_net_admin+ep /path/to/createmyvpn
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::process::Command;
-use std::sync::{Mutex, OnceLock};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use boringtun::noise::{Tunn, TunnResult};
 use boringtun::x25519::{PublicKey, StaticSecret};
@@ -18,14 +19,29 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::UdpSocket;
 
 use crate::error::AppError;
+use crate::state::TunnelStats;
 
 use super::config_parser::ParsedClientConfig;
+use super::dns::{self, DnsMethod};
+use super::killswitch;
 
 const TUN_NAME: &str = "createmyvpn0";
 const MTU: usize = 1420;
+/// `::/0` is split into two halves, like 0.0.0.0/0, so it doesn't replace the
+/// system's default IPv6 route.
+const IPV6_HALVES: [&str; 2] = ["::/1", "8000::/1"];
 /// WireGuard keepalive/handshake timer: how often boringtun's internal timers
 /// are serviced.  200 ms is the WireGuard spec recommendation.
 const TIMER_INTERVAL_MS: u64 = 200;
+/// How often handshake/RTT are sampled and a `TunnelEvent::Stats` is sent.
+const STATS_INTERVAL_MS: u64 = 1000;
+/// How often the watchdog checks the default gateway and handshake age.
+const WATCHDOG_INTERVAL_MS: u64 = 5000;
+/// No handshake for this long means the session is dead: WireGuard stops
+/// accepting data on a session after 180 s (REJECT_AFTER_TIME).
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(180);
+/// Minimum gap between recovery attempts while the handshake stays stale.
+const RECOVERY_BACKOFF: Duration = Duration::from_secs(30);
 
 // ─── Active tunnel state ────────────────────────────────────────────────────
 
@@ -33,7 +49,72 @@ struct ActiveTunnel {
     /// Dropping (or `send`-ing) this stops the tunnel loop.
     stop_tx: tokio::sync::oneshot::Sender<()>,
     server_ip: String,
-    gateway: Option<String>,
+    /// Gateway the server route is pinned through. Shared with the packet loop,
+    /// which re-pins it when the network changes.
+    gateway: Arc<Mutex<Option<String>>>,
+    kill_switch: bool,
+    /// How the config's DNS servers were applied, if they were.
+    dns: Option<DnsMethod>,
+    counters: Arc<TunnelCounters>,
+}
+
+/// Counters shared between the packet loop and `stats()`.
+#[derive(Default)]
+struct TunnelCounters {
+    tx_bytes: AtomicU64,
+    rx_bytes: AtomicU64,
+    tx_packets: AtomicU64,
+    rx_packets: AtomicU64,
+    decapsulate_errors: AtomicU64,
+    /// Handshake time and RTT, sampled from `Tunn` every STATS_INTERVAL_MS.
+    handshake: Mutex<(Option<chrono::DateTime<chrono::Utc>>, Option<u32>)>,
+}
+
+impl TunnelCounters {
+    fn record_tx(&self, len: usize) {
+        self.tx_bytes.fetch_add(len as u64, Ordering::Relaxed);
+        self.tx_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_rx(&self, len: usize) {
+        self.rx_bytes.fetch_add(len as u64, Ordering::Relaxed);
+        self.rx_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> TunnelStats {
+        let (last_handshake_at, rtt_ms) = *self.handshake.lock().unwrap();
+        TunnelStats {
+            tx_bytes: self.tx_bytes.load(Ordering::Relaxed),
+            rx_bytes: self.rx_bytes.load(Ordering::Relaxed),
+            tx_packets: self.tx_packets.load(Ordering::Relaxed),
+            rx_packets: self.rx_packets.load(Ordering::Relaxed),
+            last_handshake_at,
+            decapsulate_errors: self.decapsulate_errors.load(Ordering::Relaxed),
+            rtt_ms,
+        }
+    }
+}
+
+/// Notifications sent from the tunnel thread while it runs.
+#[derive(Debug, Clone)]
+pub enum TunnelEvent {
+    /// Fresh counters, every STATS_INTERVAL_MS.
+    Stats(TunnelStats),
+    /// The watchdog is rebuilding the connection; carries the reason.
+    Reconnecting(String),
+    /// A handshake completed after `Reconnecting`.
+    Reconnected,
+}
+
+pub type EventCallback = Box<dyn Fn(TunnelEvent) + Send>;
+
+/// Per-connection options taken from the user's settings.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    /// Block all traffic outside the tunnel until `disconnect` (Linux only).
+    pub kill_switch: bool,
+    /// Let LAN traffic bypass the kill switch.
+    pub kill_switch_allow_lan: bool,
 }
 
 static TUNNEL: OnceLock<Mutex<Option<ActiveTunnel>>> = OnceLock::new();
@@ -77,7 +158,12 @@ fn find_wintun_dll() -> Result<std::ffi::OsString, AppError> {
 }
 
 /// Connect: create TUN device, start the WireGuard packet loop, set up routes.
-pub fn connect(config_str: &str) -> Result<(), AppError> {
+/// `on_event` is invoked from the tunnel thread until it stops.
+pub fn connect(
+    config_str: &str,
+    opts: &ConnectOptions,
+    on_event: Option<EventCallback>,
+) -> Result<(), AppError> {
     // Disconnect any existing tunnel first
     let _ = disconnect();
 
@@ -175,14 +261,48 @@ pub fn connect(config_str: &str) -> Result<(), AppError> {
         }
     })?;
 
+    // tun2 only configures IPv4; add the tunnel's IPv6 address ourselves.
+    if let Some(v6) = &cfg.vpn_address_v6 {
+        add_tunnel_ipv6_address(v6);
+    }
+
     let endpoint = cfg.endpoint;
     let server_ip = endpoint.ip().to_string();
 
     // Set up routing: send server traffic via real gateway (not TUN, or we loop)
     setup_routes(&server_ip, &gateway, &cfg.allowed_ips)?;
 
+    // Install the kill switch before any packets flow. If it can't be installed
+    // the user asked for protection we can't give, so undo the routes and fail.
+    if opts.kill_switch {
+        if let Err(e) = killswitch::enable(TUN_NAME, endpoint, opts.kill_switch_allow_lan) {
+            remove_routes(&server_ip, &gateway);
+            return Err(e);
+        }
+    }
+
+    // Point the resolver at the config's DNS servers so lookups don't leak
+    // outside the tunnel. A tunnel that leaks them is not the one asked for,
+    // so undo everything and fail, like the kill switch above.
+    let dns_method = match apply_dns(cfg.dns.as_deref(), dns::SUPPORTED, |servers| {
+        dns::apply(TUN_NAME, servers)
+    }) {
+        Ok(method) => method,
+        Err(e) => {
+            remove_routes(&server_ip, &gateway);
+            if opts.kill_switch {
+                killswitch::disable();
+            }
+            return Err(e);
+        }
+    };
+
     // Oneshot channel used to stop the tunnel loop cleanly
     let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let counters = Arc::new(TunnelCounters::default());
+    let loop_counters = counters.clone();
+    let gateway = Arc::new(Mutex::new(gateway));
+    let loop_gateway = gateway.clone();
 
     // Spawn the tunnel loop in its own OS thread (avoids Send constraints on Tunn)
     std::thread::spawn(move || {
@@ -190,27 +310,70 @@ pub fn connect(config_str: &str) -> Result<(), AppError> {
             .enable_all()
             .build()
             .expect("failed to create tunnel runtime");
-        rt.block_on(tunnel_loop(tun, tunn, endpoint, stop_rx));
+        rt.block_on(tunnel_loop(
+            tun,
+            tunn,
+            endpoint,
+            stop_rx,
+            loop_counters,
+            loop_gateway,
+            on_event,
+        ));
     });
 
     *tunnel_lock().lock().unwrap() = Some(ActiveTunnel {
         stop_tx,
         server_ip: server_ip.clone(),
         gateway,
+        kill_switch: opts.kill_switch,
+        dns: dns_method,
+        counters,
     });
 
     tracing::info!("WireGuard tunnel active — VPN address: {}", cfg.vpn_address);
     Ok(())
 }
 
-/// Disconnect: stop the packet loop and remove routes.
+/// Apply the DNS servers listed in a config's `DNS =` value with `apply`.
+/// Where the platform supports it, failing to is an error; elsewhere the
+/// system resolver is left alone with a warning.
+fn apply_dns(
+    dns: Option<&str>,
+    supported: bool,
+    apply: impl FnOnce(&[IpAddr]) -> Result<DnsMethod, AppError>,
+) -> Result<Option<DnsMethod>, AppError> {
+    let servers = dns.map(dns::parse_dns_servers).unwrap_or_default();
+    if servers.is_empty() {
+        return Ok(None);
+    }
+    if !supported {
+        tracing::warn!("VPN DNS can't be applied on this platform; using the system resolver");
+        return Ok(None);
+    }
+    apply(&servers).map(Some).map_err(|e| {
+        AppError::WireGuard(format!(
+            "Could not point DNS at the VPN's resolvers ({}), so lookups would leak \
+             outside the tunnel. Disconnected.",
+            e
+        ))
+    })
+}
+
+/// Disconnect: stop the packet loop, remove routes and restore DNS.
 pub fn disconnect() -> Result<(), AppError> {
     let mut guard = tunnel_lock().lock().unwrap();
     if let Some(active) = guard.take() {
         tracing::info!("Stopping WireGuard tunnel...");
         // Dropping the sender (or sending) wakes the select! in tunnel_loop
         let _ = active.stop_tx.send(());
-        remove_routes(&active.server_ip, &active.gateway);
+        let gateway = active.gateway.lock().unwrap().clone();
+        remove_routes(&active.server_ip, &gateway);
+        if let Some(method) = active.dns {
+            dns::restore(TUN_NAME, method);
+        }
+        if active.kill_switch {
+            killswitch::disable();
+        }
         tracing::info!("WireGuard tunnel stopped");
     }
     Ok(())
@@ -221,6 +384,15 @@ pub fn is_active() -> bool {
     tunnel_lock().lock().unwrap().is_some()
 }
 
+/// Current counters for the active tunnel, or None if disconnected.
+pub fn stats() -> Option<TunnelStats> {
+    tunnel_lock()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|active| active.counters.snapshot())
+}
+
 // ─── Packet loop ────────────────────────────────────────────────────────────
 
 async fn tunnel_loop(
@@ -228,7 +400,16 @@ async fn tunnel_loop(
     mut tunn: Tunn,
     endpoint: SocketAddr,
     mut stop_rx: tokio::sync::oneshot::Receiver<()>,
+    counters: Arc<TunnelCounters>,
+    gateway: Arc<Mutex<Option<String>>>,
+    on_event: Option<EventCallback>,
 ) {
+    let emit = |event: TunnelEvent| {
+        if let Some(cb) = &on_event {
+            cb(event);
+        }
+    };
+
     let tun = match tun2::AsyncDevice::new(tun) {
         Ok(t) => t,
         Err(e) => {
@@ -245,17 +426,13 @@ async fn tunnel_loop(
         }
     };
 
-    let udp = match UdpSocket::bind("0.0.0.0:0").await {
+    let mut udp = match bind_udp(endpoint).await {
         Ok(s) => s,
         Err(e) => {
-            tracing::error!("Failed to bind UDP socket: {}", e);
+            tracing::error!("Failed to set up UDP socket to {}: {}", endpoint, e);
             return;
         }
     };
-    if let Err(e) = udp.connect(endpoint).await {
-        tracing::error!("Failed to connect UDP socket to {}: {}", endpoint, e);
-        return;
-    }
 
     let mut tun_buf = vec![0u8; 65536];
     let mut udp_buf = vec![0u8; 65536];
@@ -265,6 +442,10 @@ async fn tunnel_loop(
 
     // Fires every TIMER_INTERVAL_MS for WireGuard keepalives
     let mut timer = tokio::time::interval(Duration::from_millis(TIMER_INTERVAL_MS));
+    let mut stats_timer = tokio::time::interval(Duration::from_millis(STATS_INTERVAL_MS));
+    let mut watchdog_timer = tokio::time::interval(Duration::from_millis(WATCHDOG_INTERVAL_MS));
+    let mut watchdog = Watchdog::new(gateway.lock().unwrap().clone());
+    let server_ip = endpoint.ip().to_string();
 
     loop {
         tokio::select! {
@@ -280,18 +461,88 @@ async fn tunnel_loop(
             _ = timer.tick() => {
                 // Send WireGuard keepalives / handshake retries
                 match tunn.update_timers(&mut out_buf) {
-                    TunnResult::WriteToNetwork(pkt) => { let _ = udp.send(pkt).await; }
+                    TunnResult::WriteToNetwork(pkt) => match udp.send(pkt).await {
+                        Ok(_) => counters.record_tx(pkt.len()),
+                        Err(_) => watchdog.socket_failed = true,
+                    },
                     TunnResult::Err(e) => tracing::warn!("WireGuard timer error: {:?}", e),
                     _ => {}
                 }
             }
 
+            // ── Stats: sample handshake/RTT and publish ──────────────────────
+            _ = stats_timer.tick() => {
+                let last_handshake = tunn.time_since_last_handshake().and_then(|ago| {
+                    chrono::Duration::from_std(ago)
+                        .ok()
+                        .map(|ago| chrono::Utc::now() - ago)
+                });
+                let (_, _, _, _, rtt_ms) = tunn.stats();
+                *counters.handshake.lock().unwrap() = (last_handshake, rtt_ms);
+                emit(TunnelEvent::Stats(counters.snapshot()));
+            }
+
+            // ── Watchdog: network changes and dead sessions ─────────────────
+            _ = watchdog_timer.tick() => {
+                let now = Instant::now();
+                let since_handshake = tunn.time_since_last_handshake();
+                if watchdog.recovered(since_handshake, now) {
+                    tracing::info!("Tunnel recovered — handshake completed");
+                    emit(TunnelEvent::Reconnected);
+                }
+
+                let current_gw = tokio::task::spawn_blocking(get_default_gateway)
+                    .await
+                    .ok()
+                    .flatten();
+                let Some(reason) = watchdog.check(&current_gw, since_handshake, now) else {
+                    continue;
+                };
+                tracing::warn!("Tunnel watchdog: {} — reconnecting", reason);
+                emit(TunnelEvent::Reconnecting(reason));
+
+                // Re-pin the server route through the current gateway so
+                // handshake packets don't fall into the tunnel.
+                {
+                    let mut pinned = gateway.lock().unwrap();
+                    if current_gw.is_some() && *pinned != current_gw {
+                        if let Some(old) = pinned.as_deref() {
+                            unpin_server_route(&server_ip, old);
+                        }
+                        if let Some(new) = current_gw.as_deref() {
+                            if let Err(e) = pin_server_route(&server_ip, new) {
+                                tracing::warn!("Failed to re-pin server route: {}", e);
+                            }
+                        }
+                        *pinned = current_gw.clone();
+                    }
+                }
+
+                // A fresh socket picks up the new source address and port.
+                match bind_udp(endpoint).await {
+                    Ok(s) => udp = s,
+                    Err(e) => tracing::warn!("Failed to rebind UDP socket: {}", e),
+                }
+
+                if let TunnResult::WriteToNetwork(pkt) =
+                    tunn.format_handshake_initiation(&mut out_buf, true)
+                {
+                    if udp.send(pkt).await.is_ok() {
+                        counters.record_tx(pkt.len());
+                    }
+                }
+                watchdog.started_recovery(current_gw, now);
+            }
+
             // ── Outgoing from TUN (local apps) → encrypt → UDP ──────────────
             result = tun_reader.read(&mut tun_buf) => {
                 match result {
                     Ok(n) if n > 0 => {
                         match tunn.encapsulate(&tun_buf[..n], &mut out_buf) {
-                            TunnResult::WriteToNetwork(pkt) => { let _ = udp.send(pkt).await; }
+                            TunnResult::WriteToNetwork(pkt) => match udp.send(pkt).await {
+                                Ok(_) => counters.record_tx(pkt.len()),
+                                Err(_) => watchdog.socket_failed = true,
+                            },
                             TunnResult::Err(e) => tracing::debug!("Encapsulate error: {:?}", e),
                             _ => {}
                         }
@@ -304,6 +555,7 @@ async fn tunnel_loop(
             result = udp.recv(&mut udp_buf) => {
                 match result {
                     Ok(n) => {
+                        counters.record_rx(n);
                         let mut data_slice: &[u8] = &udp_buf[..n];
                         loop {
                             match tunn.decapsulate(None, data_slice, &mut out_buf) {
@@ -313,18 +565,24 @@ async fn tunnel_loop(
                                     data_slice = &[];
                                 }
                                 TunnResult::WriteToNetwork(pkt) => {
-                                    let _ = udp.send(pkt).await;
+                                    if udp.send(pkt).await.is_ok() {
+                                        counters.record_tx(pkt.len());
+                                    }
                                     data_slice = &[];
                                 }
                                 TunnResult::Done => break,
                                 TunnResult::Err(e) => {
+                                    counters.decapsulate_errors.fetch_add(1, Ordering::Relaxed);
                                     tracing::debug!("Decapsulate error: {:?}", e);
                                     break;
                                 }
                             }
                         }
                     }
-                    Err(e) => tracing::debug!("UDP recv error: {}", e),
+                    Err(e) => {
+                        tracing::debug!("UDP recv error: {}", e);
+                        watchdog.socket_failed = true;
+                    }
                 }
             }
         }
@@ -333,6 +591,93 @@ async fn tunnel_loop(
     tracing::info!("Tunnel packet loop exited");
 }
 
+/// Bind a fresh UDP socket connected to the server.
+async fn bind_udp(endpoint: SocketAddr) -> std::io::Result<UdpSocket> {
+    let udp = UdpSocket::bind("0.0.0.0:0").await?;
+    udp.connect(endpoint).await?;
+    Ok(udp)
+}
+
+// ─── Watchdog ───────────────────────────────────────────────────────────────
+
+/// Decides when the packet loop should rebuild its connection. Holds no I/O
+/// so the rules can be unit tested.
+struct Watchdog {
+    /// Gateway the server route is currently pinned through.
+    pinned_gateway: Option<String>,
+    /// Set by the loop when a send or recv on the UDP socket fails.
+    socket_failed: bool,
+    /// When the in-progress recovery started, until a handshake completes.
+    recovering_since: Option<Instant>,
+    last_attempt: Option<Instant>,
+}
+
+impl Watchdog {
+    fn new(pinned_gateway: Option<String>) -> Self {
+        Self {
+            pinned_gateway,
+            socket_failed: false,
+            recovering_since: None,
+            last_attempt: None,
+        }
+    }
+
+    /// Returns the reason to reconnect, or None if the tunnel looks healthy.
+    fn check(
+        &self,
+        current_gateway: &Option<String>,
+        since_handshake: Option<Duration>,
+        now: Instant,
+    ) -> Option<String> {
+        // No gateway at all means we're offline: nothing to re-pin to yet.
+        if let Some(gw) = current_gateway {
+            if self.pinned_gateway.as_ref() != Some(gw) {
+                return Some(format!(
+                    "default gateway changed from {} to {}",
+                    self.pinned_gateway.as_deref().unwrap_or("none"),
+                    gw
+                ));
+            }
+        }
+        if self.socket_failed {
+            return Some("UDP socket error".into());
+        }
+        let backed_off = self
+            .last_attempt
+            .map(|t| now.duration_since(t) >= RECOVERY_BACKOFF)
+            .unwrap_or(true);
+        match since_handshake {
+            Some(age) if age > HANDSHAKE_TIMEOUT && backed_off => {
+                Some(format!("no handshake for {} s", age.as_secs()))
+            }
+            _ => None,
+        }
+    }
+
+    fn started_recovery(&mut self, gateway: Option<String>, now: Instant) {
+        if gateway.is_some() {
+            self.pinned_gateway = gateway;
+        }
+        self.socket_failed = false;
+        self.last_attempt = Some(now);
+        self.recovering_since.get_or_insert(now);
+    }
+
+    /// True once, when a handshake completes after a recovery started.
+    fn recovered(&mut self, since_handshake: Option<Duration>, now: Instant) -> bool {
+        let Some(started) = self.recovering_since else {
+            return false;
+        };
+        match since_handshake {
+            Some(age) if age < now.duration_since(started) => {
+                self.recovering_since = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 // ─── Routing ────────────────────────────────────────────────────────────────
 
 fn get_default_gateway() -> Option<String> {
@@ -437,17 +782,7 @@ fn setup_routes(
         //    Without this, when full-tunnel is active, WireGuard handshake packets
         //    themselves would be routed through the TUN → infinite loop → no connection.
         if let Some(gw) = gateway {
-            let out = Command::new("ip")
-                .args(["route", "add", server_ip, "via", gw])
-                .output()
-                .map_err(|e| AppError::WireGuard(format!("ip route add (server): {}", e)))?;
-            if !out.status.success() {
-                let err = String::from_utf8_lossy(&out.stderr);
-                // "RTNETLINK answers: File exists" is harmless
-                if !err.contains("File exists") {
-                    tracing::warn!("ip route add server: {}", err);
-                }
-            }
+            pin_server_route(server_ip, gw)?;
         } else if full_tunnel {
             return Err(AppError::WireGuard(
                 "Cannot set up full-tunnel VPN routing: the system's default gateway \
@@ -474,6 +809,10 @@ fn setup_routes(
                 for half in &["0.0.0.0/1", "128.0.0.0/1"] {
                     run_ip_route_add(half)?;
                 }
+            } else if cidr == "::/0" {
+                for half in IPV6_HALVES {
+                    run_ip_route_add(half)?;
+                }
             } else {
                 run_ip_route_add(cidr)?;
             }
@@ -485,9 +824,7 @@ fn setup_routes(
     #[cfg(target_os = "macos")]
     {
         if let Some(gw) = gateway {
-            let _ = Command::new("route")
-                .args(["add", &format!("{}/32", server_ip), gw])
-                .output();
+            pin_server_route(server_ip, gw)?;
         }
         for cidr in allowed_ips {
             if cidr == "0.0.0.0/0" {
@@ -497,6 +834,12 @@ fn setup_routes(
                 let _ = Command::new("route")
                     .args(["add", "-net", "128.0.0.0/1", "-interface", TUN_NAME])
                     .output();
+            } else if cidr == "::/0" {
+                for half in IPV6_HALVES {
+                    let _ = Command::new("route")
+                        .args(["add", "-inet6", "-net", half, "-interface", TUN_NAME])
+                        .output();
+                }
             }
         }
     }
@@ -508,17 +851,7 @@ fn setup_routes(
         // 1. Pin the WireGuard server's IP to the real gateway BEFORE redirecting
         //    all traffic through the TUN — otherwise the handshake packets loop.
         if let Some(gw) = gateway {
-            let out = Command::new("route")
-                .args(["add", server_ip, "mask", "255.255.255.255", gw])
-                .output();
-            if let Ok(o) = out {
-                if !o.status.success() {
-                    let err = String::from_utf8_lossy(&o.stderr);
-                    if !err.contains("already exists") {
-                        tracing::warn!("route add server {}: {}", server_ip, err.trim());
-                    }
-                }
-            }
+            pin_server_route(server_ip, gw)?;
         } else if full_tunnel {
             return Err(AppError::WireGuard(
                 "Cannot set up full-tunnel VPN routing on Windows: \
@@ -558,6 +891,22 @@ fn setup_routes(
                 );
             }
         }
+        if allowed_ips.iter().any(|ip| ip == "::/0") {
+            for half in IPV6_HALVES {
+                let out = Command::new("netsh")
+                    .args(["interface", "ipv6", "add", "route", half, TUN_NAME, "metric=6"])
+                    .output();
+                if let Ok(o) = out {
+                    if !o.status.success() {
+                        tracing::warn!(
+                            "netsh add route {}: {}",
+                            half,
+                            String::from_utf8_lossy(&o.stdout).trim()
+                        );
+                    }
+                }
+            }
+        }
     }
 
     Ok(())
@@ -581,15 +930,13 @@ fn run_ip_route_add(cidr: &str) -> Result<(), AppError> {
 fn remove_routes(server_ip: &str, gateway: &Option<String>) {
     #[cfg(target_os = "linux")]
     {
-        for half in &["0.0.0.0/1", "128.0.0.0/1"] {
+        for half in ["0.0.0.0/1", "128.0.0.0/1"].iter().chain(&IPV6_HALVES) {
             let _ = Command::new("ip")
                 .args(["route", "del", half, "dev", TUN_NAME])
                 .output();
         }
         if let Some(gw) = gateway {
-            let _ = Command::new("ip")
-                .args(["route", "del", server_ip, "via", gw])
-                .output();
+            unpin_server_route(server_ip, gw);
         }
     }
 
@@ -601,11 +948,14 @@ fn remove_routes(server_ip: &str, gateway: &Option<String>) {
         let _ = Command::new("route")
             .args(["delete", "-net", "128.0.0.0/1"])
             .output();
-        if let Some(gw) = gateway {
+        for half in IPV6_HALVES {
             let _ = Command::new("route")
-                .args(["delete", &format!("{}/32", server_ip), gw])
+                .args(["delete", "-inet6", "-net", half])
                 .output();
         }
+        if let Some(gw) = gateway {
+            unpin_server_route(server_ip, gw);
+        }
     }
 
     #[cfg(target_os = "windows")]
@@ -617,10 +967,235 @@ fn remove_routes(server_ip: &str, gateway: &Option<String>) {
         let _ = Command::new("route")
             .args(["delete", "128.0.0.0", "mask", "128.0.0.0"])
             .output();
+        for half in IPV6_HALVES {
+            let _ = Command::new("netsh")
+                .args(["interface", "ipv6", "delete", "route", half, TUN_NAME])
+                .output();
+        }
         // Remove the server pin route
+        unpin_server_route(server_ip, gateway.as_deref().unwrap_or_default());
+    }
+}
+
+/// Assign the tunnel's IPv6 address to the TUN device. Failure is logged, not
+/// fatal: IPv6 packets routed into the tunnel then carry a source address the
+/// server rejects, so they are dropped rather than leaked.
+fn add_tunnel_ipv6_address(address: &str) {
+    #[cfg(target_os = "linux")]
+    let out = Command::new("ip")
+        .args(["-6", "addr", "add", &format!("{}/128", address), "dev", TUN_NAME])
+        .output();
+
+    #[cfg(target_os = "macos")]
+    let out = Command::new("ifconfig")
+        .args([TUN_NAME, "inet6", address, "prefixlen", "128", "alias"])
+        .output();
+
+    #[cfg(target_os = "windows")]
+    let out = Command::new("netsh")
+        .args(["interface", "ipv6", "add", "address", TUN_NAME, &format!("{}/128", address)])
+        .output();
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    match out {
+        Ok(o) if o.status.success() => {
+            tracing::info!("Tunnel IPv6 address: {}", address);
+        }
+        Ok(o) => tracing::warn!(
+            "Failed to add tunnel IPv6 address {}: {}",
+            address,
+            String::from_utf8_lossy(&o.stderr).trim()
+        ),
+        Err(e) => tracing::warn!("Failed to add tunnel IPv6 address {}: {}", address, e),
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let _ = address;
+}
+
+/// Route the WireGuard server itself via the real gateway, so handshake and
+/// data packets don't loop back into the tunnel.
+fn pin_server_route(server_ip: &str, gw: &str) -> Result<(), AppError> {
+    #[cfg(target_os = "linux")]
+    {
+        let out = Command::new("ip")
+            .args(["route", "add", server_ip, "via", gw])
+            .output()
+            .map_err(|e| AppError::WireGuard(format!("ip route add (server): {}", e)))?;
+        if !out.status.success() {
+            let err = String::from_utf8_lossy(&out.stderr);
+            // "RTNETLINK answers: File exists" is harmless
+            if !err.contains("File exists") {
+                tracing::warn!("ip route add server: {}", err);
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("route")
+            .args(["add", &format!("{}/32", server_ip), gw])
+            .output();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let out = Command::new("route")
+            .args(["add", server_ip, "mask", "255.255.255.255", gw])
+            .output();
+        if let Ok(o) = out {
+            if !o.status.success() {
+                let err = String::from_utf8_lossy(&o.stderr);
+                if !err.contains("already exists") {
+                    tracing::warn!("route add server {}: {}", server_ip, err.trim());
+                }
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let _ = (server_ip, gw);
+
+    Ok(())
+}
+
+/// Undo `pin_server_route`. Errors are ignored: the route may already be gone.
+fn unpin_server_route(server_ip: &str, gw: &str) {
+    #[cfg(target_os = "linux")]
+    {
+        let _ = Command::new("ip")
+            .args(["route", "del", server_ip, "via", gw])
+            .output();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("route")
+            .args(["delete", &format!("{}/32", server_ip), gw])
+            .output();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = gw; // the pin is matched by destination alone
         let _ = Command::new("route")
             .args(["delete", server_ip, "mask", "255.255.255.255"])
             .output();
-        let _ = gateway; // not needed on Windows
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let _ = (server_ip, gw);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dns_failure_fails_the_connect() {
+        let err = apply_dns(Some("1.1.1.1, 2606:4700:4700::1111"), true, |_| {
+            Err(AppError::WireGuard("resolvectl not found".into()))
+        })
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("resolvectl not found"), "{}", err);
+        assert!(err.contains("leak"), "{}", err);
+    }
+
+    #[test]
+    fn dns_is_applied_only_when_listed_and_supported() {
+        let applied = apply_dns(Some("1.1.1.1"), true, |servers| {
+            assert_eq!(servers, ["1.1.1.1".parse::<IpAddr>().unwrap()]);
+            Ok(DnsMethod::Resolved)
+        });
+        assert_eq!(applied.unwrap(), Some(DnsMethod::Resolved));
+
+        let fail = |_: &[IpAddr]| -> Result<DnsMethod, AppError> { panic!("not applied") };
+        assert_eq!(apply_dns(None, true, fail).unwrap(), None);
+        assert_eq!(apply_dns(Some("example.com"), true, fail).unwrap(), None);
+        assert_eq!(apply_dns(Some("1.1.1.1"), false, fail).unwrap(), None);
+    }
+
+    #[test]
+    fn counters_snapshot_reflects_recorded_traffic() {
+        let counters = TunnelCounters::default();
+        counters.record_tx(148);
+        counters.record_tx(32);
+        counters.record_rx(92);
+        counters.decapsulate_errors.fetch_add(1, Ordering::Relaxed);
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.tx_bytes, 180);
+        assert_eq!(stats.tx_packets, 2);
+        assert_eq!(stats.rx_bytes, 92);
+        assert_eq!(stats.rx_packets, 1);
+        assert_eq!(stats.decapsulate_errors, 1);
+        assert!(stats.last_handshake_at.is_none());
+    }
+
+    fn gw(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn watchdog_healthy_tunnel_needs_nothing() {
+        let wd = Watchdog::new(gw("192.168.1.1"));
+        let now = Instant::now();
+        assert_eq!(
+            wd.check(&gw("192.168.1.1"), Some(Duration::from_secs(30)), now),
+            None
+        );
+        // Before the first handshake there is nothing to recover.
+        assert_eq!(wd.check(&gw("192.168.1.1"), None, now), None);
+    }
+
+    #[test]
+    fn watchdog_detects_gateway_change_but_not_going_offline() {
+        let wd = Watchdog::new(gw("192.168.1.1"));
+        let now = Instant::now();
+        let reason = wd
+            .check(&gw("10.0.0.1"), Some(Duration::from_secs(5)), now)
+            .unwrap();
+        assert!(reason.contains("192.168.1.1 to 10.0.0.1"));
+        assert_eq!(wd.check(&None, Some(Duration::from_secs(5)), now), None);
+    }
+
+    #[test]
+    fn watchdog_detects_socket_error_and_clears_it_on_recovery() {
+        let mut wd = Watchdog::new(gw("192.168.1.1"));
+        wd.socket_failed = true;
+        let now = Instant::now();
+        assert!(wd.check(&gw("192.168.1.1"), None, now).is_some());
+        wd.started_recovery(gw("192.168.1.1"), now);
+        assert_eq!(wd.check(&gw("192.168.1.1"), None, now), None);
+    }
+
+    #[test]
+    fn watchdog_handshake_timeout_backs_off_between_attempts() {
+        let mut wd = Watchdog::new(gw("192.168.1.1"));
+        let start = Instant::now();
+        let stale = Some(HANDSHAKE_TIMEOUT + Duration::from_secs(1));
+        assert!(wd.check(&gw("192.168.1.1"), stale, start).is_some());
+
+        wd.started_recovery(gw("192.168.1.1"), start);
+        assert_eq!(wd.check(&gw("192.168.1.1"), stale, start), None);
+        assert!(wd
+            .check(&gw("192.168.1.1"), stale, start + RECOVERY_BACKOFF)
+            .is_some());
+    }
+
+    #[test]
+    fn watchdog_reports_recovery_once_after_new_handshake() {
+        let mut wd = Watchdog::new(gw("192.168.1.1"));
+        let start = Instant::now();
+        assert!(!wd.recovered(Some(Duration::from_secs(1)), start));
+
+        wd.started_recovery(gw("10.0.0.1"), start);
+        let later = start + Duration::from_secs(10);
+        // Handshake is older than the recovery: still waiting.
+        assert!(!wd.recovered(Some(Duration::from_secs(200)), later));
+        // Handshake completed 2 s ago, after recovery started.
+        assert!(wd.recovered(Some(Duration::from_secs(2)), later));
+        assert!(!wd.recovered(Some(Duration::from_secs(2)), later));
     }
 }
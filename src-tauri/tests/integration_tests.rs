@@ -6,6 +6,7 @@
 //!   - Error conversion chains
 
 use createmyvpn_lib::state::*;
+use createmyvpn_lib::wireguard::subnet::TunnelSubnet;
 use createmyvpn_lib::wireguard::{client_config, config_parser, keys, server_config};
 
 // ── Key generation → Config rendering → Config parsing round-trip ────────────
@@ -17,10 +18,14 @@ fn full_wireguard_config_roundtrip() {
     let client_kp = keys::generate_keypair();
 
     // 2. Render server config
+    let subnet = TunnelSubnet::of(None).unwrap();
     let server_conf = server_config::render_server_config(
         &server_kp.private_key,
         &client_kp.public_key,
         51820,
+        &subnet,
+        "eth0",
+        true,
     );
     assert!(server_conf.contains(&server_kp.private_key));
     assert!(server_conf.contains(&client_kp.public_key));
@@ -32,6 +37,8 @@ fn full_wireguard_config_roundtrip() {
         &server_kp.public_key,
         "203.0.113.10",
         51820,
+        &subnet,
+        true,
     );
     assert!(client_conf.contains(&client_kp.private_key));
     assert!(client_conf.contains(&server_kp.public_key));
@@ -43,9 +50,13 @@ fn full_wireguard_config_roundtrip() {
     assert_eq!(parsed.private_key_b64, client_kp.private_key);
     assert_eq!(parsed.server_public_key_b64, server_kp.public_key);
     assert_eq!(parsed.vpn_address, "10.8.0.2");
+    assert_eq!(parsed.vpn_address_v6.as_deref(), Some("fd86:ea04:1115::2"));
     assert_eq!(parsed.endpoint.to_string(), "203.0.113.10:51820");
-    assert_eq!(parsed.dns, Some("1.1.1.1".to_string()));
-    assert_eq!(parsed.allowed_ips, vec!["0.0.0.0/0"]);
+    assert_eq!(
+        parsed.dns,
+        Some("1.1.1.1, 2606:4700:4700::1111".to_string())
+    );
+    assert_eq!(parsed.allowed_ips, vec!["0.0.0.0/0", "::/0"]);
     assert_eq!(parsed.persistent_keepalive, Some(25));
 
     // 5. Decode the parsed keys into bytes
@@ -67,11 +78,15 @@ fn config_roundtrip_custom_port() {
         &server_kp.public_key,
         "10.0.0.1",
         12345,
+        &TunnelSubnet::of(None).unwrap(),
+        false,
     );
 
     let parsed = config_parser::ParsedClientConfig::parse(&client_conf).unwrap();
     assert_eq!(parsed.endpoint.port(), 12345);
     assert_eq!(parsed.endpoint.ip().to_string(), "10.0.0.1");
+    assert_eq!(parsed.vpn_address_v6, None);
+    assert_eq!(parsed.allowed_ips, vec!["0.0.0.0/0", "::/0"]);
 }
 
 #[test]
@@ -117,6 +132,7 @@ fn deployment_state_full_roundtrip() {
         droplet_id: None,
         do_firewall_id: None,
         do_ssh_key_id: None,
+        ..DeploymentState::default()
     };
 
     let json = serde_json::to_string_pretty(&state).expect("serialize");
@@ -17,34 +17,8 @@ beforeEach(() => {
         return null;
       case "load_do_credentials":
         return null;
-      case "get_deployment_state":
-        return {
-          status: "not_deployed",
-          deployment_mode: null,
-          region: null,
-          vpc_id: null,
-          igw_id: null,
-          subnet_id: null,
-          route_table_id: null,
-          security_group_id: null,
-          key_pair_name: null,
-          instance_id: null,
-          allocation_id: null,
-          association_id: null,
-          elastic_ip: null,
-          ssh_private_key: null,
-          ssh_user: null,
-          server_public_key: null,
-          client_private_key: null,
-          client_public_key: null,
-          client_config: null,
-          deployed_at: null,
-          auto_destroy_at: null,
-          error_message: null,
-          droplet_id: null,
-          do_firewall_id: null,
-          do_ssh_key_id: null,
-        };
+      case "list_deployments":
+        return [];
       case "get_vpn_status":
         return "disconnected";
       case "get_settings":
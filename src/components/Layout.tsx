@@ -1,6 +1,7 @@
 import { NavLink, Outlet } from "react-router-dom";
 import { Settings, Rocket, LayoutDashboard, ScrollText, Shield } from "lucide-react";
 import logo from "../assets/logo.png";
+import VaultGate from "./VaultGate";
 
 const navItems = [
   { to: "/dashboard", label: "Dashboard", icon: LayoutDashboard },
@@ -49,7 +50,9 @@ function Layout() {
 
       {/* Main content */}
       <main className="flex-1 overflow-y-auto p-6 animate-fade-in">
-        <Outlet />
+        <VaultGate>
+          <Outlet />
+        </VaultGate>
       </main>
     </div>
   );
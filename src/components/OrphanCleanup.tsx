@@ -0,0 +1,170 @@
+import { useState } from "react";
+import { Search, Trash2, Loader2 } from "lucide-react";
+import ConfirmDialog from "./ConfirmDialog";
+import type { OrphanResource, OrphanScan } from "../lib/types";
+
+interface Props {
+  provider: string; // shown in labels, e.g. "AWS"
+  scan: () => Promise<OrphanScan>;
+  cleanup: (region: string, resourceIds: string[]) => Promise<void>;
+}
+
+function formatCost(usd: number): string {
+  return `$${usd.toFixed(2)}/mo`;
+}
+
+function OrphanCleanup({ provider, scan, cleanup }: Props) {
+  const [result, setResult] = useState<OrphanScan | null>(null);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [scanning, setScanning] = useState(false);
+  const [removing, setRemoving] = useState(false);
+  const [confirmOpen, setConfirmOpen] = useState(false);
+  const [error, setError] = useState("");
+
+  const handleScan = async () => {
+    setScanning(true);
+    setError("");
+    try {
+      const found = await scan();
+      setResult(found);
+      setSelected(new Set(found.resources.map((r) => r.id)));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setScanning(false);
+    }
+  };
+
+  const toggle = (id: string) => {
+    const next = new Set(selected);
+    if (next.has(id)) next.delete(id);
+    else next.add(id);
+    setSelected(next);
+  };
+
+  const handleRemove = async () => {
+    setConfirmOpen(false);
+    if (!result) return;
+    setRemoving(true);
+    setError("");
+    // One cleanup call per region, each with only that region's ids.
+    const byRegion = new Map<string, string[]>();
+    for (const r of result.resources) {
+      if (!selected.has(r.id)) continue;
+      byRegion.set(r.region, [...(byRegion.get(r.region) ?? []), r.id]);
+    }
+    try {
+      for (const [region, ids] of byRegion) {
+        await cleanup(region, ids);
+      }
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setRemoving(false);
+    }
+    await handleScan();
+  };
+
+  const regions = result
+    ? [...new Set(result.resources.map((r) => r.region))]
+    : [];
+  const selectedCost = result
+    ? result.resources
+        .filter((r) => selected.has(r.id))
+        .reduce((sum, r) => sum + r.monthly_cost_usd, 0)
+    : 0;
+
+  const label = (r: OrphanResource) =>
+    [r.name ?? r.id, r.detail].filter(Boolean).join(" — ");
+
+  return (
+    <div className="space-y-3">
+      <button
+        onClick={handleScan}
+        disabled={scanning || removing}
+        className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-gray-300 bg-gray-800 hover:bg-gray-700 disabled:opacity-50 rounded-lg transition-colors"
+      >
+        {scanning ? (
+          <Loader2 className="w-4 h-4 animate-spin" />
+        ) : (
+          <Search className="w-4 h-4" />
+        )}
+        Scan {provider}
+      </button>
+
+      {result && result.resources.length === 0 && (
+        <p className="text-sm text-gray-400">
+          No leftover {provider} resources found.
+        </p>
+      )}
+
+      {result && result.resources.length > 0 && (
+        <>
+          <p className="text-sm text-gray-400">
+            Found {result.resources.length} leftover resources costing about{" "}
+            {formatCost(result.monthly_cost_usd)}.
+          </p>
+          {regions.map((region) => (
+            <div key={region} className="space-y-1">
+              <p className="text-xs font-semibold text-gray-500">{region}</p>
+              {result.resources
+                .filter((r) => r.region === region)
+                .map((r) => (
+                  <label
+                    key={r.id}
+                    className="flex items-center gap-2 text-sm text-gray-300"
+                  >
+                    <input
+                      type="checkbox"
+                      checked={selected.has(r.id)}
+                      onChange={() => toggle(r.id)}
+                      aria-label={r.id}
+                      className="accent-primary-500"
+                    />
+                    <span className="text-gray-500">{r.kind}</span>
+                    <span className="truncate">{label(r)}</span>
+                    {r.monthly_cost_usd > 0 && (
+                      <span className="ml-auto text-xs text-gray-500">
+                        {formatCost(r.monthly_cost_usd)}
+                      </span>
+                    )}
+                  </label>
+                ))}
+            </div>
+          ))}
+          <button
+            onClick={() => setConfirmOpen(true)}
+            disabled={selected.size === 0 || removing}
+            className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-red-400 bg-red-500/10 hover:bg-red-500/20 disabled:opacity-50 rounded-lg transition-colors"
+          >
+            {removing ? (
+              <Loader2 className="w-4 h-4 animate-spin" />
+            ) : (
+              <Trash2 className="w-4 h-4" />
+            )}
+            Remove Selected
+          </button>
+        </>
+      )}
+
+      {result && result.errors.length > 0 && (
+        <p className="text-xs text-yellow-500">
+          Could not scan: {result.errors.join("; ")}
+        </p>
+      )}
+
+      {error && <p className="text-sm text-red-400">{error}</p>}
+
+      <ConfirmDialog
+        isOpen={confirmOpen}
+        title={`Remove leftover ${provider} resources?`}
+        message={`${selected.size} resources (about ${formatCost(selectedCost)}) will be permanently deleted. This cannot be undone.`}
+        confirmLabel="Remove"
+        onConfirm={handleRemove}
+        onCancel={() => setConfirmOpen(false)}
+      />
+    </div>
+  );
+}
+
+export default OrphanCleanup;
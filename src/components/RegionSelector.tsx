@@ -1,12 +1,19 @@
 import { AWS_REGIONS } from "../lib/types";
+import type { AwsRegion } from "../lib/types";
 
 interface Props {
   value: string;
   onChange: (value: string) => void;
   disabled?: boolean;
+  regions?: AwsRegion[]; // defaults to the bundled list
 }
 
-function RegionSelector({ value, onChange, disabled }: Props) {
+function RegionSelector({
+  value,
+  onChange,
+  disabled,
+  regions = AWS_REGIONS,
+}: Props) {
   return (
     <select
       value={value}
@@ -15,7 +22,7 @@ function RegionSelector({ value, onChange, disabled }: Props) {
       style={{ colorScheme: "dark" }}
       className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent disabled:opacity-50"
     >
-      {AWS_REGIONS.map((region) => (
+      {regions.map((region) => (
         <option
           key={region.code}
           value={region.code}
@@ -0,0 +1,97 @@
+import { useEffect, useState, type FormEvent, type ReactNode } from "react";
+import { Lock, Loader2 } from "lucide-react";
+import { getVaultStatus, unlockVault } from "../lib/tauri";
+import type { VaultStatus } from "../lib/types";
+
+interface Props {
+  children: ReactNode;
+}
+
+/**
+ * Holds back the pages until stored secrets are readable. Only shown when no
+ * OS keyring is available and secrets use the passphrase vault.
+ */
+function VaultGate({ children }: Props) {
+  const [status, setStatus] = useState<VaultStatus | null>(null);
+  const [checked, setChecked] = useState(false);
+  const [passphrase, setPassphrase] = useState("");
+  const [confirm, setConfirm] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [unlocking, setUnlocking] = useState(false);
+
+  useEffect(() => {
+    getVaultStatus()
+      .then((s) => setStatus(s ?? null))
+      .catch(() => setStatus(null))
+      .finally(() => setChecked(true));
+  }, []);
+
+  if (!checked) return null;
+  if (!status || !status.locked) return <>{children}</>;
+
+  const creating = !status.initialized;
+
+  const handleSubmit = async (e: FormEvent) => {
+    e.preventDefault();
+    if (creating && passphrase !== confirm) {
+      setError("Passphrases do not match");
+      return;
+    }
+    setUnlocking(true);
+    setError(null);
+    try {
+      await unlockVault(passphrase);
+      setStatus({ ...status, initialized: true, locked: false });
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  return (
+    <form onSubmit={handleSubmit} className="max-w-md mx-auto mt-16 space-y-4">
+      <div className="flex items-center gap-3">
+        <div className="w-10 h-10 rounded-full bg-primary-600/20 flex items-center justify-center">
+          <Lock className="w-5 h-5 text-primary-400" />
+        </div>
+        <h2 className="text-lg font-semibold text-white">
+          {creating ? "Choose a passphrase" : "Unlock your secrets"}
+        </h2>
+      </div>
+      <p className="text-sm text-gray-400 leading-relaxed">
+        {creating
+          ? "No system keyring was found, so cloud credentials and VPN keys are encrypted with a passphrase. You'll enter it each time the app starts."
+          : "Cloud credentials and VPN keys are encrypted. Enter your passphrase to continue."}
+      </p>
+      <input
+        type="password"
+        value={passphrase}
+        onChange={(e) => setPassphrase(e.target.value)}
+        placeholder="Passphrase"
+        autoFocus
+        className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 focus:outline-none focus:ring-2 focus:ring-primary-500"
+      />
+      {creating && (
+        <input
+          type="password"
+          value={confirm}
+          onChange={(e) => setConfirm(e.target.value)}
+          placeholder="Confirm passphrase"
+          className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 focus:outline-none focus:ring-2 focus:ring-primary-500"
+        />
+      )}
+      {error && <p className="text-sm text-red-400">{error}</p>}
+      <button
+        type="submit"
+        disabled={!passphrase || unlocking}
+        className="w-full flex items-center justify-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+      >
+        {unlocking && <Loader2 className="w-4 h-4 animate-spin" />}
+        {creating ? "Create" : "Unlock"}
+      </button>
+    </form>
+  );
+}
+
+export default VaultGate;
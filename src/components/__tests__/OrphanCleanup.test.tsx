@@ -0,0 +1,75 @@
+import { describe, it, expect, vi } from "vitest";
+import { render, screen, fireEvent, waitFor } from "@testing-library/react";
+import OrphanCleanup from "../OrphanCleanup";
+import type { OrphanScan } from "../../lib/types";
+
+const found: OrphanScan = {
+  resources: [
+    {
+      kind: "instance",
+      id: "i-0abc",
+      region: "eu-west-1",
+      name: "createmyvpn-server",
+      detail: "t2.micro, running",
+      monthly_cost_usd: 13.07,
+    },
+    {
+      kind: "vpc",
+      id: "vpc-0def",
+      region: "eu-west-1",
+      name: "createmyvpn-vpc",
+      detail: null,
+      monthly_cost_usd: 0,
+    },
+    {
+      kind: "key_pair",
+      id: "createmyvpn-key-1",
+      region: "us-east-1",
+      name: null,
+      detail: null,
+      monthly_cost_usd: 0,
+    },
+  ],
+  monthly_cost_usd: 13.07,
+  errors: ["me-south-1: opt-in required"],
+};
+
+describe("OrphanCleanup", () => {
+  it("lists what the scan found with its cost", async () => {
+    render(
+      <OrphanCleanup provider="AWS" scan={async () => found} cleanup={vi.fn()} />
+    );
+    fireEvent.click(screen.getByText("Scan AWS"));
+    expect(
+      await screen.findByText(/Found 3 leftover resources costing about \$13.07\/mo/)
+    ).toBeInTheDocument();
+    expect(screen.getByText("eu-west-1")).toBeInTheDocument();
+    expect(screen.getByText(/me-south-1: opt-in required/)).toBeInTheDocument();
+  });
+
+  it("says so when nothing is left over", async () => {
+    const empty = { resources: [], monthly_cost_usd: 0, errors: [] };
+    render(
+      <OrphanCleanup provider="AWS" scan={async () => empty} cleanup={vi.fn()} />
+    );
+    fireEvent.click(screen.getByText("Scan AWS"));
+    expect(
+      await screen.findByText("No leftover AWS resources found.")
+    ).toBeInTheDocument();
+  });
+
+  it("removes the selected resources one region at a time", async () => {
+    const cleanup = vi.fn().mockResolvedValue(undefined);
+    render(
+      <OrphanCleanup provider="AWS" scan={async () => found} cleanup={cleanup} />
+    );
+    fireEvent.click(screen.getByText("Scan AWS"));
+    fireEvent.click(await screen.findByLabelText("createmyvpn-key-1"));
+    fireEvent.click(screen.getByText("Remove Selected"));
+    fireEvent.click(screen.getByText("Remove"));
+    await waitFor(() => {
+      expect(cleanup).toHaveBeenCalledWith("eu-west-1", ["i-0abc", "vpc-0def"]);
+    });
+    expect(cleanup).toHaveBeenCalledTimes(1);
+  });
+});
@@ -0,0 +1,59 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { render, screen, fireEvent, waitFor } from "@testing-library/react";
+import { invoke } from "@tauri-apps/api/core";
+import VaultGate from "../VaultGate";
+
+const mockInvoke = vi.mocked(invoke);
+
+function mockStatus(status: object | null) {
+  mockInvoke.mockImplementation(async (cmd: string) => {
+    if (cmd === "get_vault_status") return status;
+    return null;
+  });
+}
+
+beforeEach(() => {
+  mockInvoke.mockReset();
+});
+
+describe("VaultGate", () => {
+  it("renders children when the keyring is used", async () => {
+    mockStatus({ backend: "keyring", initialized: true, locked: false });
+    render(<VaultGate>page</VaultGate>);
+    expect(await screen.findByText("page")).toBeInTheDocument();
+  });
+
+  it("asks for the passphrase when locked", async () => {
+    mockStatus({ backend: "passphrase", initialized: true, locked: true });
+    render(<VaultGate>page</VaultGate>);
+    expect(await screen.findByText("Unlock your secrets")).toBeInTheDocument();
+    expect(screen.queryByText("page")).not.toBeInTheDocument();
+  });
+
+  it("shows children after unlocking", async () => {
+    mockStatus({ backend: "passphrase", initialized: true, locked: true });
+    render(<VaultGate>page</VaultGate>);
+    fireEvent.change(await screen.findByPlaceholderText("Passphrase"), {
+      target: { value: "hunter2" },
+    });
+    fireEvent.click(screen.getByText("Unlock"));
+    expect(await screen.findByText("page")).toBeInTheDocument();
+    expect(mockInvoke).toHaveBeenCalledWith("unlock_vault", { passphrase: "hunter2" });
+  });
+
+  it("requires matching passphrases when creating the vault", async () => {
+    mockStatus({ backend: "passphrase", initialized: false, locked: true });
+    render(<VaultGate>page</VaultGate>);
+    fireEvent.change(await screen.findByPlaceholderText("Passphrase"), {
+      target: { value: "one" },
+    });
+    fireEvent.change(screen.getByPlaceholderText("Confirm passphrase"), {
+      target: { value: "two" },
+    });
+    fireEvent.click(screen.getByText("Create"));
+    await waitFor(() =>
+      expect(screen.getByText("Passphrases do not match")).toBeInTheDocument(),
+    );
+    expect(mockInvoke).not.toHaveBeenCalledWith("unlock_vault", expect.anything());
+  });
+});
@@ -0,0 +1,50 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { renderHook, waitFor } from "@testing-library/react";
+import { invoke } from "@tauri-apps/api/core";
+import { useCatalog } from "../../hooks/useCatalog";
+
+const mockInvoke = vi.mocked(invoke);
+
+beforeEach(() => {
+  mockInvoke.mockReset();
+});
+
+describe("useCatalog", () => {
+  it("loads regions and sizes for the provider", async () => {
+    mockInvoke.mockImplementation(async (cmd: string) =>
+      cmd === "list_regions"
+        ? { items: [{ code: "ams3", name: "Amsterdam 3" }], source: "live", fetched_at: null }
+        : {
+            items: [{ code: "s-1vcpu-1gb", description: "1 vCPU", monthly_price: 6 }],
+            source: "cache",
+            fetched_at: null,
+          }
+    );
+    const { result } = renderHook(() => useCatalog("do", "ams3"));
+
+    await waitFor(() => {
+      expect(result.current.regions).toEqual([{ code: "ams3", name: "Amsterdam 3" }]);
+      expect(result.current.sizes?.[0].code).toBe("s-1vcpu-1gb");
+    });
+    expect(mockInvoke).toHaveBeenCalledWith("list_sizes", {
+      provider: "do",
+      region: "ams3",
+    });
+  });
+
+  it("stays empty on error so the static lists are used", async () => {
+    mockInvoke.mockRejectedValue(new Error("offline"));
+    const { result } = renderHook(() => useCatalog("vultr", "ewr"));
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("list_regions", { provider: "vultr" });
+    });
+    expect(result.current.regions).toBeNull();
+    expect(result.current.sizes).toBeNull();
+  });
+
+  it("does not ask when there is no provider", () => {
+    renderHook(() => useCatalog(null, ""));
+    expect(mockInvoke).not.toHaveBeenCalled();
+  });
+});
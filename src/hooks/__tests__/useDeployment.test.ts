@@ -10,6 +10,8 @@ beforeEach(() => {
 });
 
 const mockDefaultState = {
+  id: "a1b2c3d4",
+  name: "AWS us-east-1",
   status: "not_deployed" as const,
   deployment_mode: null,
   region: null,
@@ -29,12 +31,26 @@ const mockDefaultState = {
   client_private_key: null,
   client_public_key: null,
   client_config: null,
+  tunnel_cidr: null,
+  tunnel_ipv6: null,
   deployed_at: null,
   auto_destroy_at: null,
+  hourly_cost: null,
+  cost_currency: null,
   error_message: null,
+  status_before_destroy: null,
+  completed_step: 0,
   droplet_id: null,
   do_firewall_id: null,
   do_ssh_key_id: null,
+  hetzner_server_id: null,
+  hetzner_firewall_id: null,
+  hetzner_ssh_key_id: null,
+  vultr_instance_id: null,
+  vultr_firewall_id: null,
+  vultr_ssh_key_id: null,
+  linode_instance_id: null,
+  linode_firewall_id: null,
 };
 
 const mockDeployedState = {
@@ -47,20 +63,21 @@ const mockDeployedState = {
 
 describe("useDeployment", () => {
   it("starts with loading=true", () => {
-    mockInvoke.mockResolvedValue(mockDefaultState);
+    mockInvoke.mockResolvedValue([]);
     const { result } = renderHook(() => useDeployment());
     expect(result.current.loading).toBe(true);
   });
 
-  it("loads deployment state on mount", async () => {
-    mockInvoke.mockResolvedValue(mockDefaultState);
+  it("loads the deployment list on mount", async () => {
+    mockInvoke.mockResolvedValue([mockDeployedState]);
     const { result } = renderHook(() => useDeployment());
 
     await waitFor(() => {
       expect(result.current.loading).toBe(false);
     });
 
-    expect(result.current.state).toEqual(mockDefaultState);
+    expect(mockInvoke).toHaveBeenCalledWith("list_deployments");
+    expect(result.current.deployments).toEqual([mockDeployedState]);
   });
 
   it("handles load error gracefully", async () => {
@@ -71,11 +88,11 @@ describe("useDeployment", () => {
       expect(result.current.loading).toBe(false);
     });
 
-    expect(result.current.state).toBeNull();
+    expect(result.current.deployments).toEqual([]);
   });
 
-  it("deploy calls Tauri and updates state", async () => {
-    mockInvoke.mockResolvedValue(mockDefaultState);
+  it("deploy calls Tauri and adds the deployment", async () => {
+    mockInvoke.mockResolvedValue([]);
     const { result } = renderHook(() => useDeployment());
 
     await waitFor(() => {
@@ -88,11 +105,11 @@ describe("useDeployment", () => {
       expect(deployResult.status).toBe("deployed");
     });
 
-    expect(result.current.state?.status).toBe("deployed");
+    expect(result.current.deployments.map((d) => d.status)).toEqual(["deployed"]);
   });
 
   it("deploy clears previous progress", async () => {
-    mockInvoke.mockResolvedValue(mockDefaultState);
+    mockInvoke.mockResolvedValue([]);
     const { result } = renderHook(() => useDeployment());
 
     await waitFor(() => {
@@ -107,35 +124,37 @@ describe("useDeployment", () => {
     expect(result.current.progress).toEqual([]);
   });
 
-  it("destroy calls Tauri and clears state", async () => {
-    mockInvoke.mockResolvedValue(mockDeployedState);
+  it("destroy removes only that deployment", async () => {
+    const other = { ...mockDeployedState, id: "e5f6a7b8", region: "eu-west-1" };
+    mockInvoke.mockResolvedValue([mockDeployedState, other]);
     const { result } = renderHook(() => useDeployment());
 
     await waitFor(() => {
-      expect(result.current.state?.status).toBe("deployed");
+      expect(result.current.deployments).toHaveLength(2);
     });
 
     mockInvoke.mockResolvedValue(undefined);
     await act(async () => {
-      await result.current.destroy();
+      await result.current.destroy("a1b2c3d4");
     });
 
-    expect(result.current.state).toBeNull();
+    expect(mockInvoke).toHaveBeenCalledWith("destroy_vpn", { deploymentId: "a1b2c3d4" });
+    expect(result.current.deployments).toEqual([other]);
   });
 
-  it("refresh reloads state", async () => {
-    mockInvoke.mockResolvedValue(mockDefaultState);
+  it("refresh reloads the list", async () => {
+    mockInvoke.mockResolvedValue([]);
     const { result } = renderHook(() => useDeployment());
 
     await waitFor(() => {
       expect(result.current.loading).toBe(false);
     });
 
-    mockInvoke.mockResolvedValue(mockDeployedState);
+    mockInvoke.mockResolvedValue([mockDeployedState]);
     await act(async () => {
       await result.current.refresh();
     });
 
-    expect(result.current.state?.status).toBe("deployed");
+    expect(result.current.deployments[0]?.status).toBe("deployed");
   });
 });
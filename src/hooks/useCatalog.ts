@@ -0,0 +1,40 @@
+import { useState, useEffect } from "react";
+import { listRegions, listSizes } from "../lib/tauri";
+import type { AwsRegion, ServerSize } from "../lib/types";
+
+// Regions and sizes `provider` offers; null until fetched, or when the
+// backend could not be reached, so callers fall back to their static lists.
+export function useCatalog(provider: string | null, region: string) {
+  const [regions, setRegions] = useState<AwsRegion[] | null>(null);
+  const [sizes, setSizes] = useState<ServerSize[] | null>(null);
+
+  useEffect(() => {
+    setRegions(null);
+    if (!provider) return;
+    let cancelled = false;
+    listRegions(provider)
+      .then((c) => {
+        if (!cancelled && c?.items.length) setRegions(c.items);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [provider]);
+
+  useEffect(() => {
+    setSizes(null);
+    if (!provider || !region) return;
+    let cancelled = false;
+    listSizes(provider, region)
+      .then((c) => {
+        if (!cancelled && c?.items.length) setSizes(c.items);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [provider, region]);
+
+  return { regions, sizes };
+}
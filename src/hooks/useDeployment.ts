@@ -1,19 +1,18 @@
 import { useState, useEffect, useCallback } from "react";
 import { listen } from "@tauri-apps/api/event";
-import { getDeploymentState, deployVpn, destroyVpn } from "../lib/tauri";
+import { listDeployments, deployVpn, destroyVpn } from "../lib/tauri";
 import type { DeploymentState, ProgressEvent } from "../lib/types";
 
 export function useDeployment() {
-  const [state, setState] = useState<DeploymentState | null>(null);
+  const [deployments, setDeployments] = useState<DeploymentState[]>([]);
   const [progress, setProgress] = useState<ProgressEvent[]>([]);
   const [loading, setLoading] = useState(true);
 
   const refresh = useCallback(async () => {
     try {
-      const s = await getDeploymentState();
-      setState(s);
+      setDeployments(await listDeployments());
     } catch {
-      setState(null);
+      setDeployments([]);
     } finally {
       setLoading(false);
     }
@@ -37,18 +36,18 @@ export function useDeployment() {
     };
   }, []);
 
-  const deploy = async (region: string) => {
+  const deploy = async (region: string, deploymentId?: string) => {
     setProgress([]);
-    const result = await deployVpn(region);
-    setState(result);
+    const result = await deployVpn(region, undefined, deploymentId);
+    setDeployments((prev) => [...prev.filter((d) => d.id !== result.id), result]);
     return result;
   };
 
-  const destroy = async () => {
+  const destroy = async (deploymentId: string) => {
     setProgress([]);
-    await destroyVpn();
-    setState(null);
+    await destroyVpn(deploymentId);
+    setDeployments((prev) => prev.filter((d) => d.id !== deploymentId));
   };
 
-  return { state, progress, loading, deploy, destroy, refresh };
+  return { deployments, progress, loading, deploy, destroy, refresh };
 }
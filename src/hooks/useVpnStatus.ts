@@ -1,4 +1,5 @@
 import { useState, useEffect, useCallback } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { getVpnStatus } from "../lib/tauri";
 import type { VpnConnectionStatus } from "../lib/types";
 
@@ -20,5 +21,19 @@ export function useVpnStatus(pollInterval = 5000) {
     return () => clearInterval(interval);
   }, [refresh, pollInterval]);
 
+  // The tunnel watchdog reports network changes between polls
+  useEffect(() => {
+    const unlistenReconnecting = listen<string>("vpn-reconnecting", () => {
+      setStatus("connecting");
+    });
+    const unlistenReconnected = listen("vpn-reconnected", () => {
+      refresh();
+    });
+    return () => {
+      unlistenReconnecting.then((fn) => fn());
+      unlistenReconnected.then((fn) => fn());
+    };
+  }, [refresh]);
+
   return { status, refresh };
 }
@@ -9,23 +9,57 @@ import {
   saveDoCredentials,
   loadDoCredentials,
   deleteDoCredentials,
+  validateHetznerCredentials,
+  saveHetznerCredentials,
+  loadHetznerCredentials,
+  deleteHetznerCredentials,
+  validateVultrCredentials,
+  saveVultrCredentials,
+  deleteVultrCredentials,
+  validateLinodeCredentials,
+  saveLinodeCredentials,
+  deleteLinodeCredentials,
   deployVpn,
   deployDo,
+  deployHetzner,
+  deployVultr,
+  deployLinode,
   deployByoVps,
+  resumeDeployment,
+  listDeployments,
   getDeploymentState,
+  renameDeployment,
   resetDeploymentState,
   destroyVpn,
   connectVpn,
+  getConnectedDeployment,
   disconnectVpn,
   getVpnStatus,
+  getTunnelStats,
   getClientConfig,
+  listPeers,
+  addPeer,
+  revokePeer,
+  lockSsh,
+  unlockSsh,
   getRegions,
+  listRegions,
+  listSizes,
+  estimateCost,
+  getDeploymentCost,
   getSettings,
   updateSettings,
+  scanAwsOrphans,
+  cleanupAwsOrphans,
+  scanDoOrphans,
+  cleanupDoOrphans,
   getLogs,
   exportLogs,
   clearLogs,
   exportClientConfig,
+  getVaultStatus,
+  unlockVault,
+  lockVault,
 } from "../../lib/tauri";
 
 const mockInvoke = vi.mocked(invoke);
@@ -111,8 +145,90 @@ describe("DigitalOcean credential functions", () => {
   });
 });
 
+describe("Hetzner credential functions", () => {
+  it("validateHetznerCredentials invokes with token", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await validateHetznerCredentials("hz-token");
+    expect(mockInvoke).toHaveBeenCalledWith("validate_hetzner_credentials", {
+      apiToken: "hz-token",
+    });
+  });
+
+  it("saveHetznerCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await saveHetznerCredentials("hz-token");
+    expect(mockInvoke).toHaveBeenCalledWith("save_hetzner_credentials", {
+      apiToken: "hz-token",
+    });
+  });
+
+  it("loadHetznerCredentials returns creds or null", async () => {
+    mockInvoke.mockResolvedValue(null);
+    const creds = await loadHetznerCredentials();
+    expect(creds).toBeNull();
+  });
+
+  it("deleteHetznerCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await deleteHetznerCredentials();
+    expect(mockInvoke).toHaveBeenCalledWith("delete_hetzner_credentials");
+  });
+});
+
+describe("Vultr credential functions", () => {
+  it("validateVultrCredentials returns the account email", async () => {
+    mockInvoke.mockResolvedValue("me@example.com");
+    const email = await validateVultrCredentials("vultr-key");
+    expect(email).toBe("me@example.com");
+    expect(mockInvoke).toHaveBeenCalledWith("validate_vultr_credentials", {
+      apiKey: "vultr-key",
+    });
+  });
+
+  it("saveVultrCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await saveVultrCredentials("vultr-key");
+    expect(mockInvoke).toHaveBeenCalledWith("save_vultr_credentials", {
+      apiKey: "vultr-key",
+    });
+  });
+
+  it("deleteVultrCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await deleteVultrCredentials();
+    expect(mockInvoke).toHaveBeenCalledWith("delete_vultr_credentials");
+  });
+});
+
+describe("Linode credential functions", () => {
+  it("validateLinodeCredentials returns the username", async () => {
+    mockInvoke.mockResolvedValue("alice");
+    const username = await validateLinodeCredentials("linode-token");
+    expect(username).toBe("alice");
+    expect(mockInvoke).toHaveBeenCalledWith("validate_linode_credentials", {
+      apiToken: "linode-token",
+    });
+  });
+
+  it("saveLinodeCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await saveLinodeCredentials("linode-token");
+    expect(mockInvoke).toHaveBeenCalledWith("save_linode_credentials", {
+      apiToken: "linode-token",
+    });
+  });
+
+  it("deleteLinodeCredentials invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await deleteLinodeCredentials();
+    expect(mockInvoke).toHaveBeenCalledWith("delete_linode_credentials");
+  });
+});
+
 describe("Deploy functions", () => {
   const mockState = {
+    id: "a1b2c3d4",
+    name: "AWS us-east-1",
     status: "deployed" as const,
     deployment_mode: "aws",
     region: "us-east-1",
@@ -132,18 +248,33 @@ describe("Deploy functions", () => {
     client_private_key: null,
     client_public_key: null,
     client_config: null,
+    tunnel_cidr: null,
+    tunnel_ipv6: null,
     deployed_at: null,
     auto_destroy_at: null,
+    hourly_cost: null,
+    cost_currency: null,
     error_message: null,
+    status_before_destroy: null,
+    completed_step: 10,
     droplet_id: null,
     do_firewall_id: null,
     do_ssh_key_id: null,
+    hetzner_server_id: null,
+    hetzner_firewall_id: null,
+    hetzner_ssh_key_id: null,
+    vultr_instance_id: null,
+    vultr_firewall_id: null,
+    vultr_ssh_key_id: null,
+    linode_instance_id: null,
+    linode_firewall_id: null,
   };
 
   it("deployVpn invokes with region and null autoDestroy", async () => {
     mockInvoke.mockResolvedValue(mockState);
     const result = await deployVpn("us-east-1");
     expect(mockInvoke).toHaveBeenCalledWith("deploy_vpn", {
+      deploymentId: null,
       region: "us-east-1",
       autoDestroyHours: null,
     });
@@ -154,57 +285,160 @@ describe("Deploy functions", () => {
     mockInvoke.mockResolvedValue(mockState);
     await deployVpn("us-west-2", 4);
     expect(mockInvoke).toHaveBeenCalledWith("deploy_vpn", {
+      deploymentId: null,
       region: "us-west-2",
       autoDestroyHours: 4,
     });
   });
 
+  it("deployVpn passes a requested deployment id", async () => {
+    mockInvoke.mockResolvedValue(mockState);
+    await deployVpn("eu-west-1", undefined, "work");
+    expect(mockInvoke).toHaveBeenCalledWith("deploy_vpn", {
+      deploymentId: "work",
+      region: "eu-west-1",
+      autoDestroyHours: null,
+    });
+  });
+
   it("deployDo invokes with region, size, autoDestroy", async () => {
     mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "do" });
     await deployDo("nyc1", "s-1vcpu-1gb", 2);
     expect(mockInvoke).toHaveBeenCalledWith("deploy_do", {
+      deploymentId: null,
       region: "nyc1",
       size: "s-1vcpu-1gb",
       autoDestroyHours: 2,
     });
   });
 
+  it("deployHetzner invokes with location, server type, autoDestroy", async () => {
+    mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "hetzner" });
+    await deployHetzner("fsn1", "cx22", undefined, "ab12");
+    expect(mockInvoke).toHaveBeenCalledWith("deploy_hetzner", {
+      deploymentId: "ab12",
+      location: "fsn1",
+      serverType: "cx22",
+      autoDestroyHours: null,
+    });
+  });
+
+  it("deployVultr invokes with region, plan, autoDestroy", async () => {
+    mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "vultr" });
+    await deployVultr("ams", "vc2-1c-1gb", 4);
+    expect(mockInvoke).toHaveBeenCalledWith("deploy_vultr", {
+      deploymentId: null,
+      region: "ams",
+      plan: "vc2-1c-1gb",
+      autoDestroyHours: 4,
+    });
+  });
+
+  it("deployLinode invokes with region, type, autoDestroy", async () => {
+    mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "linode" });
+    await deployLinode("eu-west", "g6-nanode-1", undefined, "cd34");
+    expect(mockInvoke).toHaveBeenCalledWith("deploy_linode", {
+      deploymentId: "cd34",
+      region: "eu-west",
+      linodeType: "g6-nanode-1",
+      autoDestroyHours: null,
+    });
+  });
+
   it("deployByoVps invokes with server details", async () => {
     mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "byo" });
     await deployByoVps("10.0.0.1", "key-data", "root", 22);
     expect(mockInvoke).toHaveBeenCalledWith("deploy_byo_vps", {
+      deploymentId: null,
       serverIp: "10.0.0.1",
       sshPrivateKey: "key-data",
       sshUser: "root",
       sshPort: 22,
+      sshHostKeyFingerprint: null,
       autoDestroyHours: null,
     });
   });
 
-  it("getDeploymentState returns state", async () => {
+  it("deployByoVps passes expected host key fingerprint", async () => {
+    mockInvoke.mockResolvedValue({ ...mockState, deployment_mode: "byo" });
+    await deployByoVps("10.0.0.1", "key-data", "root", 22, undefined, "SHA256:abc");
+    expect(mockInvoke).toHaveBeenCalledWith("deploy_byo_vps", {
+      deploymentId: null,
+      serverIp: "10.0.0.1",
+      sshPrivateKey: "key-data",
+      sshUser: "root",
+      sshPort: 22,
+      sshHostKeyFingerprint: "SHA256:abc",
+      autoDestroyHours: null,
+    });
+  });
+
+  it("resumeDeployment passes id and timer", async () => {
+    mockInvoke.mockResolvedValue(mockState);
+    const result = await resumeDeployment("a1b2c3d4", 4);
+    expect(mockInvoke).toHaveBeenCalledWith("resume_deployment", {
+      deploymentId: "a1b2c3d4",
+      autoDestroyHours: 4,
+    });
+    expect(result.status).toBe("deployed");
+  });
+
+  it("listDeployments returns all deployments", async () => {
+    mockInvoke.mockResolvedValue([mockState]);
+    const result = await listDeployments();
+    expect(mockInvoke).toHaveBeenCalledWith("list_deployments");
+    expect(result).toHaveLength(1);
+  });
+
+  it("getDeploymentState passes the deployment id", async () => {
     mockInvoke.mockResolvedValue(mockState);
-    const result = await getDeploymentState();
+    const result = await getDeploymentState("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("get_deployment_state", {
+      deploymentId: "a1b2c3d4",
+    });
     expect(result.status).toBe("deployed");
   });
 
+  it("renameDeployment invokes with id and name", async () => {
+    mockInvoke.mockResolvedValue({ ...mockState, name: "Office" });
+    const result = await renameDeployment("a1b2c3d4", "Office");
+    expect(mockInvoke).toHaveBeenCalledWith("rename_deployment", {
+      deploymentId: "a1b2c3d4",
+      name: "Office",
+    });
+    expect(result.name).toBe("Office");
+  });
+
   it("resetDeploymentState invokes correctly", async () => {
     mockInvoke.mockResolvedValue(undefined);
-    await resetDeploymentState();
-    expect(mockInvoke).toHaveBeenCalledWith("reset_deployment_state");
+    await resetDeploymentState("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("reset_deployment_state", {
+      deploymentId: "a1b2c3d4",
+    });
   });
 });
 
 describe("Destroy / Connect / Status functions", () => {
   it("destroyVpn invokes correctly", async () => {
     mockInvoke.mockResolvedValue(undefined);
-    await destroyVpn();
-    expect(mockInvoke).toHaveBeenCalledWith("destroy_vpn");
+    await destroyVpn("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("destroy_vpn", {
+      deploymentId: "a1b2c3d4",
+    });
   });
 
   it("connectVpn invokes correctly", async () => {
     mockInvoke.mockResolvedValue(undefined);
-    await connectVpn();
-    expect(mockInvoke).toHaveBeenCalledWith("connect_vpn");
+    await connectVpn("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("connect_vpn", {
+      deploymentId: "a1b2c3d4",
+    });
+  });
+
+  it("getConnectedDeployment returns id or null", async () => {
+    mockInvoke.mockResolvedValue("a1b2c3d4");
+    expect(await getConnectedDeployment()).toBe("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("get_connected_deployment");
   });
 
   it("disconnectVpn invokes correctly", async () => {
@@ -219,13 +453,67 @@ describe("Destroy / Connect / Status functions", () => {
     expect(status).toBe("connected");
   });
 
+  it("getTunnelStats calls get_tunnel_stats", async () => {
+    mockInvoke.mockResolvedValue(null);
+    const stats = await getTunnelStats();
+    expect(mockInvoke).toHaveBeenCalledWith("get_tunnel_stats");
+    expect(stats).toBeNull();
+  });
+
   it("getClientConfig returns config or null", async () => {
     mockInvoke.mockResolvedValue("[Interface]\nPrivateKey = ...");
-    const config = await getClientConfig();
+    const config = await getClientConfig("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("get_client_config", {
+      deploymentId: "a1b2c3d4",
+    });
     expect(config).toContain("[Interface]");
   });
 });
 
+describe("Peer functions", () => {
+  it("listPeers returns array", async () => {
+    mockInvoke.mockResolvedValue([]);
+    const peers = await listPeers("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("list_peers", {
+      deploymentId: "a1b2c3d4",
+    });
+    expect(peers).toEqual([]);
+  });
+
+  it("addPeer invokes with name", async () => {
+    mockInvoke.mockResolvedValue({
+      name: "phone",
+      public_key: "PUB",
+      address: "10.8.0.3",
+      created_at: "2026-01-01T00:00:00Z",
+      client_config: "[Interface]",
+    });
+    const peer = await addPeer("a1b2c3d4", "phone");
+    expect(mockInvoke).toHaveBeenCalledWith("add_peer", {
+      deploymentId: "a1b2c3d4",
+      name: "phone",
+    });
+    expect(peer.address).toBe("10.8.0.3");
+  });
+
+  it("revokePeer invokes with name", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await revokePeer("a1b2c3d4", "phone");
+    expect(mockInvoke).toHaveBeenCalledWith("revoke_peer", {
+      deploymentId: "a1b2c3d4",
+      name: "phone",
+    });
+  });
+
+  it("lockSsh and unlockSsh invoke with deploymentId", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await lockSsh("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("lock_ssh", { deploymentId: "a1b2c3d4" });
+    await unlockSsh("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("unlock_ssh", { deploymentId: "a1b2c3d4" });
+  });
+});
+
 describe("Settings functions", () => {
   it("getSettings returns settings object", async () => {
     const settings = {
@@ -248,11 +536,127 @@ describe("Settings functions", () => {
     });
   });
 
+  it("updateSettings passes kill switch options", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await updateSettings("eu-west-1", "t3.micro", 51821, {
+      killSwitch: true,
+      killSwitchAllowLan: false,
+    });
+    expect(mockInvoke).toHaveBeenCalledWith("update_settings", {
+      region: "eu-west-1",
+      instanceType: "t3.micro",
+      wireguardPort: 51821,
+      killSwitch: true,
+      killSwitchAllowLan: false,
+    });
+  });
+
+  it("updateSettings passes the rollback option", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await updateSettings("eu-west-1", "t3.micro", 51821, {
+      rollbackOnFailure: false,
+    });
+    expect(mockInvoke).toHaveBeenCalledWith("update_settings", {
+      region: "eu-west-1",
+      instanceType: "t3.micro",
+      wireguardPort: 51821,
+      rollbackOnFailure: false,
+    });
+  });
+
   it("getRegions returns array", async () => {
     mockInvoke.mockResolvedValue([{ code: "us-east-1", name: "US East" }]);
     const regions = await getRegions();
     expect(regions).toHaveLength(1);
   });
+
+  it("listRegions asks for one provider's regions", async () => {
+    mockInvoke.mockResolvedValue({
+      items: [{ code: "ams3", name: "Amsterdam 3" }],
+      source: "live",
+      fetched_at: "2026-01-01T00:00:00Z",
+    });
+    const catalog = await listRegions("do");
+    expect(catalog.items).toHaveLength(1);
+    expect(mockInvoke).toHaveBeenCalledWith("list_regions", { provider: "do" });
+  });
+
+  it("listSizes passes provider and region", async () => {
+    mockInvoke.mockResolvedValue({ items: [], source: "bundled", fetched_at: null });
+    await listSizes("hetzner", "fsn1");
+    expect(mockInvoke).toHaveBeenCalledWith("list_sizes", {
+      provider: "hetzner",
+      region: "fsn1",
+    });
+  });
+});
+
+describe("Cost functions", () => {
+  it("estimateCost sends null when auto-destroy is off", async () => {
+    mockInvoke.mockResolvedValue(null);
+    await estimateCost("aws", "us-east-1", "t3.micro");
+    expect(mockInvoke).toHaveBeenCalledWith("estimate_cost", {
+      provider: "aws",
+      region: "us-east-1",
+      size: "t3.micro",
+      autoDestroyHours: null,
+    });
+  });
+
+  it("getDeploymentCost returns the accrued cost", async () => {
+    const cost = { currency: "EUR", hourly: 0.006, hours: 2, amount: 0.012 };
+    mockInvoke.mockResolvedValue(cost);
+    expect(await getDeploymentCost("a1b2c3d4")).toEqual(cost);
+    expect(mockInvoke).toHaveBeenCalledWith("get_deployment_cost", {
+      deploymentId: "a1b2c3d4",
+    });
+  });
+});
+
+describe("Leftover resource functions", () => {
+  it("scanAwsOrphans returns the scan", async () => {
+    mockInvoke.mockResolvedValue({
+      resources: [
+        {
+          kind: "instance",
+          id: "i-0abc",
+          region: "eu-west-1",
+          name: "createmyvpn-server",
+          detail: "t2.micro, running",
+          monthly_cost_usd: 13.07,
+        },
+      ],
+      monthly_cost_usd: 13.07,
+      errors: [],
+    });
+    const scan = await scanAwsOrphans();
+    expect(mockInvoke).toHaveBeenCalledWith("scan_aws_orphans");
+    expect(scan.resources[0].id).toBe("i-0abc");
+  });
+
+  it("cleanupAwsOrphans passes region and ids", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await cleanupAwsOrphans("eu-west-1", ["i-0abc", "vpc-0def"]);
+    expect(mockInvoke).toHaveBeenCalledWith("cleanup_aws_orphans", {
+      region: "eu-west-1",
+      resourceIds: ["i-0abc", "vpc-0def"],
+    });
+  });
+
+  it("scanDoOrphans calls scan_do_orphans", async () => {
+    mockInvoke.mockResolvedValue({ resources: [], monthly_cost_usd: 0, errors: [] });
+    const scan = await scanDoOrphans();
+    expect(mockInvoke).toHaveBeenCalledWith("scan_do_orphans");
+    expect(scan.resources).toHaveLength(0);
+  });
+
+  it("cleanupDoOrphans passes the ids", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await cleanupDoOrphans(["412345", "fw-uuid"]);
+    expect(mockInvoke).toHaveBeenCalledWith("cleanup_do_orphans", {
+      resourceIds: ["412345", "fw-uuid"],
+    });
+  });
 });
 
 describe("Log functions", () => {
@@ -276,7 +680,31 @@ describe("Log functions", () => {
 
   it("exportClientConfig returns file path", async () => {
     mockInvoke.mockResolvedValue("/home/user/Downloads/client.conf");
-    const path = await exportClientConfig();
+    const path = await exportClientConfig("a1b2c3d4");
+    expect(mockInvoke).toHaveBeenCalledWith("export_client_config", {
+      deploymentId: "a1b2c3d4",
+    });
     expect(path).toContain("client.conf");
   });
 });
+
+describe("Vault functions", () => {
+  it("getVaultStatus calls get_vault_status", async () => {
+    mockInvoke.mockResolvedValue({ backend: "passphrase", initialized: true, locked: true });
+    const status = await getVaultStatus();
+    expect(mockInvoke).toHaveBeenCalledWith("get_vault_status");
+    expect(status.locked).toBe(true);
+  });
+
+  it("unlockVault passes the passphrase", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await unlockVault("hunter2");
+    expect(mockInvoke).toHaveBeenCalledWith("unlock_vault", { passphrase: "hunter2" });
+  });
+
+  it("lockVault invokes correctly", async () => {
+    mockInvoke.mockResolvedValue(undefined);
+    await lockVault();
+    expect(mockInvoke).toHaveBeenCalledWith("lock_vault");
+  });
+});
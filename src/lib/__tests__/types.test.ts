@@ -65,6 +65,8 @@ describe("Type shape validation", () => {
       status: "not_deployed",
       deployment_mode: null,
       region: null,
+      os_release: null,
+      architecture: null,
       vpc_id: null,
       igw_id: null,
       subnet_id: null,
@@ -77,16 +79,36 @@ describe("Type shape validation", () => {
       elastic_ip: null,
       ssh_private_key: null,
       ssh_user: null,
+      ssh_port: null,
+      ssh_host_key_fingerprint: null,
+      ssh_source: null,
+      ssh_locked: false,
+      user_data_bootstrap: false,
       server_public_key: null,
       client_private_key: null,
       client_public_key: null,
       client_config: null,
+      tunnel_cidr: null,
+      tunnel_ipv6: null,
       deployed_at: null,
       auto_destroy_at: null,
+      hourly_cost: null,
+      cost_currency: null,
       error_message: null,
+      status_before_destroy: null,
+      completed_step: 0,
       droplet_id: null,
       do_firewall_id: null,
       do_ssh_key_id: null,
+      hetzner_server_id: null,
+      hetzner_firewall_id: null,
+      hetzner_ssh_key_id: null,
+      vultr_instance_id: null,
+      vultr_firewall_id: null,
+      vultr_ssh_key_id: null,
+      linode_instance_id: null,
+      linode_firewall_id: null,
+      peers: [],
     };
     expect(state.status).toBe("not_deployed");
     expect(state.deployment_mode).toBeNull();
@@ -97,6 +119,9 @@ describe("Type shape validation", () => {
       region: "us-east-1",
       instance_type: "t2.micro",
       wireguard_port: 51820,
+      kill_switch: false,
+      kill_switch_allow_lan: false,
+      rollback_on_failure: true,
     };
     expect(settings.region).toBe("us-east-1");
     expect(settings.wireguard_port).toBe(51820);
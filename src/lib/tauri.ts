@@ -1,10 +1,22 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  AccruedCost,
   AppSettings,
   AwsCredentials,
   AwsRegion,
+  Catalog,
+  CostEstimate,
   DeploymentState,
   DoCredentials,
+  HetznerCredentials,
+  LinodeCredentials,
+  OrphanScan,
+  PeerInfo,
+  ServerSize,
+  SettingsOptions,
+  TunnelStats,
+  VaultStatus,
+  VultrCredentials,
   VpnConnectionStatus,
 } from "./types";
 
@@ -53,12 +65,68 @@ export async function deleteDoCredentials(): Promise<void> {
   return invoke("delete_do_credentials");
 }
 
+// Hetzner Credentials
+export async function validateHetznerCredentials(apiToken: string): Promise<void> {
+  return invoke("validate_hetzner_credentials", { apiToken });
+}
+
+export async function saveHetznerCredentials(apiToken: string): Promise<void> {
+  return invoke("save_hetzner_credentials", { apiToken });
+}
+
+export async function loadHetznerCredentials(): Promise<HetznerCredentials | null> {
+  return invoke("load_hetzner_credentials");
+}
+
+export async function deleteHetznerCredentials(): Promise<void> {
+  return invoke("delete_hetzner_credentials");
+}
+
+// Vultr Credentials
+// Resolves to the email of the account the key belongs to.
+export async function validateVultrCredentials(apiKey: string): Promise<string> {
+  return invoke("validate_vultr_credentials", { apiKey });
+}
+
+export async function saveVultrCredentials(apiKey: string): Promise<void> {
+  return invoke("save_vultr_credentials", { apiKey });
+}
+
+export async function loadVultrCredentials(): Promise<VultrCredentials | null> {
+  return invoke("load_vultr_credentials");
+}
+
+export async function deleteVultrCredentials(): Promise<void> {
+  return invoke("delete_vultr_credentials");
+}
+
+// Linode Credentials
+// Resolves to the username the token belongs to.
+export async function validateLinodeCredentials(apiToken: string): Promise<string> {
+  return invoke("validate_linode_credentials", { apiToken });
+}
+
+export async function saveLinodeCredentials(apiToken: string): Promise<void> {
+  return invoke("save_linode_credentials", { apiToken });
+}
+
+export async function loadLinodeCredentials(): Promise<LinodeCredentials | null> {
+  return invoke("load_linode_credentials");
+}
+
+export async function deleteLinodeCredentials(): Promise<void> {
+  return invoke("delete_linode_credentials");
+}
+
 // Deploy
+// `deploymentId` is optional for deploys: the backend picks a fresh id.
 export async function deployVpn(
   region: string,
-  autoDestroyHours?: number
+  autoDestroyHours?: number,
+  deploymentId?: string
 ): Promise<DeploymentState> {
   return invoke("deploy_vpn", {
+    deploymentId: deploymentId ?? null,
     region,
     autoDestroyHours: autoDestroyHours ?? null,
   });
@@ -67,47 +135,118 @@ export async function deployVpn(
 export async function deployDo(
   region: string,
   size: string,
-  autoDestroyHours?: number
+  autoDestroyHours?: number,
+  deploymentId?: string
 ): Promise<DeploymentState> {
   return invoke("deploy_do", {
+    deploymentId: deploymentId ?? null,
     region,
     size,
     autoDestroyHours: autoDestroyHours ?? null,
   });
 }
 
+export async function deployHetzner(
+  location: string,
+  serverType: string,
+  autoDestroyHours?: number,
+  deploymentId?: string
+): Promise<DeploymentState> {
+  return invoke("deploy_hetzner", {
+    deploymentId: deploymentId ?? null,
+    location,
+    serverType,
+    autoDestroyHours: autoDestroyHours ?? null,
+  });
+}
+
+export async function deployVultr(
+  region: string,
+  plan: string,
+  autoDestroyHours?: number,
+  deploymentId?: string
+): Promise<DeploymentState> {
+  return invoke("deploy_vultr", {
+    deploymentId: deploymentId ?? null,
+    region,
+    plan,
+    autoDestroyHours: autoDestroyHours ?? null,
+  });
+}
+
+export async function deployLinode(
+  region: string,
+  linodeType: string,
+  autoDestroyHours?: number,
+  deploymentId?: string
+): Promise<DeploymentState> {
+  return invoke("deploy_linode", {
+    deploymentId: deploymentId ?? null,
+    region,
+    linodeType,
+    autoDestroyHours: autoDestroyHours ?? null,
+  });
+}
+
 export async function deployByoVps(
   serverIp: string,
   sshPrivateKey: string,
   sshUser: string,
   sshPort: number,
-  autoDestroyHours?: number
+  autoDestroyHours?: number,
+  sshHostKeyFingerprint?: string,
+  deploymentId?: string
 ): Promise<DeploymentState> {
   return invoke("deploy_byo_vps", {
+    deploymentId: deploymentId ?? null,
     serverIp,
     sshPrivateKey,
     sshUser,
     sshPort,
+    sshHostKeyFingerprint: sshHostKeyFingerprint || null,
     autoDestroyHours: autoDestroyHours ?? null,
   });
 }
 
-export async function getDeploymentState(): Promise<DeploymentState> {
-  return invoke("get_deployment_state");
+export async function resumeDeployment(
+  deploymentId: string,
+  autoDestroyHours?: number
+): Promise<DeploymentState> {
+  return invoke("resume_deployment", {
+    deploymentId,
+    autoDestroyHours: autoDestroyHours ?? null,
+  });
 }
 
-export async function resetDeploymentState(): Promise<void> {
-  return invoke("reset_deployment_state");
+export async function listDeployments(): Promise<DeploymentState[]> {
+  return invoke("list_deployments");
+}
+
+export async function getDeploymentState(
+  deploymentId: string
+): Promise<DeploymentState> {
+  return invoke("get_deployment_state", { deploymentId });
+}
+
+export async function renameDeployment(
+  deploymentId: string,
+  name: string
+): Promise<DeploymentState> {
+  return invoke("rename_deployment", { deploymentId, name });
+}
+
+export async function resetDeploymentState(deploymentId: string): Promise<void> {
+  return invoke("reset_deployment_state", { deploymentId });
 }
 
 // Destroy
-export async function destroyVpn(): Promise<void> {
-  return invoke("destroy_vpn");
+export async function destroyVpn(deploymentId: string): Promise<void> {
+  return invoke("destroy_vpn", { deploymentId });
 }
 
 // Connect
-export async function connectVpn(): Promise<void> {
-  return invoke("connect_vpn");
+export async function connectVpn(deploymentId: string): Promise<void> {
+  return invoke("connect_vpn", { deploymentId });
 }
 
 export async function disconnectVpn(): Promise<void> {
@@ -118,8 +257,46 @@ export async function getVpnStatus(): Promise<VpnConnectionStatus> {
   return invoke("get_vpn_status");
 }
 
-export async function getClientConfig(): Promise<string | null> {
-  return invoke("get_client_config");
+export async function getTunnelStats(): Promise<TunnelStats | null> {
+  return invoke("get_tunnel_stats");
+}
+
+export async function getConnectedDeployment(): Promise<string | null> {
+  return invoke("get_connected_deployment");
+}
+
+export async function getClientConfig(
+  deploymentId: string
+): Promise<string | null> {
+  return invoke("get_client_config", { deploymentId });
+}
+
+// Peers
+export async function listPeers(deploymentId: string): Promise<PeerInfo[]> {
+  return invoke("list_peers", { deploymentId });
+}
+
+export async function addPeer(
+  deploymentId: string,
+  name: string
+): Promise<PeerInfo> {
+  return invoke("add_peer", { deploymentId, name });
+}
+
+export async function revokePeer(
+  deploymentId: string,
+  name: string
+): Promise<void> {
+  return invoke("revoke_peer", { deploymentId, name });
+}
+
+// SSH access to a deployed server's firewall
+export async function lockSsh(deploymentId: string): Promise<DeploymentState> {
+  return invoke("lock_ssh", { deploymentId });
+}
+
+export async function unlockSsh(deploymentId: string): Promise<DeploymentState> {
+  return invoke("unlock_ssh", { deploymentId });
 }
 
 // Settings
@@ -127,6 +304,36 @@ export async function getRegions(): Promise<AwsRegion[]> {
   return invoke("get_regions");
 }
 
+// Catalogs: regions and sizes fetched from the provider, cached for a day
+export async function listRegions(provider: string): Promise<Catalog<AwsRegion>> {
+  return invoke("list_regions", { provider });
+}
+
+export async function listSizes(
+  provider: string,
+  region: string
+): Promise<Catalog<ServerSize>> {
+  return invoke("list_sizes", { provider, region });
+}
+
+export async function estimateCost(
+  provider: string,
+  region: string,
+  size: string,
+  autoDestroyHours?: number
+): Promise<CostEstimate | null> {
+  return invoke("estimate_cost", {
+    provider,
+    region,
+    size,
+    autoDestroyHours: autoDestroyHours ?? null,
+  });
+}
+
+export async function getDeploymentCost(deploymentId: string): Promise<AccruedCost | null> {
+  return invoke("get_deployment_cost", { deploymentId });
+}
+
 export async function getSettings(): Promise<AppSettings> {
   return invoke("get_settings");
 }
@@ -134,15 +341,37 @@ export async function getSettings(): Promise<AppSettings> {
 export async function updateSettings(
   region: string,
   instanceType: string,
-  wireguardPort: number
+  wireguardPort: number,
+  options: SettingsOptions = {}
 ): Promise<void> {
   return invoke("update_settings", {
     region,
     instanceType,
     wireguardPort,
+    ...options,
   });
 }
 
+// Leftover resources
+export async function scanAwsOrphans(): Promise<OrphanScan> {
+  return invoke("scan_aws_orphans");
+}
+
+export async function cleanupAwsOrphans(
+  region: string,
+  resourceIds: string[]
+): Promise<void> {
+  return invoke("cleanup_aws_orphans", { region, resourceIds });
+}
+
+export async function scanDoOrphans(): Promise<OrphanScan> {
+  return invoke("scan_do_orphans");
+}
+
+export async function cleanupDoOrphans(resourceIds: string[]): Promise<void> {
+  return invoke("cleanup_do_orphans", { resourceIds });
+}
+
 // Logs
 export async function getLogs(): Promise<string> {
   return invoke("get_logs");
@@ -156,6 +385,19 @@ export async function clearLogs(): Promise<void> {
   return invoke("clear_logs");
 }
 
-export async function exportClientConfig(): Promise<string> {
-  return invoke("export_client_config");
+export async function exportClientConfig(deploymentId: string): Promise<string> {
+  return invoke("export_client_config", { deploymentId });
+}
+
+// Secrets vault
+export async function getVaultStatus(): Promise<VaultStatus> {
+  return invoke("get_vault_status");
+}
+
+export async function unlockVault(passphrase: string): Promise<void> {
+  return invoke("unlock_vault", { passphrase });
+}
+
+export async function lockVault(): Promise<void> {
+  return invoke("lock_vault");
 }
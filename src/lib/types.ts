@@ -7,11 +7,51 @@ export interface DoCredentials {
   api_token: string;
 }
 
+export interface HetznerCredentials {
+  api_token: string;
+}
+
+export interface VultrCredentials {
+  api_key: string;
+}
+
+export interface LinodeCredentials {
+  api_token: string;
+}
+
 export interface AwsRegion {
   code: string;
   name: string;
 }
 
+export interface ServerSize {
+  code: string;
+  description: string;
+  monthly_price: number | null; // in the provider's billing currency
+}
+
+// A region or size list; "bundled" means the provider could not be asked
+export interface Catalog<T> {
+  items: T[];
+  source: "live" | "cache" | "bundled";
+  fetched_at: string | null;
+}
+
+export interface CostEstimate {
+  currency: string; // "USD" | "EUR"
+  hourly: number;
+  monthly: number;
+  until_auto_destroy: number | null;
+}
+
+// What a deployment has cost since it was deployed
+export interface AccruedCost {
+  currency: string;
+  hourly: number;
+  hours: number;
+  amount: number;
+}
+
 export type DeploymentStatus =
   | "not_deployed"
   | "deploying"
@@ -25,10 +65,24 @@ export type VpnConnectionStatus =
   | "connected"
   | "disconnecting";
 
+export type OsRelease = "ubuntu-22.04" | "ubuntu-24.04" | "debian-12";
+
+export type Architecture = "amd64" | "arm64";
+
+export const OS_RELEASES: { code: OsRelease; name: string }[] = [
+  { code: "ubuntu-22.04", name: "Ubuntu 22.04 LTS" },
+  { code: "ubuntu-24.04", name: "Ubuntu 24.04 LTS" },
+  { code: "debian-12", name: "Debian 12" },
+];
+
 export interface DeploymentState {
+  id: string; // stable key; several deployments can exist side by side
+  name: string; // user-visible label, e.g. "AWS eu-west-1"
   status: DeploymentStatus;
-  deployment_mode: string | null; // "aws" | "byo" | "do" (null = "aws" for old state files)
+  deployment_mode: string | null; // "aws" | "byo" | "do" | "hetzner" | "vultr" | "linode" (null = "aws" for old state files)
   region: string | null;
+  os_release: OsRelease | null; // null for BYO and old state files (= Ubuntu 22.04)
+  architecture: Architecture | null;
   vpc_id: string | null;
   igw_id: string | null;
   subnet_id: string | null;
@@ -41,23 +95,111 @@ export interface DeploymentState {
   elastic_ip: string | null;
   ssh_private_key: string | null;
   ssh_user: string | null;
+  ssh_port: number | null;
+  ssh_host_key_fingerprint: string | null; // "SHA256:..." pinned on first connect
+  ssh_source: string | null; // e.g. "203.0.113.7/32"; null = open to anywhere (old state files)
+  ssh_locked: boolean; // the firewall has no SSH rule (removed, or a deploy without SSH)
+  user_data_bootstrap: boolean; // WireGuard was set up from the server's user data, not over SSH
   server_public_key: string | null;
   client_private_key: string | null;
   client_public_key: string | null;
   client_config: string | null;
+  tunnel_cidr: string | null; // null = "10.8.0.0/24" for old state files
+  tunnel_ipv6: boolean | null; // whether the tunnel carries IPv6; null = yes (old state files)
   deployed_at: string | null;
   auto_destroy_at: string | null; // ISO datetime, null if not set
+  hourly_cost: number | null; // estimated when deployed, null if the price is unknown
+  cost_currency: string | null; // "USD" | "EUR"
   error_message: string | null;
+  status_before_destroy: DeploymentStatus | null; // set while destroying, so an interrupted destroy can go back to it
+  completed_step: number; // last finished deploy step (AWS: 1-10), for resume
   // DigitalOcean-specific fields
   droplet_id: number | null;
   do_firewall_id: string | null;
   do_ssh_key_id: number | null;
+  // Hetzner-specific fields
+  hetzner_server_id: number | null;
+  hetzner_firewall_id: number | null;
+  hetzner_ssh_key_id: number | null;
+  // Vultr-specific fields
+  vultr_instance_id: string | null;
+  vultr_firewall_id: string | null;
+  vultr_ssh_key_id: string | null;
+  // Linode-specific fields
+  linode_instance_id: number | null;
+  linode_firewall_id: number | null;
+  // Additional client devices registered on the server
+  peers: PeerInfo[];
+}
+
+export interface PeerInfo {
+  name: string;
+  public_key: string;
+  address: string; // e.g. "10.8.0.3"
+  created_at: string;
+  client_config: string | null;
+}
+
+// A cloud resource tagged as ours that no local deployment tracks
+export interface OrphanResource {
+  kind: string; // "instance", "elastic_ip", "vpc", ...
+  id: string;
+  region: string;
+  name: string | null;
+  detail: string | null; // e.g. "t2.micro, running"
+  monthly_cost_usd: number;
+}
+
+export interface OrphanScan {
+  resources: OrphanResource[];
+  monthly_cost_usd: number;
+  errors: string[]; // regions that could not be scanned, as "region: reason"
+}
+
+// Where secrets are stored: the OS keyring, or a passphrase-encrypted vault
+export interface VaultStatus {
+  backend: "keyring" | "passphrase";
+  initialized: boolean; // false until a passphrase has been chosen
+  locked: boolean;
+}
+
+// Emitted as "tunnel-stats" once a second while connected
+export interface TunnelStats {
+  tx_bytes: number;
+  rx_bytes: number;
+  tx_packets: number;
+  rx_packets: number;
+  last_handshake_at: string | null; // null until the first handshake completes
+  decapsulate_errors: number;
+  rtt_ms: number | null;
 }
 
 export interface AppSettings {
   region: string;
   instance_type: string;
   wireguard_port: number;
+  kill_switch: boolean; // Linux only
+  kill_switch_allow_lan: boolean;
+  rollback_on_failure: boolean; // tear down what a failed deploy created
+  tunnel_cidr: string; // tunnel network for new deployments, e.g. "10.8.0.0/24"
+  ip_lookup_url: string; // answers with this computer's public IPv4 address
+  lock_down_ssh: boolean; // remove the SSH rule once WireGuard is configured
+  deploy_without_ssh: boolean; // never open SSH (AWS and DigitalOcean only)
+  os_release: OsRelease; // what new cloud servers run
+  architecture: Architecture; // arm64 needs an arm64 size (AWS t4g.*, Hetzner cax*)
+}
+
+/** Optional update_settings arguments; omitted ones keep their saved value. */
+export interface SettingsOptions {
+  killSwitch?: boolean;
+  killSwitchAllowLan?: boolean;
+  rollbackOnFailure?: boolean;
+  tunnelCidr?: string;
+  ipLookupUrl?: string;
+  lockDownSsh?: boolean;
+  deployWithoutSsh?: boolean;
+  osRelease?: OsRelease;
+  architecture?: Architecture;
 }
 
 export interface ProgressEvent {
@@ -67,6 +209,10 @@ export interface ProgressEvent {
   status: "running" | "done" | "error";
 }
 
+export interface DeployLogEvent {
+  line: string;
+}
+
 export interface DoRegion {
   code: string;
   name: string;
@@ -85,6 +231,51 @@ export const DO_REGIONS: DoRegion[] = [
   { code: "syd1", name: "Sydney 1" },
 ];
 
+export interface HetznerLocation {
+  code: string;
+  name: string;
+}
+
+// Hetzner locations offering the cheap shared-vCPU (CX) server types.
+export const HETZNER_LOCATIONS: HetznerLocation[] = [
+  { code: "fsn1", name: "Falkenstein, Germany" },
+  { code: "nbg1", name: "Nuremberg, Germany" },
+  { code: "hel1", name: "Helsinki, Finland" },
+];
+
+export interface VultrRegion {
+  code: string;
+  name: string;
+}
+
+export const VULTR_REGIONS: VultrRegion[] = [
+  { code: "ewr", name: "New Jersey" },
+  { code: "ord", name: "Chicago" },
+  { code: "lax", name: "Los Angeles" },
+  { code: "ams", name: "Amsterdam" },
+  { code: "lhr", name: "London" },
+  { code: "fra", name: "Frankfurt" },
+  { code: "nrt", name: "Tokyo" },
+  { code: "sgp", name: "Singapore" },
+  { code: "syd", name: "Sydney" },
+];
+
+export interface LinodeRegion {
+  code: string;
+  name: string;
+}
+
+export const LINODE_REGIONS: LinodeRegion[] = [
+  { code: "us-east", name: "Newark, NJ" },
+  { code: "us-central", name: "Dallas, TX" },
+  { code: "us-west", name: "Fremont, CA" },
+  { code: "eu-west", name: "London, UK" },
+  { code: "eu-central", name: "Frankfurt, DE" },
+  { code: "ap-south", name: "Singapore, SG" },
+  { code: "ap-northeast", name: "Tokyo, JP" },
+  { code: "ap-southeast", name: "Sydney, AU" },
+];
+
 export const AWS_REGIONS: AwsRegion[] = [
   { code: "us-east-1", name: "US East (N. Virginia)" },
   { code: "us-east-2", name: "US East (Ohio)" },
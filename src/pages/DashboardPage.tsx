@@ -11,19 +11,32 @@ import {
   Loader2,
   QrCode,
   X,
+  Plus,
+  RotateCw,
+  Lock,
+  Unlock,
 } from "lucide-react";
 import { QRCodeSVG } from "qrcode.react";
 import StatusBadge from "../components/StatusBadge";
 import ConfirmDialog from "../components/ConfirmDialog";
-import type { DeploymentState, VpnConnectionStatus } from "../lib/types";
+import type {
+  AccruedCost,
+  DeploymentState,
+  VpnConnectionStatus,
+} from "../lib/types";
+import { OS_RELEASES } from "../lib/types";
 import {
-  getDeploymentState,
+  listDeployments,
+  getConnectedDeployment,
   getVpnStatus,
   connectVpn,
   disconnectVpn,
   destroyVpn,
   getClientConfig,
+  getDeploymentCost,
   resetDeploymentState,
+  lockSsh,
+  unlockSsh,
 } from "../lib/tauri";
 
 function formatCountdown(isoString: string): string {
@@ -35,15 +48,23 @@ function formatCountdown(isoString: string): string {
   return h > 0 ? `${h}h ${m}m` : `${m}m`;
 }
 
+function formatCost(cost: AccruedCost): string {
+  const symbol = cost.currency === "EUR" ? "€" : "$";
+  return `${symbol}${cost.amount.toFixed(2)} (${symbol}${cost.hourly.toFixed(3)}/h)`;
+}
+
 function DashboardPage() {
   const navigate = useNavigate();
-  const [deployment, setDeployment] = useState<DeploymentState | null>(null);
+  const [deployments, setDeployments] = useState<DeploymentState[]>([]);
+  const [selectedId, setSelectedId] = useState<string | null>(null);
+  const [connectedId, setConnectedId] = useState<string | null>(null);
   const [vpnStatus, setVpnStatus] = useState<VpnConnectionStatus>("disconnected");
   const [showDestroy, setShowDestroy] = useState(false);
   const [destroying, setDestroying] = useState(false);
   const [connecting, setConnecting] = useState(false);
   const [copied, setCopied] = useState(false);
   const [resetting, setResetting] = useState(false);
+  const [changingSsh, setChangingSsh] = useState(false);
   const [error, setError] = useState("");
 
   // QR code
@@ -53,13 +74,21 @@ function DashboardPage() {
   // Auto-destroy countdown
   const [countdown, setCountdown] = useState<string | null>(null);
 
+  // What the selected server has cost so far
+  const [cost, setCost] = useState<AccruedCost | null>(null);
+
+  // The selected deployment, falling back to the first one once it is gone.
+  const deployment =
+    deployments.find((d) => d.id === selectedId) ?? deployments[0] ?? null;
+
   const refreshState = useCallback(async () => {
     try {
-      const state = await getDeploymentState();
-      setDeployment(state);
-      if (state.status === "deployed") {
+      const list = await listDeployments();
+      setDeployments(list);
+      if (list.some((d) => d.status === "deployed")) {
         const status = await getVpnStatus();
         setVpnStatus(status);
+        setConnectedId(await getConnectedDeployment());
       }
     } catch (err) {
       console.error("Failed to load state:", err);
@@ -84,11 +113,35 @@ function DashboardPage() {
     return () => clearInterval(interval);
   }, [deployment?.auto_destroy_at]);
 
+  // Accrued cost, refreshed with the countdown
+  useEffect(() => {
+    setCost(null);
+    if (deployment?.status !== "deployed" || !deployment.hourly_cost) return;
+    const id = deployment.id;
+    const update = () =>
+      getDeploymentCost(id)
+        .then(setCost)
+        .catch(() => setCost(null));
+    update();
+    const interval = setInterval(update, 60_000);
+    return () => clearInterval(interval);
+  }, [deployment?.id, deployment?.status, deployment?.hourly_cost]);
+
+  const removeDeployment = (id: string) => {
+    setDeployments((prev) => prev.filter((d) => d.id !== id));
+    if (connectedId === id) {
+      setConnectedId(null);
+      setVpnStatus("disconnected");
+    }
+  };
+
   const handleConnect = async () => {
+    if (!deployment) return;
     setConnecting(true);
     setError("");
     try {
-      await connectVpn();
+      await connectVpn(deployment.id);
+      setConnectedId(deployment.id);
       setVpnStatus("connected");
     } catch (err) {
       setError(String(err));
@@ -102,6 +155,7 @@ function DashboardPage() {
     setError("");
     try {
       await disconnectVpn();
+      setConnectedId(null);
       setVpnStatus("disconnected");
     } catch (err) {
       setError(String(err));
@@ -111,13 +165,13 @@ function DashboardPage() {
   };
 
   const handleDestroy = async () => {
+    if (!deployment) return;
     setShowDestroy(false);
     setDestroying(true);
     setError("");
     try {
-      await destroyVpn();
-      setDeployment(null);
-      setVpnStatus("disconnected");
+      await destroyVpn(deployment.id);
+      removeDeployment(deployment.id);
     } catch (err) {
       setError(String(err));
     } finally {
@@ -125,8 +179,26 @@ function DashboardPage() {
     }
   };
 
+  // Opening SSH lets it in from this computer's current address only.
+  const handleSshAccess = async () => {
+    if (!deployment) return;
+    setChangingSsh(true);
+    setError("");
+    try {
+      const updated = deployment.ssh_locked
+        ? await unlockSsh(deployment.id)
+        : await lockSsh(deployment.id);
+      setDeployments((prev) => prev.map((d) => (d.id === updated.id ? updated : d)));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setChangingSsh(false);
+    }
+  };
+
   const handleCopyConfig = async () => {
-    const config = await getClientConfig();
+    if (!deployment) return;
+    const config = await getClientConfig(deployment.id);
     if (config) {
       await navigator.clipboard.writeText(config);
       setCopied(true);
@@ -135,21 +207,57 @@ function DashboardPage() {
   };
 
   const handleShowQr = async () => {
-    const config = await getClientConfig();
+    if (!deployment) return;
+    const config = await getClientConfig(deployment.id);
     setQrConfig(config);
     setShowQr(true);
   };
 
   const isByo = deployment?.deployment_mode === "byo";
   const isDo = deployment?.deployment_mode === "do";
+  const isHetzner = deployment?.deployment_mode === "hetzner";
+  const isVultr = deployment?.deployment_mode === "vultr";
+  const isLinode = deployment?.deployment_mode === "linode";
   const isDeployed = deployment?.status === "deployed";
-  const isConnected = vpnStatus === "connected";
+  const tunnelUp = vpnStatus === "connected" && connectedId !== null;
+  const isConnected = tunnelUp && connectedId === deployment?.id;
+  const connectedElsewhere = tunnelUp && !isConnected;
+  const connectedName = deployments.find((d) => d.id === connectedId)?.name;
+
+  // Server switcher, only worth showing once there is a choice.
+  const picker =
+    deployments.length > 1 ? (
+      <div className="flex flex-wrap gap-2 mb-4">
+        {deployments.map((d) => (
+          <button
+            key={d.id}
+            onClick={() => setSelectedId(d.id)}
+            className={`px-3 py-1.5 text-xs font-medium rounded-lg transition-colors ${
+              d.id === deployment?.id
+                ? "bg-primary-600 text-white"
+                : "bg-gray-800 text-gray-300 hover:bg-gray-700"
+            }`}
+          >
+            {d.name || d.id}
+            {tunnelUp && d.id === connectedId && (
+              <span className="ml-1.5 inline-block w-1.5 h-1.5 rounded-full bg-green-400" />
+            )}
+          </button>
+        ))}
+      </div>
+    ) : null;
 
   const destroyDialogMessage = isByo
     ? "This will stop WireGuard on your server and remove the local VPN config. Your server itself will NOT be deleted."
     : isDo
       ? "This will permanently delete your DigitalOcean Droplet, Firewall, and SSH key. Your WireGuard client config will stop working. This cannot be undone."
-      : "This will permanently delete all AWS resources (EC2 instance, VPC, Elastic IP, etc.). Your WireGuard client config will stop working. This action cannot be undone.";
+      : isHetzner
+        ? "This will permanently delete your Hetzner server, firewall, and SSH key. Your WireGuard client config will stop working. This cannot be undone."
+        : isVultr
+          ? "This will permanently delete your Vultr instance, firewall group, and SSH key. Your WireGuard client config will stop working. This cannot be undone."
+          : isLinode
+            ? "This will permanently delete your Linode and its cloud firewall. Your WireGuard client config will stop working. This cannot be undone."
+            : "This will permanently delete all AWS resources (EC2 instance, VPC, Elastic IP, etc.). Your WireGuard client config will stop working. This action cannot be undone.";
 
   // ── Stuck-state ───────────────────────────────────────────────────────────
   if (
@@ -158,6 +266,7 @@ function DashboardPage() {
   ) {
     return (
       <div className="flex flex-col items-center justify-center h-full text-center">
+        {picker}
         <Loader2 className="w-12 h-12 text-primary-400 animate-spin mb-4" />
         <h2 className="text-xl font-bold text-white mb-2">
           {deployment.status === "deploying"
@@ -188,12 +297,17 @@ function DashboardPage() {
 
   // ── Failed state ──────────────────────────────────────────────────────────
   if (deployment && deployment.status === "failed") {
+    // AWS deploys record each finished step and can pick up after the last.
+    const canResume =
+      (deployment.deployment_mode ?? "aws") === "aws" &&
+      deployment.completed_step > 0;
     return (
       <div className="max-w-lg">
         <div className="flex items-center justify-between mb-6">
           <h2 className="text-2xl font-bold text-white">Dashboard</h2>
           <StatusBadge deploymentStatus={deployment.status} vpnStatus={vpnStatus} />
         </div>
+        {picker}
 
         <div className="bg-red-500/10 border border-red-500/30 rounded-xl p-6 mb-4">
           <div className="flex items-center gap-3 mb-3">
@@ -204,18 +318,29 @@ function DashboardPage() {
             <p className="text-sm text-red-300 mb-3">{deployment.error_message}</p>
           )}
           <p className="text-xs text-gray-400 mb-4">
-            Some AWS resources may have been partially created. You can attempt
-            to destroy them with the button below, or reset the state and deploy
-            again.
+            {canResume
+              ? "The resources created so far are still there. Resume to reuse them and continue where the deployment stopped, or destroy them."
+              : "Some AWS resources may have been partially created. You can attempt to destroy them with the button below, or reset the state and deploy again."}
           </p>
           <div className="flex gap-3">
+            {canResume && (
+              <button
+                onClick={() =>
+                  navigate("/deploy", { state: { resumeId: deployment.id } })
+                }
+                className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                <RotateCw className="w-4 h-4" />
+                Resume
+              </button>
+            )}
             <button
               onClick={async () => {
                 setResetting(true);
                 setError("");
                 try {
-                  await resetDeploymentState();
-                  setDeployment(null);
+                  await resetDeploymentState(deployment.id);
+                  removeDeployment(deployment.id);
                 } catch (err) {
                   setError(String(err));
                 } finally {
@@ -223,11 +348,15 @@ function DashboardPage() {
                 }
               }}
               disabled={resetting}
-              className="flex-1 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors disabled:opacity-50"
+              className={`flex-1 px-4 py-2.5 text-sm font-medium rounded-lg transition-colors disabled:opacity-50 ${
+                canResume
+                  ? "text-gray-300 bg-gray-800 hover:bg-gray-700"
+                  : "text-white bg-primary-600 hover:bg-primary-500"
+              }`}
             >
               {resetting ? "Resetting…" : "Reset & Deploy Again"}
             </button>
-            {deployment.instance_id && (
+            {(deployment.instance_id || canResume) && (
               <button
                 onClick={() => setShowDestroy(true)}
                 disabled={destroying}
@@ -282,13 +411,29 @@ function DashboardPage() {
   return (
     <div className="max-w-lg">
       <div className="flex items-center justify-between mb-6">
-        <h2 className="text-2xl font-bold text-white">Dashboard</h2>
-        <StatusBadge
-          deploymentStatus={deployment.status}
-          vpnStatus={vpnStatus}
-        />
+        <div>
+          <h2 className="text-2xl font-bold text-white">Dashboard</h2>
+          {deployment.name && (
+            <p className="text-xs text-gray-500 mt-0.5">{deployment.name}</p>
+          )}
+        </div>
+        <div className="flex items-center gap-3">
+          <button
+            onClick={() => navigate("/deploy")}
+            className="flex items-center gap-1 px-2.5 py-1.5 text-xs font-medium text-gray-300 bg-gray-800 hover:bg-gray-700 rounded-lg transition-colors"
+          >
+            <Plus className="w-3.5 h-3.5" />
+            Add Server
+          </button>
+          <StatusBadge
+            deploymentStatus={deployment.status}
+            vpnStatus={isConnected ? vpnStatus : "disconnected"}
+          />
+        </div>
       </div>
 
+      {picker}
+
       {/* Notice banner */}
       {deployment.error_message && (
         <div className="mb-4 bg-yellow-500/10 border border-yellow-500/30 rounded-xl p-4">
@@ -322,7 +467,9 @@ function DashboardPage() {
           <p className="text-sm text-gray-400 mt-1">
             {isConnected
               ? "Your traffic is secured through your private server"
-              : "Click connect to route traffic through your VPN"}
+              : connectedElsewhere
+                ? `Currently connected via ${connectedName || "another server"} — connecting here switches servers`
+                : "Click connect to route traffic through your VPN"}
           </p>
         </div>
 
@@ -364,7 +511,13 @@ function DashboardPage() {
                   ? "Custom Server"
                   : isDo
                     ? `DO / ${deployment.region || "—"}`
-                    : deployment.region || "—"}
+                    : isHetzner
+                      ? `Hetzner / ${deployment.region || "—"}`
+                      : isVultr
+                        ? `Vultr / ${deployment.region || "—"}`
+                        : isLinode
+                          ? `Linode / ${deployment.region || "—"}`
+                          : deployment.region || "—"}
               </p>
             </div>
             <div>
@@ -399,6 +552,53 @@ function DashboardPage() {
                 </>
               )}
             </div>
+            {deployment.os_release && (
+              <div>
+                <p className="text-gray-500 text-xs mb-1">OS</p>
+                <p className="text-white text-xs">
+                  {OS_RELEASES.find((os) => os.code === deployment.os_release)
+                    ?.name ?? deployment.os_release}{" "}
+                  ({deployment.architecture ?? "amd64"})
+                </p>
+              </div>
+            )}
+            {cost && (
+              <div>
+                <p className="text-gray-500 text-xs mb-1">Cost so far</p>
+                <p className="text-white font-mono text-sm">{formatCost(cost)}</p>
+              </div>
+            )}
+            {!isByo && (
+              <div>
+                <p className="text-gray-500 text-xs mb-1">SSH access</p>
+                <div className="flex items-center gap-2">
+                  <p className="text-white font-mono text-xs">
+                    {deployment.ssh_locked
+                      ? deployment.ssh_source
+                        ? "Locked down"
+                        : "Never opened"
+                      : deployment.ssh_source ?? "Anyone"}
+                  </p>
+                  <button
+                    onClick={handleSshAccess}
+                    disabled={changingSsh}
+                    title={
+                      deployment.ssh_locked
+                        ? "Let SSH in from this computer"
+                        : "Remove the SSH rule"
+                    }
+                    className="flex items-center gap-1 px-2 py-1 text-xs font-medium text-gray-300 bg-gray-800 hover:bg-gray-700 rounded-md transition-colors disabled:opacity-50"
+                  >
+                    {deployment.ssh_locked ? (
+                      <Unlock className="w-3 h-3" />
+                    ) : (
+                      <Lock className="w-3 h-3" />
+                    )}
+                    {deployment.ssh_locked ? "Open" : "Lock"}
+                  </button>
+                </div>
+              </div>
+            )}
           </div>
         </div>
       )}
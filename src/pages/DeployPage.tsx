@@ -1,5 +1,5 @@
-import { useState, useEffect } from "react";
-import { useNavigate } from "react-router-dom";
+import { useState, useEffect, useRef } from "react";
+import { useNavigate, useLocation } from "react-router-dom";
 import { listen } from "@tauri-apps/api/event";
 import {
   Rocket,
@@ -9,19 +9,42 @@ import {
   Cloud,
   Server,
   Droplets,
+  Box,
+  Globe,
+  Layers,
 } from "lucide-react";
 import RegionSelector from "../components/RegionSelector";
 import ProgressStepper from "../components/ProgressStepper";
-import type { ProgressEvent, DeploymentState } from "../lib/types";
-import { DO_REGIONS } from "../lib/types";
+import { useCatalog } from "../hooks/useCatalog";
+import type {
+  ProgressEvent,
+  DeployLogEvent,
+  DeploymentState,
+  ServerSize,
+  CostEstimate,
+} from "../lib/types";
+import {
+  DO_REGIONS,
+  HETZNER_LOCATIONS,
+  VULTR_REGIONS,
+  LINODE_REGIONS,
+} from "../lib/types";
 import {
   deployVpn,
+  resumeDeployment,
   deployByoVps,
   deployDo,
+  deployHetzner,
+  deployVultr,
+  deployLinode,
   getSettings,
+  estimateCost,
   loadCredentials,
   loadDoCredentials,
-  getDeploymentState,
+  loadHetznerCredentials,
+  loadVultrCredentials,
+  loadLinodeCredentials,
+  listDeployments,
 } from "../lib/tauri";
 
 const AUTO_DESTROY_OPTIONS = [
@@ -39,14 +62,75 @@ const DO_SIZES = [
   { value: "s-2vcpu-2gb", label: "Performance — 2GB RAM (~$18/mo)" },
 ];
 
+const HETZNER_SERVER_TYPES = [
+  { value: "cx22", label: "CX22 — 2 vCPU, 4GB RAM (~€4/mo)" },
+  { value: "cx32", label: "CX32 — 4 vCPU, 8GB RAM (~€7/mo)" },
+];
+
+const VULTR_PLANS = [
+  { value: "vc2-1c-1gb", label: "Regular — 1 vCPU, 1GB RAM (~$5/mo)" },
+  { value: "vc2-1c-2gb", label: "Regular — 1 vCPU, 2GB RAM (~$10/mo)" },
+];
+
+const LINODE_TYPES = [
+  { value: "g6-nanode-1", label: "Nanode — 1 vCPU, 1GB RAM (~$5/mo)" },
+  { value: "g6-standard-1", label: "Linode 2GB — 1 vCPU, 2GB RAM (~$12/mo)" },
+];
+
+type DeployMode = "aws" | "do" | "hetzner" | "vultr" | "linode" | "byo";
+
+// Lines of server output kept on screen while WireGuard installs.
+const SERVER_LOG_LINES = 6;
+
+// Hetzner bills in euros, the others in dollars.
+function sizeOption(size: ServerSize, mode: DeployMode) {
+  const currency = mode === "hetzner" ? "€" : "$";
+  const price =
+    size.monthly_price !== null
+      ? ` (${currency}${size.monthly_price.toFixed(2)}/mo)`
+      : "";
+  return { value: size.code, label: `${size.code} — ${size.description}${price}` };
+}
+
+function formatCost(currency: string, amount: number, digits = 2) {
+  return `${currency === "EUR" ? "€" : "$"}${amount.toFixed(digits)}`;
+}
+
+// What the server picked in the form will cost, once the backend knows.
+function CostLine({ estimate }: { estimate: CostEstimate | null }) {
+  if (!estimate) return null;
+  const { currency, hourly, monthly, until_auto_destroy } = estimate;
+  return (
+    <p className="text-xs text-gray-400">
+      Estimated cost: {formatCost(currency, hourly, 3)}/hour (
+      {formatCost(currency, monthly)}/mo)
+      {until_auto_destroy !== null &&
+        `, about ${formatCost(currency, until_auto_destroy)} until auto-destroy`}
+    </p>
+  );
+}
+
+// A deployment whose deploy or destroy is still running, if any.
+function findBusy(list: DeploymentState[]): DeploymentState | null {
+  return (
+    list.find((d) => d.status === "deploying" || d.status === "destroying") ??
+    null
+  );
+}
+
 function DeployPage() {
   const navigate = useNavigate();
+  const location = useLocation();
+  // Set when the Dashboard sends a failed AWS deployment here to resume.
+  const resumeId = (location.state as { resumeId?: string } | null)?.resumeId;
+  const resumeStarted = useRef(false);
 
   // Mode
-  const [mode, setMode] = useState<"aws" | "do" | "byo">("aws");
+  const [mode, setMode] = useState<DeployMode>("aws");
 
   // AWS form
   const [region, setRegion] = useState("us-east-1");
+  const [instanceType, setInstanceType] = useState("t2.micro");
   const [hasCreds, setHasCreds] = useState(false);
 
   // DO form
@@ -54,11 +138,62 @@ function DeployPage() {
   const [doSize, setDoSize] = useState("s-1vcpu-512mb-10gb");
   const [hasDoCredentials, setHasDoCredentials] = useState(false);
 
+  // Hetzner form
+  const [hzLocation, setHzLocation] = useState("fsn1");
+  const [hzServerType, setHzServerType] = useState("cx22");
+  const [hasHetznerCredentials, setHasHetznerCredentials] = useState(false);
+
+  // Vultr form
+  const [vultrRegion, setVultrRegion] = useState("ewr");
+  const [vultrPlan, setVultrPlan] = useState("vc2-1c-1gb");
+  const [hasVultrCredentials, setHasVultrCredentials] = useState(false);
+
+  // Linode form
+  const [linodeRegion, setLinodeRegion] = useState("us-east");
+  const [linodeType, setLinodeType] = useState("g6-nanode-1");
+  const [hasLinodeCredentials, setHasLinodeCredentials] = useState(false);
+
+  // Live region and size lists for the selected cloud. The static lists
+  // above are shown until they load, or if the backend can't fetch them.
+  const catalogRegion = {
+    aws: region,
+    do: doRegion,
+    hetzner: hzLocation,
+    vultr: vultrRegion,
+    linode: linodeRegion,
+    byo: "",
+  }[mode];
+  const catalog = useCatalog(mode === "byo" ? null : mode, catalogRegion);
+  const catalogSize = {
+    aws: instanceType,
+    do: doSize,
+    hetzner: hzServerType,
+    vultr: vultrPlan,
+    linode: linodeType,
+    byo: "",
+  }[mode];
+  const sizeOptions = (fallback: { value: string; label: string }[]) =>
+    catalog.sizes ? catalog.sizes.map((s) => sizeOption(s, mode)) : fallback;
+
+  // A size the new region doesn't offer falls back to its first (cheapest).
+  useEffect(() => {
+    if (!catalog.sizes) return;
+    const codes = catalog.sizes.map((s) => s.code);
+    const keep = (value: string, set: (v: string) => void) => {
+      if (!codes.includes(value)) set(codes[0]);
+    };
+    if (mode === "do") keep(doSize, setDoSize);
+    if (mode === "hetzner") keep(hzServerType, setHzServerType);
+    if (mode === "vultr") keep(vultrPlan, setVultrPlan);
+    if (mode === "linode") keep(linodeType, setLinodeType);
+  }, [catalog.sizes, mode, doSize, hzServerType, vultrPlan, linodeType]);
+
   // BYO form
   const [serverIp, setServerIp] = useState("");
   const [sshKey, setSshKey] = useState("");
   const [sshUser, setSshUser] = useState("ubuntu");
   const [sshPort, setSshPort] = useState(22);
+  const [hostKeyFingerprint, setHostKeyFingerprint] = useState("");
 
   // Shared
   const [autoDestroyHours, setAutoDestroyHours] = useState<number | undefined>(
@@ -69,31 +204,46 @@ function DeployPage() {
   const [error, setError] = useState("");
   const [steps, setSteps] = useState<ProgressEvent[]>([]);
   const [currentStep, setCurrentStep] = useState(0);
+  const [serverLog, setServerLog] = useState<string[]>([]);
   const [attempted, setAttempted] = useState(false);
-  const [existingDeployment, setExistingDeployment] =
+  const [resuming, setResuming] = useState(false);
+  const [rollback, setRollback] = useState("");
+  const [busyDeployment, setBusyDeployment] =
     useState<DeploymentState | null>(null);
 
+  // Re-estimated whenever the server or the auto-destroy choice changes.
+  const [estimate, setEstimate] = useState<CostEstimate | null>(null);
+  useEffect(() => {
+    setEstimate(null);
+    if (mode === "byo" || !catalogRegion || !catalogSize) return;
+    let cancelled = false;
+    estimateCost(mode, catalogRegion, catalogSize, autoDestroyHours)
+      .then((e) => {
+        if (!cancelled) setEstimate(e ?? null);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [mode, catalogRegion, catalogSize, autoDestroyHours]);
+
   useEffect(() => {
     loadCredentials().then((creds) => setHasCreds(!!creds));
     loadDoCredentials().then((creds) => setHasDoCredentials(!!creds));
-    getSettings().then((s) => setRegion(s.region));
+    loadHetznerCredentials().then((creds) => setHasHetznerCredentials(!!creds));
+    loadVultrCredentials().then((creds) => setHasVultrCredentials(!!creds));
+    loadLinodeCredentials().then((creds) => setHasLinodeCredentials(!!creds));
+    getSettings().then((s) => {
+      setRegion(s.region);
+      setInstanceType(s.instance_type);
+    });
   }, []);
 
   useEffect(() => {
     const checkState = () => {
-      getDeploymentState()
-        .then((s) => {
-          if (
-            s.status === "deployed" ||
-            s.status === "deploying" ||
-            s.status === "destroying"
-          ) {
-            setExistingDeployment(s);
-          } else {
-            setExistingDeployment(null);
-          }
-        })
-        .catch(() => setExistingDeployment(null));
+      listDeployments()
+        .then((list) => setBusyDeployment(findBusy(list)))
+        .catch(() => setBusyDeployment(null));
     };
     checkState();
     const interval = setInterval(checkState, 3000);
@@ -123,37 +273,60 @@ function DeployPage() {
     };
   }, []);
 
+  // Output from the server while it installs WireGuard; the last few lines.
+  useEffect(() => {
+    const unlisten = listen<DeployLogEvent>("deploy-log", (event) => {
+      setServerLog((prev) =>
+        [...prev, event.payload.line].slice(-SERVER_LOG_LINES),
+      );
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // A failed deploy tears down what it created before the deploy call returns.
+  useEffect(() => {
+    const unlisten = listen<ProgressEvent>("destroy-progress", (event) => {
+      setRollback(event.payload.message);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleReset = () => {
     setAttempted(false);
+    setResuming(false);
+    setRollback("");
     setError("");
     setSteps([]);
+    setServerLog([]);
     setDone(false);
     setCurrentStep(0);
   };
 
-  const handleSetMode = (m: "aws" | "do" | "byo") => {
+  const handleSetMode = (m: DeployMode) => {
     setMode(m);
     handleReset();
   };
 
   const startDeploy = async (deployFn: () => Promise<unknown>) => {
     try {
-      const currentState = await getDeploymentState();
-      if (
-        currentState.status === "deployed" ||
-        currentState.status === "deploying" ||
-        currentState.status === "destroying"
-      ) {
-        setExistingDeployment(currentState);
+      const busy = findBusy(await listDeployments());
+      if (busy) {
+        setBusyDeployment(busy);
         return;
       }
     } catch {
-      // No state file = not deployed, safe to proceed
+      // No state file = nothing deployed, safe to proceed
     }
     setDeploying(true);
     setAttempted(true);
+    setRollback("");
     setError("");
     setSteps([]);
+    setServerLog([]);
     setDone(false);
     setCurrentStep(0);
     try {
@@ -164,21 +337,51 @@ function DeployPage() {
     }
   };
 
+  useEffect(() => {
+    if (!resumeId || resumeStarted.current) return;
+    resumeStarted.current = true;
+    setMode("aws");
+    setResuming(true);
+    startDeploy(() => resumeDeployment(resumeId, autoDestroyHours));
+  }, [resumeId]);
+
   const handleAwsDeploy = () =>
     startDeploy(() => deployVpn(region, autoDestroyHours));
 
   const handleDoDeploy = () =>
     startDeploy(() => deployDo(doRegion, doSize, autoDestroyHours));
 
+  const handleHetznerDeploy = () =>
+    startDeploy(() =>
+      deployHetzner(hzLocation, hzServerType, autoDestroyHours)
+    );
+
+  const handleVultrDeploy = () =>
+    startDeploy(() => deployVultr(vultrRegion, vultrPlan, autoDestroyHours));
+
+  const handleLinodeDeploy = () =>
+    startDeploy(() =>
+      deployLinode(linodeRegion, linodeType, autoDestroyHours)
+    );
+
   const handleByoDeploy = () =>
     startDeploy(() =>
-      deployByoVps(serverIp, sshKey, sshUser, sshPort, autoDestroyHours)
+      deployByoVps(
+        serverIp,
+        sshKey,
+        sshUser,
+        sshPort,
+        autoDestroyHours,
+        hostKeyFingerprint.trim() || undefined
+      )
     );
 
-  // Block if server already deployed/deploying/destroying
-  if (existingDeployment) {
-    const isDeploying = existingDeployment.status === "deploying";
-    const isDestroying = existingDeployment.status === "destroying";
+  // Existing servers don't block a new one, but progress events carry no
+  // deployment id, so only one deploy or destroy runs from the UI at a time.
+  // The deploy started from this page is not "someone else's".
+  if (busyDeployment && !attempted) {
+    const isDestroying = busyDeployment.status === "destroying";
+    const label = busyDeployment.name || "A server";
     return (
       <div className="flex flex-col items-center justify-center h-full text-center">
         <div
@@ -186,29 +389,19 @@ function DeployPage() {
             isDestroying ? "bg-yellow-500/20" : "bg-green-500/20"
           }`}
         >
-          {isDeploying || isDestroying ? (
-            <Loader2
-              className={`w-10 h-10 animate-spin ${
-                isDestroying ? "text-yellow-400" : "text-primary-400"
-              }`}
-            />
-          ) : (
-            <CheckCircle2 className="w-10 h-10 text-green-400" />
-          )}
+          <Loader2
+            className={`w-10 h-10 animate-spin ${
+              isDestroying ? "text-yellow-400" : "text-primary-400"
+            }`}
+          />
         </div>
         <h2 className="text-2xl font-bold text-white mb-2">
-          {isDeploying
-            ? "Deployment Running"
-            : isDestroying
-              ? "Server Being Destroyed"
-              : "Server Already Deployed"}
+          {isDestroying ? "Server Being Destroyed" : "Deployment Running"}
         </h2>
         <p className="text-gray-400 text-sm mb-6 max-w-sm">
-          {isDeploying
-            ? "A deployment is currently running. Check the Dashboard for its status."
-            : isDestroying
-              ? "The server is being destroyed. Please wait before starting a new deployment."
-              : "Your VPN server is already running. Go to the Dashboard to manage it."}
+          {isDestroying
+            ? `${label} is being destroyed. Please wait before starting a new deployment.`
+            : `${label} is being deployed. Check the Dashboard for its status.`}
         </p>
         <button
           onClick={() => navigate("/dashboard")}
@@ -224,7 +417,8 @@ function DeployPage() {
     <div className="max-w-lg">
       <h2 className="text-2xl font-bold text-white mb-2">Deploy VPN</h2>
       <p className="text-gray-400 text-sm mb-5">
-        Deploy WireGuard on AWS, DigitalOcean, or install it on your own server.
+        Deploy WireGuard on AWS, DigitalOcean, Hetzner, Vultr, Linode, or
+        install it on your own server.
       </p>
 
       {/* Tab switcher */}
@@ -251,6 +445,39 @@ function DeployPage() {
           <Droplets className="w-4 h-4" />
           DigitalOcean
         </button>
+        <button
+          onClick={() => handleSetMode("hetzner")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            mode === "hetzner"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Box className="w-4 h-4" />
+          Hetzner
+        </button>
+        <button
+          onClick={() => handleSetMode("vultr")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            mode === "vultr"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Globe className="w-4 h-4" />
+          Vultr
+        </button>
+        <button
+          onClick={() => handleSetMode("linode")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            mode === "linode"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Layers className="w-4 h-4" />
+          Linode
+        </button>
         <button
           onClick={() => handleSetMode("byo")}
           className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
@@ -285,7 +512,11 @@ function DeployPage() {
                 <label className="block text-sm font-medium text-gray-300 mb-1.5">
                   Deploy Region
                 </label>
-                <RegionSelector value={region} onChange={setRegion} />
+                <RegionSelector
+                  value={region}
+                  onChange={setRegion}
+                  regions={catalog.regions ?? undefined}
+                />
               </div>
 
               <div>
@@ -309,6 +540,8 @@ function DeployPage() {
                 </select>
               </div>
 
+              <CostLine estimate={estimate} />
+
               <button
                 onClick={handleAwsDeploy}
                 className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
@@ -352,7 +585,7 @@ function DeployPage() {
                   onChange={(e) => setDoRegion(e.target.value)}
                   className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
                 >
-                  {DO_REGIONS.map((r) => (
+                  {(catalog.regions ?? DO_REGIONS).map((r) => (
                     <option key={r.code} value={r.code}>
                       {r.name}
                     </option>
@@ -369,7 +602,7 @@ function DeployPage() {
                   onChange={(e) => setDoSize(e.target.value)}
                   className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
                 >
-                  {DO_SIZES.map((s) => (
+                  {sizeOptions(DO_SIZES).map((s) => (
                     <option key={s.value} value={s.value}>
                       {s.label}
                     </option>
@@ -398,6 +631,8 @@ function DeployPage() {
                 </select>
               </div>
 
+              <CostLine estimate={estimate} />
+
               <button
                 onClick={handleDoDeploy}
                 className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
@@ -415,6 +650,279 @@ function DeployPage() {
         </>
       )}
 
+      {/* Hetzner form */}
+      {mode === "hetzner" && !attempted && (
+        <>
+          {!hasHetznerCredentials ? (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 text-center">
+              <p className="text-sm text-gray-400 mb-4">
+                Set up your Hetzner API token before deploying.
+              </p>
+              <button
+                onClick={() => navigate("/setup")}
+                className="px-6 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                Go to Setup
+              </button>
+            </div>
+          ) : (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Location
+                </label>
+                <select
+                  value={hzLocation}
+                  onChange={(e) => setHzLocation(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {(catalog.regions ?? HETZNER_LOCATIONS).map((l) => (
+                    <option key={l.code} value={l.code}>
+                      {l.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Server Type
+                </label>
+                <select
+                  value={hzServerType}
+                  onChange={(e) => setHzServerType(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {sizeOptions(HETZNER_SERVER_TYPES).map((t) => (
+                    <option key={t.value} value={t.value}>
+                      {t.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Auto-Destroy
+                </label>
+                <select
+                  value={autoDestroyHours ?? ""}
+                  onChange={(e) =>
+                    setAutoDestroyHours(
+                      e.target.value ? Number(e.target.value) : undefined
+                    )
+                  }
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {AUTO_DESTROY_OPTIONS.map((o) => (
+                    <option key={o.value} value={o.value}>
+                      {o.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <CostLine estimate={estimate} />
+
+              <button
+                onClick={handleHetznerDeploy}
+                className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                <Rocket className="w-4 h-4" />
+                Deploy Server
+              </button>
+
+              <p className="text-xs text-gray-500 text-center">
+                Creates a Hetzner Cloud server and firewall with WireGuard
+                pre-configured. Billed by the hour.
+              </p>
+            </div>
+          )}
+        </>
+      )}
+
+      {/* Vultr form */}
+      {mode === "vultr" && !attempted && (
+        <>
+          {!hasVultrCredentials ? (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 text-center">
+              <p className="text-sm text-gray-400 mb-4">
+                Set up your Vultr API key before deploying.
+              </p>
+              <button
+                onClick={() => navigate("/setup")}
+                className="px-6 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                Go to Setup
+              </button>
+            </div>
+          ) : (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Region
+                </label>
+                <select
+                  value={vultrRegion}
+                  onChange={(e) => setVultrRegion(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {(catalog.regions ?? VULTR_REGIONS).map((l) => (
+                    <option key={l.code} value={l.code}>
+                      {l.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Plan
+                </label>
+                <select
+                  value={vultrPlan}
+                  onChange={(e) => setVultrPlan(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {sizeOptions(VULTR_PLANS).map((t) => (
+                    <option key={t.value} value={t.value}>
+                      {t.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Auto-Destroy
+                </label>
+                <select
+                  value={autoDestroyHours ?? ""}
+                  onChange={(e) =>
+                    setAutoDestroyHours(
+                      e.target.value ? Number(e.target.value) : undefined
+                    )
+                  }
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {AUTO_DESTROY_OPTIONS.map((o) => (
+                    <option key={o.value} value={o.value}>
+                      {o.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <CostLine estimate={estimate} />
+
+              <button
+                onClick={handleVultrDeploy}
+                className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                <Rocket className="w-4 h-4" />
+                Deploy Server
+              </button>
+
+              <p className="text-xs text-gray-500 text-center">
+                Creates a Vultr instance and firewall group with WireGuard
+                pre-configured. Billed by the hour.
+              </p>
+            </div>
+          )}
+        </>
+      )}
+
+      {/* Linode form */}
+      {mode === "linode" && !attempted && (
+        <>
+          {!hasLinodeCredentials ? (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 text-center">
+              <p className="text-sm text-gray-400 mb-4">
+                Set up your Linode API token before deploying.
+              </p>
+              <button
+                onClick={() => navigate("/setup")}
+                className="px-6 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                Go to Setup
+              </button>
+            </div>
+          ) : (
+            <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Region
+                </label>
+                <select
+                  value={linodeRegion}
+                  onChange={(e) => setLinodeRegion(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {(catalog.regions ?? LINODE_REGIONS).map((l) => (
+                    <option key={l.code} value={l.code}>
+                      {l.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Plan
+                </label>
+                <select
+                  value={linodeType}
+                  onChange={(e) => setLinodeType(e.target.value)}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {sizeOptions(LINODE_TYPES).map((t) => (
+                    <option key={t.value} value={t.value}>
+                      {t.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Auto-Destroy
+                </label>
+                <select
+                  value={autoDestroyHours ?? ""}
+                  onChange={(e) =>
+                    setAutoDestroyHours(
+                      e.target.value ? Number(e.target.value) : undefined
+                    )
+                  }
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+                >
+                  {AUTO_DESTROY_OPTIONS.map((o) => (
+                    <option key={o.value} value={o.value}>
+                      {o.label}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <CostLine estimate={estimate} />
+
+              <button
+                onClick={handleLinodeDeploy}
+                className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
+              >
+                <Rocket className="w-4 h-4" />
+                Deploy Server
+              </button>
+
+              <p className="text-xs text-gray-500 text-center">
+                Creates a Linode and cloud firewall with WireGuard
+                pre-configured. Billed by the hour.
+              </p>
+            </div>
+          )}
+        </>
+      )}
+
       {/* BYO form */}
       {mode === "byo" && !attempted && (
         <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
@@ -470,6 +978,25 @@ function DeployPage() {
             />
           </div>
 
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1.5">
+              Host Key Fingerprint{" "}
+              <span className="text-gray-500 font-normal">(optional)</span>
+            </label>
+            <input
+              type="text"
+              value={hostKeyFingerprint}
+              onChange={(e) => setHostKeyFingerprint(e.target.value)}
+              placeholder="SHA256:..."
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+            />
+            <p className="text-xs text-gray-500 mt-1">
+              Run <code>ssh-keygen -lf /etc/ssh/ssh_host_ed25519_key.pub</code>{" "}
+              on the server. If left empty, the key seen on first connect is
+              trusted and pinned.
+            </p>
+          </div>
+
           <div>
             <label className="block text-sm font-medium text-gray-300 mb-1.5">
               Auto-Destroy
@@ -524,16 +1051,39 @@ function DeployPage() {
                   ? "Deployment Complete!"
                   : error
                     ? "Deployment Failed"
-                    : mode === "aws"
-                      ? "Deploying to AWS..."
-                      : mode === "do"
-                        ? "Deploying to DigitalOcean..."
-                        : "Configuring Server..."}
+                    : resuming
+                      ? "Resuming deployment..."
+                      : mode === "aws"
+                        ? "Deploying to AWS..."
+                        : mode === "do"
+                          ? "Deploying to DigitalOcean..."
+                          : mode === "hetzner"
+                            ? "Deploying to Hetzner..."
+                            : mode === "vultr"
+                              ? "Deploying to Vultr..."
+                              : mode === "linode"
+                                ? "Deploying to Linode..."
+                                : "Configuring Server..."}
               </h3>
             </div>
 
             <ProgressStepper steps={steps} currentStep={currentStep} />
 
+            {serverLog.length > 0 && !done && (
+              <pre
+                aria-label="Server output"
+                className="mt-4 max-h-32 overflow-hidden bg-gray-800 border border-gray-700 rounded-lg p-3 text-xs font-mono text-gray-400 whitespace-pre-wrap break-all"
+              >
+                {serverLog.join("\n")}
+              </pre>
+            )}
+
+            {rollback && !done && (
+              <p className="mt-4 text-xs text-yellow-400">
+                Rollback: {rollback}
+              </p>
+            )}
+
             {error && (
               <div className="mt-4 bg-red-500/10 border border-red-500/30 rounded-lg p-3">
                 <p className="text-xs font-mono text-red-400 break-all">
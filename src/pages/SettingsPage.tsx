@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { Save, Trash2, Download, CheckCircle2 } from "lucide-react";
 import RegionSelector from "../components/RegionSelector";
+import OrphanCleanup from "../components/OrphanCleanup";
 import {
   getSettings,
   updateSettings,
@@ -8,32 +9,92 @@ import {
   deleteCredentials,
   loadDoCredentials,
   deleteDoCredentials,
+  loadHetznerCredentials,
+  deleteHetznerCredentials,
+  loadVultrCredentials,
+  deleteVultrCredentials,
+  loadLinodeCredentials,
+  deleteLinodeCredentials,
   exportClientConfig,
+  listDeployments,
+  scanAwsOrphans,
+  cleanupAwsOrphans,
+  scanDoOrphans,
+  cleanupDoOrphans,
 } from "../lib/tauri";
+import { OS_RELEASES } from "../lib/types";
+import type { Architecture, DeploymentState, OsRelease } from "../lib/types";
+
+// Graviton instance types; the others are amd64.
+const isArmInstanceType = (t: string) => t.startsWith("t4g.");
 
 function SettingsPage() {
   const [region, setRegion] = useState("us-east-1");
   const [instanceType, setInstanceType] = useState("t2.micro");
   const [wgPort, setWgPort] = useState(51820);
+  const [killSwitch, setKillSwitch] = useState(false);
+  const [killSwitchAllowLan, setKillSwitchAllowLan] = useState(false);
+  const [rollbackOnFailure, setRollbackOnFailure] = useState(true);
+  const [tunnelCidr, setTunnelCidr] = useState("10.8.0.0/24");
+  const [ipLookupUrl, setIpLookupUrl] = useState("https://checkip.amazonaws.com");
+  const [lockDownSsh, setLockDownSsh] = useState(false);
+  const [deployWithoutSsh, setDeployWithoutSsh] = useState(false);
+  const [osRelease, setOsRelease] = useState<OsRelease>("ubuntu-22.04");
+  const [architecture, setArchitecture] = useState<Architecture>("amd64");
   const [hasCreds, setHasCreds] = useState(false);
   const [hasDoToken, setHasDoToken] = useState(false);
+  const [hasHetznerToken, setHasHetznerToken] = useState(false);
+  const [hasVultrKey, setHasVultrKey] = useState(false);
+  const [hasLinodeToken, setHasLinodeToken] = useState(false);
   const [saved, setSaved] = useState(false);
   const [error, setError] = useState("");
   const [exportMsg, setExportMsg] = useState("");
+  const [deployments, setDeployments] = useState<DeploymentState[]>([]);
+  const [exportId, setExportId] = useState("");
 
   useEffect(() => {
     getSettings().then((s) => {
       setRegion(s.region);
       setInstanceType(s.instance_type);
       setWgPort(s.wireguard_port);
+      setKillSwitch(s.kill_switch ?? false);
+      setKillSwitchAllowLan(s.kill_switch_allow_lan ?? false);
+      setRollbackOnFailure(s.rollback_on_failure ?? true);
+      setTunnelCidr(s.tunnel_cidr ?? "10.8.0.0/24");
+      setIpLookupUrl(s.ip_lookup_url ?? "https://checkip.amazonaws.com");
+      setLockDownSsh(s.lock_down_ssh ?? false);
+      setDeployWithoutSsh(s.deploy_without_ssh ?? false);
+      setOsRelease(s.os_release ?? "ubuntu-22.04");
+      setArchitecture(s.architecture ?? "amd64");
     });
     loadCredentials().then((creds) => setHasCreds(!!creds));
     loadDoCredentials().then((creds) => setHasDoToken(!!creds));
+    loadHetznerCredentials().then((creds) => setHasHetznerToken(!!creds));
+    loadVultrCredentials().then((creds) => setHasVultrKey(!!creds));
+    loadLinodeCredentials().then((creds) => setHasLinodeToken(!!creds));
+    listDeployments()
+      .then((list) => {
+        const withConfig = list.filter((d) => d.status === "deployed");
+        setDeployments(withConfig);
+        setExportId(withConfig[0]?.id ?? "");
+      })
+      .catch(() => setDeployments([]));
   }, []);
 
   const handleSave = async () => {
     try {
-      await updateSettings(region, instanceType, wgPort);
+      await updateSettings(region, instanceType, wgPort, {
+        killSwitch,
+        killSwitchAllowLan,
+        rollbackOnFailure,
+        tunnelCidr,
+        ipLookupUrl,
+        lockDownSsh,
+        deployWithoutSsh,
+        osRelease,
+        architecture,
+      });
+      setError("");
       setSaved(true);
       setTimeout(() => setSaved(false), 2000);
     } catch (err) {
@@ -41,6 +102,14 @@ function SettingsPage() {
     }
   };
 
+  // Keep the AWS instance type on the chosen architecture.
+  const handleArchitecture = (arch: Architecture) => {
+    setArchitecture(arch);
+    if ((arch === "arm64") !== isArmInstanceType(instanceType)) {
+      setInstanceType(arch === "arm64" ? "t4g.micro" : "t2.micro");
+    }
+  };
+
   const handleDeleteCreds = async () => {
     try {
       await deleteCredentials();
@@ -59,10 +128,40 @@ function SettingsPage() {
     }
   };
 
+  const handleDeleteHetznerToken = async () => {
+    try {
+      await deleteHetznerCredentials();
+      setHasHetznerToken(false);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleDeleteVultrKey = async () => {
+    try {
+      await deleteVultrCredentials();
+      setHasVultrKey(false);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleDeleteLinodeToken = async () => {
+    try {
+      await deleteLinodeCredentials();
+      setHasLinodeToken(false);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
   const handleExportConfig = async () => {
     setExportMsg("");
     try {
-      const savedPath = await exportClientConfig();
+      if (!exportId) {
+        throw new Error("No VPN config found. Deploy a server first.");
+      }
+      const savedPath = await exportClientConfig(exportId);
       setExportMsg(`Saved: ${savedPath}`);
       setTimeout(() => setExportMsg(""), 6000);
     } catch (err) {
@@ -99,9 +198,49 @@ function SettingsPage() {
             <option value="t2.micro">t2.micro (1 vCPU, 1GB - Free Tier)</option>
             <option value="t3.micro">t3.micro (2 vCPU, 1GB)</option>
             <option value="t3.small">t3.small (2 vCPU, 2GB)</option>
+            <option value="t4g.micro">t4g.micro (2 vCPU, 1GB - Graviton, arm64)</option>
+            <option value="t4g.small">t4g.small (2 vCPU, 2GB - Graviton, arm64)</option>
           </select>
         </div>
 
+        <div className="grid grid-cols-2 gap-3">
+          <div>
+            <label className="block text-sm font-medium text-gray-400 mb-1.5">
+              Server OS
+            </label>
+            <select
+              value={osRelease}
+              onChange={(e) => setOsRelease(e.target.value as OsRelease)}
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+            >
+              {OS_RELEASES.map((os) => (
+                <option key={os.code} value={os.code}>
+                  {os.name}
+                </option>
+              ))}
+            </select>
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-gray-400 mb-1.5">
+              Architecture
+            </label>
+            <select
+              value={architecture}
+              onChange={(e) => handleArchitecture(e.target.value as Architecture)}
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+            >
+              <option value="amd64">amd64 (x86)</option>
+              <option value="arm64">arm64</option>
+            </select>
+          </div>
+        </div>
+        {architecture === "arm64" && (
+          <p className="text-xs text-gray-500 -mt-2">
+            arm64 runs on AWS Graviton (t4g) and Hetzner CAX servers.
+            DigitalOcean, Vultr and Linode only offer amd64.
+          </p>
+        )}
+
         <div>
           <label className="block text-sm font-medium text-gray-400 mb-1.5">
             WireGuard Port
@@ -114,6 +253,116 @@ function SettingsPage() {
           />
         </div>
 
+        <div>
+          <label className="block text-sm font-medium text-gray-400 mb-1.5">
+            Tunnel Subnet
+          </label>
+          <input
+            type="text"
+            value={tunnelCidr}
+            onChange={(e) => setTunnelCidr(e.target.value)}
+            placeholder="10.8.0.0/24"
+            className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+          />
+          <p className="text-xs text-gray-500 mt-1">
+            Private network (/24 to /29) for new deployments. Change it if it
+            clashes with a network you use, such as an office LAN.
+          </p>
+        </div>
+
+        <div>
+          <label className="block text-sm font-medium text-gray-400 mb-1.5">
+            IP Lookup URL
+          </label>
+          <input
+            type="text"
+            value={ipLookupUrl}
+            onChange={(e) => setIpLookupUrl(e.target.value)}
+            placeholder="https://checkip.amazonaws.com"
+            className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+          />
+          <p className="text-xs text-gray-500 mt-1">
+            Answers with this computer's public IPv4 address. New servers only
+            let SSH in from there.
+          </p>
+        </div>
+
+        <label className="flex items-start gap-3 text-sm text-gray-300">
+          <input
+            type="checkbox"
+            checked={lockDownSsh}
+            onChange={(e) => setLockDownSsh(e.target.checked)}
+            className="mt-0.5"
+          />
+          <span>
+            Lock down SSH after setup
+            <span className="block text-xs text-gray-500">
+              Remove the SSH rule once WireGuard is configured. It is opened
+              again while peers are added or revoked.
+            </span>
+          </span>
+        </label>
+
+        <label className="flex items-start gap-3 text-sm text-gray-300">
+          <input
+            type="checkbox"
+            checked={deployWithoutSsh}
+            onChange={(e) => setDeployWithoutSsh(e.target.checked)}
+            className="mt-0.5"
+          />
+          <span>
+            Deploy without SSH
+            <span className="block text-xs text-gray-500">
+              Port 22 is never opened: the server sets WireGuard up on first
+              boot and the app checks it with a WireGuard handshake. AWS and
+              DigitalOcean only.
+            </span>
+          </span>
+        </label>
+
+        <label className="flex items-start gap-3 text-sm text-gray-300">
+          <input
+            type="checkbox"
+            checked={killSwitch}
+            onChange={(e) => setKillSwitch(e.target.checked)}
+            className="mt-0.5"
+          />
+          <span>
+            Kill switch
+            <span className="block text-xs text-gray-500">
+              Block all traffic outside the VPN while connected (Linux, needs
+              nftables).
+            </span>
+          </span>
+        </label>
+
+        {killSwitch && (
+          <label className="flex items-center gap-3 text-sm text-gray-300 pl-7">
+            <input
+              type="checkbox"
+              checked={killSwitchAllowLan}
+              onChange={(e) => setKillSwitchAllowLan(e.target.checked)}
+            />
+            Allow local network access
+          </label>
+        )}
+
+        <label className="flex items-start gap-3 text-sm text-gray-300">
+          <input
+            type="checkbox"
+            checked={rollbackOnFailure}
+            onChange={(e) => setRollbackOnFailure(e.target.checked)}
+            className="mt-0.5"
+          />
+          <span>
+            Roll back failed deployments
+            <span className="block text-xs text-gray-500">
+              Delete the servers and networks a failed deploy created, so
+              nothing keeps billing. Turn off to keep them and resume instead.
+            </span>
+          </span>
+        </label>
+
         <button
           onClick={handleSave}
           className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors"
@@ -150,6 +399,35 @@ function SettingsPage() {
         )}
       </div>
 
+      {/* Leftover resources */}
+      {(hasCreds || hasDoToken) && (
+        <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
+          <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
+            Leftover Resources
+          </h3>
+
+          <p className="text-xs text-gray-500">
+            Find servers and networking tagged by CreateMyVpn that no deployment
+            here knows about, e.g. after a crash or a lost state file.
+          </p>
+
+          {hasCreds && (
+            <OrphanCleanup
+              provider="AWS"
+              scan={scanAwsOrphans}
+              cleanup={cleanupAwsOrphans}
+            />
+          )}
+          {hasDoToken && (
+            <OrphanCleanup
+              provider="DigitalOcean"
+              scan={scanDoOrphans}
+              cleanup={(_region, ids) => cleanupDoOrphans(ids)}
+            />
+          )}
+        </div>
+      )}
+
       {/* DigitalOcean Credentials */}
       <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
         <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
@@ -173,12 +451,96 @@ function SettingsPage() {
         )}
       </div>
 
+      {/* Hetzner Credentials */}
+      <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
+        <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
+          Hetzner
+        </h3>
+
+        <p className="text-sm text-gray-400">
+          {hasHetznerToken
+            ? "Hetzner API token is saved in the local secret store."
+            : "No Hetzner token saved. Go to Setup to add one."}
+        </p>
+
+        {hasHetznerToken && (
+          <button
+            onClick={handleDeleteHetznerToken}
+            className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-red-400 bg-red-500/10 hover:bg-red-500/20 rounded-lg transition-colors"
+          >
+            <Trash2 className="w-4 h-4" />
+            Delete Hetzner Token
+          </button>
+        )}
+      </div>
+
+      {/* Vultr Credentials */}
+      <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
+        <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
+          Vultr
+        </h3>
+
+        <p className="text-sm text-gray-400">
+          {hasVultrKey
+            ? "Vultr API key is saved in the local secret store."
+            : "No Vultr API key saved. Go to Setup to add one."}
+        </p>
+
+        {hasVultrKey && (
+          <button
+            onClick={handleDeleteVultrKey}
+            className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-red-400 bg-red-500/10 hover:bg-red-500/20 rounded-lg transition-colors"
+          >
+            <Trash2 className="w-4 h-4" />
+            Delete Vultr Key
+          </button>
+        )}
+      </div>
+
+      {/* Linode Credentials */}
+      <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
+        <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
+          Linode
+        </h3>
+
+        <p className="text-sm text-gray-400">
+          {hasLinodeToken
+            ? "Linode API token is saved in the local secret store."
+            : "No Linode token saved. Go to Setup to add one."}
+        </p>
+
+        {hasLinodeToken && (
+          <button
+            onClick={handleDeleteLinodeToken}
+            className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-red-400 bg-red-500/10 hover:bg-red-500/20 rounded-lg transition-colors"
+          >
+            <Trash2 className="w-4 h-4" />
+            Delete Linode Token
+          </button>
+        )}
+      </div>
+
       {/* Export */}
       <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-4">
         <h3 className="text-sm font-semibold text-gray-300 uppercase tracking-wider">
           Export
         </h3>
 
+        {deployments.length > 1 && (
+          <select
+            value={exportId}
+            onChange={(e) => setExportId(e.target.value)}
+            aria-label="Server to export"
+            className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white focus:outline-none focus:ring-2 focus:ring-primary-500"
+          >
+            {deployments.map((d) => (
+              <option key={d.id} value={d.id}>
+                {d.name || d.id}
+              </option>
+            ))}
+          </select>
+        )}
+
         <button
           onClick={handleExportConfig}
           className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium text-gray-300 bg-gray-800 hover:bg-gray-700 rounded-lg transition-colors"
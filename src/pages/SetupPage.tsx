@@ -1,6 +1,15 @@
 import { useState, useEffect } from "react";
 import { useNavigate } from "react-router-dom";
-import { KeyRound, CheckCircle2, Loader2, Cloud, Droplets } from "lucide-react";
+import {
+  KeyRound,
+  CheckCircle2,
+  Loader2,
+  Cloud,
+  Droplets,
+  Box,
+  Globe,
+  Layers,
+} from "lucide-react";
 import RegionSelector from "../components/RegionSelector";
 import {
   validateCredentials,
@@ -10,13 +19,24 @@ import {
   validateDoCredentials,
   saveDoCredentials,
   loadDoCredentials,
+  validateHetznerCredentials,
+  saveHetznerCredentials,
+  loadHetznerCredentials,
+  validateVultrCredentials,
+  saveVultrCredentials,
+  loadVultrCredentials,
+  validateLinodeCredentials,
+  saveLinodeCredentials,
+  loadLinodeCredentials,
 } from "../lib/tauri";
 
 function SetupPage() {
   const navigate = useNavigate();
 
   // Tab
-  const [credTab, setCredTab] = useState<"aws" | "do">("aws");
+  const [credTab, setCredTab] = useState<
+    "aws" | "do" | "hetzner" | "vultr" | "linode"
+  >("aws");
 
   // AWS state
   const [accessKey, setAccessKey] = useState("");
@@ -34,6 +54,26 @@ function SetupPage() {
   const [doAccountEmail, setDoAccountEmail] = useState("");
   const [doError, setDoError] = useState("");
 
+  // Hetzner state
+  const [hzToken, setHzToken] = useState("");
+  const [hzValidating, setHzValidating] = useState(false);
+  const [hzValidated, setHzValidated] = useState(false);
+  const [hzError, setHzError] = useState("");
+
+  // Vultr state
+  const [vultrKey, setVultrKey] = useState("");
+  const [vultrValidating, setVultrValidating] = useState(false);
+  const [vultrValidated, setVultrValidated] = useState(false);
+  const [vultrAccountEmail, setVultrAccountEmail] = useState("");
+  const [vultrError, setVultrError] = useState("");
+
+  // Linode state
+  const [linodeToken, setLinodeToken] = useState("");
+  const [linodeValidating, setLinodeValidating] = useState(false);
+  const [linodeValidated, setLinodeValidated] = useState(false);
+  const [linodeUsername, setLinodeUsername] = useState("");
+  const [linodeError, setLinodeError] = useState("");
+
   useEffect(() => {
     loadCredentials().then((creds) => {
       if (creds) {
@@ -46,6 +86,21 @@ function SetupPage() {
         setDoToken(creds.api_token);
       }
     });
+    loadHetznerCredentials().then((creds) => {
+      if (creds) {
+        setHzToken(creds.api_token);
+      }
+    });
+    loadVultrCredentials().then((creds) => {
+      if (creds) {
+        setVultrKey(creds.api_key);
+      }
+    });
+    loadLinodeCredentials().then((creds) => {
+      if (creds) {
+        setLinodeToken(creds.api_token);
+      }
+    });
   }, []);
 
   // AWS handlers
@@ -99,6 +154,80 @@ function SetupPage() {
     }
   };
 
+  // Hetzner handlers
+  const handleHzValidate = async () => {
+    setHzValidating(true);
+    setHzError("");
+    setHzValidated(false);
+    try {
+      await validateHetznerCredentials(hzToken);
+      setHzValidated(true);
+    } catch (err) {
+      setHzError(String(err));
+    } finally {
+      setHzValidating(false);
+    }
+  };
+
+  const handleHzSave = async () => {
+    try {
+      await saveHetznerCredentials(hzToken);
+      navigate("/deploy");
+    } catch (err) {
+      setHzError(String(err));
+    }
+  };
+
+  // Vultr handlers
+  const handleVultrValidate = async () => {
+    setVultrValidating(true);
+    setVultrError("");
+    setVultrValidated(false);
+    try {
+      const email = await validateVultrCredentials(vultrKey);
+      setVultrAccountEmail(email);
+      setVultrValidated(true);
+    } catch (err) {
+      setVultrError(String(err));
+    } finally {
+      setVultrValidating(false);
+    }
+  };
+
+  const handleVultrSave = async () => {
+    try {
+      await saveVultrCredentials(vultrKey);
+      navigate("/deploy");
+    } catch (err) {
+      setVultrError(String(err));
+    }
+  };
+
+  // Linode handlers
+  const handleLinodeValidate = async () => {
+    setLinodeValidating(true);
+    setLinodeError("");
+    setLinodeValidated(false);
+    try {
+      const username = await validateLinodeCredentials(linodeToken);
+      setLinodeUsername(username);
+      setLinodeValidated(true);
+    } catch (err) {
+      setLinodeError(String(err));
+    } finally {
+      setLinodeValidating(false);
+    }
+  };
+
+  const handleLinodeSave = async () => {
+    try {
+      await saveLinodeCredentials(linodeToken);
+      navigate("/deploy");
+    } catch (err) {
+      setLinodeError(String(err));
+    }
+  };
+
   return (
     <div className="max-w-lg">
       <h2 className="text-2xl font-bold text-white mb-2">Setup</h2>
@@ -130,6 +259,39 @@ function SetupPage() {
           <Droplets className="w-4 h-4" />
           DigitalOcean
         </button>
+        <button
+          onClick={() => setCredTab("hetzner")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            credTab === "hetzner"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Box className="w-4 h-4" />
+          Hetzner
+        </button>
+        <button
+          onClick={() => setCredTab("vultr")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            credTab === "vultr"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Globe className="w-4 h-4" />
+          Vultr
+        </button>
+        <button
+          onClick={() => setCredTab("linode")}
+          className={`flex-1 flex items-center justify-center gap-2 py-2 text-sm font-medium rounded-md transition-colors ${
+            credTab === "linode"
+              ? "bg-primary-600/20 text-primary-400"
+              : "text-gray-400 hover:text-gray-300"
+          }`}
+        >
+          <Layers className="w-4 h-4" />
+          Linode
+        </button>
       </div>
 
       {/* AWS credentials form */}
@@ -279,6 +441,205 @@ function SetupPage() {
           </div>
         </div>
       )}
+
+      {/* Hetzner credentials form */}
+      {credTab === "hetzner" && (
+        <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1.5">
+              API Token
+            </label>
+            <input
+              type="password"
+              value={hzToken}
+              onChange={(e) => {
+                setHzToken(e.target.value);
+                setHzValidated(false);
+              }}
+              placeholder="Hetzner Cloud API token"
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+            />
+            <p className="mt-1.5 text-xs text-gray-500">
+              Generate a token in{" "}
+              <span className="text-primary-400 font-mono">
+                console.hetzner.cloud
+              </span>
+              {" "}under Security → API tokens, with Read & Write permission.
+              Tokens belong to a single project.
+            </p>
+          </div>
+
+          {hzError && (
+            <div className="bg-red-500/10 border border-red-500/30 rounded-lg p-3">
+              <p className="text-sm text-red-400">{hzError}</p>
+            </div>
+          )}
+
+          {hzValidated && (
+            <div className="bg-green-500/10 border border-green-500/30 rounded-lg p-3 flex items-center gap-2">
+              <CheckCircle2 className="w-4 h-4 text-green-400" />
+              <p className="text-sm text-green-400">Token valid!</p>
+            </div>
+          )}
+
+          <div className="flex gap-3">
+            <button
+              onClick={handleHzValidate}
+              disabled={!hzToken || hzValidating}
+              className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-gray-700 hover:bg-gray-600 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              {hzValidating ? (
+                <Loader2 className="w-4 h-4 animate-spin" />
+              ) : (
+                <KeyRound className="w-4 h-4" />
+              )}
+              Validate
+            </button>
+
+            <button
+              onClick={handleHzSave}
+              disabled={!hzValidated}
+              className="flex-1 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              Save & Continue
+            </button>
+          </div>
+        </div>
+      )}
+
+      {/* Vultr credentials form */}
+      {credTab === "vultr" && (
+        <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1.5">
+              API Key
+            </label>
+            <input
+              type="password"
+              value={vultrKey}
+              onChange={(e) => {
+                setVultrKey(e.target.value);
+                setVultrValidated(false);
+              }}
+              placeholder="Vultr API key"
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+            />
+            <p className="mt-1.5 text-xs text-gray-500">
+              Enable the API in{" "}
+              <span className="text-primary-400 font-mono">
+                my.vultr.com
+              </span>
+              {" "}under Account → API and copy the key. Add your IP to the
+              key's access control list if it is restricted.
+            </p>
+          </div>
+
+          {vultrError && (
+            <div className="bg-red-500/10 border border-red-500/30 rounded-lg p-3">
+              <p className="text-sm text-red-400">{vultrError}</p>
+            </div>
+          )}
+
+          {vultrValidated && (
+            <div className="bg-green-500/10 border border-green-500/30 rounded-lg p-3 flex items-center gap-2">
+              <CheckCircle2 className="w-4 h-4 text-green-400" />
+              <p className="text-sm text-green-400">
+                Key valid! Account: {vultrAccountEmail}
+              </p>
+            </div>
+          )}
+
+          <div className="flex gap-3">
+            <button
+              onClick={handleVultrValidate}
+              disabled={!vultrKey || vultrValidating}
+              className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-gray-700 hover:bg-gray-600 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              {vultrValidating ? (
+                <Loader2 className="w-4 h-4 animate-spin" />
+              ) : (
+                <KeyRound className="w-4 h-4" />
+              )}
+              Validate
+            </button>
+
+            <button
+              onClick={handleVultrSave}
+              disabled={!vultrValidated}
+              className="flex-1 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              Save & Continue
+            </button>
+          </div>
+        </div>
+      )}
+
+      {/* Linode credentials form */}
+      {credTab === "linode" && (
+        <div className="bg-gray-900 border border-gray-800 rounded-xl p-6 space-y-5">
+          <div>
+            <label className="block text-sm font-medium text-gray-300 mb-1.5">
+              API Token
+            </label>
+            <input
+              type="password"
+              value={linodeToken}
+              onChange={(e) => {
+                setLinodeToken(e.target.value);
+                setLinodeValidated(false);
+              }}
+              placeholder="Linode personal access token"
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-3 py-2.5 text-sm text-white placeholder-gray-500 font-mono focus:outline-none focus:ring-2 focus:ring-primary-500"
+            />
+            <p className="mt-1.5 text-xs text-gray-500">
+              Create a personal access token in{" "}
+              <span className="text-primary-400 font-mono">
+                cloud.linode.com
+              </span>
+              {" "}under API Tokens, with Read/Write access to Linodes and
+              Firewalls.
+            </p>
+          </div>
+
+          {linodeError && (
+            <div className="bg-red-500/10 border border-red-500/30 rounded-lg p-3">
+              <p className="text-sm text-red-400">{linodeError}</p>
+            </div>
+          )}
+
+          {linodeValidated && (
+            <div className="bg-green-500/10 border border-green-500/30 rounded-lg p-3 flex items-center gap-2">
+              <CheckCircle2 className="w-4 h-4 text-green-400" />
+              <p className="text-sm text-green-400">
+                Token valid! User: {linodeUsername}
+              </p>
+            </div>
+          )}
+
+          <div className="flex gap-3">
+            <button
+              onClick={handleLinodeValidate}
+              disabled={!linodeToken || linodeValidating}
+              className="flex-1 flex items-center justify-center gap-2 px-4 py-2.5 text-sm font-medium text-white bg-gray-700 hover:bg-gray-600 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              {linodeValidating ? (
+                <Loader2 className="w-4 h-4 animate-spin" />
+              ) : (
+                <KeyRound className="w-4 h-4" />
+              )}
+              Validate
+            </button>
+
+            <button
+              onClick={handleLinodeSave}
+              disabled={!linodeValidated}
+              className="flex-1 px-4 py-2.5 text-sm font-medium text-white bg-primary-600 hover:bg-primary-500 rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              Save & Continue
+            </button>
+          </div>
+        </div>
+      )}
     </div>
   );
 }
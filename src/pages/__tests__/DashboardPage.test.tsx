@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { render, screen, waitFor } from "@testing-library/react";
+import { render, screen, waitFor, fireEvent } from "@testing-library/react";
 import { MemoryRouter } from "react-router-dom";
 import DashboardPage from "../DashboardPage";
 import { invoke } from "@tauri-apps/api/core";
@@ -12,6 +12,8 @@ vi.mock("qrcode.react", () => ({
 const mockInvoke = vi.mocked(invoke);
 
 const notDeployed = {
+  id: "a1b2c3d4",
+  name: "",
   status: "not_deployed",
   deployment_mode: null,
   region: null,
@@ -31,12 +33,26 @@ const notDeployed = {
   client_private_key: null,
   client_public_key: null,
   client_config: null,
+  tunnel_cidr: null,
+  tunnel_ipv6: null,
   deployed_at: null,
   auto_destroy_at: null,
+  hourly_cost: null,
+  cost_currency: null,
   error_message: null,
+  status_before_destroy: null,
+  completed_step: 0,
   droplet_id: null,
   do_firewall_id: null,
   do_ssh_key_id: null,
+  hetzner_server_id: null,
+  hetzner_firewall_id: null,
+  hetzner_ssh_key_id: null,
+  vultr_instance_id: null,
+  vultr_firewall_id: null,
+  vultr_ssh_key_id: null,
+  linode_instance_id: null,
+  linode_firewall_id: null,
 };
 
 const deployed = {
@@ -64,8 +80,8 @@ describe("DashboardPage — Not Deployed", () => {
   beforeEach(() => {
     mockInvoke.mockImplementation(async (cmd: string) => {
       switch (cmd) {
-        case "get_deployment_state":
-          return notDeployed;
+        case "list_deployments":
+          return [];
         case "get_vpn_status":
           return "disconnected";
         default:
@@ -88,10 +104,10 @@ describe("DashboardPage — Not Deployed", () => {
     });
   });
 
-  it("calls get_deployment_state on mount", async () => {
+  it("calls list_deployments on mount", async () => {
     renderPage();
     await waitFor(() => {
-      expect(mockInvoke).toHaveBeenCalledWith("get_deployment_state");
+      expect(mockInvoke).toHaveBeenCalledWith("list_deployments");
     });
   });
 });
@@ -100,8 +116,8 @@ describe("DashboardPage — Deployed", () => {
   beforeEach(() => {
     mockInvoke.mockImplementation(async (cmd: string) => {
       switch (cmd) {
-        case "get_deployment_state":
-          return deployed;
+        case "list_deployments":
+          return [deployed];
         case "get_vpn_status":
           return "disconnected";
         default:
@@ -138,6 +154,49 @@ describe("DashboardPage — Deployed", () => {
       expect(screen.getByText(/Destroy/i)).toBeInTheDocument();
     });
   });
+
+  it("shows what the server has cost so far", async () => {
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "list_deployments":
+          return [{ ...deployed, hourly_cost: 0.0172, cost_currency: "USD" }];
+        case "get_vpn_status":
+          return "disconnected";
+        case "get_deployment_cost":
+          return { currency: "USD", hourly: 0.0172, hours: 10, amount: 0.172 };
+        default:
+          return null;
+      }
+    });
+    renderPage();
+    expect(await screen.findByText("$0.17 ($0.017/h)")).toBeInTheDocument();
+  });
+
+  it("locks SSH down from the dashboard", async () => {
+    const open = { ...deployed, ssh_source: "203.0.113.7/32", ssh_locked: false };
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "list_deployments":
+          return [open];
+        case "get_vpn_status":
+          return "disconnected";
+        case "lock_ssh":
+          return { ...open, ssh_locked: true };
+        default:
+          return null;
+      }
+    });
+    renderPage();
+    expect(await screen.findByText("203.0.113.7/32")).toBeInTheDocument();
+
+    fireEvent.click(screen.getByRole("button", { name: /^Lock$/ }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("lock_ssh", {
+        deploymentId: "a1b2c3d4",
+      });
+    });
+    expect(await screen.findByText("Locked down")).toBeInTheDocument();
+  });
 });
 
 describe("DashboardPage — Error state", () => {
@@ -150,8 +209,8 @@ describe("DashboardPage — Error state", () => {
 
     mockInvoke.mockImplementation(async (cmd: string) => {
       switch (cmd) {
-        case "get_deployment_state":
-          return failedState;
+        case "list_deployments":
+          return [failedState];
         case "get_vpn_status":
           return "disconnected";
         default:
@@ -165,3 +224,99 @@ describe("DashboardPage — Error state", () => {
     });
   });
 });
+
+describe("DashboardPage — Resumable failure", () => {
+  it("offers Resume for an interrupted AWS deployment", async () => {
+    const interrupted = {
+      ...notDeployed,
+      status: "failed",
+      deployment_mode: "aws",
+      vpc_id: "vpc-123",
+      completed_step: 4,
+      error_message: "The deployment was interrupted",
+    };
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "list_deployments":
+          return [interrupted];
+        default:
+          return null;
+      }
+    });
+
+    renderPage();
+    expect(await screen.findByRole("button", { name: /Resume/i })).toBeInTheDocument();
+  });
+
+  it("does not offer Resume for DigitalOcean", async () => {
+    const failedDo = {
+      ...notDeployed,
+      status: "failed",
+      deployment_mode: "do",
+      completed_step: 0,
+      error_message: "Droplet did not become active",
+    };
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "list_deployments":
+          return [failedDo];
+        default:
+          return null;
+      }
+    });
+
+    renderPage();
+    await screen.findByText(/did not become active/i);
+    expect(screen.queryByRole("button", { name: /Resume/i })).not.toBeInTheDocument();
+  });
+});
+
+describe("DashboardPage — Multiple deployments", () => {
+  const us = { ...deployed, id: "us000001", name: "US exit" };
+  const eu = {
+    ...deployed,
+    id: "eu000001",
+    name: "EU exit",
+    region: "eu-west-1",
+    elastic_ip: "198.51.100.7",
+  };
+
+  beforeEach(() => {
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "list_deployments":
+          return [us, eu];
+        case "get_vpn_status":
+          return "disconnected";
+        default:
+          return null;
+      }
+    });
+  });
+
+  it("lists every deployment and switches between them", async () => {
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByRole("button", { name: "EU exit" })).toBeInTheDocument();
+    });
+    expect(screen.getByText(/203\.0\.113\.50/)).toBeInTheDocument();
+
+    fireEvent.click(screen.getByRole("button", { name: "EU exit" }));
+    expect(screen.getByText(/198\.51\.100\.7/)).toBeInTheDocument();
+  });
+
+  it("connects the tunnel to the selected deployment", async () => {
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByRole("button", { name: "EU exit" })).toBeInTheDocument();
+    });
+
+    fireEvent.click(screen.getByRole("button", { name: "EU exit" }));
+    fireEvent.click(screen.getByRole("button", { name: /^Connect$/ }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("connect_vpn", {
+        deploymentId: "eu000001",
+      });
+    });
+  });
+});
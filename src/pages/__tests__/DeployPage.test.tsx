@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { render, screen, waitFor, fireEvent } from "@testing-library/react";
+import { render, screen, waitFor, fireEvent, act } from "@testing-library/react";
 import { MemoryRouter } from "react-router-dom";
 import DeployPage from "../DeployPage";
 import { invoke } from "@tauri-apps/api/core";
@@ -24,8 +24,14 @@ beforeEach(() => {
         return { access_key_id: "AKID", secret_access_key: "SECRET" };
       case "load_do_credentials":
         return { api_token: "dop_v1_test" };
-      case "get_deployment_state":
-        return { status: "not_deployed" };
+      case "load_hetzner_credentials":
+        return { api_token: "hz-token" };
+      case "load_vultr_credentials":
+        return { api_key: "vultr-key" };
+      case "load_linode_credentials":
+        return { api_token: "linode-token" };
+      case "list_deployments":
+        return [];
       default:
         return null;
     }
@@ -102,8 +108,186 @@ describe("DeployPage", () => {
     });
   });
 
+  it("deploys to the chosen Hetzner location", async () => {
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Hetzner/i }));
+    const button = await screen.findByRole("button", { name: /Deploy Server/i });
+    fireEvent.change(screen.getByDisplayValue(/Falkenstein/i), {
+      target: { value: "hel1" },
+    });
+    fireEvent.click(button);
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("deploy_hetzner", {
+        deploymentId: null,
+        location: "hel1",
+        serverType: "cx22",
+        autoDestroyHours: null,
+      });
+    });
+  });
+
+  it("deploys to the chosen Vultr region and plan", async () => {
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Vultr/i }));
+    const button = await screen.findByRole("button", { name: /Deploy Server/i });
+    fireEvent.change(screen.getByDisplayValue(/New Jersey/i), {
+      target: { value: "fra" },
+    });
+    fireEvent.change(screen.getByDisplayValue(/1GB RAM/i), {
+      target: { value: "vc2-1c-2gb" },
+    });
+    fireEvent.click(button);
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("deploy_vultr", {
+        deploymentId: null,
+        region: "fra",
+        plan: "vc2-1c-2gb",
+        autoDestroyHours: null,
+      });
+    });
+  });
+
+  it("shows the server's output while it installs WireGuard", async () => {
+    const handlers: Record<string, (e: { payload: unknown }) => void> = {};
+    mockListen.mockImplementation(((event: string, handler: never) => {
+      handlers[event] = handler;
+      return Promise.resolve(() => {});
+    }) as never);
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation(async (cmd: string, args?: unknown) =>
+      cmd === "deploy_hetzner" ? new Promise(() => {}) : base(cmd, args as never)
+    );
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Hetzner/i }));
+    fireEvent.click(await screen.findByRole("button", { name: /Deploy Server/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("deploy_hetzner", expect.anything());
+    });
+    act(() => {
+      handlers["deploy-log"]({ payload: { line: "Setting up wireguard-tools" } });
+    });
+    expect(await screen.findByLabelText("Server output")).toHaveTextContent(
+      "Setting up wireguard-tools"
+    );
+  });
+
+  it("deploys to the chosen Linode region", async () => {
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Linode/i }));
+    const button = await screen.findByRole("button", { name: /Deploy Server/i });
+    fireEvent.change(screen.getByDisplayValue(/Newark/i), {
+      target: { value: "eu-central" },
+    });
+    fireEvent.click(button);
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("deploy_linode", {
+        deploymentId: null,
+        region: "eu-central",
+        linodeType: "g6-nanode-1",
+        autoDestroyHours: null,
+      });
+    });
+  });
+
+  it("offers the sizes the provider lists, with prices", async () => {
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation(async (cmd: string, args?: unknown) =>
+      cmd === "list_sizes"
+        ? {
+            items: [
+              { code: "cx22", description: "2 vCPU, 4GB RAM", monthly_price: 4.51 },
+              { code: "cax11", description: "2 vCPU, 4GB RAM", monthly_price: 4.18 },
+            ],
+            source: "live",
+            fetched_at: "2026-01-01T00:00:00Z",
+          }
+        : base(cmd, args as never)
+    );
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Hetzner/i }));
+    expect(
+      await screen.findByRole("option", { name: /cax11 — 2 vCPU, 4GB RAM \(€4.18\/mo\)/ })
+    ).toBeInTheDocument();
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("list_sizes", {
+        provider: "hetzner",
+        region: "fsn1",
+      });
+    });
+  });
+
+  it("estimates the cost of the AWS instance type from settings", async () => {
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation(async (cmd: string, args?: unknown) =>
+      cmd === "estimate_cost"
+        ? { currency: "USD", hourly: 0.0188, monthly: 13.72, until_auto_destroy: null }
+        : base(cmd, args as never)
+    );
+    renderPage();
+    expect(
+      await screen.findByText(/Estimated cost: \$0\.019\/hour \(\$13\.72\/mo\)/)
+    ).toBeInTheDocument();
+    expect(mockInvoke).toHaveBeenCalledWith("estimate_cost", {
+      provider: "aws",
+      region: "us-east-1",
+      size: "t2.micro",
+      autoDestroyHours: null,
+    });
+  });
+
   it("subscribes to deploy-progress events", () => {
     renderPage();
     expect(mockListen).toHaveBeenCalledWith("deploy-progress", expect.any(Function));
   });
+
+  it("subscribes to destroy-progress events to show a rollback", () => {
+    renderPage();
+    expect(mockListen).toHaveBeenCalledWith("destroy-progress", expect.any(Function));
+  });
+});
+
+describe("DeployPage with existing deployments", () => {
+  function mockDeployments(list: object[]) {
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation(async (cmd: string, args?: unknown) =>
+      cmd === "list_deployments" ? list : base(cmd, args as never),
+    );
+  }
+
+  it("still offers a new deploy while another server is running", async () => {
+    mockDeployments([{ id: "us000001", name: "US exit", status: "deployed" }]);
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByRole("heading", { name: /Deploy VPN/i })).toBeInTheDocument();
+    });
+  });
+
+  it("waits while another deployment is in progress", async () => {
+    mockDeployments([{ id: "us000001", name: "US exit", status: "deploying" }]);
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByText(/Deployment Running/i)).toBeInTheDocument();
+    });
+    expect(screen.getByText(/US exit is being deployed/)).toBeInTheDocument();
+  });
+});
+
+describe("DeployPage resume", () => {
+  it("resumes the deployment the Dashboard sent", async () => {
+    render(
+      <MemoryRouter initialEntries={[{ pathname: "/deploy", state: { resumeId: "eu000001" } }]}>
+        <DeployPage />
+      </MemoryRouter>,
+    );
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("resume_deployment", {
+        deploymentId: "eu000001",
+        autoDestroyHours: null,
+      });
+    });
+    expect(screen.getByText("Resuming deployment...")).toBeInTheDocument();
+    expect(
+      mockInvoke.mock.calls.filter(([cmd]) => cmd === "resume_deployment")
+    ).toHaveLength(1);
+  });
 });
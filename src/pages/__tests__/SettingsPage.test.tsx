@@ -24,6 +24,8 @@ beforeEach(() => {
         return null;
       case "delete_credentials":
         return null;
+      case "list_deployments":
+        return [];
       default:
         return null;
     }
@@ -79,6 +81,104 @@ describe("SettingsPage", () => {
     });
   });
 
+  it("rolls back failed deployments unless turned off", async () => {
+    renderPage();
+    const rollback = await screen.findByRole("checkbox", {
+      name: /Roll back failed deployments/i,
+    });
+    expect(rollback).toBeChecked();
+    fireEvent.click(rollback);
+    fireEvent.click(screen.getByRole("button", { name: /Save Settings/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "update_settings",
+        expect.objectContaining({ rollbackOnFailure: false }),
+      );
+    });
+  });
+
+  it("saves the tunnel subnet", async () => {
+    renderPage();
+    const input = await screen.findByPlaceholderText("10.8.0.0/24");
+    fireEvent.change(input, { target: { value: "10.77.0.0/24" } });
+    fireEvent.click(screen.getByRole("button", { name: /Save Settings/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "update_settings",
+        expect.objectContaining({ tunnelCidr: "10.77.0.0/24" }),
+      );
+    });
+  });
+
+  it("saves SSH lock-down", async () => {
+    renderPage();
+    const lockDown = await screen.findByRole("checkbox", {
+      name: /Lock down SSH after setup/i,
+    });
+    expect(lockDown).not.toBeChecked();
+    fireEvent.click(lockDown);
+    fireEvent.click(screen.getByRole("button", { name: /Save Settings/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "update_settings",
+        expect.objectContaining({
+          lockDownSsh: true,
+          ipLookupUrl: "https://checkip.amazonaws.com",
+        }),
+      );
+    });
+  });
+
+  it("saves deploying without SSH", async () => {
+    renderPage();
+    const withoutSsh = await screen.findByRole("checkbox", {
+      name: /Deploy without SSH/i,
+    });
+    expect(withoutSsh).not.toBeChecked();
+    fireEvent.click(withoutSsh);
+    fireEvent.click(screen.getByRole("button", { name: /Save Settings/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "update_settings",
+        expect.objectContaining({ deployWithoutSsh: true }),
+      );
+    });
+  });
+
+  it("switches to a Graviton instance type for arm64", async () => {
+    renderPage();
+    const arch = await screen.findByDisplayValue("amd64 (x86)");
+    fireEvent.change(arch, { target: { value: "arm64" } });
+    fireEvent.change(screen.getByDisplayValue("Ubuntu 22.04 LTS"), {
+      target: { value: "ubuntu-24.04" },
+    });
+    expect(screen.getByDisplayValue(/t4g\.micro/)).toBeInTheDocument();
+    fireEvent.click(screen.getByRole("button", { name: /Save Settings/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "update_settings",
+        expect.objectContaining({
+          instanceType: "t4g.micro",
+          osRelease: "ubuntu-24.04",
+          architecture: "arm64",
+        }),
+      );
+    });
+  });
+
+  it("shows why a subnet was refused", async () => {
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation(async (cmd: string, args?: unknown) => {
+      if (cmd === "update_settings") {
+        throw "WireGuard error: The tunnel subnet 10.8.0.0/24 overlaps 10.0.0.0/8";
+      }
+      return base(cmd, args as never);
+    });
+    renderPage();
+    fireEvent.click(await screen.findByRole("button", { name: /Save Settings/i }));
+    expect(await screen.findByText(/overlaps 10\.0\.0\.0\/8/)).toBeInTheDocument();
+  });
+
   it("shows AWS credentials section when credentials exist", async () => {
     renderPage();
     await waitFor(() => {
@@ -108,3 +208,70 @@ describe("SettingsPage", () => {
     });
   });
 });
+
+describe("SettingsPage export", () => {
+  it("exports the config of the chosen deployment", async () => {
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "get_settings":
+          return { region: "us-east-1", instance_type: "t2.micro", wireguard_port: 51820 };
+        case "list_deployments":
+          return [
+            { id: "us000001", name: "US exit", status: "deployed" },
+            { id: "eu000001", name: "EU exit", status: "deployed" },
+          ];
+        case "export_client_config":
+          return "/home/user/Downloads/createmyvpn-client-eu000001.conf";
+        default:
+          return null;
+      }
+    });
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByLabelText(/Server to export/i)).toBeInTheDocument();
+    });
+    fireEvent.change(screen.getByLabelText(/Server to export/i), {
+      target: { value: "eu000001" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: /Export WireGuard Config/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("export_client_config", {
+        deploymentId: "eu000001",
+      });
+    });
+  });
+
+  it("offers the leftover resource scan when AWS credentials are saved", async () => {
+    renderPage();
+    await waitFor(() => {
+      expect(screen.getByText("Leftover Resources")).toBeInTheDocument();
+    });
+    fireEvent.click(screen.getByText("Scan AWS"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("scan_aws_orphans");
+    });
+  });
+
+  it("offers a DigitalOcean scan when a DO token is saved", async () => {
+    mockInvoke.mockImplementation(async (cmd: string) => {
+      switch (cmd) {
+        case "get_settings":
+          return { region: "us-east-1", instance_type: "t2.micro", wireguard_port: 51820 };
+        case "load_do_credentials":
+          return { api_token: "dop_v1_x" };
+        case "list_deployments":
+          return [];
+        case "scan_do_orphans":
+          return { resources: [], monthly_cost_usd: 0, errors: [] };
+        default:
+          return null;
+      }
+    });
+    renderPage();
+    fireEvent.click(await screen.findByText("Scan DigitalOcean"));
+    expect(
+      await screen.findByText("No leftover DigitalOcean resources found.")
+    ).toBeInTheDocument();
+    expect(screen.queryByText("Scan AWS")).not.toBeInTheDocument();
+  });
+});
@@ -63,6 +63,54 @@ describe("SetupPage", () => {
     });
   });
 
+  it("validates a Hetzner token from the Hetzner tab", async () => {
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Hetzner/i }));
+    fireEvent.change(screen.getByPlaceholderText(/Hetzner Cloud API token/i), {
+      target: { value: "hz-token" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: /Validate/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("validate_hetzner_credentials", {
+        apiToken: "hz-token",
+      });
+      expect(screen.getByText(/Token valid!/i)).toBeInTheDocument();
+    });
+  });
+
+  it("shows the Vultr account email after validating a key", async () => {
+    mockInvoke.mockImplementation(async (cmd: string) =>
+      cmd === "validate_vultr_credentials" ? "me@example.com" : null
+    );
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Vultr/i }));
+    fireEvent.change(screen.getByPlaceholderText(/Vultr API key/i), {
+      target: { value: "vultr-key" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: /Validate/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("validate_vultr_credentials", {
+        apiKey: "vultr-key",
+      });
+      expect(screen.getByText(/me@example.com/i)).toBeInTheDocument();
+    });
+  });
+
+  it("validates a Linode token from the Linode tab", async () => {
+    renderPage();
+    fireEvent.click(screen.getByRole("button", { name: /Linode/i }));
+    fireEvent.change(screen.getByPlaceholderText(/Linode personal access token/i), {
+      target: { value: "linode-token" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: /Validate/i }));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("validate_linode_credentials", {
+        apiToken: "linode-token",
+      });
+      expect(screen.getByText(/Token valid!/i)).toBeInTheDocument();
+    });
+  });
+
   it("has Access Key input on AWS tab", async () => {
     renderPage();
     await waitFor(() => {
@@ -83,6 +131,9 @@ describe("SetupPage", () => {
     await waitFor(() => {
       expect(mockInvoke).toHaveBeenCalledWith("load_credentials");
       expect(mockInvoke).toHaveBeenCalledWith("load_do_credentials");
+      expect(mockInvoke).toHaveBeenCalledWith("load_hetzner_credentials");
+      expect(mockInvoke).toHaveBeenCalledWith("load_vultr_credentials");
+      expect(mockInvoke).toHaveBeenCalledWith("load_linode_credentials");
     });
   });
 